@@ -0,0 +1,54 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use tokio_util::bytes::Bytes;
+
+use mudpuppy::client::output::{Item, Output};
+use mudpuppy::model::MudLine;
+
+const LINES: usize = 100_000;
+
+fn scrollback() -> Vec<Item> {
+    (0..LINES)
+        .map(|i| {
+            let text = match i % 4 {
+                0 => format!("You hit the \x1b[31mgoblin\x1b[0m for {i} damage."),
+                1 => "The goblin misses you.".to_string(),
+                2 => format!("<{i}hp 100m 80mv>"),
+                _ => format!("Ayla tells you 'meet me at the inn, {i}'"),
+            };
+            Item::Mud {
+                line: MudLine::from(Bytes::from(text)),
+                received: None,
+            }
+        })
+        .collect()
+}
+
+fn search(c: &mut Criterion) {
+    let items = scrollback();
+    let mut group = c.benchmark_group("search");
+
+    // The first search builds the index, lowercasing each item's text like a linear scan.
+    group.bench_function("first", |b| {
+        b.iter_batched(
+            || {
+                let mut output = Output::new();
+                output.set(items.clone().into_iter(), false);
+                output
+            },
+            |mut output| output.search("dragon", None),
+            BatchSize::LargeInput,
+        );
+    });
+
+    let mut output = Output::new();
+    output.set(items.clone().into_iter(), false);
+    for needle in ["dragon", "goblin", "inn, 9999"] {
+        group.bench_function(format!("repeated/{needle}"), |b| {
+            b.iter(|| output.search(needle, None));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, search);
+criterion_main!(benches);
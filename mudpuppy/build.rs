@@ -48,8 +48,7 @@ fn main() {
         // Otherwise, prepend `CARGO_PKG_VERSION` to `git_info`.
         if git_info.contains(cargo_pkg_version) {
             // Remove the 'g' before the commit sha
-            let git_info = &git_info.replace('g', "");
-            git_describe = git_info.to_string();
+            git_describe = git_info.replace('g', "");
         } else {
             git_describe = format!("v{cargo_pkg_version}-{git_info}");
         }
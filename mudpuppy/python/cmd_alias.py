@@ -29,6 +29,7 @@ class AliasCmd(Command):
             "--verbose", action="store_true", help="Verbose output"
         )
         list_parser.add_argument("--pattern", help="Filter by pattern")
+        list_parser.add_argument("--group", help="Filter by group")
         list_parser.set_defaults(func=self.list)
         list_parser.error = Command.on_error
 
@@ -40,6 +41,13 @@ class AliasCmd(Command):
         )
         add_parser.add_argument("--name", help="Alias name", required=True)
         add_parser.add_argument("--pattern", help="Regex pattern", required=True)
+        add_parser.add_argument(
+            "--priority",
+            type=int,
+            default=0,
+            help="Evaluation priority, higher first",
+        )
+        add_parser.add_argument("--group", help="Group to enable and disable with")
         add_parser.add_argument("command", nargs="+", help="Content to expand alias to")
         add_parser.set_defaults(func=self.add)
         add_parser.error = Command.on_error
@@ -74,6 +82,35 @@ class AliasCmd(Command):
         enable_parser.set_defaults(func=self.enable)
         enable_parser.error = Command.on_error
 
+        test_parser = subparsers.add_parser(
+            "test",
+            help="Test an alias pattern against sample text",
+            exit_on_error=False,
+            add_help=False,
+        )
+        test_parser.add_argument("alias_id", type=int, help="Alias ID to test")
+        test_parser.add_argument("sample", nargs="+", help="Text to match against")
+        test_parser.set_defaults(func=self.test)
+        test_parser.error = Command.on_error
+
+        on_parser = subparsers.add_parser(
+            "on",
+            help="Evaluate aliases",
+            exit_on_error=False,
+            add_help=False,
+        )
+        on_parser.set_defaults(func=self.on)
+        on_parser.error = Command.on_error
+
+        off_parser = subparsers.add_parser(
+            "off",
+            help="Stop evaluating all aliases",
+            exit_on_error=False,
+            add_help=False,
+        )
+        off_parser.set_defaults(func=self.off)
+        off_parser.error = Command.on_error
+
     async def run(self, sesh_id: int, args: Namespace):
         logging.debug(f"args: {args}")
         if hasattr(args, "func"):
@@ -85,7 +122,11 @@ class AliasCmd(Command):
         if args.pattern is None:
             return
         new_alias = AliasConfig(
-            args.pattern, args.name, expansion=" ".join(args.command)
+            args.pattern,
+            args.name,
+            expansion=" ".join(args.command),
+            priority=args.priority,
+            group=args.group,
         )
         alias_id = mudpuppy_core.new_alias(sesh_id, new_alias, __name__)
         await mudpuppy_core.add_output(
@@ -111,20 +152,57 @@ class AliasCmd(Command):
             sesh_id, OutputItem.command_result(f"Enabled alias {args.alias_id}")
         )
 
-    async def list(self, sesh_id: int, _args: Namespace):
+    async def test(self, sesh_id: int, args: Namespace):
+        sample = " ".join(args.sample)
+        found = await mudpuppy_core.test_alias(sesh_id, args.alias_id, sample)
+        if found is None:
+            await mudpuppy_core.add_output(
+                sesh_id,
+                OutputItem.failed_command_result(
+                    f"Alias {args.alias_id} doesn't match {sample!r}"
+                ),
+            )
+            return
+        lines = [f"Alias {args.alias_id} matched {found.text!r}"]
+        for idx, group in enumerate(found.groups, start=1):
+            lines.append(f"  group {idx}: {group!r}")
+        for name, group in found.named_groups.items():
+            lines.append(f"  group {name}: {group!r}")
+        await mudpuppy_core.add_outputs(
+            sesh_id, [OutputItem.command_result(line) for line in lines]
+        )
+
+    async def on(self, sesh_id: int, _args: Namespace):
+        await mudpuppy_core.set_aliases_enabled(sesh_id, True)
+        await mudpuppy_core.add_output(
+            sesh_id, OutputItem.command_result("Aliases enabled")
+        )
+
+    async def off(self, sesh_id: int, _args: Namespace):
+        await mudpuppy_core.set_aliases_enabled(sesh_id, False)
+        await mudpuppy_core.add_output(
+            sesh_id, OutputItem.command_result("Aliases disabled")
+        )
+
+    async def list(self, sesh_id: int, args: Namespace):
         aliases = await mudpuppy_core.aliases(sesh_id)
         output_items = []
         for alias in sorted(aliases, key=lambda a: a.id):
+            if args.group is not None and alias.config.group != args.group:
+                continue
             label = alias.config.expansion
             if alias.config.callback is not None:
                 label = str(alias.config.callback)
+            group = ""
+            if alias.config.group is not None:
+                group = f" Group={alias.config.group}"
             prefix = "<green>"
             if not alias.enabled:
                 prefix = "<red>"
             output_items.append(
                 OutputItem.command_result(
                     cformat(
-                        f"{prefix}{alias.id}: Enabled={alias.enabled} Hits={alias.config.hit_count} {repr(alias.config.pattern())} -> {repr(label)}<reset>"
+                        f"{prefix}{alias.id}: Enabled={alias.enabled} Hits={alias.config.hit_count}{group} {repr(alias.config.pattern())} -> {repr(label)}<reset>"
                     ),
                 )
             )
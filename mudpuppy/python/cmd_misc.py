@@ -32,9 +32,11 @@ class DisconnectCmd(Command):
     async def disconnect(self, sesh_id: int, _args: Namespace):
         status = await mudpuppy_core.status(sesh_id)
         if not isinstance(status, Status.Connected):
-            await mudpuppy_core.add_output(
-                sesh_id, OutputItem.command_result("Not connected")
-            )
+            if await mudpuppy_core.cancel_reconnect(sesh_id):
+                msg = "Reconnect cancelled"
+            else:
+                msg = "Not connected"
+            await mudpuppy_core.add_output(sesh_id, OutputItem.command_result(msg))
             return
 
         logging.debug(f"Disconnecting sesh ID {sesh_id}")
@@ -57,6 +59,163 @@ class ReloadCmd(Command):
         await mudpuppy_core.reload()
 
 
+class FilterCmd(Command):
+    def __init__(self, session: int):
+        super().__init__(
+            "filter",
+            session,
+            self.filter,
+            "Only show output lines matching a regex pattern",
+        )
+        self.parser.add_argument(
+            "pattern",
+            nargs="?",
+            default=None,
+            help="Regex pattern to filter with. Omit to clear the filter",
+        )
+
+    async def filter(self, sesh_id: int, args: Namespace):
+        try:
+            await mudpuppy_core.set_filter(sesh_id, args.pattern)
+        except RuntimeError as e:
+            await mudpuppy_core.add_output(
+                sesh_id, OutputItem.failed_command_result(str(e))
+            )
+            return
+
+        if args.pattern:
+            msg = f"Filtering output with {repr(args.pattern)}"
+        else:
+            msg = "Output filter cleared"
+        await mudpuppy_core.add_output(sesh_id, OutputItem.command_result(msg))
+
+
+class WrapCmd(Command):
+    def __init__(self, session: int):
+        super().__init__(
+            "wrap",
+            session,
+            self.wrap,
+            "Toggle line wrapping for the output buffer without changing the config",
+        )
+        self.parser.add_argument(
+            "state",
+            nargs="?",
+            choices=["on", "off"],
+            default=None,
+            help="Turn wrapping on or off. Omit to toggle",
+        )
+
+    async def wrap(self, sesh_id: int, args: Namespace):
+        if args.state is None:
+            wrap = not await mudpuppy_core.get_wrap(sesh_id, "output_area")
+        else:
+            wrap = args.state == "on"
+
+        await mudpuppy_core.set_wrap(sesh_id, "output_area", wrap)
+        await mudpuppy_core.add_output(
+            sesh_id,
+            OutputItem.command_result(
+                f"line wrapping {'enabled' if wrap else 'disabled'}"
+            ),
+        )
+
+
+class WalkCmd(Command):
+    def __init__(self, session: int):
+        super().__init__(
+            "walk",
+            session,
+            self.walk,
+            "Send the movement commands of a speedwalk, e.g. 3n2e",
+        )
+        self.parser.add_argument(
+            "speedwalk",
+            nargs="+",
+            help="Directions, each optionally preceded by a count",
+        )
+
+    async def walk(self, sesh_id: int, args: Namespace):
+        try:
+            commands = mudpuppy_core.expand_speedwalk(" ".join(args.speedwalk))
+        except RuntimeError as e:
+            await mudpuppy_core.add_output(
+                sesh_id, OutputItem.failed_command_result(str(e))
+            )
+            return
+
+        await mudpuppy_core.send_lines(sesh_id, commands)
+
+
+class TimestampsCmd(Command):
+    def __init__(self, session: int):
+        super().__init__(
+            "timestamps",
+            session,
+            self.timestamps,
+            "Toggle showing when output lines were received without changing the config",
+        )
+        self.parser.add_argument(
+            "state",
+            nargs="?",
+            choices=["on", "off"],
+            default=None,
+            help="Turn timestamps on or off. Omit to toggle",
+        )
+
+    async def timestamps(self, sesh_id: int, args: Namespace):
+        if args.state is None:
+            show = not await mudpuppy_core.get_timestamps(sesh_id)
+        else:
+            show = args.state == "on"
+
+        await mudpuppy_core.set_timestamps(sesh_id, show)
+        await mudpuppy_core.add_output(
+            sesh_id,
+            OutputItem.command_result(
+                f"timestamps {'enabled' if show else 'disabled'}"
+            ),
+        )
+
+
+class SettingsCmd(Command):
+    def __init__(self, session: int):
+        super().__init__(
+            "settings",
+            session,
+            self.settings,
+            "Show the resolved config settings for a MUD",
+        )
+        self.parser.add_argument(
+            "mud_name",
+            nargs="?",
+            default=None,
+            help="Name of the MUD to show. Defaults to the current session's MUD",
+        )
+
+    async def settings(self, sesh_id: int, args: Namespace):
+        mud_name = args.mud_name
+        if mud_name is None:
+            mud_name = (await mudpuppy_core.session_info(sesh_id)).mud_name
+
+        try:
+            settings = mudpuppy_core.dump_resolved_settings(mud_name)
+        except RuntimeError as e:
+            await mudpuppy_core.add_output(
+                sesh_id, OutputItem.failed_command_result(str(e))
+            )
+            return
+
+        items = [OutputItem.command_result(f"Settings for {repr(mud_name)}:")]
+        for key, setting in settings.items():
+            items.append(
+                OutputItem.command_result(
+                    f"  {key} = {repr(setting['value'])} ({setting['source']})"
+                )
+            )
+        await mudpuppy_core.add_outputs(sesh_id, items)
+
+
 @on_new_session()
 async def setup(event: Event):
     assert isinstance(event, Event.NewSession)
@@ -64,3 +223,8 @@ async def setup(event: Event):
     add_command(event.id, DisconnectCmd(event.id))
     add_command(event.id, QuitCmd(event.id))
     add_command(event.id, ReloadCmd(event.id))
+    add_command(event.id, FilterCmd(event.id))
+    add_command(event.id, WrapCmd(event.id))
+    add_command(event.id, WalkCmd(event.id))
+    add_command(event.id, TimestampsCmd(event.id))
+    add_command(event.id, SettingsCmd(event.id))
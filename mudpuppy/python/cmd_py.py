@@ -20,14 +20,12 @@ class Python(Command):
         )
 
         import commands
-        import history
         import mudpuppy
         from cformat import cformat
 
         self.eval_globals = globals().copy()
         self.eval_globals.update(
             {
-                "history": history,
                 "commands": commands,
                 "mudpuppy": mudpuppy,
                 "config": mudpuppy_core.config(),
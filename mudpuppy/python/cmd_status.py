@@ -63,7 +63,34 @@ class StatusCmd(Command):
         return items
 
 
+class StatsCmd(Command):
+    def __init__(self, session: int):
+        super().__init__("stats", session, self.stats, "Connection statistics")
+
+    async def stats(self, sesh_id: int, _args: Namespace):
+        stats = await mudpuppy_core.session_stats(sesh_id)
+        uptime = "not connected"
+        if stats.uptime is not None:
+            minutes, seconds = divmod(int(stats.uptime), 60)
+            hours, minutes = divmod(minutes, 60)
+            uptime = f"{hours}h {minutes}m {seconds}s"
+        await mudpuppy_core.add_outputs(
+            sesh_id,
+            [
+                OutputItem.command_result(f"Connected for: {uptime}"),
+                OutputItem.command_result(
+                    f"Received: {stats.bytes_received} bytes, "
+                    + f"{stats.lines_received} lines"
+                ),
+                OutputItem.command_result(
+                    f"Sent: {stats.bytes_sent} bytes, {stats.lines_sent} lines"
+                ),
+            ],
+        )
+
+
 @on_new_session()
 async def setup(event: Event):
     assert isinstance(event, Event.NewSession)
     add_command(event.id, StatusCmd(event.id))
+    add_command(event.id, StatsCmd(event.id))
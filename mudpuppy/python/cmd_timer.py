@@ -48,6 +48,16 @@ class TimerCmd(Command):
         add_parser.add_argument(
             "--max-ticks", help="Maximum number of ticks", required=False
         )
+        add_parser.add_argument(
+            "--once",
+            action="store_true",
+            help="Run the command once, then remove the timer",
+        )
+        add_parser.add_argument(
+            "--display",
+            action="store_true",
+            help="Show the time until the next tick in the input area",
+        )
         add_parser.add_argument(
             "command", nargs="+", help="Command to run on timer tick"
         )
@@ -74,6 +84,36 @@ class TimerCmd(Command):
         start_parser.set_defaults(func=self.start)
         start_parser.error = Command.on_error
 
+        pause_parser = subparsers.add_parser(
+            "pause",
+            help="Pause a running timer",
+            exit_on_error=False,
+            add_help=False,
+        )
+        pause_parser.add_argument("timer_id", type=int, help="Timer ID to pause")
+        pause_parser.set_defaults(func=self.pause)
+        pause_parser.error = Command.on_error
+
+        resume_parser = subparsers.add_parser(
+            "resume",
+            help="Resume a paused timer",
+            exit_on_error=False,
+            add_help=False,
+        )
+        resume_parser.add_argument("timer_id", type=int, help="Timer ID to resume")
+        resume_parser.set_defaults(func=self.resume)
+        resume_parser.error = Command.on_error
+
+        reset_parser = subparsers.add_parser(
+            "reset",
+            help="Restart a running timer's countdown from the full duration",
+            exit_on_error=False,
+            add_help=False,
+        )
+        reset_parser.add_argument("timer_id", type=int, help="Timer ID to reset")
+        reset_parser.set_defaults(func=self.reset)
+        reset_parser.error = Command.on_error
+
         remove_parser = subparsers.add_parser(
             "remove",
             help="Remove a timer",
@@ -105,6 +145,24 @@ class TimerCmd(Command):
             sesh_id, OutputItem.command_result(f"Started timer {args.timer_id}")
         )
 
+    async def pause(self, sesh_id: int, args: Namespace):
+        await mudpuppy_core.pause_timer(args.timer_id)
+        await mudpuppy_core.add_output(
+            sesh_id, OutputItem.command_result(f"Paused timer {args.timer_id}")
+        )
+
+    async def resume(self, sesh_id: int, args: Namespace):
+        await mudpuppy_core.resume_timer(args.timer_id)
+        await mudpuppy_core.add_output(
+            sesh_id, OutputItem.command_result(f"Resumed timer {args.timer_id}")
+        )
+
+    async def reset(self, sesh_id: int, args: Namespace):
+        await mudpuppy_core.reset_timer(args.timer_id)
+        await mudpuppy_core.add_output(
+            sesh_id, OutputItem.command_result(f"Reset timer {args.timer_id}")
+        )
+
     async def remove(self, sesh_id: int, args: Namespace):
         await mudpuppy_core.remove_timer(args.timer_id)
         await mudpuppy_core.add_output(
@@ -118,10 +176,15 @@ class TimerCmd(Command):
             prefix = "<green>"
             if not timer.running:
                 prefix = "<red>"
+            elif timer.paused:
+                prefix = "<yellow>"
+            remaining = timer.remaining()
+            remaining = f" Remaining={remaining}" if remaining is not None else ""
             output_items.append(
                 OutputItem.command_result(
                     cformat(
-                        f"{prefix}{timer.id}: Running={timer.running} {timer.config}<reset>"
+                        f"{prefix}{timer.id}: Running={timer.running} Paused={timer.paused}"
+                        f"{remaining} {timer.config}<reset>"
                     ),
                 )
             )
@@ -142,7 +205,14 @@ class TimerCmd(Command):
         if total_delay_ms <= 0:
             raise ValueError("The timer duration must be greater than zero.")
 
-        config = TimerConfig(args.name, total_delay_ms, callback, sesh_id)
+        config = TimerConfig(
+            args.name,
+            total_delay_ms,
+            callback,
+            sesh_id,
+            once=args.once,
+            display=args.display,
+        )
         if args.max_ticks:
             config.max_ticks = int(args.max_ticks)
         timer_id = await mudpuppy_core.new_timer(config, __name__)
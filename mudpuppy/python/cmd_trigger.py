@@ -32,6 +32,7 @@ class TriggerCmd(Command):
         list_parser.add_argument(
             "--gag", action="store_true", help="Filter by gag triggers"
         )
+        list_parser.add_argument("--group", help="Filter by group")
         list_parser.set_defaults(func=self.list)
         list_parser.error = Command.on_error
 
@@ -58,6 +59,25 @@ class TriggerCmd(Command):
             help="Match trigger with ANSI colour preserved",
             action="store_true",
         )
+        add_parser.add_argument(
+            "--priority",
+            type=int,
+            default=0,
+            help="Evaluation priority, higher first",
+        )
+        add_parser.add_argument(
+            "--stop",
+            help="Don't evaluate lower priority triggers after a match",
+            action="store_true",
+        )
+        add_parser.add_argument(
+            "--multi",
+            help="Run for each match in a line, not just the first",
+            action="store_true",
+        )
+        add_parser.add_argument("--group", help="Group to enable and disable with")
+        add_parser.add_argument("--fg", help="Recolour matched text foreground")
+        add_parser.add_argument("--bg", help="Recolour matched text background")
         add_parser.add_argument("command", nargs="*", help="Content to send on match")
         add_parser.set_defaults(func=self.add)
         add_parser.error = Command.on_error
@@ -94,6 +114,35 @@ class TriggerCmd(Command):
         enable_parser.set_defaults(func=self.enable)
         enable_parser.error = Command.on_error
 
+        test_parser = subparsers.add_parser(
+            "test",
+            help="Test a trigger pattern against sample text",
+            exit_on_error=False,
+            add_help=False,
+        )
+        test_parser.add_argument("trigger_id", type=int, help="Trigger ID to test")
+        test_parser.add_argument("sample", nargs="+", help="Text to match against")
+        test_parser.set_defaults(func=self.test)
+        test_parser.error = Command.on_error
+
+        on_parser = subparsers.add_parser(
+            "on",
+            help="Evaluate triggers",
+            exit_on_error=False,
+            add_help=False,
+        )
+        on_parser.set_defaults(func=self.on)
+        on_parser.error = Command.on_error
+
+        off_parser = subparsers.add_parser(
+            "off",
+            help="Stop evaluating all triggers",
+            exit_on_error=False,
+            add_help=False,
+        )
+        off_parser.set_defaults(func=self.off)
+        off_parser.error = Command.on_error
+
     async def run(self, sesh_id: int, args: Namespace):
         logging.debug(f"args: {args}")
         if hasattr(args, "func"):
@@ -110,6 +159,11 @@ class TriggerCmd(Command):
             gag=args.gag,
             prompt=args.prompt,
             strip_ansi=not args.ansi,
+            priority=args.priority,
+            stop=args.stop,
+            multi_match=args.multi,
+            recolor=(args.fg, args.bg) if args.fg or args.bg else None,
+            group=args.group,
         )
         expansion = " ".join(args.command).strip()
         if expansion != "":
@@ -140,20 +194,58 @@ class TriggerCmd(Command):
             OutputItem.command_result(f"Enabled trigger {args.trigger_id}"),
         )
 
+    async def test(self, sesh_id: int, args: Namespace):
+        sample = " ".join(args.sample)
+        found = await mudpuppy_core.test_trigger(sesh_id, args.trigger_id, sample)
+        if found is None:
+            await mudpuppy_core.add_output(
+                sesh_id,
+                OutputItem.failed_command_result(
+                    f"Trigger {args.trigger_id} doesn't match {sample!r}"
+                ),
+            )
+            return
+        lines = [f"Trigger {args.trigger_id} matched {found.text!r}"]
+        for idx, group in enumerate(found.groups, start=1):
+            lines.append(f"  group {idx}: {group!r}")
+        for name, group in found.named_groups.items():
+            lines.append(f"  group {name}: {group!r}")
+        await mudpuppy_core.add_outputs(
+            sesh_id, [OutputItem.command_result(line) for line in lines]
+        )
+
+    async def on(self, sesh_id: int, _args: Namespace):
+        await mudpuppy_core.set_triggers_enabled(sesh_id, True)
+        await mudpuppy_core.add_output(
+            sesh_id, OutputItem.command_result("Triggers enabled")
+        )
+
+    async def off(self, sesh_id: int, _args: Namespace):
+        await mudpuppy_core.set_triggers_enabled(sesh_id, False)
+        await mudpuppy_core.add_output(
+            sesh_id, OutputItem.command_result("Triggers disabled")
+        )
+
     async def list(self, sesh_id: int, args: Namespace):
         triggers = await mudpuppy_core.triggers(sesh_id)
         output_items = []
         for trigger in sorted(triggers, key=lambda t: t.id):
             if args.gag and not trigger.config.gag:
                 continue
+            if args.group is not None and trigger.config.group != args.group:
+                continue
 
-            if trigger.config.highlight is None:
+            if trigger.config.recolor is not None:
+                label = f"Recolor={trigger.config.recolor}"
+            elif trigger.config.highlight is None:
                 label = trigger.config.expansion
                 if trigger.config.callback is not None:
                     label = str(trigger.config.callback)
                 label = f"Action={repr(label)}"
             else:
                 label = f"Highlight={str(trigger.config.highlight)}"
+            if trigger.config.group is not None:
+                label = f"Group={trigger.config.group} {label}"
             prefix = "<green>"
             if not trigger.enabled:
                 prefix = "<red>"
@@ -1,5 +1,5 @@
+import functools
 import inspect
-import json
 import logging
 import warnings
 from typing import Any, Awaitable, Callable, List, Optional, Union
@@ -53,7 +53,7 @@ def on_gmcp(package: str, module=None):
         async def gmcp_message_wrapper(event: Event):
             assert isinstance(event, Event.GmcpMessage)
             if event.package == package:
-                await handler(event.id, json.loads(event.json))
+                await handler(event.id, event.data)
 
         return gmcp_message_wrapper
 
@@ -228,6 +228,8 @@ def alias(
     mud_name: Optional[Union[str, List[str]]] = None,
     module: Optional[str] = None,
     max_hits: Optional[int] = None,
+    priority: int = 0,
+    group: Optional[str] = None,
 ):
     def alias_decorator(handler: Callable):
         alias_name = name or handler.__name__
@@ -240,7 +242,12 @@ def alias(
             handler = alias_max_hits(handler=handler, max_hits=max_hits)
 
         alias_config = AliasConfig(
-            pattern, alias_name, expansion=expansion, callback=handler
+            pattern,
+            alias_name,
+            expansion=expansion,
+            callback=handler,
+            priority=priority,
+            group=group,
         )
 
         if mud_name:
@@ -293,6 +300,11 @@ def trigger(
     mud_name: Optional[Union[str, List[str]]] = None,
     module: Optional[str] = None,
     max_hits: Optional[int] = None,
+    priority: int = 0,
+    stop: bool = False,
+    multi_match: bool = False,
+    important: bool = False,
+    group: Optional[str] = None,
 ):
     def trigger_decorator(handler: TriggerCallable):
         trigger_name = name or handler.__name__
@@ -312,6 +324,11 @@ def trigger(
             strip_ansi=strip_ansi,
             expansion=expansion,
             callback=handler,
+            priority=priority,
+            stop=stop,
+            multi_match=multi_match,
+            important=important,
+            group=group,
         )
 
         if mud_name:
@@ -361,6 +378,7 @@ def highlight(
     strip_ansi: bool = True,
     mud_name: Optional[Union[str, list[str]]] = None,
     module: Optional[str] = None,
+    priority: int = 0,
 ):
     def highlight_decorator(handler: HighlightCallable):
         highlight_name = name or handler.__name__
@@ -368,7 +386,11 @@ def highlight(
             raise ValueError("pattern and name must be non-empty")
 
         trigger_config = TriggerConfig(
-            pattern, highlight_name, strip_ansi=strip_ansi, highlight=handler
+            pattern,
+            highlight_name,
+            strip_ansi=strip_ansi,
+            highlight=handler,
+            priority=priority,
         )
 
         if mud_name:
@@ -415,6 +437,8 @@ def timer(
     minutes: int = 0,
     hours: int = 0,
     max_ticks: Optional[int] = None,
+    once: bool = False,
+    display: bool = False,
     mud_name: Optional[Union[str, list[str]]] = None,
     module: Optional[str] = None,
 ):
@@ -434,7 +458,9 @@ def timer(
 
         logging.debug(f"timer {timer_name} will run every {total_delay_ms} ms")
 
-        timer_config = TimerConfig(timer_name, total_delay_ms, handler)
+        timer_config = TimerConfig(
+            timer_name, total_delay_ms, handler, once=once, display=display
+        )
         if max_ticks:
             timer_config.max_ticks = max_ticks
         logging.debug(f"config: {timer_config}")
@@ -509,11 +535,14 @@ def custom_showwarning(message, category, filename, lineno, _file=None, _line=No
 def trigger_max_hits(*, handler: TriggerCallable, max_hits: int = 1) -> TriggerCallable:
     __ensure_async(handler)
 
+    # The wrapper has the handler's signature, so it's only passed named groups if the
+    # handler accepts them.
+    @functools.wraps(handler)
     async def trigger_max_hits_wrapper(
-        session_id: int, trigger_id: int, line: str, groups: list[str]
+        session_id: int, trigger_id: int, line: str, groups: list[str], *named
     ):
         # Call the wrapped handler.
-        await handler(session_id, trigger_id, line, groups)
+        await handler(session_id, trigger_id, line, groups, *named)
 
         trigger = await mudpuppy_core.get_trigger(session_id, trigger_id)
         if trigger is None:
@@ -532,11 +561,12 @@ def trigger_max_hits(*, handler: TriggerCallable, max_hits: int = 1) -> TriggerC
 def alias_max_hits(*, handler: AliasCallable, max_hits: int = 1) -> AliasCallable:
     __ensure_async(handler)
 
+    @functools.wraps(handler)
     async def alias_max_hits_wrapper(
-        session_id: int, alias_id: int, line: str, groups: list[str]
+        session_id: int, alias_id: int, line: str, groups: list[str], *named
     ):
         # Call the wrapped handler.
-        await handler(session_id, alias_id, line, groups)
+        await handler(session_id, alias_id, line, groups, *named)
 
         alias = await mudpuppy_core.get_alias(session_id, alias_id)
         if alias is None:
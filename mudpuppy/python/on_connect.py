@@ -0,0 +1,81 @@
+import asyncio
+import logging
+
+from mudpuppy_core import Event, OutputItem, Status, mudpuppy_core
+
+from mudpuppy import on_connected
+
+
+class OnConnectCommands:
+    """
+    Sends the `Mud.on_connect_commands` (or `Mud.on_reconnect_commands`) configured
+    for a MUD after a session connects.
+    """
+
+    def __init__(self):
+        # Session IDs that have connected before, to tell reconnects apart.
+        self.connected_before: set[int] = set()
+        # Incremented for each connection of a session, so an in-progress sequence
+        # can tell it was superseded by a newer connection.
+        self.generation: dict[int, int] = {}
+
+    async def on_connect(self, session_id: int):
+        reconnect = session_id in self.connected_before
+        self.connected_before.add(session_id)
+        generation = self.generation.get(session_id, 0) + 1
+        self.generation[session_id] = generation
+
+        mud = await mudpuppy_core.mud_config(session_id)
+        if mud is None:
+            return
+
+        commands = mud.on_connect_commands
+        if reconnect and mud.on_reconnect_commands is not None:
+            commands = mud.on_reconnect_commands
+        if not commands:
+            return
+
+        logging.debug(
+            f"on_connect: sending {len(commands)} commands for session {session_id} (reconnect: {reconnect})"
+        )
+        for command in commands:
+            # Give the MUD a moment to settle before each command.
+            await asyncio.sleep(mud.on_connect_delay_ms / 1000)
+
+            # Stop if the connection dropped, or was replaced, while we were waiting.
+            status = await mudpuppy_core.status(session_id)
+            if (
+                not isinstance(status, Status.Connected)
+                or self.generation.get(session_id) != generation
+            ):
+                logging.warning(
+                    f"on_connect: session {session_id} disconnected, not sending remaining commands"
+                )
+                return
+
+            try:
+                if mud.credential is not None:
+                    command = command.replace(
+                        "{credential}", f"{{credential:{mud.credential}}}"
+                    )
+                await mudpuppy_core.send_line(
+                    session_id, command, expand_secrets=True
+                )
+            except Exception as e:
+                logging.error(f"on_connect: session {session_id} send failed: {e}")
+                await mudpuppy_core.add_output(
+                    session_id,
+                    OutputItem.failed_command_result(
+                        f"on_connect_commands stopped, sending failed: {e}"
+                    ),
+                )
+                return
+
+
+@on_connected()
+async def connected(event: Event):
+    assert isinstance(event, Event.Connection)
+    await handler.on_connect(event.id)
+
+
+handler = OnConnectCommands()
@@ -1,7 +1,9 @@
+use std::collections::VecDeque;
 use std::fmt::{Debug, Formatter};
 use std::io::{self, stdout};
 use std::num::NonZeroUsize;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use futures::channel::mpsc::{channel as futures_channel, Receiver};
@@ -12,7 +14,10 @@ use notify::{
 };
 use pyo3::{Py, PyObject, PyResult, Python};
 use ratatui::backend::{Backend, CrosstermBackend};
-use ratatui::crossterm::event::{Event as TermEvent, KeyEventKind};
+use ratatui::crossterm::event::{
+    DisableBracketedPaste, DisableFocusChange, EnableBracketedPaste, EnableFocusChange,
+    Event as TermEvent, KeyEventKind,
+};
 use ratatui::crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
@@ -25,23 +30,27 @@ use ratatui::widgets::{Block, Borders, Clear, Paragraph, Tabs};
 use ratatui::{crossterm, Frame, Terminal};
 use tokio::select;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
-use tokio::sync::RwLock;
-use tokio::time::{interval, MissedTickBehavior};
+use tokio::sync::{RwLock, Semaphore};
+use tokio::time::{interval, interval_at, Instant, Interval, MissedTickBehavior};
 use tracing::{error, info, instrument, trace, warn, Level};
 
-use crate::client::Client;
+use crate::client::{Client, TaskEvent};
 use crate::config::{config_dir, config_file, GlobalConfig};
 use crate::error::Error;
 use crate::idmap::IdMap;
 use crate::model::{InputMode, Mud, SessionInfo, Shortcut, Timer};
 use crate::net::connection;
+use crate::notify::Notifier;
 use crate::python::{self, PyApp};
+use crate::tui::dialog::{Answer, Dialog};
 use crate::tui::{mudlist, session};
 use crate::{cli, Result, CRATE_NAME};
 
 pub struct App {
     config: GlobalConfig,
     tabs: Vec<Box<dyn Tab>>,
+    /// Whether user Python modules were skipped at startup.
+    safe_mode: bool,
 }
 
 impl App {
@@ -50,6 +59,7 @@ impl App {
         Self {
             config: config.clone(),
             tabs: vec![Box::new(mudlist::Widget::new(config))],
+            safe_mode: false,
         }
     }
 
@@ -71,10 +81,12 @@ impl App {
 
         let (event_tx, mut event_rx) = unbounded_channel();
         let (conn_tx, mut conn_rx) = unbounded_channel();
+        let (task_tx, mut task_rx) = unbounded_channel();
         let state_lock = Arc::new(RwLock::new(State::new(
             self.config.clone(),
             event_tx.clone(),
             conn_tx,
+            task_tx,
         )));
 
         let mut crossterm_events = crossterm::event::EventStream::new();
@@ -89,7 +101,8 @@ impl App {
         };
 
         info!("initializing python environment");
-        let (event_handlers, py_user_modules) = match python::init(py_app) {
+        self.safe_mode = args.safe_mode;
+        let (event_handlers, py_user_modules) = match python::init(py_app, self.safe_mode) {
             Ok((event_handlers, py_user_modules)) => (event_handlers, py_user_modules),
             Err(err) => {
                 error!("{}", err);
@@ -103,11 +116,12 @@ impl App {
         let mut event_futures: FuturesUnordered<python::PyFuture> = FuturesUnordered::new();
         let mut draw_interval = interval(args.frame_rate_duration()?);
         draw_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let mut global_tick = GlobalTick::new(self.config.tick_interval());
 
         if !args.connect.is_empty() {
             let mut state = state_lock.write().await;
-            for mud_name in args.connect {
-                let mud = state.config.must_lookup_mud(&mud_name)?;
+            for mud_name in &args.connect {
+                let mud = state.config.must_lookup_mud(mud_name)?;
                 let session_info = state.new_session(mud)?;
                 info!("created new session {session_info}");
                 self.handle_tab_action(
@@ -121,6 +135,17 @@ impl App {
             }
         }
 
+        if let (Some(path), Some(mud_name)) = (&args.replay, &args.replay_mud) {
+            let mut state = state_lock.write().await;
+            let mud = state.config.must_lookup_mud(mud_name)?;
+            let session_info = state.new_session(mud)?;
+            info!("created new replay session {session_info}");
+            self.new_tab(&mut state, &session_info, true)?;
+            if let Some(client) = state.clients.get_mut(session_info.id) {
+                client.replay(path, args.replay_interval()?)?;
+            }
+        }
+
         loop {
             let mut state = state_lock.write().await;
 
@@ -133,9 +158,12 @@ impl App {
                     event_futures.clear();
 
                     state.timers.clear();
+                    state.dialogs.clear();
                     for client in state.clients.values_mut() {
                         client.triggers.clear();
                         client.aliases.clear();
+                        client.subnegotiation_handlers.clear();
+                        client.input_transforms.clear();
                     }
 
                     trace!("reloading python modules");
@@ -152,10 +180,22 @@ impl App {
                 _ => {}
             }
 
+            while let Some(action) = state.tab_requests.pop_front() {
+                if let Err(err) = self.handle_tab_action(&mut state, action).await {
+                    error!("{err}");
+                    state.ui_state = err.into();
+                }
+            }
+
             let res = select! {
                  _ = draw_interval.tick() => {
+                    let res = self.expire_dialogs(&mut state, &mut event_futures);
                     self.draw(&mut state, &mut terminal);
-                    Ok(())
+                    res
+                }
+                () = global_tick.tick(), if global_tick.enabled() => {
+                    event_tx.send(python::Event::GlobalTick { count: global_tick.count })
+                        .map_err(Into::into)
                 }
                 Some(()) = python_callback_rx.recv() => {
                     Ok(())
@@ -165,13 +205,18 @@ impl App {
                     res.map(|_| ()).map_err(Into::into)
                 }
                 Some(event) = event_rx.recv() => {
-                    dispatch_event(&event_handlers, &event, &mut event_futures)
+                    dispatch_event(&event_handlers, &self.config, &event, &mut event_futures)
                 }
                 Some(event) = conn_rx.recv() => {
-                    if let Some(client) = state.clients.get_mut(event.session_id) {
-                        client.process_event(event.event, &mut event_futures)
-                    } else {
-                        Ok(())
+                    match state.clients.get_mut(event.session_id) {
+                        Some(client) => client.process_event(event.event, &mut event_futures),
+                        None => Ok(()),
+                    }
+                }
+                Some(event) = task_rx.recv() => {
+                    match state.clients.get_mut(event.session_id) {
+                        Some(client) => client.process_task(event.task),
+                        None => Ok(()),
                     }
                 }
                 Some(Ok(event)) = crossterm_events.next().fuse() => {
@@ -186,6 +231,7 @@ impl App {
                 Some(event) = config_event_rx.next() => {
                     if let Ok(event) = event {
                         config_reload_event(&self.config, &mut self.tabs, &mut state, &event);
+                        global_tick.set_period(self.config.tick_interval());
                     }
                     Ok(())
                }
@@ -200,11 +246,52 @@ impl App {
         // TODO(XXX): Dumb and serial. Should do this in parallel.
         for client in state_lock.write().await.clients.values_mut() {
             client.disconnect().await?;
+            if let Err(err) = client.save_history() {
+                warn!("failed to save input history for {}: {err}", client.info);
+            }
         }
 
         restore_terminal()
     }
 
+    // Dismiss dialogs that have timed out without an answer.
+    fn expire_dialogs(
+        &self,
+        state: &mut State,
+        event_futures: &mut FuturesUnordered<python::PyFuture>,
+    ) -> Result<(), Error> {
+        let now = Instant::now();
+        while let Some(index) = state.dialogs.iter().position(|d| d.expired(now)) {
+            // Safety: position returned an index in bounds.
+            let dialog = state.dialogs.remove(index).unwrap();
+            trace!("dialog for session {} timed out", dialog.session_id);
+            self.answer_dialog(dialog, Answer::Cancel, event_futures)?;
+        }
+        Ok(())
+    }
+
+    fn answer_dialog(
+        &self,
+        dialog: Dialog,
+        answer: Answer,
+        event_futures: &mut FuturesUnordered<python::PyFuture>,
+    ) -> Result<(), Error> {
+        trace!("answering dialog {:?} with {answer:?}", dialog.message);
+        Python::with_gil(|py| {
+            let label = format!(
+                "dialog callback {}",
+                python::callable_label(dialog.callback.bind(py))
+            );
+            let awaitable = dialog.answer(py, answer)?;
+            event_futures.push(python::timed_future(
+                &self.config,
+                label,
+                awaitable.into_bound(py),
+            )?);
+            Ok(())
+        })
+    }
+
     fn draw(&mut self, state: &mut State, terminal: &mut Terminal<impl Backend>) {
         terminal
             .draw(|frame| {
@@ -216,6 +303,10 @@ impl App {
                     state.ui_state = err.into();
                 }
 
+                if let Some(dialog) = state.dialogs.front() {
+                    dialog.draw(frame, area);
+                }
+
                 if let UiState::Error(error) = &state.ui_state {
                     draw_error_popup(frame, area, error);
                 }
@@ -243,7 +334,24 @@ impl App {
                         .ok_or(Error::UnknownSession(sesh_id))?;
                     let sesh_focused = state.selected_tab == tab_id;
 
-                    if sesh.output.new_data > 0 && !sesh_focused {
+                    if sesh.output.important && !sesh_focused {
+                        // Important output, e.g. a tell, stands out from ordinary output.
+                        vec![
+                            Span::styled(
+                                tab.title().to_string(),
+                                Style::default()
+                                    .fg(Color::LightRed)
+                                    .add_modifier(Modifier::BOLD),
+                            ),
+                            Span::styled(
+                                format!(" [{}!]", sesh.output.new_data),
+                                Style::default()
+                                    .fg(Color::LightRed)
+                                    .add_modifier(Modifier::BOLD),
+                            ),
+                        ]
+                        .into()
+                    } else if sesh.output.new_data > 0 && !sesh_focused {
                         vec![
                             Span::styled(
                                 tab.title().to_string(),
@@ -273,14 +381,17 @@ impl App {
             });
         }
 
+        let mut title = Line::from(CRATE_NAME.to_uppercase());
+        if self.safe_mode {
+            title.push_span(Span::styled(
+                " [SAFE MODE: user scripts not loaded]",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ));
+        }
         let tabs = Tabs::new(titles)
             .select(state.selected_tab)
             .highlight_style(Style::default().fg(Color::Black).bg(Color::LightMagenta))
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title(CRATE_NAME.to_uppercase()),
-            );
+            .block(Block::default().borders(Borders::ALL).title(title));
         frame.render_widget(tabs, tab_bar);
         Ok(())
     }
@@ -292,6 +403,13 @@ impl App {
         event_futures: &mut FuturesUnordered<python::PyFuture>,
         event: &TermEvent,
     ) -> Result<Option<TabAction>, Error> {
+        if let TermEvent::FocusGained | TermEvent::FocusLost = event {
+            state.event_tx.send(python::Event::Focus {
+                focused: matches!(event, TermEvent::FocusGained),
+            })?;
+            return Ok(None);
+        }
+
         let Some(current_tab) = self.tabs.get_mut(state.selected_tab) else {
             return Ok(None);
         };
@@ -324,6 +442,15 @@ impl App {
             }
         }
 
+        if let Some(dialog) = state.dialogs.front_mut() {
+            if let Some(answer) = dialog.kind.key(key_event) {
+                // Safety: we just checked there's a front dialog.
+                let dialog = state.dialogs.pop_front().unwrap();
+                self.answer_dialog(dialog, answer, event_futures)?;
+            }
+            return Ok(None); // Eat the keypress
+        }
+
         let Some(shortcut) = self.config.key_binding(current_tab.input_mode(), key_event) else {
             return current_tab.term_event(state, event_futures, event);
         };
@@ -368,7 +495,7 @@ impl App {
             TabAction::New {
                 session_info,
                 switch,
-            } => return self.new_session(state, session_info, switch).await,
+            } => return self.new_session(state, &session_info, switch),
             TabAction::Next => {
                 state.selected_tab = (state.selected_tab + 1) % self.tabs.len();
             }
@@ -376,8 +503,11 @@ impl App {
                 state.selected_tab = (state.selected_tab + self.tabs.len() - 1) % self.tabs.len();
             }
             TabAction::Close => {
-                self.tabs.remove(state.selected_tab);
+                let tab = self.tabs.remove(state.selected_tab);
                 state.selected_tab = state.selected_tab.saturating_sub(1);
+                if let Some(session_id) = tab.session_id() {
+                    state.close_session(session_id).await?;
+                }
             }
             TabAction::SwapLeft => {
                 if state.selected_tab > 1 {
@@ -391,12 +521,24 @@ impl App {
                     state.selected_tab += 1;
                 }
             }
+            TabAction::Focus { session_id } => {
+                let Some(tab_id) = self
+                    .tabs
+                    .iter()
+                    .position(|tab| tab.session_id() == Some(session_id))
+                else {
+                    warn!("no tab for session {session_id} to focus");
+                    return Ok(());
+                };
+                state.selected_tab = tab_id;
+            }
         }
 
         state.active_session_id = self
             .tabs
             .get(state.selected_tab)
             .and_then(|tab| tab.session_id());
+        state.active_tab = state.active_session_id;
 
         let new_title = self
             .tabs
@@ -408,10 +550,28 @@ impl App {
         Ok(())
     }
 
-    async fn new_session(
+    fn new_session(
         &mut self,
         state: &mut State,
-        session_info: Arc<SessionInfo>,
+        session_info: &Arc<SessionInfo>,
+        switch: bool,
+    ) -> Result<(), Error> {
+        self.new_tab(state, session_info, switch)?;
+
+        let clients = &mut state.clients;
+        let Some(client) = clients.get_mut(session_info.id) else {
+            warn!("missing client for new tab action: {session_info}");
+            return Ok(());
+        };
+
+        client.connect()?;
+        Ok(())
+    }
+
+    fn new_tab(
+        &mut self,
+        state: &mut State,
+        session_info: &Arc<SessionInfo>,
         switch: bool,
     ) -> Result<(), Error> {
         trace!("creating new session tab for {session_info}");
@@ -424,6 +584,7 @@ impl App {
 
         if switch {
             state.selected_tab = self.tabs.len() - 1;
+            state.active_tab = Some(session_info.id);
             let new_title = self
                 .tabs
                 .get(state.selected_tab)
@@ -432,13 +593,6 @@ impl App {
             info!("switched to tab {} ({})", state.selected_tab, new_title);
         }
 
-        let clients = &mut state.clients;
-        let Some(client) = clients.get_mut(session_info.id) else {
-            warn!("missing client for new tab action: {session_info}");
-            return Ok(());
-        };
-
-        client.connect().await?;
         Ok(())
     }
 }
@@ -474,8 +628,53 @@ fn draw_error_popup(frame: &mut Frame, area: Rect, error: &Error) {
     frame.render_widget(help_paragraph, help);
 }
 
+/// A clock for emitting `GlobalTick` events at the configured interval.
+struct GlobalTick {
+    interval: Option<Interval>,
+    /// The number of ticks so far.
+    count: u64,
+}
+
+impl GlobalTick {
+    fn new(period: Option<Duration>) -> Self {
+        let mut tick = Self {
+            interval: None,
+            count: 0,
+        };
+        tick.set_period(period);
+        tick
+    }
+
+    fn enabled(&self) -> bool {
+        self.interval.is_some()
+    }
+
+    /// Change how often ticks happen, or stop them if `None`. The count carries on.
+    fn set_period(&mut self, period: Option<Duration>) {
+        if self.interval.as_ref().map(Interval::period) == period {
+            return;
+        }
+        self.interval = period.map(|period| {
+            // The first tick of an interval is immediate, but the first event should come a
+            // full period after starting.
+            let mut interval = interval_at(Instant::now() + period, period);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            interval
+        });
+    }
+
+    /// Wait for the next tick. Must only be called while enabled.
+    async fn tick(&mut self) {
+        if let Some(interval) = &mut self.interval {
+            interval.tick().await;
+            self.count = self.count.wrapping_add(1);
+        }
+    }
+}
+
 fn dispatch_event(
     event_handlers: &Py<python::EventHandlers>,
+    config: &GlobalConfig,
     event: &python::Event,
     futures: &mut FuturesUnordered<python::PyFuture>,
 ) -> Result<(), Error> {
@@ -484,7 +683,7 @@ fn dispatch_event(
         event_handlers
             .bind(py)
             .borrow()
-            .dispatch(py, event, futures)
+            .dispatch(py, config, event, futures)
     })
 }
 
@@ -553,7 +752,7 @@ pub trait Tab: Debug + Send + Sync {
 
     fn input_mode(&self) -> InputMode;
 
-    fn title(&self) -> Line;
+    fn title(&self) -> Line<'_>;
 
     #[must_use]
     fn session_id(&self) -> Option<u32> {
@@ -623,12 +822,21 @@ pub struct State {
     pub ui_state: UiState,
     pub event_tx: UnboundedSender<python::Event>,
     pub active_session_id: Option<u32>,
+    /// The session ID of the focused tab, or `None` while the MUD list is focused.
+    pub active_tab: Option<u32>,
+    /// Tab actions requested by Python, carried out in order by the event loop.
+    pub tab_requests: VecDeque<TabAction>,
+    pub notifier: Notifier,
     pub timers: IdMap<Timer>,
+    /// Dialogs opened by Python, shown one at a time in the order they were opened.
+    pub dialogs: VecDeque<Dialog>,
 
     config: GlobalConfig,
     selected_tab: usize,
     clients: IdMap<Client>,
     conn_tx: UnboundedSender<connection::Event>,
+    task_tx: UnboundedSender<TaskEvent>,
+    connect_limit: Option<Arc<Semaphore>>,
 }
 
 impl State {
@@ -637,16 +845,26 @@ impl State {
         config: GlobalConfig,
         event_tx: UnboundedSender<python::Event>,
         conn_tx: UnboundedSender<connection::Event>,
+        task_tx: UnboundedSender<TaskEvent>,
     ) -> Self {
+        let connect_limit = config
+            .max_concurrent_connects()
+            .map(|limit| Arc::new(Semaphore::new(limit)));
         Self {
             ui_state: UiState::default(),
             event_tx,
             active_session_id: None,
+            active_tab: None,
+            tab_requests: VecDeque::default(),
+            notifier: Notifier::default(),
             timers: IdMap::default(),
+            dialogs: VecDeque::default(),
             config,
             selected_tab: 0,
             clients: IdMap::default(),
             conn_tx,
+            task_tx,
+            connect_limit,
         }
     }
 
@@ -662,6 +880,8 @@ impl State {
                 self.config.clone(),
                 self.event_tx.clone(),
                 self.conn_tx.clone(),
+                self.task_tx.clone(),
+                self.connect_limit.clone(),
             )
         });
 
@@ -683,10 +903,34 @@ impl State {
         Ok(info)
     }
 
+    /// Disconnect the session with the given ID and drop its client, along with its
+    /// `session_data()` dict. Input history is saved first.
+    ///
+    /// # Errors
+    /// If the session can't be disconnected.
+    pub async fn close_session(&mut self, session_id: u32) -> Result<(), Error> {
+        let Some(client) = self.clients.get_mut(session_id) else {
+            return Ok(());
+        };
+        client.disconnect().await?;
+        if let Err(err) = client.save_history() {
+            warn!("failed to save input history for {}: {err}", client.info);
+        }
+        self.clients.remove(session_id);
+        // Nobody's left to answer the session's dialogs.
+        self.dialogs
+            .retain(|dialog| dialog.session_id != session_id);
+        Ok(())
+    }
+
     pub fn client_for_id_mut(&mut self, session_id: u32) -> Option<&mut Client> {
         self.clients.get_mut(session_id)
     }
 
+    pub fn clients_mut(&mut self) -> impl Iterator<Item = &mut Client> {
+        self.clients.values_mut()
+    }
+
     pub fn client_for_id(&self, session_id: u32) -> Option<&Client> {
         self.clients.get(session_id)
     }
@@ -695,6 +939,14 @@ impl State {
         self.clients.ids()
     }
 
+    /// Returns the clients for sessions of the MUD named `mud_name`.
+    pub fn clients_for_mud<'a>(&'a self, mud_name: &'a str) -> impl Iterator<Item = &'a Client> {
+        self.clients
+            .iter()
+            .map(|(_, client)| client)
+            .filter(move |client| client.info.mud_name == mud_name)
+    }
+
     pub fn all_client_info(&self) -> Vec<SessionInfo> {
         self.clients
             .iter()
@@ -711,6 +963,7 @@ impl Debug for State {
             .field("clients", &self.clients)
             .field("timers", &self.timers)
             .field("active_session_id", &self.active_session_id)
+            .field("active_tab", &self.active_tab)
             .finish_non_exhaustive()
     }
 }
@@ -741,11 +994,19 @@ pub enum TabAction {
     Prev,
     SwapLeft,
     SwapRight,
+    /// Focus the tab showing the given session.
+    Focus {
+        session_id: u32,
+    },
 }
 
 fn init_terminal() -> io::Result<Terminal<impl Backend>> {
     enable_raw_mode()?;
     stdout().execute(EnterAlternateScreen)?;
+    // Deliver pastes as a single event, rather than a key press per character.
+    stdout().execute(EnableBracketedPaste)?;
+    // Report when the terminal gains or loses focus, for `Focus` events.
+    stdout().execute(EnableFocusChange)?;
     // increase the cache size to avoid flickering for indeterminate layouts
     Layout::init_cache(NonZeroUsize::new(100).unwrap());
     Terminal::new(CrosstermBackend::new(stdout()))
@@ -753,6 +1014,8 @@ fn init_terminal() -> io::Result<Terminal<impl Backend>> {
 
 pub(crate) fn restore_terminal() -> Result<()> {
     disable_raw_mode()?;
+    stdout().execute(DisableFocusChange)?;
+    stdout().execute(DisableBracketedPaste)?;
     stdout().execute(LeaveAlternateScreen)?;
     Ok(())
 }
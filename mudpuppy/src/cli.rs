@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::time::Duration;
 
 use clap::Parser;
@@ -35,6 +36,36 @@ pub struct Args {
         help = "Log level filter. Default is INFO"
     )]
     pub log_level: LevelFilter,
+
+    #[arg(
+        long,
+        help = "Skip loading user Python modules from the config directory, e.g. to recover from a broken script"
+    )]
+    pub safe_mode: bool,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "replay_mud",
+        help = "Replay captured output (e.g. a session log) through the triggers of a new disconnected session"
+    )]
+    pub replay: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "MUD_NAME",
+        requires = "replay",
+        help = "MUD name whose config and scripts are used for the --replay session"
+    )]
+    pub replay_mud: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "FLOAT",
+        requires = "replay",
+        help = "Replay at a fixed number of lines per second, instead of the captured timing"
+    )]
+    pub replay_rate: Option<f64>,
     // If you add new CLI args, don't forget to update `user-guide/src/cli.md`.
 }
 
@@ -55,4 +86,22 @@ impl Args {
             (1_000_000.0 / self.frame_rate) as u64,
         ))
     }
+
+    /// Returns the time to wait between replayed lines, or `None` to keep the captured timing.
+    ///
+    /// # Errors
+    /// If the replay rate is not greater than 0, or so small the interval overflows
+    pub fn replay_interval(&self) -> Result<Option<Duration>> {
+        let Some(rate) = self.replay_rate else {
+            return Ok(None);
+        };
+        if rate <= 0.0 || !rate.is_finite() {
+            return Err(Error::Cli(format!(
+                "replay_rate must be greater than 0. Provided: {rate}"
+            )));
+        }
+        Duration::try_from_secs_f64(1.0 / rate)
+            .map(Some)
+            .map_err(|_| Error::Cli(format!("replay_rate is too small. Provided: {rate}")))
+    }
 }
@@ -0,0 +1,70 @@
+use regex::Regex;
+
+use crate::error::Error;
+use crate::model::MudLine;
+
+/// A block of output lines being gagged and collected until a sentinel line, e.g. the
+/// lines of a score screen.
+#[derive(Debug)]
+pub(super) struct BlockCapture {
+    /// Matched against the stripped text of each line. The first matching line is the last
+    /// line of the block.
+    until: Regex,
+    /// The most lines collected before giving up on seeing the sentinel.
+    max_lines: usize,
+    lines: Vec<MudLine>,
+}
+
+impl BlockCapture {
+    /// # Errors
+    /// If the `until` pattern is an invalid regex.
+    pub(super) fn new(until: &str, max_lines: usize) -> Result<Self, Error> {
+        Ok(Self {
+            until: Regex::new(until).map_err(Error::CapturePattern)?,
+            max_lines,
+            lines: Vec::default(),
+        })
+    }
+
+    /// Collect `line`, returning true if it completes the block because it's the sentinel,
+    /// or because `max_lines` were collected.
+    pub(super) fn push(&mut self, line: MudLine) -> bool {
+        let sentinel = self.until.is_match(&line.stripped());
+        self.lines.push(line);
+        sentinel || self.lines.len() >= self.max_lines
+    }
+
+    /// Returns the collected lines, oldest first.
+    pub(super) fn into_lines(self) -> Vec<MudLine> {
+        self.lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio_util::bytes::Bytes;
+
+    use super::*;
+
+    fn line(text: &'static str) -> MudLine {
+        MudLine::from(Bytes::from_static(text.as_bytes()))
+    }
+
+    #[test]
+    fn collects_until_sentinel_or_limit() {
+        let mut capture = BlockCapture::new(r"^-+$", 10).unwrap();
+        assert!(!capture.push(line("Name: Ayla")));
+        assert!(!capture.push(line("\x1b[33mLevel:\x1b[0m 12")));
+        // The sentinel is matched without ANSI colour, and is part of the block.
+        assert!(capture.push(line("\x1b[1m------\x1b[0m")));
+        let lines = capture.into_lines();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].stripped(), "Level: 12");
+
+        let mut capture = BlockCapture::new(r"^-+$", 2).unwrap();
+        assert!(!capture.push(line("one")));
+        assert!(capture.push(line("two")));
+
+        assert!(BlockCapture::new("(", 2).is_err());
+    }
+}
@@ -0,0 +1,249 @@
+use std::fmt::{self, Display, Formatter};
+
+use encoding_rs::{EncoderResult, UTF_8};
+use tokio_util::bytes::Bytes;
+use tracing::{debug, info};
+
+use crate::net::telnet;
+use crate::net::telnet::codec::Item as TelnetItem;
+
+const REQUEST: u8 = 1;
+const ACCEPTED: u8 = 2;
+const REJECTED: u8 = 3;
+
+// A REQUEST may start with this marker and a version byte, offering translation tables.
+const TTABLE_MARKER: &[u8] = b"[TTABLE]";
+
+/// Negotiates the character set used by a MUD with the telnet CHARSET option (RFC 2066),
+/// and converts text between it and UTF-8.
+#[derive(Debug)]
+pub struct Charset {
+    preferred: Vec<Encoding>,
+    encoding: Encoding,
+}
+
+impl Charset {
+    /// Create a charset negotiator that accepts the `preferred` charset names, most preferred
+    /// first. Unsupported names are ignored.
+    ///
+    /// Until a charset is agreed the first preferred charset is used, or UTF-8 if there
+    /// isn't one.
+    #[must_use]
+    pub fn new(preferred: &[String]) -> Self {
+        let preferred = preferred
+            .iter()
+            .filter_map(|name| Encoding::from_name(name))
+            .collect::<Vec<_>>();
+        Self {
+            encoding: preferred.first().copied().unwrap_or_default(),
+            preferred,
+        }
+    }
+
+    /// Returns the encoding used for text received from, and sent to, the MUD.
+    #[must_use]
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Handle a CHARSET subnegotiation from the MUD, returning the reply to send if any.
+    ///
+    /// A REQUEST is accepted with the most preferred charset that the MUD offered, or
+    /// rejected if none of them are preferred.
+    pub fn handle(&mut self, data: &[u8]) -> Option<TelnetItem> {
+        let Some((&REQUEST, mut offer)) = data.split_first() else {
+            debug!("ignoring CHARSET subnegotiation {data:?}");
+            return None;
+        };
+        if let Some(rest) = offer.strip_prefix(TTABLE_MARKER) {
+            // Skip the version byte. Translation tables aren't supported.
+            offer = rest.get(1..).unwrap_or_default();
+        }
+        let (&separator, names) = offer.split_first()?;
+        let offered = names
+            .split(|b| *b == separator)
+            .filter_map(|name| {
+                let name = String::from_utf8_lossy(name);
+                Encoding::from_name(&name).map(|encoding| (encoding, name.into_owned()))
+            })
+            .collect::<Vec<_>>();
+        debug!("MUD offered charsets {offered:?}");
+
+        let accepted = self.preferred.iter().find_map(|preferred| {
+            offered
+                .iter()
+                .find(|(encoding, _)| encoding == preferred)
+                .map(|(encoding, name)| (*encoding, name))
+        });
+        let reply = if let Some((encoding, name)) = accepted {
+            info!("accepted charset {name}");
+            self.encoding = encoding;
+            let mut reply = vec![ACCEPTED];
+            reply.extend_from_slice(name.as_bytes());
+            reply
+        } else {
+            info!("rejected charsets, none preferred");
+            vec![REJECTED]
+        };
+        Some(TelnetItem::Subnegotiation(
+            telnet::option::CHARSET,
+            reply.into(),
+        ))
+    }
+
+    /// Convert text received from the MUD to UTF-8. Malformed text is replaced with U+FFFD.
+    #[must_use]
+    pub fn decode(&self, data: Bytes) -> Bytes {
+        if self.encoding == Encoding::UTF_8 {
+            // Invalid UTF-8 is replaced when the line's text is used.
+            return data;
+        }
+        let (text, _) = self.encoding.0.decode_without_bom_handling(&data);
+        text.into_owned().into()
+    }
+
+    /// Convert text to send to the MUD from UTF-8. Characters that can't be represented are
+    /// replaced with `?`.
+    #[must_use]
+    pub fn encode(&self, text: &str) -> Bytes {
+        if self.encoding == Encoding::UTF_8 {
+            return Bytes::copy_from_slice(text.as_bytes());
+        }
+        let mut encoder = self.encoding.0.new_encoder();
+        let mut out = Vec::with_capacity(text.len());
+        let mut rest = text;
+        loop {
+            if let Some(len) = encoder.max_buffer_length_from_utf8_without_replacement(rest.len()) {
+                out.reserve(len);
+            }
+            let (result, read) =
+                encoder.encode_from_utf8_to_vec_without_replacement(rest, &mut out, true);
+            rest = &rest[read..];
+            match result {
+                EncoderResult::InputEmpty => return out.into(),
+                EncoderResult::Unmappable(_) => out.push(b'?'),
+                EncoderResult::OutputFull => {}
+            }
+        }
+    }
+}
+
+/// A character set supported for CHARSET negotiation: any of the ASCII compatible encodings
+/// in the [Encoding Standard](https://encoding.spec.whatwg.org/#names-and-labels).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Encoding(&'static encoding_rs::Encoding);
+
+impl Encoding {
+    pub const UTF_8: Self = Self(UTF_8);
+
+    /// Look up an encoding by one of its (case-insensitive) names or labels.
+    ///
+    /// Following the Encoding Standard, `US-ASCII` and `ISO-8859-1` are decoded as
+    /// `windows-1252`, which is a superset of both. UTF-16 isn't supported, since telnet
+    /// lines can't be split in it.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        encoding_rs::Encoding::for_label(name.trim().as_bytes())
+            .filter(|encoding| encoding.output_encoding() == *encoding)
+            .map(Self)
+    }
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self::UTF_8
+    }
+}
+
+impl Display for Encoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(item: Option<TelnetItem>) -> Vec<u8> {
+        match item {
+            Some(TelnetItem::Subnegotiation(telnet::option::CHARSET, data)) => data.to_vec(),
+            item => panic!("unexpected reply {item:?}"),
+        }
+    }
+
+    #[test]
+    fn accepts_most_preferred_offer() {
+        let mut charset = Charset::new(&["UTF-8".to_string(), "ISO-8859-1".to_string()]);
+        assert_eq!(charset.encoding(), Encoding::UTF_8);
+
+        // The MUD's name for the charset is used in the reply.
+        assert_eq!(
+            reply(charset.handle(b"\x01 latin1 UTF-8")),
+            b"\x02UTF-8".to_vec()
+        );
+        assert_eq!(charset.encoding(), Encoding::UTF_8);
+
+        assert_eq!(
+            reply(charset.handle(b"\x01;KOI8-R;Latin1")),
+            b"\x02Latin1".to_vec()
+        );
+        assert_eq!(charset.encoding().to_string(), "windows-1252");
+
+        // Translation table versions are skipped.
+        let mut charset = Charset::new(&["WINDOWS-1252".to_string()]);
+        assert_eq!(
+            reply(charset.handle(b"\x01[TTABLE]\x01 CP1252")),
+            b"\x02CP1252".to_vec()
+        );
+
+        assert_eq!(reply(charset.handle(b"\x01 UTF-8 KOI8-R")), vec![REJECTED]);
+        assert!(charset.handle(b"\x03").is_none());
+    }
+
+    #[test]
+    fn looks_up_encodings() {
+        assert_eq!(Encoding::from_name(" utf8 "), Some(Encoding::UTF_8));
+        assert_eq!(
+            Encoding::from_name("Shift_JIS").map(|e| e.to_string()),
+            Some("Shift_JIS".to_string())
+        );
+        assert_eq!(Encoding::from_name("UTF-16LE"), None);
+        assert_eq!(Encoding::from_name("nope"), None);
+    }
+
+    #[test]
+    fn converts_text() {
+        let charset = Charset::new(&["ISO-8859-1".to_string()]);
+        assert_eq!(
+            charset.decode(Bytes::from_static(b"caf\xe9")),
+            "café".as_bytes()
+        );
+        assert_eq!(charset.encode("café ☕"), b"caf\xe9 ?".as_ref());
+
+        let charset = Charset::new(&["CP1252".to_string()]);
+        assert_eq!(
+            charset.decode(Bytes::from_static(b"\x93hi\x94 \x80")),
+            "“hi” €".as_bytes()
+        );
+        assert_eq!(charset.encode("“hi” €"), b"\x93hi\x94 \x80".as_ref());
+
+        let charset = Charset::new(&["koi8-r".to_string()]);
+        assert_eq!(
+            charset.decode(Bytes::from_static(b"\xf0\xd2\xc9\xd7\xc5\xd4!")),
+            "Привет!".as_bytes()
+        );
+        assert_eq!(
+            charset.encode("Привет ☕"),
+            b"\xf0\xd2\xc9\xd7\xc5\xd4 ?".as_ref()
+        );
+
+        // UTF-8 passes through unchanged.
+        let charset = Charset::new(&[]);
+        assert_eq!(
+            charset.decode(Bytes::from_static(b"caf\xc3\xa9")),
+            "café".as_bytes()
+        );
+        assert_eq!(charset.encode("☕"), "☕".as_bytes());
+    }
+}
@@ -0,0 +1,117 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tracing::warn;
+
+use crate::config;
+
+/// A structured log of connection lifecycle events for a single session.
+///
+/// Each event is written as a line of JSON to the configured file. Compared to a full
+/// session log this is low volume, and focused on connectivity.
+#[derive(Debug)]
+pub(super) struct ConnectionLog {
+    session_id: u32,
+    mud: String,
+    file: File,
+}
+
+impl ConnectionLog {
+    /// Open the connection log at `path` for appending.
+    ///
+    /// Relative paths are resolved against the data directory.
+    pub(super) fn open(session_id: u32, mud: String, path: &Path) -> io::Result<Self> {
+        let path = config::data_dir().join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            session_id,
+            mud,
+            file,
+        })
+    }
+
+    /// Write a connection lifecycle event to the log.
+    ///
+    /// Failures are logged but otherwise ignored: a broken connection log shouldn't
+    /// interrupt the session.
+    pub(super) fn write(&mut self, event: Event<'_>) {
+        let entry = Entry {
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |ts| ts.as_millis()),
+            session_id: self.session_id,
+            mud: &self.mud,
+            event,
+        };
+        let res = serde_json::to_string(&entry)
+            .map_err(io::Error::from)
+            .and_then(|line| writeln!(self.file, "{line}"));
+        if let Err(err) = res {
+            warn!("failed to write connection log: {err}");
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Entry<'a> {
+    timestamp_ms: u128,
+    session_id: u32,
+    mud: &'a str,
+    #[serde(flatten)]
+    event: Event<'a>,
+}
+
+/// A connection lifecycle event.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub(super) enum Event<'a> {
+    Connecting { host: &'a str, port: u16, tls: bool },
+    Connected { stream: String },
+    ConnectFailed { reason: String },
+    OptionEnabled { option: u8 },
+    OptionDisabled { option: u8 },
+    DisconnectRequested,
+    Disconnected,
+    Error { reason: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_json_lines() {
+        let path =
+            std::env::temp_dir().join(format!("mudpuppy-conn-log-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut log = ConnectionLog::open(1, "Test MUD".to_string(), &path).unwrap();
+        log.write(Event::Connecting {
+            host: "localhost",
+            port: 4000,
+            tls: false,
+        });
+        log.write(Event::Error {
+            reason: "connection reset".to_string(),
+        });
+
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let lines = contents
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["event"], "connecting");
+        assert_eq!(lines[0]["mud"], "Test MUD");
+        assert_eq!(lines[0]["port"], 4000);
+        assert_eq!(lines[1]["event"], "error");
+        assert_eq!(lines[1]["reason"], "connection reset");
+    }
+}
@@ -1,10 +1,17 @@
-use pyo3::pyclass;
+use std::collections::HashSet;
+
+use pyo3::types::{
+    PyAnyMethods, PyBool, PyDict, PyDictMethods, PyFloat, PyInt, PyList, PyListMethods, PyString,
+    PyTuple, PyTypeMethods,
+};
+use pyo3::{pyclass, Bound, IntoPyObjectExt, PyAny, PyObject, PyResult, Python};
 use serde::Serialize;
-use serde_json;
+use serde_json::{self, Map, Number, Value};
 use tracing::{debug, trace};
 
 use crate::client::output;
 use crate::error::GmcpError;
+use crate::model::GmcpDebugFormat;
 use crate::net::telnet;
 use crate::net::telnet::codec::Item as TelnetItem;
 use crate::{python, Result, CRATE_NAME, GIT_COMMIT_HASH};
@@ -115,16 +122,186 @@ pub struct Message {
     pub json: String,
 }
 
+impl Message {
+    /// Format the message as debug output items using the given format.
+    ///
+    /// In [`GmcpDebugFormat::Pretty`] mode, payloads that don't parse as JSON fall back to the
+    /// raw format.
+    #[must_use]
+    pub fn debug_output(&self, format: GmcpDebugFormat) -> Vec<output::Item> {
+        let value = match format {
+            GmcpDebugFormat::Raw => None,
+            GmcpDebugFormat::Pretty => serde_json::from_str::<Value>(&self.json).ok(),
+        };
+        let Some(value) = value else {
+            return vec![self.clone().into()];
+        };
+
+        let mut lines = Vec::new();
+        pretty_lines(&value, 0, "", "", &mut lines);
+
+        let truncated = lines.len().saturating_sub(PRETTY_MAX_LINES);
+        lines.truncate(PRETTY_MAX_LINES);
+        if truncated > 0 {
+            lines.push(format!(
+                "\x1b[2m... truncated {truncated} more lines\x1b[0m"
+            ));
+        }
+
+        std::iter::once(format!("GMCP: {}", self.package))
+            .chain(lines)
+            .map(|line| output::Item::Debug { line })
+            .collect()
+    }
+}
+
+// Recursively format a JSON value as indented lines, highlighted with ANSI colours.
+fn pretty_lines(value: &Value, indent: usize, prefix: &str, suffix: &str, lines: &mut Vec<String>) {
+    let padding = "  ".repeat(indent);
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            lines.push(format!("{prefix}{{"));
+            for (i, (key, value)) in map.iter().enumerate() {
+                let key = format!("{padding}  \x1b[36m{}\x1b[0m: ", Value::from(key.as_str()));
+                let suffix = if i + 1 < map.len() { "," } else { "" };
+                pretty_lines(value, indent + 1, &key, suffix, lines);
+            }
+            lines.push(format!("{padding}}}{suffix}"));
+        }
+        Value::Array(items) if !items.is_empty() => {
+            lines.push(format!("{prefix}["));
+            for (i, value) in items.iter().enumerate() {
+                let suffix = if i + 1 < items.len() { "," } else { "" };
+                pretty_lines(value, indent + 1, &format!("{padding}  "), suffix, lines);
+            }
+            lines.push(format!("{padding}]{suffix}"));
+        }
+        Value::String(_) => lines.push(format!("{prefix}\x1b[32m{value}\x1b[0m{suffix}")),
+        Value::Number(_) => lines.push(format!("{prefix}\x1b[33m{value}\x1b[0m{suffix}")),
+        // Empty objects/arrays, bools and null.
+        _ => lines.push(format!("{prefix}\x1b[35m{value}\x1b[0m{suffix}")),
+    }
+}
+
+/// The maximum number of lines of pretty printed GMCP JSON to display.
+const PRETTY_MAX_LINES: usize = 50;
+
 impl From<Message> for python::Event {
     fn from(msg: Message) -> Self {
+        // Payloads that aren't valid JSON are delivered with `None` data.
+        let data = Python::with_gil(|py| {
+            serde_json::from_str::<Value>(&msg.json)
+                .ok()
+                .and_then(|value| to_python(py, &value).ok())
+                .unwrap_or_else(|| py.None())
+        });
         python::Event::GmcpMessage {
             id: msg.session_id,
             package: msg.package,
             json: msg.json,
+            data,
+        }
+    }
+}
+
+/// Convert a JSON value to the equivalent Python object.
+///
+/// # Errors
+/// If a Python object can't be created.
+pub fn to_python(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    match value {
+        Value::Null => Ok(py.None()),
+        Value::Bool(b) => b.into_py_any(py),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(n), _) => n.into_py_any(py),
+            (None, Some(n)) => n.into_py_any(py),
+            // Safety: a number that isn't an i64 or u64 is always an f64.
+            _ => n.as_f64().unwrap().into_py_any(py),
+        },
+        Value::String(s) => s.into_py_any(py),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(to_python(py, item)?)?;
+            }
+            list.into_py_any(py)
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, value) in map {
+                dict.set_item(key, to_python(py, value)?)?;
+            }
+            dict.into_py_any(py)
         }
     }
 }
 
+/// Convert a Python object made of dicts, lists, tuples, strings, numbers, bools and `None`
+/// to the equivalent JSON value.
+///
+/// # Errors
+/// If the object contains a value of another type, a dict key that isn't a string, a
+/// float that isn't finite, or a dict or list that contains itself.
+pub fn to_json(obj: &Bound<'_, PyAny>) -> Result<Value> {
+    to_json_inner(obj, &mut HashSet::new())
+}
+
+// `containers` holds the ids of the dicts and lists enclosing `obj`, to detect cycles.
+fn to_json_inner(obj: &Bound<'_, PyAny>, containers: &mut HashSet<usize>) -> Result<Value> {
+    if obj.is_none() {
+        return Ok(Value::Null);
+    }
+    // Note: bool must be checked before int, since Python bools are ints.
+    if let Ok(b) = obj.downcast::<PyBool>() {
+        return Ok(Value::Bool(b.extract()?));
+    }
+    if obj.is_instance_of::<PyInt>() {
+        return match obj.extract::<i64>() {
+            Ok(n) => Ok(n.into()),
+            Err(_) => Ok(obj.extract::<u64>()?.into()),
+        };
+    }
+    if obj.is_instance_of::<PyFloat>() {
+        let f = obj.extract::<f64>()?;
+        return Number::from_f64(f)
+            .map(Value::Number)
+            .ok_or_else(|| GmcpError::BadData(format!("non-finite float {f}")).into());
+    }
+    if obj.is_instance_of::<PyString>() {
+        return Ok(Value::String(obj.extract()?));
+    }
+    let is_dict = obj.is_instance_of::<PyDict>();
+    if !is_dict && !obj.is_instance_of::<PyList>() && !obj.is_instance_of::<PyTuple>() {
+        return Err(GmcpError::BadData(format!(
+            "unsupported type {} for JSON data",
+            obj.get_type().name()?
+        ))
+        .into());
+    }
+
+    let id = obj.as_ptr() as usize;
+    if !containers.insert(id) {
+        return Err(GmcpError::CircularReference.into());
+    }
+    let value = if let Ok(dict) = obj.downcast::<PyDict>() {
+        let mut map = Map::new();
+        for (key, value) in dict.iter() {
+            let Ok(key) = key.extract::<String>() else {
+                return Err(GmcpError::BadData(format!("non-string dict key {key}")).into());
+            };
+            map.insert(key, to_json_inner(&value, containers)?);
+        }
+        Value::Object(map)
+    } else {
+        obj.try_iter()?
+            .map(|item| to_json_inner(&item?, containers))
+            .collect::<Result<_>>()
+            .map(Value::Array)?
+    };
+    containers.remove(&id);
+    Ok(value)
+}
+
 impl From<Message> for output::Item {
     fn from(msg: Message) -> Self {
         output::Item::Debug {
@@ -0,0 +1,64 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::client::session_log::file_name_safe;
+use crate::config;
+use crate::model::InputLine;
+
+/// Load the saved input history for `character` of `mud`, oldest first.
+///
+/// A MUD without saved history has an empty history.
+pub(super) fn load(mud: &str, character: Option<&str>) -> io::Result<Vec<InputLine>> {
+    let contents = match fs::read_to_string(path(mud, character)) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::default()),
+        Err(err) => return Err(err),
+    };
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| InputLine::new(line.to_string(), true, false))
+        .collect())
+}
+
+/// Save `history` for `character` of `mud`, replacing any previously saved history.
+pub(super) fn save(mud: &str, character: Option<&str>, history: &[InputLine]) -> io::Result<()> {
+    let path = path(mud, character);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut contents = String::new();
+    for line in history {
+        contents.push_str(&line.sent);
+        contents.push('\n');
+    }
+    fs::write(path, contents)
+}
+
+// History for a character lives in a directory named for the MUD, so that e.g. MUD `a-b`
+// with character `c` can't share a file with MUD `a` and character `b-c`.
+fn path(mud: &str, character: Option<&str>) -> PathBuf {
+    let dir = config::data_dir().join("history");
+    match character {
+        Some(character) => dir
+            .join(file_name_safe(mud))
+            .join(format!("{}.txt", file_name_safe(character))),
+        None => dir.join(format!("{}.txt", file_name_safe(mud))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_are_unambiguous() {
+        assert_ne!(path("a-b", Some("c")), path("a", Some("b-c")));
+        assert_ne!(path("a-b", None), path("a", Some("b")));
+        assert_eq!(
+            path("Dark Realm", Some("Bob")),
+            config::data_dir().join("history/Dark_Realm/Bob.txt")
+        );
+    }
+}
@@ -0,0 +1,559 @@
+use std::fmt::{self, Display, Formatter};
+
+use pyo3::{pyclass, pymethods};
+use serde::Deserialize;
+use serde_json::Value;
+use strum::EnumString;
+
+use crate::error::Error;
+use crate::model::{AliasConfig, TriggerConfig};
+
+/// A file format that triggers and aliases can be imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum ImportFormat {
+    /// mudpuppy's own JSON format, for converters from other clients to target.
+    Json,
+    /// `TinyFugue` `/def` commands with a `-t` trigger pattern.
+    #[strum(serialize = "tinyfugue", serialize = "tf")]
+    TinyFugue,
+}
+
+/// The outcome of importing a file of triggers and aliases.
+#[derive(Clone, Debug, Default)]
+#[pyclass]
+pub struct ImportReport {
+    /// The IDs of the triggers that were created.
+    #[pyo3(get)]
+    pub triggers: Vec<u32>,
+
+    /// The IDs of the aliases that were created.
+    #[pyo3(get)]
+    pub aliases: Vec<u32>,
+
+    /// The name of each entry that couldn't be imported, and why.
+    #[pyo3(get)]
+    pub failures: Vec<(String, String)>,
+}
+
+#[pymethods]
+impl ImportReport {
+    fn __str__(&self) -> String {
+        format!("{self}")
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{self:?}")
+    }
+}
+
+impl Display for ImportReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "imported {} triggers and {} aliases, {} failed",
+            self.triggers.len(),
+            self.aliases.len(),
+            self.failures.len()
+        )
+    }
+}
+
+/// The triggers and aliases parsed from an import file, before they're added to a session.
+#[derive(Debug, Default)]
+pub(super) struct Imported {
+    pub(super) triggers: Vec<TriggerConfig>,
+    pub(super) aliases: Vec<AliasConfig>,
+    /// The name of each entry that couldn't be parsed, and why.
+    pub(super) failures: Vec<(String, String)>,
+}
+
+/// Parse `text` in the given `format`. Entries that can't be imported are reported in
+/// [`Imported::failures`] rather than failing the whole file.
+///
+/// Multiple commands in a `TinyFugue` body are joined with `separator`.
+///
+/// # Errors
+/// If the file as a whole can't be parsed.
+pub(super) fn parse(
+    text: &str,
+    format: ImportFormat,
+    separator: Option<&str>,
+) -> Result<Imported, Error> {
+    match format {
+        ImportFormat::Json => parse_json(text),
+        ImportFormat::TinyFugue => Ok(parse_tinyfugue(text, separator)),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonFile {
+    #[serde(default)]
+    triggers: Vec<Value>,
+    #[serde(default)]
+    aliases: Vec<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[allow(clippy::struct_excessive_bools)]
+struct JsonTrigger {
+    name: String,
+    pattern: String,
+    #[serde(default)]
+    expansion: Option<String>,
+    #[serde(default)]
+    gag: bool,
+    #[serde(default)]
+    prompt: bool,
+    #[serde(default)]
+    match_ansi: bool,
+    #[serde(default)]
+    priority: i32,
+    #[serde(default)]
+    stop: bool,
+    #[serde(default)]
+    multi_match: bool,
+    #[serde(default)]
+    important: bool,
+    #[serde(default)]
+    group: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonAlias {
+    name: String,
+    pattern: String,
+    #[serde(default)]
+    expansion: Option<String>,
+    #[serde(default)]
+    priority: i32,
+    #[serde(default)]
+    group: Option<String>,
+}
+
+fn parse_json(text: &str) -> Result<Imported, Error> {
+    let file: JsonFile = serde_json::from_str(text)
+        .map_err(|err| Error::Import(format!("invalid JSON import file: {err}")))?;
+    let mut imported = Imported::default();
+
+    for (index, entry) in file.triggers.into_iter().enumerate() {
+        let name = entry_name(&entry, "trigger", index);
+        let trigger = serde_json::from_value::<JsonTrigger>(entry)
+            .map_err(|err| err.to_string())
+            .and_then(|t| {
+                TriggerConfig::with_pattern(&t.pattern, t.name)
+                    .map(|trigger| TriggerConfig {
+                        match_ansi: t.match_ansi,
+                        prompt: t.prompt,
+                        gag: t.gag,
+                        expansion: t.expansion,
+                        priority: t.priority,
+                        stop: t.stop,
+                        multi_match: t.multi_match,
+                        important: t.important,
+                        group: t.group,
+                        ..trigger
+                    })
+                    .map_err(|err| err.to_string())
+            });
+        match trigger {
+            Ok(trigger) => imported.triggers.push(trigger),
+            Err(err) => imported.failures.push((name, err)),
+        }
+    }
+
+    for (index, entry) in file.aliases.into_iter().enumerate() {
+        let name = entry_name(&entry, "alias", index);
+        let alias = serde_json::from_value::<JsonAlias>(entry)
+            .map_err(|err| err.to_string())
+            .and_then(|a| {
+                AliasConfig::new(&a.pattern, a.name, None, a.expansion, a.priority, a.group)
+                    .map_err(|err| err.to_string())
+            });
+        match alias {
+            Ok(alias) => imported.aliases.push(alias),
+            Err(err) => imported.failures.push((name, err)),
+        }
+    }
+
+    Ok(imported)
+}
+
+// The entry's name for reporting a failure, falling back to its kind and position for
+// entries without one.
+fn entry_name(entry: &Value, kind: &str, index: usize) -> String {
+    entry
+        .get("name")
+        .and_then(Value::as_str)
+        .map_or_else(|| format!("{kind} #{}", index + 1), ToString::to_string)
+}
+
+// TinyFugue /def options that change when a trigger fires in ways triggers can't express.
+const TF_UNSUPPORTED_OPTIONS: &[char] = &['b', 'B', 'c', 'E', 'h', 'n', '1'];
+
+// TinyFugue /def options that take a value.
+const TF_VALUE_OPTIONS: &[char] = &[
+    'a', 'b', 'B', 'c', 'E', 'h', 'm', 'n', 'p', 'P', 't', 'T', 'w',
+];
+
+fn parse_tinyfugue(text: &str, separator: Option<&str>) -> Imported {
+    let mut imported = Imported::default();
+    for (line_number, line) in tinyfugue_lines(text) {
+        let Some(def) = line
+            .strip_prefix("/def")
+            .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+        else {
+            continue;
+        };
+        match parse_tinyfugue_def(def, separator) {
+            Ok(trigger) => imported.triggers.push(trigger),
+            Err((name, err)) => imported
+                .failures
+                .push((name.unwrap_or_else(|| format!("line {line_number}")), err)),
+        }
+    }
+    imported
+}
+
+// Returns the logical lines of a TinyFugue file with their line numbers, joining lines
+// continued with a trailing backslash, and skipping blank lines and comments.
+fn tinyfugue_lines(text: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    for (index, line) in text.lines().enumerate() {
+        let (number, mut joined) = match current.take() {
+            Some((number, joined)) => (number, joined + line.trim_start()),
+            None => (index + 1, line.trim().to_string()),
+        };
+        if let Some(continued) = joined.strip_suffix('\\') {
+            joined.truncate(continued.len());
+            current = Some((number, joined));
+            continue;
+        }
+        let joined = joined.trim_end().to_string();
+        if !joined.is_empty() && !joined.starts_with(';') && !joined.starts_with('#') {
+            lines.push((number, joined));
+        }
+    }
+    if let Some((number, joined)) = current {
+        lines.push((number, joined));
+    }
+    lines
+}
+
+// Parse the arguments of a /def into a trigger, or return the /def's name, if it has one,
+// and why it can't be imported.
+fn parse_tinyfugue_def(
+    def: &str,
+    separator: Option<&str>,
+) -> Result<TriggerConfig, (Option<String>, String)> {
+    let mut rest = def.trim_start();
+    let mut pattern = None;
+    let mut matching = "glob".to_string();
+    let mut priority = 0;
+    let mut gag = false;
+    let mut fall_through = false;
+    let mut unsupported = None;
+
+    while let Some(option) = rest.strip_prefix('-') {
+        let Some(flag) = option.chars().next() else {
+            break;
+        };
+        rest = &option[flag.len_utf8()..];
+        if flag == '-' {
+            rest = rest.trim_start();
+            break;
+        }
+        if TF_UNSUPPORTED_OPTIONS.contains(&flag) {
+            unsupported.get_or_insert(format!("the -{flag} option isn't supported"));
+        }
+        if !TF_VALUE_OPTIONS.contains(&flag) {
+            match flag {
+                'F' => fall_through = true,
+                'i' | 'q' => {}
+                _ => {
+                    unsupported.get_or_insert(format!("unknown option -{flag}"));
+                }
+            }
+            rest = rest.trim_start();
+            continue;
+        }
+
+        let (value, after) = tinyfugue_word(rest.trim_start_matches(' '));
+        rest = after.trim_start();
+        match flag {
+            't' => pattern = Some(value),
+            'm' => matching = value,
+            'p' => match value.parse() {
+                Ok(value) => priority = value,
+                Err(_) => {
+                    unsupported.get_or_insert(format!("invalid priority {value:?}"));
+                }
+            },
+            'a' => gag |= value.contains('g'),
+            _ => {}
+        }
+    }
+
+    let (name, body) = rest.split_once('=').unwrap_or((rest, ""));
+    let name = Some(name.trim()).filter(|name| !name.is_empty());
+    let fail = |err: String| (name.map(ToString::to_string), err);
+
+    if let Some(err) = unsupported {
+        return Err(fail(err));
+    }
+    let Some(pattern) = pattern else {
+        return Err(fail(
+            "only /def with a -t trigger pattern can be imported".to_string(),
+        ));
+    };
+    let regex = match matching.as_str() {
+        "regexp" => pattern.clone(),
+        "glob" => glob_to_regex(&pattern),
+        "simple" => format!("^{}$", regex::escape(&pattern)),
+        _ => return Err(fail(format!("unknown matching style {matching:?}"))),
+    };
+    let expansion = tinyfugue_body(body.trim(), separator).map_err(fail)?;
+
+    TriggerConfig::with_pattern(&regex, name.map_or(pattern, ToString::to_string))
+        .map(|trigger| TriggerConfig {
+            gag,
+            expansion,
+            priority,
+            // Without -F TinyFugue only fires the highest priority matching triggers.
+            stop: !fall_through,
+            ..trigger
+        })
+        .map_err(|err| fail(err.to_string()))
+}
+
+// Split an option value off the front of `text`. Values are either quoted, with a backslash
+// escaping the quote character or a backslash, or run up to the next whitespace.
+fn tinyfugue_word(text: &str) -> (String, &str) {
+    let Some(quote) = text
+        .chars()
+        .next()
+        .filter(|c| matches!(c, '"' | '\'' | '`'))
+    else {
+        let end = text.find(char::is_whitespace).unwrap_or(text.len());
+        return (text[..end].to_string(), &text[end..]);
+    };
+
+    let mut value = String::new();
+    let mut chars = text[1..].char_indices();
+    while let Some((pos, c)) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some((_, escaped)) if escaped == quote || escaped == '\\' => value.push(escaped),
+                Some((_, escaped)) => {
+                    value.push('\\');
+                    value.push(escaped);
+                }
+                None => value.push('\\'),
+            },
+            c if c == quote => return (value, &text[1 + pos + 1..]),
+            c => value.push(c),
+        }
+    }
+    (value, "")
+}
+
+// Convert a /def body to a trigger expansion, joining its `%;` separated commands with
+// `separator`.
+fn tinyfugue_body(body: &str, separator: Option<&str>) -> Result<Option<String>, String> {
+    if body.is_empty() {
+        return Ok(None);
+    }
+
+    let mut commands = vec![String::new()];
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            commands.last_mut().unwrap().push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(';') => commands.push(String::new()),
+            Some('%') => commands.last_mut().unwrap().push('%'),
+            _ => return Err("TinyFugue substitutions in the body aren't supported".to_string()),
+        }
+    }
+
+    let commands = commands
+        .iter()
+        .map(|command| command.trim())
+        .filter(|command| !command.is_empty())
+        .collect::<Vec<_>>();
+    if commands.iter().any(|command| command.starts_with('/')) {
+        return Err("TinyFugue commands in the body aren't supported".to_string());
+    }
+    match (commands.len(), separator) {
+        (0, _) => Ok(None),
+        (1, _) => Ok(Some(commands[0].to_string())),
+        (_, Some(separator)) => Ok(Some(commands.join(separator))),
+        (_, None) => {
+            Err("the MUD has no command_separator to join the body's commands".to_string())
+        }
+    }
+}
+
+// Convert a TinyFugue glob to an equivalent regex. Like TinyFugue the glob must match the
+// whole line, ignoring case.
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = "(?i)^".to_string();
+    let mut chars = glob.chars();
+    let mut in_alternatives = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    regex.push_str(&regex::escape(&escaped.to_string()));
+                }
+            }
+            '[' => {
+                regex.push('[');
+                for c in chars.by_ref() {
+                    match c {
+                        ']' => break,
+                        '\\' | '[' | '&' | '~' => {
+                            regex.push('\\');
+                            regex.push(c);
+                        }
+                        c => regex.push(c),
+                    }
+                }
+                regex.push(']');
+            }
+            '{' => {
+                in_alternatives = true;
+                regex.push_str("(?:");
+            }
+            '|' if in_alternatives => regex.push('|'),
+            '}' if in_alternatives => {
+                in_alternatives = false;
+                regex.push(')');
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    if in_alternatives {
+        regex.push(')');
+    }
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imports_json_entries() {
+        let imported = parse(
+            r#"{
+                "triggers": [
+                    {"name": "tells", "pattern": "^(\\w+) tells you", "important": true},
+                    {"name": "broken", "pattern": "(unclosed"},
+                    {"pattern": "^no name$"},
+                    {"name": "hungry", "pattern": "^You are hungry", "expansion": "eat bread",
+                     "priority": 5, "stop": true}
+                ],
+                "aliases": [
+                    {"name": "kk", "pattern": "^kk (.*)$", "expansion": "kill %1"},
+                    {"name": "typo", "pattern": "^x$", "expanson": "look"}
+                ]
+            }"#,
+            ImportFormat::Json,
+            None,
+        )
+        .unwrap();
+
+        let names = |triggers: &[TriggerConfig]| {
+            triggers.iter().map(|t| t.name.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(names(&imported.triggers), vec!["tells", "hungry"]);
+        assert!(imported.triggers[0].important);
+        assert_eq!(imported.triggers[1].expansion.as_deref(), Some("eat bread"));
+        assert_eq!(imported.triggers[1].priority, 5);
+        assert_eq!(imported.aliases.len(), 1);
+        assert_eq!(imported.aliases[0].name, "kk");
+
+        let failed = imported
+            .failures
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(failed, vec!["broken", "trigger #3", "typo"]);
+
+        assert!(parse("not json", ImportFormat::Json, None).is_err());
+        assert!(parse(r#"{"timers": []}"#, ImportFormat::Json, None).is_err());
+    }
+
+    #[test]
+    fn imports_tinyfugue_defs() {
+        let imported = parse(
+            r#"
+; Auto-eat.
+/def -mregexp -p10 -t"^You are (hungry|thirsty)\.$" feed = eat bread%;drink water
+/def -t'* arrives from the *' arrival = look
+/def -ag -F -msimple -t"The rain falls." =
+/def -t"Your {sword|axe} breaks" \
+    breaks = wield dagger
+/def heal = cast 'heal' %1
+/def -t"* tells you *" tell = /echo tell!
+/def -h"CONNECT" login = ayla
+/set quiet=on
+"#,
+            ImportFormat::TinyFugue,
+            Some(";;"),
+        )
+        .unwrap();
+
+        let feed = &imported.triggers[0];
+        assert_eq!(feed.name, "feed");
+        assert_eq!(feed.priority, 10);
+        assert!(feed.stop);
+        assert_eq!(feed.expansion.as_deref(), Some("eat bread;;drink water"));
+        assert!(feed.regex.is_match("You are thirsty."));
+
+        let arrival = &imported.triggers[1];
+        assert!(arrival.regex.is_match("a goblin ARRIVES from the north"));
+        assert!(!arrival.regex.is_match("a goblin arrives"));
+
+        let rain = &imported.triggers[2];
+        assert_eq!(rain.name, "The rain falls.");
+        assert!(rain.gag);
+        assert!(!rain.stop);
+        assert_eq!(rain.expansion, None);
+        assert!(rain.regex.is_match("The rain falls."));
+        assert!(!rain.regex.is_match("The rain falls!"));
+
+        let breaks = &imported.triggers[3];
+        assert_eq!(breaks.name, "breaks");
+        assert!(breaks.regex.is_match("Your axe breaks"));
+        assert!(!breaks.regex.is_match("Your bow breaks"));
+
+        let failed = imported
+            .failures
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(failed, vec!["heal", "tell", "login"]);
+        assert_eq!(imported.triggers.len(), 4);
+
+        let imported = parse("/def -t'x' = one%;two", ImportFormat::TinyFugue, None).unwrap();
+        assert_eq!(imported.failures[0].0, "line 1");
+    }
+
+    #[test]
+    fn converts_globs() {
+        assert_eq!(glob_to_regex("a*b?c"), "(?i)^a.*b.c$");
+        assert_eq!(glob_to_regex("[^abc].{x|y}"), r"(?i)^[^abc]\.(?:x|y)$");
+        assert_eq!(glob_to_regex(r"\*lit"), r"(?i)^\*lit$");
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::fmt::{Display, Formatter};
 use std::{iter, mem};
 
@@ -18,6 +19,8 @@ use crate::model::InputLine;
 //  * Uses pyo3's pyclass macros to be FFI friendly with python.
 //  * Adapts state to InputLine.
 //  * Maintains a separate EchoState.
+//  * Adds a bounded undo/redo history of coarse edits.
+//  * Adds a bounded history of sent lines.
 //
 // We want to track EchoState both per-line and at the telnet level so that
 // items can be masked when loaded from history when we're back in normal
@@ -28,6 +31,15 @@ pub struct Input {
     line: InputLine,
     telnet_echo: EchoState,
     cursor: usize,
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+    // The kind of the most recent edit, and the cursor position after it was made.
+    last_edit: Option<(Edit, usize)>,
+    // Lines sent from the input area, oldest first.
+    history: VecDeque<InputLine>,
+    history_size: usize,
+    // The index of the history line being shown, while moving through history.
+    history_pos: Option<usize>,
 }
 
 impl Input {
@@ -65,11 +77,160 @@ impl Input {
     }
 
     pub fn paste(&mut self, data: &str) {
-        for c in data.chars() {
-            self.insert(c);
+        self.edit(Edit::Paste, |input| {
+            for c in data.chars() {
+                input.insert_char(c);
+            }
+        });
+    }
+
+    /// Set the maximum number of history lines kept, dropping the oldest lines if needed.
+    pub fn set_history_size(&mut self, size: usize) {
+        self.history_size = size;
+        while self.history.len() > size {
+            self.history.pop_front();
+        }
+        self.history_pos = None;
+    }
+
+    /// Add a sent line to the history.
+    ///
+    /// Blank lines, lines sent by scripts, password lines and repeats of the most recent
+    /// line aren't added. Any line sent by the user ends movement through history.
+    pub fn add_history(&mut self, line: &InputLine) {
+        if line.scripted {
+            return;
+        }
+        self.history_pos = None;
+
+        let line = match &line.original {
+            // Store the line as it was typed, before alias expansion.
+            Some(original) => InputLine {
+                sent: original.clone(),
+                original: None,
+                echo: line.echo,
+                scripted: false,
+            },
+            None => line.clone(),
+        };
+        if line.empty()
+            || line.echo == EchoState::Password
+            || self.history_size == 0
+            || self
+                .history
+                .back()
+                .is_some_and(|last| last.sent == line.sent)
+        {
+            return;
+        }
+
+        if self.history.len() == self.history_size {
+            self.history.pop_front();
+        }
+        self.history.push_back(line);
+    }
+
+    /// Returns the history lines, oldest first.
+    #[must_use]
+    pub fn history(&self) -> Vec<InputLine> {
+        self.history.iter().cloned().collect()
+    }
+
+    /// Replace the input with the previous (older) history line.
+    ///
+    /// Stays on the oldest line once it's reached.
+    pub fn history_previous(&mut self) {
+        if self.telnet_echo == EchoState::Password {
+            return;
+        }
+        let pos = match self.history_pos {
+            Some(0) => return,
+            Some(pos) => pos - 1,
+            None => match self.history.len().checked_sub(1) {
+                Some(pos) => pos,
+                None => return,
+            },
+        };
+        self.show_history(Some(pos));
+    }
+
+    /// Replace the input with the next (newer) history line.
+    ///
+    /// Moving past the newest line clears the input.
+    pub fn history_next(&mut self) {
+        if self.telnet_echo == EchoState::Password {
+            return;
+        }
+        let Some(pos) = self.history_pos else {
+            return;
+        };
+        self.show_history(Some(pos + 1).filter(|pos| *pos < self.history.len()));
+    }
+
+    fn show_history(&mut self, pos: Option<usize>) {
+        match pos.and_then(|pos| self.history.get(pos)).cloned() {
+            Some(line) => self.set_value(InputLine {
+                echo: self.telnet_echo,
+                ..line
+            }),
+            None => self.reset(),
+        }
+        self.history_pos = pos;
+    }
+
+    // Apply an edit to the input line, recording the prior state for undo.
+    //
+    // Edits that don't change the line aren't recorded. Consecutive inserts (or
+    // deletes) made without moving the cursor in between are coalesced into one
+    // undo step so that undo reverses a run of typing rather than a single character.
+    fn edit(&mut self, kind: Edit, f: impl FnOnce(&mut Self)) {
+        let before = self.snapshot();
+        f(self);
+        if self.line.sent == before.line.sent {
+            return;
+        }
+
+        self.redo.clear();
+        let coalesce = kind.coalesces()
+            && !self.undo.is_empty()
+            && self.last_edit == Some((kind, before.cursor));
+        self.last_edit = Some((kind, self.cursor));
+        if coalesce {
+            return;
+        }
+
+        if self.undo.len() == UNDO_LIMIT {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(before);
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            line: self.line.clone(),
+            cursor: self.cursor,
         }
     }
 
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.line = snapshot.line;
+        self.cursor = snapshot.cursor;
+        self.last_edit = None;
+    }
+
+    fn insert_char(&mut self, c: char) {
+        if self.cursor == self.chars().count() {
+            self.line.sent.push(c);
+        } else {
+            self.line.sent = self
+                .chars()
+                .take(self.cursor)
+                .chain(iter::once(c).chain(self.chars().skip(self.cursor)))
+                .collect();
+        }
+        self.cursor += 1;
+    }
+
     fn words_left(&self) -> impl Iterator<Item = char> + '_ {
         self.chars()
             .rev()
@@ -78,7 +239,7 @@ impl Input {
             .skip_while(|c| c.is_alphanumeric())
     }
 
-    fn chars(&self) -> std::str::Chars {
+    fn chars(&self) -> std::str::Chars<'_> {
         self.line.sent.chars()
     }
 }
@@ -140,10 +301,12 @@ impl Input {
     }
 
     pub fn reset(&mut self) {
-        self.line.sent.clear();
-        self.line.original = None;
-        self.line.echo = EchoState::default();
-        self.cursor = 0;
+        self.edit(Edit::Replace, |input| {
+            input.line.sent.clear();
+            input.line.original = None;
+            input.line.echo = EchoState::default();
+            input.cursor = 0;
+        });
     }
 
     pub fn pop(&mut self) -> Option<InputLine> {
@@ -152,6 +315,10 @@ impl Input {
         }
 
         self.cursor = 0;
+        // Once a line is sent there's nothing sensible left to undo.
+        self.undo.clear();
+        self.redo.clear();
+        self.last_edit = None;
 
         Some(InputLine {
             sent: mem::take(&mut self.line.sent),
@@ -163,8 +330,30 @@ impl Input {
     }
 
     pub fn set_value(&mut self, value: InputLine) {
-        self.line = value;
-        self.cursor = self.line.sent.chars().count();
+        self.edit(Edit::Replace, |input| {
+            input.line = value;
+            input.cursor = input.line.sent.chars().count();
+        });
+    }
+
+    /// Undo the most recent edit, returning whether there was anything to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(prev) = self.undo.pop_back() else {
+            return false;
+        };
+        self.redo.push(self.snapshot());
+        self.restore(prev);
+        true
+    }
+
+    /// Redo the most recently undone edit, returning whether there was anything to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(next) = self.redo.pop() else {
+            return false;
+        };
+        self.undo.push_back(self.snapshot());
+        self.restore(next);
+        true
     }
 
     pub fn set_telnet_echo(&mut self, echo: EchoState) {
@@ -180,65 +369,67 @@ impl Input {
     }
 
     pub fn insert(&mut self, c: char) {
-        if self.cursor == self.chars().count() {
-            self.line.sent.push(c);
-        } else {
-            self.line.sent = self
-                .chars()
-                .take(self.cursor)
-                .chain(iter::once(c).chain(self.chars().skip(self.cursor)))
-                .collect();
-        }
-        self.cursor += 1;
+        self.edit(Edit::Insert, |input| input.insert_char(c));
     }
 
     pub fn delete_prev(&mut self) {
-        if self.cursor == 0 {
-            return;
-        }
-        self.cursor -= 1;
-        self.drop_index(self.cursor);
+        self.edit(Edit::Delete, |input| {
+            if input.cursor == 0 {
+                return;
+            }
+            input.cursor -= 1;
+            input.drop_index(input.cursor);
+        });
     }
 
     pub fn delete_next(&mut self) {
-        if self.cursor == self.chars().count() {
-            return;
-        }
-        self.drop_index(self.cursor);
+        self.edit(Edit::Delete, |input| {
+            if input.cursor == input.chars().count() {
+                return;
+            }
+            input.drop_index(input.cursor);
+        });
     }
 
     pub fn delete_word_left(&mut self) {
-        if self.cursor == 0 {
-            return;
-        }
-        let rev = self.words_left().collect::<Vec<_>>();
-        let rev_len = rev.len();
-        self.line.sent = rev
-            .into_iter()
-            .rev()
-            .chain(self.chars().skip(self.cursor))
-            .collect();
-        self.cursor = rev_len;
+        self.edit(Edit::DeleteWord, |input| {
+            if input.cursor == 0 {
+                return;
+            }
+            let rev = input.words_left().collect::<Vec<_>>();
+            let rev_len = rev.len();
+            input.line.sent = rev
+                .into_iter()
+                .rev()
+                .chain(input.chars().skip(input.cursor))
+                .collect();
+            input.cursor = rev_len;
+        });
     }
 
     pub fn delete_word_right(&mut self) {
-        if self.cursor == self.chars().count() {
-            return;
-        }
-        self.line.sent = self
-            .chars()
-            .take(self.cursor)
-            .chain(
-                self.chars()
-                    .skip(self.cursor)
-                    .skip_while(|c| c.is_alphanumeric())
-                    .skip_while(|c| !c.is_alphanumeric()),
-            )
-            .collect();
+        self.edit(Edit::DeleteWord, |input| {
+            if input.cursor == input.chars().count() {
+                return;
+            }
+            input.line.sent = input
+                .chars()
+                .take(input.cursor)
+                .chain(
+                    input
+                        .chars()
+                        .skip(input.cursor)
+                        .skip_while(|c| c.is_alphanumeric())
+                        .skip_while(|c| !c.is_alphanumeric()),
+                )
+                .collect();
+        });
     }
 
     pub fn delete_to_end(&mut self) {
-        self.line.sent = self.chars().take(self.cursor).collect();
+        self.edit(Edit::DeleteWord, |input| {
+            input.line.sent = input.chars().take(input.cursor).collect();
+        });
     }
 
     pub fn cursor_left(&mut self) {
@@ -299,6 +490,31 @@ impl Display for Input {
     }
 }
 
+// The maximum number of undo steps retained for the input line.
+const UNDO_LIMIT: usize = 100;
+
+#[derive(Debug, Clone)]
+struct Snapshot {
+    line: InputLine,
+    cursor: usize,
+}
+
+// Coarse categories of edit used to decide which edits are undone together.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Edit {
+    Insert,
+    Delete,
+    DeleteWord,
+    Paste,
+    Replace,
+}
+
+impl Edit {
+    fn coalesces(self) -> bool {
+        matches!(self, Self::Insert | Self::Delete)
+    }
+}
+
 #[pyclass(eq, eq_int)]
 #[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
 pub enum EchoState {
@@ -439,5 +655,82 @@ mod tests {
         assert_eq!(input.visual_scroll(6), 18);
     }
 
+    #[test]
+    fn undo_redo() {
+        let mut input = Input::default();
+        for c in "look north".chars() {
+            input.insert(c);
+        }
+        input.delete_word_left();
+        assert_eq!(input.value().sent, "look ");
+        input.reset();
+        assert_eq!(input.value().sent, "");
+
+        assert!(input.undo());
+        assert_eq!(input.value().sent, "look ");
+        assert!(input.undo());
+        assert_eq!(input.value().sent, "look north");
+        assert_eq!(input.cursor(), 10);
+        // The typing run was a single edit.
+        assert!(input.undo());
+        assert_eq!(input.value().sent, "");
+        assert!(!input.undo());
+
+        assert!(input.redo());
+        assert_eq!(input.value().sent, "look north");
+        input.insert('!');
+        assert!(!input.redo());
+
+        input.pop();
+        assert!(!input.undo());
+    }
+
+    #[test]
+    fn history() {
+        let sent = |text: &str| InputLine::new(text.to_owned(), true, false);
+        let mut input = Input::default();
+        input.set_history_size(3);
+        input.add_history(&sent("look"));
+        input.add_history(&sent("look"));
+        input.add_history(&sent(" "));
+        input.add_history(&InputLine::new("secret".to_owned(), false, false));
+        input.add_history(&InputLine::new("flee".to_owned(), true, true));
+        let mut expanded = sent("kill orc");
+        expanded.original = Some("k orc".to_owned());
+        input.add_history(&expanded);
+        assert_eq!(
+            input
+                .history()
+                .iter()
+                .map(|line| line.sent.as_str())
+                .collect::<Vec<_>>(),
+            ["look", "k orc"]
+        );
+
+        input.add_history(&sent("north"));
+        input.add_history(&sent("south"));
+        assert_eq!(input.history().len(), 3);
+
+        input.history_previous();
+        assert_eq!(input.value().sent, "south");
+        input.history_previous();
+        input.history_previous();
+        assert_eq!(input.value().sent, "k orc");
+        // Stays on the oldest line.
+        input.history_previous();
+        assert_eq!(input.value().sent, "k orc");
+        input.history_next();
+        assert_eq!(input.value().sent, "north");
+        input.history_next();
+        input.history_next();
+        assert_eq!(input.value().sent, "");
+        input.history_next();
+        assert_eq!(input.value().sent, "");
+
+        input.set_telnet_echo(EchoState::Password);
+        input.history_previous();
+        assert_eq!(input.value().sent, "");
+    }
+
     const TEXT: &str = "first second, third.";
 }
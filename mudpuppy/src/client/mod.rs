@@ -1,38 +1,114 @@
-mod gmcp;
+mod capture;
+pub mod charset;
+mod conn_log;
+pub mod gmcp;
+mod history;
+pub mod import;
 pub mod input;
+mod msdp;
 pub mod output;
 mod prompt_flusher;
-
+mod replay;
+mod screen_control;
+mod search;
+mod session_log;
+pub mod speedwalk;
+pub mod stats;
+pub mod terminal_type;
+
+use std::collections::VecDeque;
 use std::fmt::{Debug, Display, Formatter};
 use std::mem;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use futures::stream::FuturesUnordered;
-use pyo3::{pyclass, pymethods, Py, PyRefMut, Python};
+use pyo3::types::{PyAnyMethods, PyDict};
+use pyo3::{pyclass, pymethods, IntoPyObjectExt, Py, PyAny, PyRefMut, Python};
 use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::text::Line;
+use regex::Regex;
 use serde::Serialize;
 use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use tokio_util::bytes::Bytes;
 use tracing::{debug, info, instrument, trace, warn, Level};
 
+use crate::client::capture::BlockCapture;
+use crate::client::charset::Charset;
+use crate::client::conn_log::ConnectionLog;
 use crate::client::gmcp::Gmcp;
+use crate::client::import::{ImportFormat, ImportReport};
 use crate::client::input::{EchoState, Input};
+use crate::client::msdp::Msdp;
 use crate::client::output::Output;
 use crate::client::prompt_flusher::PromptFlusher;
-use crate::config::GlobalConfig;
-use crate::error::Error;
+use crate::client::search::Search;
+use crate::client::session_log::SessionLog;
+use crate::client::stats::SessionStats;
+use crate::client::terminal_type::TerminalType;
+use crate::config::{GlobalConfig, Secrets};
+use crate::error::{ConfigError, Error};
 use crate::idmap::{IdMap, Identifiable};
 use crate::model::{
-    Alias, AliasConfig, InputLine, KeyEvent as PyKeyEvent, MudLine, PromptMode, PromptSignal,
-    SessionInfo, Trigger, TriggerConfig,
+    split_commands, unescape_separator, Alias, AliasConfig, ClearScreen, EmptyEnter, InputLine,
+    InputTransform, KeyEvent as PyKeyEvent, LogFormat, Mud, MudLine, PasteMode, PromptMode,
+    PromptSignal, SessionInfo, SubnegotiationHandler, Substitution, Tls, Trigger, TriggerConfig,
 };
 use crate::net::telnet::codec::{Item as TelnetItem, Negotiation};
 use crate::net::{connection, stream, telnet};
 use crate::python;
+use crate::tui::buffer::BufferDirection;
 use crate::tui::extrabuffer::ExtraBuffer;
 use crate::tui::gauge::Gauge;
 use crate::tui::layout::LayoutNode;
+use crate::tui::mudbuffer::OUTPUT_SECTION_NAME;
 use crate::tui::session;
+use crate::tui::terminal::ColorSupport;
+
+/// The maximum number of sent lines remembered for [`Client::sent_lines`].
+const SENT_LINES_LIMIT: usize = 100;
+
+/// The most lines captured into [`Client::banner`] for one connection.
+const BANNER_LINES_LIMIT: usize = 1000;
+
+/// The delay before the first automatic reconnect attempt. Doubled for each later attempt.
+const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(2);
+
+/// The longest delay between automatic reconnect attempts.
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// How long the output buffer size has to settle after a resize before it's sent with NAWS.
+const NAWS_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// An event from one of a client's own background tasks, e.g. its reconnect timer. Events
+/// from the client's connection are [`connection::Event`]s instead.
+#[derive(Debug)]
+pub struct TaskEvent {
+    pub session_id: u32,
+    pub task: Task,
+}
+
+/// The client background task that finished, see [`Client::process_task`].
+#[derive(Debug)]
+pub enum Task {
+    /// A scheduled automatic reconnect attempt is due.
+    Reconnect,
+
+    /// The next line in the command queue is due to be sent.
+    SendQueued,
+
+    /// Nothing has been sent for long enough that an idle keepalive is due.
+    IdleKeepalive,
+
+    /// The output buffer size has settled after a resize, and is due to be sent with NAWS.
+    SendNaws,
+
+    /// A connect attempt finished, successfully or not.
+    Connected(Result<(connection::Handle, stream::Info), Error>),
+}
 
 /// A telnet MUD client.
 #[derive(Debug)]
@@ -42,20 +118,95 @@ pub struct Client {
     pub input: Input,
     pub output: Output,
     pub prompt: Option<MudLine>,
+    /// Recently received prompts and when they arrived, oldest first. Cleared on connect.
+    prompt_history: VecDeque<(SystemTime, MudLine)>,
     pub triggers: IdMap<Trigger>,
+    /// Whether triggers are evaluated at all. Overrides the enabled state of each trigger.
+    pub triggers_enabled: bool,
     pub aliases: IdMap<Alias>,
+    /// Whether aliases are evaluated at all. Overrides the enabled state of each alias.
+    pub aliases_enabled: bool,
+    /// Search-and-replace rules applied to each line of output, after triggers.
+    pub substitutions: IdMap<Substitution>,
+    // Substitution IDs in the order they're applied, kept to avoid sorting for each line.
+    substitution_order: Vec<u32>,
     pub buffer_dimensions: (u16, u16),
+    /// The rows of output rendered in the main output buffer by the most recent draw.
+    pub visible_output: Vec<Line<'static>>,
+    /// The main output's scroll position as of the most recent draw, counted in items back
+    /// from the most recent item. Zero when it isn't scrolled back.
+    pub output_scroll: usize,
     pub layout: Py<LayoutNode>,
+    /// A dict for scripts to keep state in for the life of the session, including across
+    /// reconnects. It's dropped when the session is closed.
+    pub data: Py<PyDict>,
+    /// The output received after connecting, before logging in, like a MOTD. Kept until
+    /// the next connection.
+    pub banner: Vec<output::Item>,
+    /// Whether output is still being added to the banner.
+    capturing_banner: bool,
+    /// The pattern of the line that ends the banner, if any.
+    banner_end: Option<Regex>,
     pub extra_buffers: IdMap<ExtraBuffer>,
     pub gauges: IdMap<Py<Gauge>>,
     pub gmcp: Gmcp,
+    pub msdp: Msdp,
+    /// The character set negotiated with the MUD, used to decode and encode text.
+    charset: Charset,
+    terminal_type: TerminalType,
+    pub subnegotiation_handlers: Vec<SubnegotiationHandler>,
+    pub input_transforms: Vec<InputTransform>,
+    line_batch: Vec<MudLine>,
+    /// The most recent lines transmitted to the MUD, oldest first. Kept across reconnects.
+    sent_lines: VecDeque<InputLine>,
+    /// Throughput statistics for the current connection.
+    stats: SessionStats,
+    /// A block of output being gagged and collected, with the callback to give it to.
+    block_capture: Option<(BlockCapture, Py<PyAny>)>,
+    /// A task that will connect the client at a scheduled time, if one is pending.
+    pub scheduled_connect: Option<JoinHandle<()>>,
+    /// A timer for the next automatic reconnect attempt, if one is pending.
+    reconnect_task: Option<JoinHandle<()>>,
+    /// The number of automatic reconnect attempts made since the last successful connection.
+    reconnect_attempt: u32,
+    /// Lines waiting to be sent, each with how long to wait after sending it.
+    command_queue: VecDeque<(InputLine, Duration)>,
+    /// A timer for releasing the next queued line, while sends are being paced.
+    command_queue_task: Option<JoinHandle<()>>,
+    /// A timer for the next idle keepalive, restarted whenever something is sent.
+    keepalive_task: Option<JoinHandle<()>>,
+    /// A timer for sending the output buffer size with NAWS, restarted on each resize.
+    naws_task: Option<JoinHandle<()>>,
+    output_filter: Option<Regex>,
+    /// A transient override of the MUD's line wrapping config for the output buffer.
+    line_wrap: Option<bool>,
+    timestamps: Option<bool>,
+    filter_stash: Option<InputLine>,
+    /// A search of the output, edited in the input area in place of normal input.
+    search: Option<Search>,
+    /// The output item index of a search match the output should be scrolled to.
+    search_jump: Option<usize>,
+    /// A scroll of the main output requested by a script, see [`Client::scroll_buffer`].
+    /// Items are counted from the first item ever added, so evictions don't move them.
+    scroll_request: Option<ScrollRequest>,
+    last_input: Option<InputLine>,
     config: GlobalConfig,
     event_tx: UnboundedSender<python::Event>,
     conn_tx: UnboundedSender<connection::Event>,
+    task_tx: UnboundedSender<TaskEvent>,
     conn_state: State,
     telnet_state: telnet::negotiation::Table,
     prompt_mode: PromptMode,
     prompt_flusher: Option<PromptFlusher>,
+    conn_log: Option<ConnectionLog>,
+    session_log: Option<SessionLog>,
+    /// A task feeding captured output to the client, if a replay is running.
+    replay_task: Option<JoinHandle<()>>,
+    /// Limits the number of sessions connecting at once. Shared by all clients.
+    connect_limit: Option<Arc<Semaphore>>,
+    /// A task waiting for a free slot under `connect_limit` and then dialing the MUD, while
+    /// connecting.
+    connect_task: Option<JoinHandle<()>>,
 }
 
 impl Client {
@@ -69,27 +220,85 @@ impl Client {
         config: GlobalConfig,
         event_tx: UnboundedSender<python::Event>,
         conn_tx: UnboundedSender<connection::Event>,
+        task_tx: UnboundedSender<TaskEvent>,
+        connect_limit: Option<Arc<Semaphore>>,
     ) -> Self {
         let id = info.id;
+        let mud = config.lookup_mud(&info.mud_name);
+        let use_eor = mud.as_ref().map_or(true, |mud| mud.use_eor);
+        let terminal_type = mud
+            .as_ref()
+            .map_or(true, |mud| !mud.terminal_types.is_empty());
+        let mut input = Input::default();
+        input.set_history_size(config.history_size());
+        let mut output = Output::default();
+        output.set_max_len(config.max_buffer_lines());
+        if let Some(mud) = mud.as_ref().filter(|mud| mud.persist_history) {
+            match history::load(&info.mud_name, mud.character.as_deref()) {
+                Ok(lines) => lines.iter().for_each(|line| input.add_history(line)),
+                Err(err) => warn!("failed to load input history: {err}"),
+            }
+        }
         Self {
             info,
-            input: Input::default(),
-            output: Output::default(),
+            input,
+            output,
             prompt: None,
+            prompt_history: VecDeque::default(),
             triggers: IdMap::default(),
+            triggers_enabled: true,
             aliases: IdMap::default(),
+            aliases_enabled: true,
+            substitutions: IdMap::default(),
+            substitution_order: Vec::default(),
             buffer_dimensions: (0, 0),
+            visible_output: Vec::default(),
+            output_scroll: 0,
             layout: session::initial_layout(),
+            data: Python::with_gil(|py| PyDict::new(py).unbind()),
+            banner: Vec::default(),
+            capturing_banner: false,
+            banner_end: None,
             extra_buffers: IdMap::default(),
             gauges: IdMap::default(),
             gmcp: Gmcp::new(id),
+            msdp: Msdp::new(id),
+            charset: Charset::new(&[]),
+            terminal_type: TerminalType::new(&[], 0),
+            subnegotiation_handlers: Vec::default(),
+            input_transforms: Vec::default(),
+            line_batch: Vec::default(),
+            sent_lines: VecDeque::default(),
+            stats: SessionStats::default(),
+            block_capture: None,
+            scheduled_connect: None,
+            reconnect_task: None,
+            reconnect_attempt: 0,
+            command_queue: VecDeque::default(),
+            command_queue_task: None,
+            keepalive_task: None,
+            naws_task: None,
+            output_filter: None,
+            line_wrap: None,
+            timestamps: None,
+            filter_stash: None,
+            search: None,
+            search_jump: None,
+            scroll_request: None,
+            last_input: None,
             config,
             event_tx,
             conn_tx,
+            task_tx,
             conn_state: State::default(),
-            telnet_state: initial_telnet_state(),
+            telnet_state: initial_telnet_state(use_eor, terminal_type),
             prompt_mode: PromptMode::default(),
             prompt_flusher: None,
+            conn_log: None,
+            session_log: None,
+            replay_task: None,
+            connect_limit,
+            connect_task: None,
         }
     }
 
@@ -102,23 +311,46 @@ impl Client {
     ) -> Result<(), Error> {
         match event {
             connection::SessionEvent::Error(err) => {
+                self.log_connection_event(conn_log::Event::Error {
+                    reason: err.to_string(),
+                });
+                self.cancel_queued_commands();
+                self.stop_idle_keepalive();
+                self.save_history_on_disconnect();
+                self.stats.connected_at = None;
+                // A user initiated disconnect has already left the connected state.
+                let dropped = self.connected();
                 self.conn_state = State::Disconnected;
                 self.event_tx.send(self.connection_event())?;
                 self.output.push(output::Item::ConnectionEvent {
                     status: self.status(),
                 });
+                if dropped {
+                    self.schedule_reconnect()?;
+                }
                 return Err(err);
             }
             connection::SessionEvent::Disconnected => {
+                self.log_connection_event(conn_log::Event::Disconnected);
+                self.cancel_queued_commands();
+                self.stop_idle_keepalive();
+                self.save_history_on_disconnect();
+                self.stats.connected_at = None;
+                let dropped = self.connected();
                 self.conn_state = State::Disconnected;
                 self.event_tx.send(self.connection_event())?;
                 self.output.push(output::Item::ConnectionEvent {
                     status: self.status(),
                 });
+                if dropped {
+                    self.schedule_reconnect()?;
+                }
                 return Ok(());
             }
             connection::SessionEvent::PartialLine(data) => {
-                let mut prompt = MudLine::from(data);
+                self.stats.received(data.len());
+                let data = self.charset.decode(data);
+                let mut prompt = MudLine::from(self.screen_control(data));
                 prompt.prompt = true;
 
                 self.process_prompt(&mut prompt, futures)?;
@@ -129,19 +361,46 @@ impl Client {
                 trace!("{item}");
                 self.output.push(item);
                 self.prompt = Some(prompt.clone());
+                self.record_prompt(prompt.clone());
                 self.event_tx.send(python::Event::Prompt {
                     id: self.info.id,
                     prompt,
                 })?;
             }
             connection::SessionEvent::Telnet(item) => {
+                if let TelnetItem::Line(data) | TelnetItem::Subnegotiation(_, data) = &item {
+                    self.stats.received(data.len());
+                }
                 self.process_telnet(item, futures)?;
             }
+            connection::SessionEvent::ReadComplete => {
+                self.flush_line_batch()?;
+            }
         }
 
         Ok(())
     }
 
+    /// Process the result of one of the client's background tasks.
+    ///
+    /// # Errors
+    /// If the work the task was waiting to do fails, e.g. sending a queued line.
+    pub fn process_task(&mut self, task: Task) -> Result<(), Error> {
+        match task {
+            Task::Reconnect => self.reconnect(),
+            Task::Connected(result) => self.connect_finished(result),
+            Task::SendQueued => self.send_queued_command(),
+            Task::IdleKeepalive => self.send_idle_keepalive(),
+            Task::SendNaws => {
+                self.naws_task = None;
+                if let Err(err) = self.send_naws() {
+                    warn!("failed to send NAWS size: {err}");
+                }
+                Ok(())
+            }
+        }
+    }
+
     /// Process a key event, potentially sending a line of input.
     ///
     /// If the key press is the enter key, the input buffer's contents are popped,
@@ -157,17 +416,34 @@ impl Client {
         futures: &mut FuturesUnordered<python::PyFuture>,
         event: &KeyEvent,
     ) -> Result<(), Error> {
-        // If the key event was Enter being pressed, send the queued input.
+        if self.searching() {
+            self.search_key_event(event);
+            return Ok(());
+        }
+
+        // If the key event was Enter being pressed, send the queued input, or finish editing
+        // the live output filter.
         if let &KeyEvent {
             code: KeyCode::Enter,
             ..
         } = event
         {
+            if self.filter_editing() {
+                self.finish_filter_editing();
+                return Ok(());
+            }
             return self.transmit_queued_input(futures);
         }
 
         // Otherwise, handle the input key event.
         self.input.handle_key_event(event);
+
+        // While editing the live output filter, update it as-you-type. Partial patterns are
+        // often invalid regexes, so errors are ignored here and the previous filter is kept.
+        if self.filter_editing() {
+            let _ = self.set_output_filter(Some(&self.input.value().sent));
+        }
+
         if let Ok(model_event) = PyKeyEvent::try_from(*event) {
             self.event_tx.send(python::Event::KeyPress {
                 id: self.info.id,
@@ -177,19 +453,439 @@ impl Client {
         Ok(())
     }
 
+    /// Handle text pasted into the input, according to the MUD's `paste_mode`.
+    ///
+    /// # Errors
+    /// If sending a pasted line fails.
+    pub fn paste(
+        &mut self,
+        futures: &mut FuturesUnordered<python::PyFuture>,
+        text: &str,
+    ) -> Result<(), Error> {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+
+        // Search and the live output filter are single patterns, so never send while editing
+        // them.
+        if let Some(search) = &mut self.search {
+            search.browsing = false;
+            self.input.paste(&text.replace('\n', " "));
+            let _ = search.set_pattern(&self.input.value().sent, &mut self.output);
+            return Ok(());
+        }
+        if self.filter_editing() {
+            self.input.paste(&text.replace('\n', " "));
+            let _ = self.set_output_filter(Some(&self.input.value().sent));
+            return Ok(());
+        }
+
+        let mud = self.config.must_lookup_mud(&self.info.mud_name)?;
+        match mud.paste_mode {
+            PasteMode::Input => {
+                let separator = mud.command_separator.as_deref().unwrap_or(" ");
+                self.input
+                    .paste(&text.trim_end_matches('\n').replace('\n', separator));
+            }
+            PasteMode::SendLines => {
+                let mut lines = text.split('\n');
+                // Safety: split always yields at least one item.
+                let rest = lines.next_back().unwrap();
+                for line in lines {
+                    self.input.paste(line);
+                    self.transmit_queued_input(futures)?;
+                }
+                self.input.paste(rest);
+            }
+        }
+        Ok(())
+    }
+
+    /// Set the live output filter pattern. Only output matching the pattern is displayed
+    /// while a filter is set. Passing `None`, or an empty pattern, clears the filter.
+    ///
+    /// # Errors
+    /// If the pattern isn't a valid regex. The previous filter is kept in this case.
+    pub fn set_output_filter(&mut self, pattern: Option<&str>) -> Result<(), Error> {
+        self.output_filter = match pattern {
+            Some(pattern) if !pattern.is_empty() => {
+                Some(Regex::new(pattern).map_err(Error::Filter)?)
+            }
+            _ => None,
+        };
+        Ok(())
+    }
+
+    /// Returns the live output filter, if one is set.
+    #[must_use]
+    pub fn output_filter(&self) -> Option<&Regex> {
+        self.output_filter.as_ref()
+    }
+
+    /// Update the dimensions of the output buffer.
+    ///
+    /// When the telnet NAWS option is enabled the new size is sent to the MUD, once it has
+    /// stopped changing for a moment so that dragging a window edge doesn't flood the MUD.
+    pub fn set_buffer_dimensions(&mut self, dimensions: (u16, u16)) {
+        self.buffer_dimensions = dimensions;
+        if let Some(task) = self.naws_task.take() {
+            task.abort();
+        }
+        if !self.naws_enabled() {
+            return;
+        }
+        let task_tx = self.task_tx.clone();
+        let session_id = self.info.id;
+        self.naws_task = Some(tokio::spawn(async move {
+            tokio::time::sleep(NAWS_DEBOUNCE).await;
+            let _ = task_tx.send(TaskEvent {
+                session_id,
+                task: Task::SendNaws,
+            });
+        }));
+    }
+
+    /// Returns true if the telnet NAWS (window size) option has been negotiated with the MUD.
+    #[must_use]
+    pub fn naws_enabled(&self) -> bool {
+        self.telnet_state
+            .option(telnet::option::NAWS)
+            .local_enabled()
+    }
+
+    // Send the output buffer dimensions to the MUD, as described in RFC 1073.
+    fn send_naws(&self) -> Result<(), Error> {
+        // The negotiated state lingers after a disconnect, until the next connection.
+        let Ok(handle) = self.connected_handle() else {
+            return Ok(());
+        };
+        if !self.naws_enabled() || self.buffer_dimensions == (0, 0) {
+            return Ok(());
+        }
+        let (width, height) = self.buffer_dimensions;
+        let data = [width.to_be_bytes(), height.to_be_bytes()].concat();
+        trace!("sending NAWS size {width}x{height}");
+        handle.send(TelnetItem::Subnegotiation(telnet::option::NAWS, data.into()).into())
+    }
+
+    /// Returns whether the output buffer wraps long lines.
+    ///
+    /// A runtime override set with [`Client::set_line_wrap`] takes precedence over the MUD's
+    /// `no_line_wrap` config.
+    #[must_use]
+    pub fn line_wrap(&self) -> bool {
+        self.line_wrap.unwrap_or_else(|| {
+            self.config
+                .lookup_mud(&self.info.mud_name)
+                .map_or(true, |mud| !mud.no_line_wrap)
+        })
+    }
+
+    /// Override line wrapping for the output buffer without changing the MUD's config.
+    /// Passing `None` reverts to the configured behaviour.
+    pub fn set_line_wrap(&mut self, line_wrap: Option<bool>) {
+        self.line_wrap = line_wrap;
+    }
+
+    /// Returns whether MUD output lines are shown with the time they were received.
+    ///
+    /// A runtime override set with [`Client::set_show_timestamps`] takes precedence over the
+    /// `show_timestamps` config.
+    #[must_use]
+    pub fn show_timestamps(&self) -> bool {
+        self.timestamps
+            .unwrap_or_else(|| self.config.show_timestamps())
+    }
+
+    /// Override showing timestamps without changing the config. Passing `None` reverts to the
+    /// configured behaviour.
+    pub fn set_show_timestamps(&mut self, timestamps: Option<bool>) {
+        self.timestamps = timestamps;
+    }
+
+    /// Returns the hanging indent of wrapped output lines, or `None` if continuation lines
+    /// aren't indented.
+    #[must_use]
+    pub fn wrap_indent(&self) -> Option<u16> {
+        self.config.wrap_indent()
+    }
+
+    /// Returns the format of the timestamp gutter drawn beside output, or `None` if
+    /// timestamps aren't shown.
+    #[must_use]
+    pub fn timestamp_gutter(&self) -> Option<String> {
+        self.show_timestamps()
+            .then(|| self.config.timestamp_format())
+    }
+
+    /// Returns true if the input area is being used to edit the live output filter.
+    #[must_use]
+    pub fn filter_editing(&self) -> bool {
+        self.filter_stash.is_some()
+    }
+
+    /// Toggle editing the live output filter in the input area.
+    ///
+    /// When toggled on, the in-progress input is stashed and replaced with the current filter
+    /// pattern. When toggled off the filter is cleared and the stashed input is restored.
+    pub fn toggle_filter_editing(&mut self) {
+        self.close_search();
+        if let Some(stashed) = self.filter_stash.take() {
+            self.output_filter = None;
+            self.input.set_value(stashed);
+        } else {
+            let pattern = self
+                .output_filter
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+            self.filter_stash = Some(self.input.value());
+            self.input.set_value(InputLine::new(pattern, true, false));
+        }
+    }
+
+    fn finish_filter_editing(&mut self) {
+        let pattern = self.input.value().sent;
+        if let Some(stashed) = self.filter_stash.take() {
+            self.input.set_value(stashed);
+        }
+
+        if let Err(err) = self.set_output_filter(Some(&pattern)) {
+            self.output.push(output::Item::CommandResult {
+                error: true,
+                message: err.to_string(),
+            });
+        }
+    }
+
+    /// Returns true if the input area is being used to search the output.
+    #[must_use]
+    pub fn searching(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// Toggle searching the output in the input area.
+    ///
+    /// When toggled on, the in-progress input is stashed and replaced with an empty search
+    /// pattern. Matches are highlighted as the pattern is typed. Pressing enter jumps to the
+    /// most recent match, after which `n` and `N` move to older and newer matches. When
+    /// toggled off (or on escape) the stashed input is restored.
+    pub fn toggle_search(&mut self) {
+        if self.searching() {
+            self.close_search();
+            return;
+        }
+        if self.filter_editing() {
+            self.finish_filter_editing();
+        }
+        self.search = Some(Search::new(self.input.value()));
+        self.input
+            .set_value(InputLine::new(String::new(), true, false));
+    }
+
+    /// Returns the pattern used to highlight search matches, while searching.
+    #[must_use]
+    pub fn search_highlight(&self) -> Option<&Regex> {
+        self.search.as_ref()?.regex.as_ref()
+    }
+
+    /// Returns a title for the input area while searching, e.g. `search 2/5`.
+    #[must_use]
+    pub fn search_title(&self) -> Option<String> {
+        self.search.as_ref().map(Search::title)
+    }
+
+    /// Take the output item index of the search match the output should scroll to, if
+    /// the search moved since the last call.
+    pub fn take_search_jump(&mut self) -> Option<usize> {
+        self.search_jump.take()
+    }
+
+    /// Scroll the buffer with the layout name `buffer_name`, either the main output or an
+    /// extra buffer, returning its new scroll position.
+    ///
+    /// Positions are clamped to the buffer's scroll range, so scrolling past the oldest or
+    /// newest output stops there. The main output is scrolled by the session tab on its next
+    /// draw.
+    ///
+    /// # Errors
+    /// If there's no buffer with the given layout name.
+    pub fn scroll_buffer(
+        &mut self,
+        buffer_name: &str,
+        target: ScrollTarget,
+    ) -> Result<usize, Error> {
+        if buffer_name == OUTPUT_SECTION_NAME {
+            // Scroll positions count items back from the most recent item.
+            let last = self.output.len().saturating_sub(1);
+            let pos = match target {
+                ScrollTarget::Lines(lines) => self.output_scroll.saturating_add_signed(lines),
+                ScrollTarget::Item(idx) => last.saturating_sub(idx),
+            }
+            .min(last);
+            // The output may change before the next draw, so the request is anchored to the
+            // item that's shown at the bottom rather than to the position.
+            self.scroll_request = Some(match pos {
+                0 => ScrollRequest::Bottom,
+                pos => ScrollRequest::Item(self.output.evicted() + last - pos),
+            });
+            self.output_scroll = pos;
+            return Ok(pos);
+        }
+
+        Python::with_gil(|py| {
+            let buffer = self
+                .extra_buffers
+                .iter()
+                .map(|(_, buffer)| buffer)
+                .find(|buffer| buffer.config.borrow(py).layout_name == buffer_name)
+                .ok_or_else(|| Error::LayoutMissing(buffer_name.to_string()))?;
+            let mut config = buffer.config.borrow_mut(py);
+            let last = config.output.borrow(py).len().saturating_sub(1);
+            let pos = match (target, config.direction) {
+                (ScrollTarget::Lines(lines), _) => config.scroll_pos.saturating_add_signed(lines),
+                (ScrollTarget::Item(idx), BufferDirection::BottomToTop) => last.saturating_sub(idx),
+                (ScrollTarget::Item(idx), BufferDirection::TopToBottom) => idx,
+            };
+            // The scroll range depends on the buffer's size, known as of the last draw.
+            let max_scroll = config.max_scroll;
+            config.scroll_to(pos.min(max_scroll));
+            Ok(config.scroll_pos)
+        })
+    }
+
+    /// Scroll the main output so the closest prompt before (or after) the bottom-most visible
+    /// item is shown at the bottom, returning the new scroll position.
+    ///
+    /// Like the prompt navigation shortcuts, this stops at the oldest and newest prompts
+    /// rather than wrapping around, and keeps the current position when there's no prompt to
+    /// move to.
+    ///
+    /// # Errors
+    /// If the main output is missing from the layout.
+    pub fn scroll_to_prompt(&mut self, previous: bool) -> Result<usize, Error> {
+        let bottom = self
+            .output
+            .len()
+            .saturating_sub(1)
+            .saturating_sub(self.output_scroll);
+        let target = match previous {
+            true => self.output.prompt_before(bottom),
+            false => self.output.prompt_after(bottom),
+        };
+        match target {
+            Some(idx) => self.scroll_buffer(OUTPUT_SECTION_NAME, ScrollTarget::Item(idx)),
+            None => Ok(self.output_scroll),
+        }
+    }
+
+    /// Clear the main output, or the extra buffer with `buffer_id`, and scroll it back to
+    /// the bottom.
+    ///
+    /// # Errors
+    /// If there's no extra buffer with `buffer_id`, or the event can't be sent.
+    pub fn clear_buffer(&mut self, buffer_id: Option<u32>) -> Result<(), Error> {
+        match buffer_id {
+            None => {
+                self.output.clear();
+                self.scroll_request = Some(ScrollRequest::Bottom);
+                self.output_scroll = 0;
+            }
+            Some(buffer_id) => {
+                let buffer = self
+                    .extra_buffers
+                    .get(buffer_id)
+                    .ok_or(Error::UnknownBuffer(buffer_id))?;
+                Python::with_gil(|py| {
+                    let mut config = buffer.config.borrow_mut(py);
+                    config.output.borrow_mut(py).clear();
+                    config.scroll_to(0);
+                });
+            }
+        }
+        self.event_tx.send(python::Event::BufferCleared {
+            id: self.info.id,
+            buffer_id,
+        })?;
+        Ok(())
+    }
+
+    /// Take the scroll of the main output requested by a script, if there's one that hasn't
+    /// been applied yet. Requested items are returned as indexes into the output as it is now.
+    pub fn take_scroll_request(&mut self) -> Option<ScrollRequest> {
+        Some(match self.scroll_request.take()? {
+            ScrollRequest::Bottom => ScrollRequest::Bottom,
+            // An evicted item is replaced by the oldest remaining one.
+            ScrollRequest::Item(item) => {
+                ScrollRequest::Item(item.saturating_sub(self.output.evicted()))
+            }
+        })
+    }
+
+    fn search_key_event(&mut self, event: &KeyEvent) {
+        // Safety: only called while searching.
+        let search = self.search.as_mut().unwrap();
+        match event.code {
+            KeyCode::Esc => self.close_search(),
+            KeyCode::Enter => {
+                let pattern = self.input.value().sent;
+                if let Err(err) = search.set_pattern(&pattern, &mut self.output) {
+                    self.output.push(output::Item::CommandResult {
+                        error: true,
+                        message: err.to_string(),
+                    });
+                    return;
+                }
+                search.browsing = true;
+                self.search_jump = search.step(&mut self.output, true);
+            }
+            KeyCode::Char(c @ ('n' | 'N')) if search.browsing => {
+                if let Some(idx) = search.step(&mut self.output, c == 'n') {
+                    self.search_jump = Some(idx);
+                }
+            }
+            _ => {
+                // Partial patterns are often invalid regexes, so errors are ignored here
+                // and the previous pattern is kept until enter is pressed.
+                search.browsing = false;
+                self.input.handle_key_event(event);
+                let _ = search.set_pattern(&self.input.value().sent, &mut self.output);
+            }
+        }
+    }
+
+    fn close_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.input.set_value(search.stash);
+        }
+        self.search_jump = None;
+    }
+
     fn transmit_queued_input(
         &mut self,
         futures: &mut FuturesUnordered<python::PyFuture>,
     ) -> Result<(), Error> {
-        // Pull the to-be-sent input. If it's None, transmit an empty line.
-        let Some(queued_input) = self.input.pop() else {
+        let mud = self.config.must_lookup_mud(&self.info.mud_name)?;
+
+        // Pull the to-be-sent input. If it's None, the MUD's empty enter config decides
+        // what to do.
+        let queued_input = match self.input.pop() {
+            Some(queued_input) => Some(queued_input),
+            None => match mud.empty_enter {
+                EmptyEnter::SendBlank => None,
+                EmptyEnter::Ignore => {
+                    trace!("ignoring empty input");
+                    return Ok(());
+                }
+                EmptyEnter::RepeatLast => self.last_input.clone(),
+            },
+        };
+
+        // If there's still nothing to send, transmit an empty line.
+        let Some(queued_input) = queued_input else {
             return self.transmit_input(InputLine::default(), futures);
         };
+        self.last_input = Some(queued_input.clone());
 
-        let cmd_separator = self
-            .config
-            .must_lookup_mud(&self.info.mud_name)?
-            .command_separator;
+        let cmd_separator = mud.command_separator;
 
         // If the queued input is itself empty after trim, or there's no command
         // separator configured for the MUD, we can blast it out as-is.
@@ -207,20 +903,29 @@ impl Client {
 
     fn transmit_input(
         &mut self,
-        mut input: InputLine,
+        input: InputLine,
         futures: &mut FuturesUnordered<python::PyFuture>,
     ) -> Result<(), Error> {
         let session_id = self.info.id;
 
+        // Run the input line through each input transform, in order. A transform returning
+        // None cancels sending the input entirely.
+        let Some(mut input) = self.apply_input_transforms(input)? else {
+            trace!("input cancelled by input transform");
+            return Ok(());
+        };
+
         let empty_transmit = input.sent.is_empty();
         let mut skip_transmit = false;
 
         // Empty lines can't match aliases.
-        if !empty_transmit {
+        if !empty_transmit && self.aliases_enabled {
             // Run the input line through each enabled alias to see if any match. A mutable ref to
             // input is passed to allow changing it when an alias matches.
-            for alias in self.aliases.values_mut().filter(|alias| alias.enabled) {
-                Self::evaluate_alias(session_id, alias, &mut input, futures)?;
+            for id in self.alias_order() {
+                // Safety: alias_order only returns ids of aliases in the map.
+                let alias = self.aliases.get_mut(id).unwrap();
+                Self::evaluate_alias(&self.config, session_id, alias, &mut input, futures)?;
 
                 // If an alias replaced the to-be-sent text that we know wasn't empty originally
                 // with empty text, then we take that as an indicator that the alias "ate" the
@@ -237,6 +942,7 @@ impl Client {
         // buffer as if it were sent, but never send any content to the MUD.
         if skip_transmit {
             trace!("pushing non-transmitted line: {input:?}");
+            self.input.add_history(&input);
             self.output.push(output::Item::Input {
                 line: input.clone(),
             });
@@ -255,115 +961,750 @@ impl Client {
 
     /// Connect the client to the MUD server.
     ///
+    /// This returns straight away, leaving the client connecting. The connection is dialed in
+    /// a task, once a slot is free under the concurrent connect limit, so that the caller
+    /// (and the state lock it holds) isn't held up. If the connection can't be established
+    /// within the MUD's `connect_timeout`, or at all, the failure is reported in the output
+    /// and the client returns to the disconnected state.
+    ///
     /// # Errors
-    /// If the connection can't be established.
-    // TODO(XXX): Timeouts....
+    /// If the MUD's config is invalid.
     #[instrument(level = Level::TRACE, skip(self), fields(self.info = %self.info))]
-    pub async fn connect(&mut self) -> Result<(), Error> {
+    pub fn connect(&mut self) -> Result<(), Error> {
         if !matches!(self.conn_state, State::Disconnected) {
             warn!("already connected");
             return Ok(());
         }
 
-        let mud = self.config.must_lookup_mud(&self.info.mud_name)?;
+        let mud = self.config.must_lookup_mud(&self.info.mud_name)?;
+        self.banner_end = mud
+            .banner_end
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|err| {
+                ConfigError::InvalidMud(format!("MUD {:?} banner_end {err}", mud.name))
+            })?;
+        self.cancel_replay();
+        self.charset = Charset::new(&mud.charset);
+        self.terminal_type = TerminalType::new(
+            &mud.terminal_types,
+            terminal_type::mtts(ColorSupport::detect()),
+        );
+
+        self.conn_log = mud.connection_log.as_ref().and_then(|path| {
+            match ConnectionLog::open(self.info.id, mud.name.clone(), path) {
+                Ok(log) => Some(log),
+                Err(err) => {
+                    warn!("failed to open connection log {path:?}: {err}");
+                    None
+                }
+            }
+        });
+        if mud.auto_log && self.session_log.is_none() {
+            if let Err(err) = self.start_logging(None, None) {
+                warn!("failed to start session log: {err}");
+                self.output.push(output::Item::CommandResult {
+                    error: true,
+                    message: format!("failed to start session log: {err}"),
+                });
+            }
+        }
+        self.log_connection_event(conn_log::Event::Connecting {
+            host: &mud.host,
+            port: mud.port,
+            tls: mud.tls != Tls::Disabled,
+        });
+
+        self.conn_state = State::Connecting;
+        self.telnet_state = initial_telnet_state(mud.use_eor, !mud.terminal_types.is_empty());
+        self.event_tx.send(self.connection_event())?;
+
+        self.spawn_connect(mud);
+        Ok(())
+    }
+
+    fn spawn_connect(&mut self, mud: Mud) {
+        let limit = self.connect_limit.clone();
+        let conn_tx = self.conn_tx.clone();
+        let task_tx = self.task_tx.clone();
+        let session_id = self.info.id;
+        let task = tokio::spawn(async move {
+            // The permit is held until the connection attempt completes.
+            let _permit = match limit {
+                Some(limit) => {
+                    if limit.available_permits() == 0 {
+                        info!("waiting for a free connect slot");
+                    }
+                    let Ok(permit) = limit.acquire_owned().await else {
+                        return;
+                    };
+                    Some(permit)
+                }
+                None => None,
+            };
+            let connect = connection::connect(session_id, &mud, conn_tx);
+            let result = match mud.connect_timeout {
+                0 => connect.await,
+                secs => tokio::time::timeout(Duration::from_secs(secs), connect)
+                    .await
+                    .unwrap_or(Err(Error::ConnectTimeout(secs))),
+            };
+            let _ = task_tx.send(TaskEvent {
+                session_id,
+                task: Task::Connected(result),
+            });
+        });
+        if let Some(previous) = self.connect_task.replace(task) {
+            previous.abort();
+        }
+    }
+
+    /// Record the outcome of a connect attempt started by [`Client::connect`].
+    ///
+    /// A failure is reported in the output rather than returned, and another reconnect
+    /// attempt is scheduled if this was one.
+    ///
+    /// # Errors
+    /// If sending the connection event fails.
+    fn connect_finished(
+        &mut self,
+        result: Result<(connection::Handle, stream::Info), Error>,
+    ) -> Result<(), Error> {
+        // The connect may have been cancelled after the attempt finished.
+        if self.connect_task.take().is_none() || !matches!(self.conn_state, State::Connecting) {
+            if let Ok((handle, _)) = result {
+                let _ = handle.send(connection::Action::Disconnect);
+            }
+            return Ok(());
+        }
+
+        match result {
+            Ok((handle, info)) => {
+                self.reconnect_attempt = 0;
+                self.prompt_history.clear();
+                self.stats = SessionStats::connected();
+                self.block_capture = None;
+                self.banner.clear();
+                self.capturing_banner = true;
+                self.log_connection_event(conn_log::Event::Connected {
+                    stream: info.to_string(),
+                });
+                let tx = handle.action_tx.clone();
+                self.conn_state = State::Connected { handle, info };
+                self.event_tx.send(self.connection_event())?;
+                self.output.push(output::Item::ConnectionEvent {
+                    status: self.status(),
+                });
+
+                if matches!(self.prompt_mode, PromptMode::Unsignalled { .. }) {
+                    trace!("spawning new prompt flusher");
+                    if let Some(flusher) = self
+                        .prompt_flusher
+                        .replace(PromptFlusher::new(tx, Duration::from_millis(200)))
+                    {
+                        trace!("stopping old prompt flusher");
+                        flusher.stop();
+                    }
+                }
+
+                self.request_enable_option(telnet::option::GMCP)?;
+                self.request_enable_option(telnet::option::CHARSET)?;
+                self.restart_idle_keepalive();
+            }
+            Err(err) => {
+                warn!("connect failed: {err}");
+                self.log_connection_event(conn_log::Event::ConnectFailed {
+                    reason: err.to_string(),
+                });
+                self.conn_state = State::Disconnected;
+                self.event_tx.send(self.connection_event())?;
+                self.output.push(output::Item::ConnectionEvent {
+                    status: self.status(),
+                });
+                self.output.push(output::Item::CommandResult {
+                    error: true,
+                    message: format!("connect failed: {err}"),
+                });
+                if self.reconnect_attempt > 0 {
+                    self.schedule_reconnect()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Disconnect the client from the MUD server.
+    ///
+    /// Returns immediately and without error if the connection to a MUD server
+    /// is already disconnected.
+    ///
+    /// # Errors
+    /// If joining on the client connection task fails.
+    #[instrument(level = Level::TRACE, skip(self))]
+    pub async fn disconnect(&mut self) -> Result<(), Error> {
+        // An intentional disconnect shouldn't be undone by an automatic reconnect.
+        self.cancel_reconnect();
+        self.cancel_queued_commands();
+        self.stop_idle_keepalive();
+        if let Some(task) = self.connect_task.take() {
+            task.abort();
+            self.conn_state = State::Disconnected;
+            self.log_connection_event(conn_log::Event::DisconnectRequested);
+            self.event_tx.send(self.connection_event())?;
+            return Ok(());
+        }
+        let State::Connected { handle, .. } = mem::take(&mut self.conn_state) else {
+            return Ok(());
+        };
+        self.stats.connected_at = None;
+        self.log_connection_event(conn_log::Event::DisconnectRequested);
+        if let Some(flusher) = self.prompt_flusher.take() {
+            flusher.stop();
+        }
+        handle.send(connection::Action::Disconnect)?;
+        handle
+            .task
+            .await
+            .map_err(|_| Error::Internal("joining on client conn".into()))??;
+        Ok(())
+    }
+
+    /// Run a pending automatic reconnect attempt, once its delay has elapsed.
+    ///
+    /// If the attempt fails another is scheduled, until the MUD's `reconnect_max_attempts`
+    /// is reached.
+    ///
+    /// # Errors
+    /// If scheduling the next attempt fails.
+    fn reconnect(&mut self) -> Result<(), Error> {
+        // The reconnect may have been cancelled after its timer fired.
+        if self.reconnect_task.take().is_none() || !matches!(self.conn_state, State::Disconnected) {
+            return Ok(());
+        }
+
+        info!("reconnect attempt {}", self.reconnect_attempt);
+        if let Err(err) = self.connect() {
+            warn!("reconnect attempt failed: {err}");
+            self.output.push(output::Item::CommandResult {
+                error: true,
+                message: format!("reconnect failed: {err}"),
+            });
+        }
+        // A connect that got under way reschedules itself if it fails.
+        if matches!(self.conn_state, State::Disconnected) {
+            self.schedule_reconnect()?;
+        }
+        Ok(())
+    }
+
+    /// Cancel a pending automatic reconnect, if there is one.
+    ///
+    /// Returns true if a reconnect was cancelled.
+    pub fn cancel_reconnect(&mut self) -> bool {
+        self.reconnect_attempt = 0;
+        match self.reconnect_task.take() {
+            Some(task) => {
+                debug!("cancelling reconnect");
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Schedule an automatic reconnect attempt after an unexpected disconnect, if the MUD is
+    // configured for it and attempts remain. The delay doubles with each attempt.
+    fn schedule_reconnect(&mut self) -> Result<(), Error> {
+        let Some(mud) = self.config.lookup_mud(&self.info.mud_name) else {
+            return Ok(());
+        };
+        if !mud.auto_reconnect {
+            return Ok(());
+        }
+        if mud.reconnect_max_attempts != 0 && self.reconnect_attempt >= mud.reconnect_max_attempts {
+            info!(
+                "giving up after {} reconnect attempts",
+                self.reconnect_attempt
+            );
+            self.output.push(output::Item::CommandResult {
+                error: true,
+                message: format!(
+                    "giving up after {} reconnect attempts",
+                    self.reconnect_attempt
+                ),
+            });
+            self.reconnect_attempt = 0;
+            return Ok(());
+        }
+
+        self.reconnect_attempt += 1;
+        let attempt = self.reconnect_attempt;
+        let delay = RECONNECT_BASE_DELAY
+            .saturating_mul(2_u32.saturating_pow(attempt - 1))
+            .min(RECONNECT_MAX_DELAY);
+        info!("reconnect attempt {attempt} in {delay:?}");
+        self.output.push(output::Item::CommandResult {
+            error: false,
+            message: format!("reconnecting in {}s (attempt {attempt})", delay.as_secs()),
+        });
+
+        let task_tx = self.task_tx.clone();
+        let session_id = self.info.id;
+        let task = tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let _ = task_tx.send(TaskEvent {
+                session_id,
+                task: Task::Reconnect,
+            });
+        });
+        if let Some(previous) = self.reconnect_task.replace(task) {
+            previous.abort();
+        }
+
+        self.event_tx.send(python::Event::Reconnecting {
+            id: session_id,
+            attempt,
+        })?;
+        Ok(())
+    }
+
+    /// Cancel a pending scheduled connection, if there is one.
+    ///
+    /// Returns true if a scheduled connection was cancelled.
+    pub fn cancel_scheduled_connect(&mut self) -> bool {
+        match self.scheduled_connect.take() {
+            Some(task) => {
+                debug!("cancelling scheduled connect");
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Send a line to the connection.
+    ///
+    /// # Errors
+    /// If the client is not connected.
+    #[instrument(level = Level::TRACE, skip(self, line), fields(sent = ?line.sent, original = ?line.original, scripted = ?line.scripted))]
+    pub fn send_line(&mut self, line: InputLine) -> Result<(), Error> {
+        let mud = self.config.must_lookup_mud(&self.info.mud_name)?;
+        let interval = Duration::from_millis(mud.command_interval);
+        self.send_line_paced(line, mud.command_separator.as_deref(), interval)
+    }
+
+    /// Send a line to the connection like [`Client::send_line`], but as a single command
+    /// without splitting it on the command separator.
+    ///
+    /// # Errors
+    /// If the client is not connected.
+    pub fn send_line_unsplit(&mut self, line: InputLine) -> Result<(), Error> {
+        let mud = self.config.must_lookup_mud(&self.info.mud_name)?;
+        let interval = Duration::from_millis(mud.command_interval);
+        self.send_line_paced(line, None, interval)
+    }
+
+    /// Send a line referencing `secrets` like [`Client::send_line`], or like
+    /// [`Client::send_line_unsplit`] if `split` is false. The line is split into commands
+    /// before its secrets are expanded, see [`Secrets::expand_commands`].
+    ///
+    /// # Errors
+    /// If the client is not connected, or a secret reference can't be expanded.
+    pub fn send_line_with_secrets(
+        &mut self,
+        line: &str,
+        secrets: &Secrets,
+        split: bool,
+    ) -> Result<(), Error> {
+        let mud = self.config.must_lookup_mud(&self.info.mud_name)?;
+        let interval = Duration::from_millis(mud.command_interval);
+        let separator = mud.command_separator.as_deref().filter(|_| split);
+        for command in secrets.expand_commands(line, separator)? {
+            self.pace_line(command, interval)?;
+        }
+        Ok(())
+    }
+
+    /// Send `password` as a single command like [`Client::send_line_unsplit`], masking it
+    /// in the output and `InputLine` event regardless of the telnet echo state.
+    ///
+    /// # Errors
+    /// If the client is not connected.
+    pub fn send_password(&mut self, password: String) -> Result<(), Error> {
+        self.send_line_unsplit(InputLine::new(password, false, true))
+    }
+
+    /// Send lines to the connection, waiting `interval` after each before sending the next.
+    ///
+    /// Lines are split on the command separator like [`Client::send_line`], and queued
+    /// behind any lines already waiting to be sent.
+    ///
+    /// # Errors
+    /// If the client is not connected.
+    pub fn queue_lines(&mut self, lines: Vec<InputLine>, interval: Duration) -> Result<(), Error> {
+        let mud = self.config.must_lookup_mud(&self.info.mud_name)?;
+        for line in lines {
+            self.send_line_paced(line, mud.command_separator.as_deref(), interval)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of lines waiting in the command queue.
+    #[must_use]
+    pub fn queued_commands(&self) -> usize {
+        self.command_queue.len()
+    }
+
+    /// Discard the lines waiting in the command queue, returning how many there were.
+    pub fn cancel_queued_commands(&mut self) -> usize {
+        if let Some(task) = self.command_queue_task.take() {
+            task.abort();
+        }
+        let count = self.command_queue.len();
+        if count > 0 {
+            debug!("discarding {count} queued commands");
+        }
+        self.command_queue.clear();
+        count
+    }
+
+    fn send_line_paced(
+        &mut self,
+        line: InputLine,
+        separator: Option<&str>,
+        interval: Duration,
+    ) -> Result<(), Error> {
+        match separator {
+            Some(sep) => {
+                for fragment in split_commands(&line.sent, sep) {
+                    let fragment = unescape_separator(fragment, sep);
+                    let mut line = line.clone();
+                    if line.sent != fragment {
+                        line.original = Some(line.sent);
+                        line.sent = fragment;
+                    }
+                    self.pace_line(line, interval)?;
+                }
+                Ok(())
+            }
+            None => self.pace_line(line, interval),
+        }
+    }
+
+    // Send the line right away unless earlier lines are still being paced, in which case it
+    // waits its turn in the command queue.
+    fn pace_line(&mut self, line: InputLine, interval: Duration) -> Result<(), Error> {
+        if self.command_queue_task.is_some() {
+            trace!("queueing {:?}", line.sent);
+            self.command_queue.push_back((line, interval));
+            return Ok(());
+        }
+        self.send_line_internal(line)?;
+        self.start_command_queue_timer(interval);
+        Ok(())
+    }
+
+    // Send the next queued line, once the interval after the previous line has elapsed.
+    fn send_queued_command(&mut self) -> Result<(), Error> {
+        self.command_queue_task = None;
+        let Some((line, interval)) = self.command_queue.pop_front() else {
+            return Ok(());
+        };
+        self.send_line_internal(line)?;
+        self.start_command_queue_timer(interval);
+        Ok(())
+    }
+
+    fn start_command_queue_timer(&mut self, interval: Duration) {
+        if interval.is_zero() {
+            return;
+        }
+        let task_tx = self.task_tx.clone();
+        let session_id = self.info.id;
+        self.command_queue_task = Some(tokio::spawn(async move {
+            tokio::time::sleep(interval).await;
+            let _ = task_tx.send(TaskEvent {
+                session_id,
+                task: Task::SendQueued,
+            });
+        }));
+    }
+
+    // Start waiting for the MUD's idle keepalive period again, e.g. because something was
+    // just sent. Does nothing when idle keepalives are disabled or the client isn't connected.
+    fn restart_idle_keepalive(&mut self) {
+        self.stop_idle_keepalive();
+        if !self.connected() {
+            return;
+        }
+        let period = match self.config.lookup_mud(&self.info.mud_name) {
+            Some(mud) if mud.idle_keepalive > 0 => Duration::from_secs(mud.idle_keepalive),
+            _ => return,
+        };
+        let task_tx = self.task_tx.clone();
+        let session_id = self.info.id;
+        self.keepalive_task = Some(tokio::spawn(async move {
+            tokio::time::sleep(period).await;
+            let _ = task_tx.send(TaskEvent {
+                session_id,
+                task: Task::IdleKeepalive,
+            });
+        }));
+    }
+
+    fn stop_idle_keepalive(&mut self) {
+        if let Some(task) = self.keepalive_task.take() {
+            task.abort();
+        }
+    }
+
+    // Send the MUD's idle keepalive line, or a telnet NOP if it doesn't have one. Keepalives
+    // aren't echoed, or recorded as input.
+    fn send_idle_keepalive(&mut self) -> Result<(), Error> {
+        self.keepalive_task = None;
+        if !self.connected() {
+            return Ok(());
+        }
+        let mud = self.config.must_lookup_mud(&self.info.mud_name)?;
+        let item = match mud.idle_keepalive_line {
+            Some(line) => TelnetItem::Line(self.charset.encode(&line)),
+            None => TelnetItem::IacCommand(telnet::command::NOP),
+        };
+        trace!("sending idle keepalive");
+        self.connected_handle()?
+            .send(connection::Action::Send(item))?;
+        self.restart_idle_keepalive();
+        Ok(())
+    }
+
+    #[instrument(level = Level::TRACE, skip(self, line), fields(sent = ?line.sent))]
+    fn send_line_internal(&mut self, line: InputLine) -> Result<(), Error> {
+        debug!("send");
+        // While replaying there's nowhere to send input. Echo it so scripts still work.
+        if self.connected() || !self.replaying() {
+            let data = self.charset.encode(&line.sent);
+            self.stats.sent(data.len());
+            self.connected_handle()?
+                .send(connection::Action::Send(TelnetItem::Line(data)))?;
+            self.restart_idle_keepalive();
+        }
+        // Passwords are recorded without their content, so they can't leak into the output
+        // buffer, session logs, or scripts.
+        let line = match line.echo {
+            EchoState::Password => {
+                // Sending a password means logging in, ending the banner.
+                self.capturing_banner = false;
+                line.redacted()
+            }
+            EchoState::Enabled => line,
+        };
+        if self.sent_lines.len() == SENT_LINES_LIMIT {
+            self.sent_lines.pop_front();
+        }
+        self.sent_lines.push_back(line.clone());
+        self.input.add_history(&line);
+        self.event_tx.send(python::Event::InputLine {
+            id: self.info.id,
+            input: line.clone(),
+        })?;
+        self.output.push(output::Item::Input { line });
+        Ok(())
+    }
+
+    /// Add each line of `text` to the output, converting cformat markup to colours.
+    ///
+    /// See [`output::cformat`].
+    pub fn echo(&mut self, text: &str) {
+        self.output.echo(text);
+    }
+
+    /// Returns up to the `n` most recent lines transmitted to the MUD, oldest first.
+    ///
+    /// Unlike input history these are the lines as they were sent, after alias expansion
+    /// and command splitting, and include lines sent by scripts.
+    #[must_use]
+    pub fn sent_lines(&self, n: usize) -> Vec<InputLine> {
+        let skip = self.sent_lines.len().saturating_sub(n);
+        self.sent_lines.iter().skip(skip).cloned().collect()
+    }
+
+    /// Returns throughput statistics for the current, or most recent, connection.
+    #[must_use]
+    pub fn stats(&self) -> SessionStats {
+        self.stats.clone()
+    }
+
+    /// Save the input history, if the MUD is configured to persist it.
+    ///
+    /// # Errors
+    /// If the history file can't be written.
+    pub fn save_history(&self) -> Result<(), Error> {
+        let mud = self.config.must_lookup_mud(&self.info.mud_name)?;
+        if mud.persist_history {
+            history::save(
+                &self.info.mud_name,
+                mud.character.as_deref(),
+                &self.input.history(),
+            )?;
+        }
+        Ok(())
+    }
+
+    // History is saved on disconnect as well as on close, so it isn't lost if mudpuppy
+    // exits uncleanly afterwards.
+    fn save_history_on_disconnect(&self) {
+        if let Err(err) = self.save_history() {
+            warn!("failed to save input history for {}: {err}", self.info);
+        }
+    }
+
+    /// Returns up to the `n` most recently received prompts and when they arrived, oldest first.
+    #[must_use]
+    pub fn prompt_history(&self, n: usize) -> Vec<(SystemTime, MudLine)> {
+        let skip = self.prompt_history.len().saturating_sub(n);
+        self.prompt_history.iter().skip(skip).cloned().collect()
+    }
 
-        self.conn_state = State::Connecting;
-        self.telnet_state = initial_telnet_state();
-        self.event_tx.send(self.connection_event())?;
-        match connection::connect(self.info.id, &mud, self.conn_tx.clone()).await {
-            Ok((handle, info)) => {
-                let tx = handle.action_tx.clone();
-                self.conn_state = State::Connected { handle, info };
-                self.event_tx.send(self.connection_event())?;
-                self.output.push(output::Item::ConnectionEvent {
-                    status: self.status(),
-                });
+    /// Gag the current prompt, hiding it from the output buffer.
+    ///
+    /// Returns false if no prompt has been received.
+    pub fn gag_prompt(&mut self) -> bool {
+        let Some(prompt) = &mut self.prompt else {
+            return false;
+        };
+        prompt.gag = true;
+        self.output.gag_last_prompt();
+        true
+    }
 
-                if matches!(self.prompt_mode, PromptMode::Unsignalled { .. }) {
-                    trace!("spawning new prompt flusher");
-                    if let Some(flusher) = mem::replace(
-                        &mut self.prompt_flusher,
-                        Some(PromptFlusher::new(tx, Duration::from_millis(200))),
-                    ) {
-                        trace!("stopping old prompt flusher");
-                        flusher.stop();
-                    }
-                }
+    /// Replay output captured in the file at `path`, as if it were received from the MUD.
+    ///
+    /// Triggers, prompts and events are processed as for a live connection, while the
+    /// client stays disconnected. Lines sent while replaying are echoed, but not sent.
+    /// With an `interval` lines are replayed that far apart, otherwise the original timing
+    /// of timestamped lines (e.g. from a session log) is kept.
+    ///
+    /// # Errors
+    /// If the capture file can't be read.
+    pub fn replay(&mut self, path: &Path, interval: Option<Duration>) -> Result<(), Error> {
+        self.cancel_replay();
+        let entries = replay::parse(&std::fs::read(path)?);
+        info!("replaying {} lines from {}", entries.len(), path.display());
+        self.replay_task = Some(tokio::spawn(replay::run(
+            self.info.id,
+            entries,
+            interval,
+            self.conn_tx.clone(),
+        )));
+        Ok(())
+    }
 
-                self.request_enable_option(telnet::option::GMCP)?;
+    /// Returns true if captured output is being replayed.
+    #[must_use]
+    pub fn replaying(&self) -> bool {
+        self.replay_task
+            .as_ref()
+            .is_some_and(|task| !task.is_finished())
+    }
 
-                Ok(())
-            }
-            Err(err) => {
-                self.conn_state = State::Disconnected;
-                self.event_tx.send(self.connection_event())?;
-                Err(err)
-            }
+    fn cancel_replay(&mut self) {
+        if let Some(task) = self.replay_task.take() {
+            task.abort();
         }
     }
 
-    /// Disconnect the client from the MUD server.
+    /// Start logging everything added to the output buffer to a file.
     ///
-    /// Returns immediately and without error if the connection to a MUD server
-    /// is already disconnected.
+    /// Without a `path` a new timestamped file is created in the data directory's `logs`
+    /// directory. Without a `format` the MUD's `log_format` is used. Any existing session
+    /// log is closed first. Returns the path of the log file.
     ///
     /// # Errors
-    /// If joining on the client connection task fails.
-    #[instrument(level = Level::TRACE, skip(self))]
-    pub async fn disconnect(&mut self) -> Result<(), Error> {
-        let State::Connected { handle, .. } = mem::take(&mut self.conn_state) else {
-            return Ok(());
+    /// If the log file can't be opened.
+    pub fn start_logging(
+        &mut self,
+        path: Option<&Path>,
+        format: Option<LogFormat>,
+    ) -> Result<PathBuf, Error> {
+        self.stop_logging();
+        let format = match format {
+            Some(format) => format,
+            None => self.config.must_lookup_mud(&self.info.mud_name)?.log_format,
         };
-        if let Some(flusher) = self.prompt_flusher.take() {
-            flusher.stop();
-        }
-        handle.send(connection::Action::Disconnect)?;
-        handle
-            .task
-            .await
-            .map_err(|_| Error::Internal("joining on client conn".into()))??;
-        Ok(())
+        let log = SessionLog::open(&self.info.mud_name, path, format)?;
+        let path = log.path().to_path_buf();
+        self.output.set_log(Some(log.sender()));
+        self.session_log = Some(log);
+        Ok(path)
     }
 
-    /// Send a line to the connection.
+    /// Stop logging the output buffer, returning the path of the log file if one was open.
     ///
-    /// # Errors
-    /// If the client is not connected.
-    #[instrument(level = Level::TRACE, skip(self, line), fields(sent = ?line.sent, original = ?line.original, scripted = ?line.scripted))]
-    pub fn send_line(&mut self, line: InputLine) -> Result<(), Error> {
-        let mud = self.config.must_lookup_mud(&self.info.mud_name)?;
+    /// Outstanding output is still written to the file before it's closed.
+    pub fn stop_logging(&mut self) -> Option<PathBuf> {
+        self.output.set_log(None);
+        self.session_log.take().map(|log| log.path().to_path_buf())
+    }
 
-        match &mud.command_separator {
-            Some(sep) => {
-                for fragment in line.sent.split(sep) {
-                    let mut line = line.clone();
-                    if line.sent != fragment {
-                        line.original = Some(line.sent);
-                        line.sent = fragment.to_string();
-                    }
-                    self.send_line_internal(line)?;
-                }
-                Ok(())
-            }
-            None => self.send_line_internal(line),
+    /// Returns the path of the session log file, if the output buffer is being logged.
+    #[must_use]
+    pub fn log_path(&self) -> Option<&Path> {
+        self.session_log.as_ref().map(SessionLog::path)
+    }
+
+    fn record_prompt(&mut self, prompt: MudLine) {
+        let limit = self.config.lookup(
+            |config| {
+                config
+                    .muds
+                    .iter()
+                    .find(|mud| mud.name == self.info.mud_name)
+                    .map_or(0, |mud| mud.prompt_history_size)
+            },
+            0,
+        );
+        if limit == 0 {
+            self.prompt_history.clear();
+            return;
+        }
+        while self.prompt_history.len() >= limit {
+            self.prompt_history.pop_front();
         }
+        self.prompt_history.push_back((SystemTime::now(), prompt));
     }
 
-    #[instrument(level = Level::TRACE, skip(self, line), fields(sent = ?line.sent))]
-    fn send_line_internal(&mut self, line: InputLine) -> Result<(), Error> {
-        debug!("send");
+    /// Send raw bytes to the connection, without any re-encoding.
+    ///
+    /// IAC bytes in the data are escaped. The MUD's `line_ending` is only appended when
+    /// `newline` is true.
+    ///
+    /// # Errors
+    /// If the client is not connected.
+    #[instrument(level = Level::TRACE, skip(self, data), fields(len = data.len()))]
+    pub fn send_bytes(&mut self, data: Bytes, newline: bool) -> Result<(), Error> {
+        debug!("send bytes");
+        let item = match newline {
+            true => TelnetItem::Line(data),
+            false => TelnetItem::Data(data),
+        };
         self.connected_handle()?
-            .send(connection::Action::Send(TelnetItem::Line(
-                line.sent.clone().into(),
-            )))?;
-        self.event_tx.send(python::Event::InputLine {
-            id: self.info.id,
-            input: line.clone(),
-        })?;
-        self.output.push(output::Item::Input { line });
+            .send(connection::Action::Send(item))?;
+        self.restart_idle_keepalive();
         Ok(())
     }
 
+    /// Send raw bytes to the connection exactly as given, without a line ending.
+    ///
+    /// This is shorthand for [`Client::send_bytes`] without a newline, e.g. for answering a
+    /// single key menu prompt. IAC bytes in the data are still escaped.
+    ///
+    /// # Errors
+    /// If the client is not connected.
+    pub fn send_raw(&mut self, data: Bytes) -> Result<(), Error> {
+        self.send_bytes(data, false)
+    }
+
     /// Enable a telnet protocol option.
     ///
     /// # Errors
@@ -445,6 +1786,20 @@ impl Client {
             .send(self.gmcp.unregister(module)?.into())
     }
 
+    /// Returns true if MSDP has been negotiated.
+    pub fn msdp_enabled(&self) -> bool {
+        self.msdp.ready
+    }
+
+    /// Ask the MUD to report updates to the given MSDP variables.
+    ///
+    /// # Errors
+    /// If not connected, or if MSDP is not negotiated.
+    pub fn msdp_report(&self, variables: &[String]) -> Result<(), Error> {
+        self.connected_handle()?
+            .send(self.msdp.report(variables)?.into())
+    }
+
     /// Returns whether the client is presently connected.
     ///
     /// For more granular information, prefer [`Client::status()`].
@@ -474,11 +1829,40 @@ impl Client {
             debug!("{item:?}");
         }
         match item {
-            TelnetItem::Line(data) => self.process_output_line(MudLine::from(data), futures),
+            TelnetItem::Line(data) => {
+                let data = self.charset.decode(data);
+                let line = MudLine::from(self.screen_control(data));
+                self.process_output_line(line, futures)
+            }
+            // The decoder never produces raw data, it's only ever sent.
+            TelnetItem::Data(_) => Ok(()),
             TelnetItem::Negotiation(negotiation) => self.process_negotiation(negotiation),
             TelnetItem::IacCommand(iac) => self.process_iac(iac),
-            TelnetItem::Subnegotiation(opt, data) => self.process_subnegotiation(opt, &data),
+            TelnetItem::Subnegotiation(opt, data) => {
+                self.process_subnegotiation(opt, &data, futures)
+            }
+        }
+    }
+
+    // Handle screen clearing and cursor movement sequences in received data according to
+    // the `handle_clear_screen` setting.
+    fn screen_control(&mut self, data: Bytes) -> Bytes {
+        let mode = self.config.handle_clear_screen();
+        if mode == ClearScreen::Passthrough {
+            return data;
+        }
+
+        let filtered = screen_control::filter(data);
+        if filtered.cleared && mode == ClearScreen::Blank {
+            // Push the existing output out of view, like a clear would on a terminal.
+            for _ in 0..self.buffer_dimensions.1.max(1) {
+                self.output.push(output::Item::Mud {
+                    line: MudLine::default(),
+                    received: None,
+                });
+            }
         }
+        filtered.data
     }
 
     fn process_output_line(
@@ -486,9 +1870,32 @@ impl Client {
         mut line: MudLine,
         futures: &mut FuturesUnordered<python::PyFuture>,
     ) -> Result<(), Error> {
-        self.process_mudline(&mut line, futures)?;
+        // Lines in a captured block are hidden, and aren't seen by triggers.
+        if self.block_capture.is_some() {
+            self.capture_line(line.clone(), futures)?;
+            line.gag = true;
+        } else {
+            self.process_mudline(&mut line, futures)?;
+            self.substitute(&mut line);
+        }
 
-        let item = output::Item::Mud { line };
+        // Unlike `Lines`, this is emitted with the line as it's added to the output. It's
+        // skipped when there are no handlers to save waking the event loop for each line.
+        if python::output_line_handled() {
+            self.event_tx.send(python::Event::OutputLine {
+                id: self.info.id,
+                line: line.clone(),
+            })?;
+        }
+        self.stats.lines_received = self.stats.lines_received.saturating_add(1);
+        if self.batching_lines() {
+            self.line_batch.push(line.clone());
+        }
+        let item = output::Item::Mud {
+            line,
+            received: Some(SystemTime::now()),
+        };
+        self.capture_banner(&item);
         self.output.push(item);
 
         if let Some(flusher) = &self.prompt_flusher {
@@ -498,6 +1905,232 @@ impl Client {
         Ok(())
     }
 
+    // Add `item` to the banner if it's still being captured. The line matching the MUD's
+    // `banner_end` pattern isn't included.
+    fn capture_banner(&mut self, item: &output::Item) {
+        if !self.capturing_banner {
+            return;
+        }
+        let ended = match (item, &self.banner_end) {
+            (output::Item::Mud { line, .. }, Some(end)) => end.is_match(&line.stripped()),
+            _ => false,
+        };
+        if ended || self.banner.len() == BANNER_LINES_LIMIT {
+            trace!("banner capture ended after {} lines", self.banner.len());
+            self.capturing_banner = false;
+            return;
+        }
+        self.banner.push(item.clone());
+    }
+
+    // Apply each substitution to the line's raw text, in the order they were added.
+    fn substitute(&self, line: &mut MudLine) {
+        if self.substitution_order.is_empty() {
+            return;
+        }
+        let mut text = line.to_str().into_owned();
+        let mut changed = false;
+        for substitution in self
+            .substitution_order
+            .iter()
+            .filter_map(|id| self.substitutions.get(*id))
+        {
+            if let Some(replaced) = substitution.apply(&text) {
+                text = replaced;
+                changed = true;
+            }
+        }
+        if changed {
+            line.set(&text);
+        }
+    }
+
+    /// Add a substitution that replaces each match of `pattern` in output lines with
+    /// `replacement`, returning its ID. With `match_ansi` the pattern is matched against
+    /// the raw line including ANSI escape sequences, otherwise against the visible text.
+    ///
+    /// # Errors
+    /// If `pattern` is an invalid regex.
+    pub fn add_substitution(
+        &mut self,
+        pattern: &str,
+        replacement: String,
+        match_ansi: bool,
+    ) -> Result<u32, Error> {
+        let regex = Regex::new(pattern).map_err(Error::SubstitutionPattern)?;
+        let id = self.substitutions.construct(|id| Substitution {
+            id,
+            replacement,
+            match_ansi,
+            regex,
+        });
+        // IDs only increase, so the order stays sorted.
+        self.substitution_order.push(id);
+        Ok(id)
+    }
+
+    /// Remove the substitution with the given ID, if it exists.
+    pub fn remove_substitution(&mut self, id: u32) {
+        self.substitutions.remove(id);
+        self.substitution_order.retain(|existing| *existing != id);
+    }
+
+    // Add `line` to the block being captured, handing the block to the capture's callback
+    // if it's complete.
+    fn capture_line(
+        &mut self,
+        line: MudLine,
+        futures: &mut FuturesUnordered<python::PyFuture>,
+    ) -> Result<(), Error> {
+        let Some((capture, _)) = &mut self.block_capture else {
+            return Ok(());
+        };
+        if !capture.push(line) {
+            return Ok(());
+        }
+
+        // Safety: checked above.
+        let (capture, callback) = self.block_capture.take().unwrap();
+        let lines = capture.into_lines();
+        debug!("captured block of {} lines", lines.len());
+        Python::with_gil(|py| {
+            futures.push(python::timed_future(
+                &self.config,
+                format!(
+                    "block capture callback {}",
+                    python::callable_label(callback.bind(py))
+                ),
+                callback.bind(py).call1((self.info.id, lines))?,
+            )?);
+            Ok(())
+        })
+    }
+
+    /// Gag the following lines of output, collecting them until one matches the `until`
+    /// pattern, or `max_lines` have been collected. The block of lines, including the last,
+    /// is then passed to the **async** `callback` with the session ID.
+    ///
+    /// Captured lines aren't evaluated by triggers.
+    ///
+    /// # Errors
+    /// If `until` is an invalid regex, or a block is already being captured.
+    pub fn capture_block(
+        &mut self,
+        until: &str,
+        max_lines: usize,
+        callback: Py<PyAny>,
+    ) -> Result<(), Error> {
+        if self.block_capture.is_some() {
+            return Err(Error::CaptureInProgress);
+        }
+        self.block_capture = Some((BlockCapture::new(until, max_lines.max(1))?, callback));
+        Ok(())
+    }
+
+    /// Stop capturing a block of output without calling its callback, returning whether
+    /// one was being captured. Lines already captured stay gagged.
+    pub fn cancel_capture(&mut self) -> bool {
+        self.block_capture.take().is_some()
+    }
+
+    /// Import triggers and aliases from `text` in the given `format`, associating them with
+    /// `module` like triggers and aliases created from Python.
+    ///
+    /// Entries that can't be imported, including those with the same name as an existing
+    /// trigger or alias, are reported rather than failing the whole import.
+    ///
+    /// # Errors
+    /// If the MUD config can't be found, or the file as a whole can't be parsed.
+    pub fn import_triggers(
+        &mut self,
+        text: &str,
+        format: ImportFormat,
+        module: &str,
+    ) -> Result<ImportReport, Error> {
+        let mud = self.config.must_lookup_mud(&self.info.mud_name)?;
+        let imported = import::parse(text, format, mud.command_separator.as_deref())?;
+        let mut report = ImportReport {
+            failures: imported.failures,
+            ..ImportReport::default()
+        };
+
+        Python::with_gil(|py| {
+            for config in imported.triggers {
+                if self
+                    .triggers
+                    .iter()
+                    .any(|(_, trigger)| trigger.config.borrow(py).name == config.name)
+                {
+                    report.failures.push((
+                        config.name,
+                        "a trigger with this name already exists".into(),
+                    ));
+                    continue;
+                }
+                let config = Py::new(py, config)?;
+                report.triggers.push(self.triggers.construct(|id| Trigger {
+                    id,
+                    enabled: true,
+                    module: module.to_string(),
+                    config,
+                }));
+            }
+
+            for config in imported.aliases {
+                if self
+                    .aliases
+                    .iter()
+                    .any(|(_, alias)| alias.config.borrow(py).name == config.name)
+                {
+                    report
+                        .failures
+                        .push((config.name, "an alias with this name already exists".into()));
+                    continue;
+                }
+                let config = Py::new(py, config)?;
+                report.aliases.push(self.aliases.construct(|id| Alias {
+                    id,
+                    enabled: true,
+                    config,
+                    module: module.to_string(),
+                }));
+            }
+            Ok(report)
+        })
+    }
+
+    // Returns true if the MUD is configured for batched line events. This is checked for each
+    // line, so avoids cloning the MUD's config.
+    fn batching_lines(&self) -> bool {
+        self.config.lookup(
+            |config| {
+                config
+                    .muds
+                    .iter()
+                    .any(|mud| mud.name == self.info.mud_name && mud.batch_line_events)
+            },
+            false,
+        )
+    }
+
+    // Emit the output lines received since the last read completed as a single event, if
+    // the MUD is configured for batched line events.
+    fn flush_line_batch(&mut self) -> Result<(), Error> {
+        if self.line_batch.is_empty() {
+            return Ok(());
+        }
+        let lines = mem::take(&mut self.line_batch);
+        if !self.batching_lines() {
+            return Ok(());
+        }
+        trace!("emitting batch of {} lines", lines.len());
+        self.event_tx.send(python::Event::Lines {
+            id: self.info.id,
+            lines,
+        })?;
+        Ok(())
+    }
+
     fn process_prompt(
         &mut self,
         prompt: &mut MudLine,
@@ -511,13 +2144,29 @@ impl Client {
         line: &mut MudLine,
         futures: &mut FuturesUnordered<python::PyFuture>,
     ) -> Result<(), Error> {
+        if !self.triggers_enabled {
+            return Ok(());
+        }
+
         // TODO(XXX): awkward. avoid alloc. Doing this presently to avoid two mutable
         //  borrows of self - one for triggers, and one for send_line.
         let mut trigger_send = Vec::new();
 
-        for trigger in self.triggers.values_mut().filter(|trigger| trigger.enabled) {
-            if let Some(expansion) = Self::evaluate_trigger(self.info.id, trigger, line, futures)? {
-                trigger_send.push(expansion);
+        for id in self.trigger_order() {
+            // Safety: trigger_order only returns ids of triggers in the map.
+            let trigger = self.triggers.get_mut(id).unwrap();
+            let outcome =
+                Self::evaluate_trigger(&self.config, self.info.id, trigger, line, futures)?;
+            trigger_send.extend(outcome.expansion);
+            if outcome.important {
+                self.output.important = true;
+            }
+
+            // A matching trigger configured to stop processing gives "first match wins"
+            // behaviour: lower priority triggers don't see the line.
+            if outcome.stop {
+                trace!("trigger {id} stopped trigger processing");
+                break;
             }
         }
 
@@ -528,6 +2177,32 @@ impl Client {
         Ok(())
     }
 
+    // Returns the ids of enabled triggers in evaluation order: highest priority first, then
+    // oldest first.
+    fn trigger_order(&self) -> Vec<u32> {
+        Python::with_gil(|py| {
+            evaluation_order(
+                self.triggers
+                    .iter()
+                    .filter(|(_, trigger)| trigger.enabled)
+                    .map(|(id, trigger)| (trigger.config.borrow(py).priority, *id)),
+            )
+        })
+    }
+
+    // Returns the ids of enabled aliases in evaluation order: highest priority first, then
+    // oldest first.
+    fn alias_order(&self) -> Vec<u32> {
+        Python::with_gil(|py| {
+            evaluation_order(
+                self.aliases
+                    .iter()
+                    .filter(|(_, alias)| alias.enabled)
+                    .map(|(id, alias)| (alias.config.borrow(py).priority, *id)),
+            )
+        })
+    }
+
     fn process_iac(&self, command: u8) -> Result<(), Error> {
         if let Some(prompt_signal) = self.prompt_mode.signal() {
             if u8::from(prompt_signal) == command {
@@ -562,6 +2237,7 @@ impl Client {
                     .reply_enable_if_supported(opt, matches!(negotiation, Negotiation::Will(_)))
                 {
                     info!("option {opt} enabled");
+                    self.log_connection_event(conn_log::Event::OptionEnabled { option: opt });
                     trace!("sending reply: {reply:?}");
                     self.connected_handle()?
                         .send(connection::Action::Send(reply.into()))?;
@@ -571,6 +2247,10 @@ impl Client {
                         telnet::option::EOR => self.set_prompt_mode(PromptMode::Signalled {
                             signal: PromptSignal::EndOfRecord,
                         }),
+                        // Tell the MUD the current size right away, rather than waiting for
+                        // the next resize.
+                        telnet::option::NAWS => self.send_naws()?,
+                        telnet::option::MSDP => self.msdp.ready = true,
                         _ => {}
                     }
 
@@ -578,6 +2258,17 @@ impl Client {
                         id: self.info.id,
                         option: opt,
                     })?;
+                } else if opt == telnet::option::EOR
+                    && !self.config.must_lookup_mud(&self.info.mud_name)?.use_eor
+                {
+                    // Explicitly refuse EOR when it's been disabled for the MUD.
+                    let refusal = match negotiation {
+                        Negotiation::Will(_) => Negotiation::Dont(opt),
+                        _ => Negotiation::Wont(opt),
+                    };
+                    info!("refusing option {opt}: disabled by config");
+                    self.connected_handle()?
+                        .send(connection::Action::Send(refusal.into()))?;
                 }
             }
             Negotiation::Wont(opt) | Negotiation::Dont(opt) => {
@@ -586,6 +2277,7 @@ impl Client {
                     .reply_disable_if_enabled(opt, matches!(negotiation, Negotiation::Wont(_)))
                 {
                     info!("option {opt} disabled");
+                    self.log_connection_event(conn_log::Event::OptionDisabled { option: opt });
                     trace!("sending reply: {reply:?}");
                     self.connected_handle()?
                         .send(connection::Action::Send(reply.into()))?;
@@ -596,6 +2288,7 @@ impl Client {
                         telnet::option::EOR => self.set_prompt_mode(PromptMode::Unsignalled {
                             timeout: Duration::from_millis(200),
                         }),
+                        telnet::option::MSDP => self.msdp.ready = false,
                         _ => {}
                     }
 
@@ -610,14 +2303,60 @@ impl Client {
         Ok(())
     }
 
-    fn process_subnegotiation(&mut self, opt: u8, data: &[u8]) -> Result<(), Error> {
+    fn process_subnegotiation(
+        &mut self,
+        opt: u8,
+        data: &[u8],
+        futures: &mut FuturesUnordered<python::PyFuture>,
+    ) -> Result<(), Error> {
         if opt == telnet::option::GMCP {
             if let Some(event) = self.gmcp.decode(data)? {
-                if self.config.must_lookup_mud(&self.info.mud_name)?.debug_gmcp {
-                    self.output.push(event.clone().into());
+                let mud = self.config.must_lookup_mud(&self.info.mud_name)?;
+                if mud.debug_gmcp {
+                    self.output
+                        .extend(event.debug_output(mud.debug_gmcp_format).into_iter());
                 }
                 self.event_tx.send(event.into())?;
             }
+        } else if opt == telnet::option::MSDP {
+            for message in self.msdp.decode(data)? {
+                self.event_tx.send(message.into())?;
+            }
+        } else if opt == telnet::option::CHARSET {
+            if let Some(reply) = self.charset.handle(data) {
+                self.connected_handle()?
+                    .send(connection::Action::Send(reply))?;
+            }
+        } else if opt == telnet::option::TTYPE && self.telnet_state.option(opt).local_enabled() {
+            if let Some(reply) = self.terminal_type.handle(data) {
+                self.connected_handle()?
+                    .send(connection::Action::Send(reply))?;
+            }
+        }
+
+        // Handlers registered for a specific option only fire once the option is negotiated.
+        if self.telnet_state.option(opt).local_enabled() {
+            Python::with_gil(|py| {
+                for handler in self
+                    .subnegotiation_handlers
+                    .iter()
+                    .filter(|handler| handler.option == opt)
+                {
+                    trace!("preparing subnegotiation handler future for option {opt}");
+                    futures.push(python::timed_future(
+                        &self.config,
+                        format!(
+                            "subnegotiation handler {}",
+                            python::callable_label(handler.callback.bind(py))
+                        ),
+                        handler
+                            .callback
+                            .call1(py, (self.info.id, opt, data.to_vec()))?
+                            .into_bound(py),
+                    )?);
+                }
+                Ok::<_, Error>(())
+            })?;
         }
 
         self.event_tx
@@ -634,36 +2373,69 @@ impl Client {
         skip(trigger, line, futures),
         fields(trigger_id = %trigger.id()))
     ]
+    // Returns what the trigger asks for if it matched, or the default outcome if it didn't.
     fn evaluate_trigger(
+        config: &GlobalConfig,
         session_id: u32,
         trigger: &mut Trigger,
         line: &mut MudLine,
         futures: &mut FuturesUnordered<python::PyFuture>,
-    ) -> Result<Option<String>, Error> {
-        let expansion = Python::with_gil(|py| {
+    ) -> Result<TriggerOutcome, Error> {
+        let result = Python::with_gil(|py| {
             let mut trigger_config: PyRefMut<'_, TriggerConfig> = trigger.config.extract(py)?;
 
-            let (matched, groups) = trigger_config.matches(line);
-            if !matched {
-                return Ok::<_, Error>(None);
-            }
-            trigger_config.hit_count += 1;
-
-            debug!("trigger {} matched line", trigger.id());
+            // In multi-match mode the callback is invoked for each match, while the highlight,
+            // gag and expansion apply once for the line, using the first match.
+            let all_groups = match trigger_config.multi_match {
+                true => trigger_config.all_matches(line),
+                false => trigger_config.matches(line).into_iter().collect(),
+            };
+            let Some(groups) = all_groups.first() else {
+                return Ok::<_, Error>(TriggerOutcome::default());
+            };
+            trigger_config.hit_count += all_groups.len() as u64;
+
+            debug!(
+                "trigger {} matched line {} time(s)",
+                trigger.id(),
+                all_groups.len()
+            );
 
             if let Some(callback) = &trigger_config.callback {
-                trace!("preparing callback future for matches: {groups:?}");
-                futures.push(Box::pin(pyo3_async_runtimes::tokio::into_future(
-                    callback
-                        .call1(py, (session_id, trigger.id(), line.clone(), groups.clone()))?
-                        .into_bound(py),
-                )?));
+                for groups in &all_groups {
+                    trace!("preparing callback future for matches: {groups:?}");
+                    futures.push(python::timed_future(
+                        config,
+                        format!(
+                            "trigger '{}' callback {}",
+                            trigger_config.name,
+                            python::callable_label(callback.bind(py))
+                        ),
+                        python::call_with_groups(
+                            callback.bind(py),
+                            [
+                                session_id.into_py_any(py)?,
+                                trigger.id().into_py_any(py)?,
+                                line.clone().into_py_any(py)?,
+                            ],
+                            groups,
+                            trigger_config.callback_named_groups,
+                        )?,
+                    )?);
+                }
             }
 
+            trigger_config.recolor(line)?;
+
             if let Some(highlight) = &trigger_config.highlight {
                 trace!("invoking trigger highlight with match groups: {groups:?}");
-                let new_line = highlight.call1(py, (line.clone(), groups))?;
-                let new_line: MudLine = new_line.extract(py)?;
+                let new_line = python::call_with_groups(
+                    highlight.bind(py),
+                    [line.clone().into_py_any(py)?],
+                    groups,
+                    trigger_config.highlight_named_groups,
+                )?;
+                let new_line: MudLine = new_line.extract()?;
                 trace!("line was replaced by trigger: {new_line:?}");
                 *line = new_line;
             }
@@ -673,10 +2445,34 @@ impl Client {
                 line.gag = true;
             }
 
-            Ok(trigger_config.expansion.clone())
+            Ok(TriggerOutcome {
+                expansion: trigger_config.expansion.clone(),
+                stop: trigger_config.stop,
+                important: trigger_config.important,
+            })
         })?;
 
-        Ok(expansion)
+        Ok(result)
+    }
+
+    fn apply_input_transforms(&self, mut input: InputLine) -> Result<Option<InputLine>, Error> {
+        Python::with_gil(|py| {
+            for transform in &self.input_transforms {
+                let result = transform
+                    .callback
+                    .call1(py, (self.info.id, input.clone()))?;
+                if result.is_none(py) {
+                    return Ok(None);
+                }
+                let mut transformed: InputLine = result.extract(py)?;
+                // Preserve what was originally entered if the transform didn't.
+                if transformed.sent != input.sent && transformed.original.is_none() {
+                    transformed.original = Some(input.sent);
+                }
+                input = transformed;
+            }
+            Ok(Some(input))
+        })
     }
 
     #[instrument(
@@ -685,6 +2481,7 @@ impl Client {
         fields(alias_id = %alias.id()))
     ]
     fn evaluate_alias(
+        config: &GlobalConfig,
         session_id: u32,
         alias: &mut Alias,
         input: &mut InputLine,
@@ -692,32 +2489,59 @@ impl Client {
     ) -> Result<(), Error> {
         Python::with_gil(|py| {
             let mut alias_config: PyRefMut<'_, AliasConfig> = alias.config.extract(py)?;
-            let (matched, groups) = alias_config.matches(&input.sent);
-            if !matched {
+            let Some(groups) = alias_config.matches(&input.sent) else {
                 return Ok(());
-            }
+            };
 
             alias_config.hit_count += 1;
             debug!("alias {} matched line", alias.id());
 
             if let Some(callback) = &alias_config.callback {
                 trace!("preparing callback future for matches: {groups:?}");
-                futures.push(Box::pin(pyo3_async_runtimes::tokio::into_future(
-                    callback
-                        .call1(py, (session_id, alias.id(), input.clone(), groups.clone()))?
-                        .into_bound(py),
-                )?));
+                futures.push(python::timed_future(
+                    config,
+                    format!(
+                        "alias '{}' callback {}",
+                        alias_config.name,
+                        python::callable_label(callback.bind(py))
+                    ),
+                    python::call_with_groups(
+                        callback.bind(py),
+                        [
+                            session_id.into_py_any(py)?,
+                            alias.id().into_py_any(py)?,
+                            input.clone().into_py_any(py)?,
+                        ],
+                        &groups,
+                        alias_config.callback_named_groups,
+                    )?,
+                )?);
             }
 
-            // Preserve the original input, and replace what will be sent with the alias expansion
-            // or "" if there is no expansion.
+            // Preserve the original input, and replace what will be sent with the alias expansion,
+            // with the match groups substituted in, or "" if there is no expansion.
             input.original = Some(input.sent.clone());
-            input.sent = alias_config.expansion.clone().unwrap_or_default();
+            input.sent = alias_config
+                .expansion
+                .as_deref()
+                .map(|expansion| groups.expand(expansion))
+                .unwrap_or_default();
             Ok(())
         })
     }
 
-    fn set_prompt_mode(&mut self, new_mode: PromptMode) {
+    /// Returns the [`PromptMode`] in use for the client.
+    #[must_use]
+    pub fn prompt_mode(&self) -> PromptMode {
+        self.prompt_mode.clone()
+    }
+
+    /// Set the [`PromptMode`] for the client, replacing any mode that was determined
+    /// through telnet negotiation.
+    ///
+    /// Switching to an unsignalled mode spawns a new prompt flusher if connected, and any
+    /// existing flusher is stopped.
+    pub fn set_prompt_mode(&mut self, new_mode: PromptMode) {
         info!("prompt mode set to {new_mode}");
         self.prompt_mode = new_mode;
 
@@ -754,6 +2578,12 @@ impl Client {
         }
     }
 
+    fn log_connection_event(&mut self, event: conn_log::Event<'_>) {
+        if let Some(log) = &mut self.conn_log {
+            log.write(event);
+        }
+    }
+
     fn connected_handle(&self) -> Result<&connection::Handle, Error> {
         match &self.conn_state {
             State::Connected { handle, .. } => Ok(handle),
@@ -790,6 +2620,27 @@ impl Identifiable for Client {
     }
 }
 
+/// A scroll of the main output requested by a script, see [`Client::take_scroll_request`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScrollRequest {
+    /// Stop scrolling back, showing the most recent output.
+    Bottom,
+
+    /// Scroll so the output item at the index is shown at the bottom.
+    Item(usize),
+}
+
+/// Where to scroll a buffer to, see [`Client::scroll_buffer`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScrollTarget {
+    /// Move the scroll position by a number of items. Positive numbers scroll back through
+    /// older output, and negative numbers towards the newest output.
+    Lines(isize),
+
+    /// Scroll so the output item at the index is shown.
+    Item(usize),
+}
+
 /// Status of the client's connection to the MUD server.
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[pyclass]
@@ -842,11 +2693,47 @@ enum State {
     },
 }
 
-// TODO(XXX): Use config/MUD to determine this?
-fn initial_telnet_state() -> telnet::negotiation::Table {
+// TODO(XXX): Use config/MUD to determine the rest of this?
+fn initial_telnet_state(use_eor: bool, terminal_type: bool) -> telnet::negotiation::Table {
     use telnet::command::GA;
-    use telnet::option::{ECHO, EOR};
-    // TODO(XXX): MCCP...
+    use telnet::option::{CHARSET, ECHO, EOR, MCCP2, MSDP, NAWS, TTYPE};
+
+    let mut supported = vec![CHARSET, ECHO, GA, MCCP2, MSDP, NAWS];
+    if use_eor {
+        supported.push(EOR);
+    }
+    if terminal_type {
+        supported.push(TTYPE);
+    }
+    telnet::negotiation::Table::from(supported)
+}
+
+// What a trigger that matched a line asks for.
+#[derive(Debug, Default)]
+struct TriggerOutcome {
+    // The trigger's expansion, to send.
+    expansion: Option<String>,
+    // Whether lower priority triggers shouldn't be evaluated for the line.
+    stop: bool,
+    // Whether the line should mark the session's tab as having important activity.
+    important: bool,
+}
 
-    telnet::negotiation::Table::from([ECHO, EOR, GA])
+// Sort `(priority, id)` pairs highest priority first, breaking ties by the lowest (i.e.
+// oldest) id, and return the ids.
+fn evaluation_order(items: impl Iterator<Item = (i32, u32)>) -> Vec<u32> {
+    let mut items = items.collect::<Vec<_>>();
+    items.sort_unstable_by_key(|&(priority, id)| (std::cmp::Reverse(priority), id));
+    items.into_iter().map(|(_, id)| id).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluation_order_by_priority_then_id() {
+        let items = [(0, 3), (-10, 0), (5, 4), (0, 1), (5, 2)];
+        assert_eq!(evaluation_order(items.into_iter()), vec![2, 4, 1, 3, 0]);
+    }
 }
@@ -0,0 +1,296 @@
+use pyo3::Python;
+use serde_json::{Map, Value};
+use tracing::{debug, warn};
+
+use crate::client::gmcp;
+use crate::error::MsdpError;
+use crate::net::telnet;
+use crate::net::telnet::codec::Item as TelnetItem;
+use crate::{python, Result};
+
+const VAR: u8 = 1;
+const VAL: u8 = 2;
+const TABLE_OPEN: u8 = 3;
+const TABLE_CLOSE: u8 = 4;
+const ARRAY_OPEN: u8 = 5;
+const ARRAY_CLOSE: u8 = 6;
+
+// How deeply tables and arrays may be nested. Decoding recurses for each level, so this stops
+// a hostile server from overflowing the stack.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug)]
+pub struct Msdp {
+    pub ready: bool,
+    session_id: u32,
+}
+
+impl Msdp {
+    #[must_use]
+    pub fn new(session_id: u32) -> Self {
+        Self {
+            ready: false,
+            session_id,
+        }
+    }
+
+    /// Ask the MUD to report updates to the given variables.
+    pub fn report(&self, variables: &[String]) -> Result<TelnetItem> {
+        debug!("REPORT {variables:?}");
+        self.encode("REPORT", variables)
+    }
+
+    /// Encode a `VAR` with the given `VAL`s, e.g. a command like `LIST` or `REPORT`.
+    pub fn encode(&self, variable: &str, values: &[String]) -> Result<TelnetItem> {
+        if !self.ready {
+            return Err(MsdpError::NotReady.into());
+        }
+        let mut data = vec![VAR];
+        data.extend_from_slice(variable.as_bytes());
+        for value in values {
+            data.push(VAL);
+            data.extend_from_slice(value.as_bytes());
+        }
+        Ok(TelnetItem::Subnegotiation(
+            telnet::option::MSDP,
+            data.into(),
+        ))
+    }
+
+    /// Decode the variables in a subnegotiation into a message for each variable.
+    pub fn decode(&self, raw_data: &[u8]) -> Result<Vec<Message>> {
+        Ok(decode_variables(raw_data)?
+            .into_iter()
+            .map(|(variable, value)| Message {
+                session_id: self.session_id,
+                variable,
+                value,
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Message {
+    pub session_id: u32,
+    pub variable: String,
+    /// The value, as a string, an array (`Value::Array`) or a table (`Value::Object`).
+    pub value: Value,
+}
+
+impl From<Message> for python::Event {
+    fn from(msg: Message) -> Self {
+        let value = Python::with_gil(|py| {
+            gmcp::to_python(py, &msg.value).unwrap_or_else(|err| {
+                warn!("failed to convert MSDP {} value: {err}", msg.variable);
+                py.None()
+            })
+        });
+        python::Event::MsdpMessage {
+            id: msg.session_id,
+            variable: msg.variable,
+            value,
+        }
+    }
+}
+
+// Decode a sequence of `VAR name VAL value` pairs.
+//
+// A variable with several `VAL`s is decoded as an array, and one without any as an empty string.
+fn decode_variables(data: &[u8]) -> Result<Vec<(String, Value)>, MsdpError> {
+    Decoder {
+        data,
+        pos: 0,
+        depth: 0,
+    }
+    .variables(None)
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    // How many tables and arrays enclose the current position.
+    depth: usize,
+}
+
+impl Decoder<'_> {
+    // Decode variables until `close` is consumed, or until the end of the data if there's no
+    // `close`.
+    fn variables(&mut self, close: Option<u8>) -> Result<Vec<(String, Value)>, MsdpError> {
+        let mut variables = Vec::new();
+        loop {
+            match self.next() {
+                None if close.is_none() => return Ok(variables),
+                None => return Err(MsdpError::BadData("unterminated table".to_string())),
+                Some(byte) if Some(byte) == close => return Ok(variables),
+                Some(VAR) => {
+                    let name = self.string();
+                    let mut values = Vec::new();
+                    while self.peek() == Some(VAL) {
+                        self.pos += 1;
+                        values.push(self.value()?);
+                    }
+                    let value = match values.len() {
+                        0 => Value::String(String::new()),
+                        1 => values.pop().unwrap(), // Safety: we just checked the len.
+                        _ => Value::Array(values),
+                    };
+                    variables.push((name, value));
+                }
+                Some(byte) => {
+                    return Err(MsdpError::BadData(format!(
+                        "unexpected byte {byte} at offset {}, expected VAR",
+                        self.pos - 1
+                    )))
+                }
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, MsdpError> {
+        match self.peek() {
+            Some(TABLE_OPEN) => {
+                self.open()?;
+                let table = self.variables(Some(TABLE_CLOSE))?;
+                self.depth -= 1;
+                Ok(Value::Object(table.into_iter().collect::<Map<_, _>>()))
+            }
+            Some(ARRAY_OPEN) => {
+                self.open()?;
+                let mut items = Vec::new();
+                loop {
+                    match self.next() {
+                        Some(ARRAY_CLOSE) => {
+                            self.depth -= 1;
+                            return Ok(Value::Array(items));
+                        }
+                        Some(VAL) => items.push(self.value()?),
+                        None => return Err(MsdpError::BadData("unterminated array".to_string())),
+                        Some(byte) => {
+                            return Err(MsdpError::BadData(format!(
+                                "unexpected byte {byte} at offset {}, expected VAL",
+                                self.pos - 1
+                            )))
+                        }
+                    }
+                }
+            }
+            _ => Ok(Value::String(self.string())),
+        }
+    }
+
+    // Consume a table or array opening byte, checking the nesting limit isn't exceeded.
+    fn open(&mut self) -> Result<(), MsdpError> {
+        if self.depth == MAX_DEPTH {
+            return Err(MsdpError::BadData(format!(
+                "tables and arrays nested more than {MAX_DEPTH} deep"
+            )));
+        }
+        self.depth += 1;
+        self.pos += 1;
+        Ok(())
+    }
+
+    // Read a string up to the next MSDP control byte, or the end of the data.
+    fn string(&mut self) -> String {
+        let rest = &self.data[self.pos..];
+        let len = rest
+            .iter()
+            .position(|b| (VAR..=ARRAY_CLOSE).contains(b))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        String::from_utf8_lossy(&rest[..len]).into_owned()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.pos += 1;
+        Some(byte)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn decodes_variables() {
+        // VAR "HEALTH" VAL "100" VAR "EMPTY"
+        let data = b"\x01HEALTH\x02100\x01EMPTY";
+        assert_eq!(
+            decode_variables(data).unwrap(),
+            [
+                ("HEALTH".to_string(), json!("100")),
+                ("EMPTY".to_string(), json!("")),
+            ]
+        );
+
+        // Several VALs for one VAR are an array.
+        let data = b"\x01REPORTABLE_VARIABLES\x02HEALTH\x02MANA";
+        assert_eq!(
+            decode_variables(data).unwrap(),
+            [(
+                "REPORTABLE_VARIABLES".to_string(),
+                json!(["HEALTH", "MANA"])
+            )]
+        );
+    }
+
+    #[test]
+    fn decodes_nested_tables_and_arrays() {
+        // VAR "ROOM" VAL TABLE_OPEN
+        //   VAR "VNUM" VAL "6008"
+        //   VAR "EXITS" VAL TABLE_OPEN VAR "n" VAL "6011" VAR "e" VAL "6007" TABLE_CLOSE
+        //   VAR "ITEMS" VAL ARRAY_OPEN VAL "sword" VAL TABLE_OPEN VAR "name" VAL "shield" TABLE_CLOSE
+        //     VAL ARRAY_OPEN ARRAY_CLOSE ARRAY_CLOSE
+        // TABLE_CLOSE
+        let data = b"\x01ROOM\x02\x03\
+            \x01VNUM\x026008\
+            \x01EXITS\x02\x03\x01n\x026011\x01e\x026007\x04\
+            \x01ITEMS\x02\x05\x02sword\x02\x03\x01name\x02shield\x04\x02\x05\x06\x06\
+            \x04";
+        assert_eq!(
+            decode_variables(data).unwrap(),
+            [(
+                "ROOM".to_string(),
+                json!({
+                    "VNUM": "6008",
+                    "EXITS": {"n": "6011", "e": "6007"},
+                    "ITEMS": ["sword", {"name": "shield"}, []],
+                })
+            )]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_data() {
+        for data in [
+            &b"HEALTH\x02100"[..],
+            b"\x01ROOM\x02\x03\x01VNUM\x026008",
+            b"\x01LIST\x02\x05\x02one",
+            b"\x01LIST\x02\x05\x01one\x06",
+        ] {
+            assert!(decode_variables(data).is_err(), "{data:?}");
+        }
+    }
+
+    #[test]
+    fn limits_nesting_depth() {
+        let nested = |depth: usize| {
+            let mut data = b"\x01DEEP".to_vec();
+            data.extend(b"\x02\x05".repeat(depth));
+            data.extend(b"\x06".repeat(depth));
+            data
+        };
+        assert!(decode_variables(&nested(MAX_DEPTH)).is_ok());
+        assert!(decode_variables(&nested(MAX_DEPTH + 1)).is_err());
+        // Far deeper than the limit fails without overflowing the stack.
+        assert!(decode_variables(&nested(1_000_000)).is_err());
+    }
+}
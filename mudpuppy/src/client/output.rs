@@ -1,7 +1,12 @@
 use std::collections::VecDeque;
 use std::fmt::{Display, Formatter};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use memchr::memmem::Finder;
 use pyo3::{pyclass, pymethods};
+use regex::Regex;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_util::bytes::Bytes;
 
 use crate::client::Status;
 use crate::model::{InputLine, MudLine};
@@ -10,7 +15,31 @@ use crate::model::{InputLine, MudLine};
 #[pyclass]
 pub struct Output {
     pub new_data: usize,
+    /// Whether output flagged as important, e.g. by a trigger, was added since the output
+    /// was last read.
+    pub important: bool,
     received: VecDeque<Item>,
+    /// A search index entry for each received item, built the first time the output is
+    /// searched and kept in sync with `received` afterwards.
+    search_index: Option<VecDeque<IndexEntry>>,
+    /// Receives a copy of each item added, while the session is being logged.
+    log_tx: LogSender,
+    /// The maximum number of items kept. The oldest items are evicted beyond this.
+    max_len: Option<usize>,
+    /// The number of items evicted so far, for keeping item indexes valid across evictions.
+    evicted: usize,
+    /// The number of times an item was replaced, or inserted before existing items.
+    rewrites: usize,
+}
+
+// Copies of the output (e.g. handed to Python) aren't logged, and mustn't keep the log open.
+#[derive(Debug, Default)]
+struct LogSender(Option<UnboundedSender<Item>>);
+
+impl Clone for LogSender {
+    fn clone(&self) -> Self {
+        Self(None)
+    }
 }
 
 impl Output {
@@ -22,12 +51,28 @@ impl Output {
     pub fn read_received(&mut self) -> &VecDeque<Item> {
         // assume all new data will be read by the caller.
         self.new_data = 0;
+        self.important = false;
         &self.received
     }
 
     pub fn extend(&mut self, items: impl IntoIterator<Item = Item> + ExactSizeIterator) {
         self.new_data = self.new_data.saturating_add(items.len());
+        let start = self.received.len();
         self.received.extend(items);
+        if let Some(index) = &mut self.search_index {
+            index.extend(self.received.range(start..).map(IndexEntry::new));
+        }
+        if let Some(tx) = &self.log_tx.0 {
+            for item in self.received.range(start..) {
+                let _ = tx.send(item.clone());
+            }
+        }
+        self.evict();
+    }
+
+    /// Send a copy of each item added from now on to `log_tx`, or stop if `None`.
+    pub(crate) fn set_log(&mut self, log_tx: Option<UnboundedSender<Item>>) {
+        self.log_tx = LogSender(log_tx);
     }
 
     pub fn set(
@@ -35,7 +80,7 @@ impl Output {
         items: impl IntoIterator<Item = Item> + ExactSizeIterator,
         changed: bool,
     ) {
-        self.received.clear();
+        self.clear();
         self.extend(items);
         if !changed {
             self.new_data = 0;
@@ -57,22 +102,286 @@ impl Output {
 
     pub fn push(&mut self, item: Item) {
         //trace!("adding item {item:?}");
+        if let Some(index) = &mut self.search_index {
+            index.push_back(IndexEntry::new(&item));
+        }
+        if let Some(tx) = &self.log_tx.0 {
+            let _ = tx.send(item.clone());
+        }
         self.received.push_back(item);
         self.new_data = self.new_data.saturating_add(1);
+        self.evict();
+    }
+
+    /// Replace the most recent item with `item`, or add it if there are no items. The number
+    /// of items doesn't change, so scroll positions are kept.
+    ///
+    /// Replacements aren't logged: the session log keeps the item as it was first added, so
+    /// a line updated in place (e.g. a progress indicator) doesn't flood the log.
+    pub fn replace_last(&mut self, item: Item) {
+        let Some(last) = self.received.back_mut() else {
+            return self.push(item);
+        };
+        if let Some(entry) = self.search_index.as_mut().and_then(VecDeque::back_mut) {
+            *entry = IndexEntry::new(&item);
+        }
+        *last = item;
+        self.rewrites = self.rewrites.saturating_add(1);
+    }
+
+    /// Insert `item` before the item at `index`. Indexes past the end add the item after the
+    /// most recent item, like `push`.
+    ///
+    /// Only items added after the most recent item are logged, since the session log can't
+    /// be rewritten to put earlier items in order.
+    pub fn insert(&mut self, index: usize, item: Item) {
+        if index >= self.received.len() {
+            return self.push(item);
+        }
+        if let Some(search_index) = &mut self.search_index {
+            search_index.insert(index, IndexEntry::new(&item));
+        }
+        self.received.insert(index, item);
+        self.rewrites = self.rewrites.saturating_add(1);
+        self.new_data = self.new_data.saturating_add(1);
+        self.evict();
+    }
+
+    /// The maximum number of items kept, or `None` if unlimited. When more items are added
+    /// the oldest are evicted.
+    #[getter]
+    #[must_use]
+    pub fn max_len(&self) -> Option<usize> {
+        self.max_len
+    }
+
+    #[setter]
+    pub fn set_max_len(&mut self, max_len: Option<usize>) {
+        self.max_len = max_len;
+        self.evict();
+    }
+
+    /// Remove all items. Like eviction, this keeps the indexes of items added later valid
+    /// for anything that tracks [`Output::evicted`].
+    pub fn clear(&mut self) {
+        self.evicted = self.evicted.saturating_add(self.received.len());
+        self.received.clear();
+        if let Some(index) = &mut self.search_index {
+            index.clear();
+        }
+        self.new_data = 0;
+        self.important = false;
     }
 
     #[pyo3(name = "set")]
     pub fn set_py(&mut self, items: Vec<Item>) {
-        self.received.clear();
+        self.clear();
+        self.extend(items.into_iter());
+    }
+
+    /// Returns the indexes of items with text containing `needle`, ignoring case. The most
+    /// recent matches are returned first, up to `max_results` if provided.
+    #[pyo3(signature = (needle, max_results=None))]
+    pub fn search(&mut self, needle: &str, max_results: Option<usize>) -> Vec<usize> {
+        let query = Query::new(needle);
+        self.search_index()
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, entry)| query.matches(entry))
+            .map(|(idx, _)| idx)
+            .take(max_results.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    /// Returns the item at `index`, if it exists.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<Item> {
+        self.received.get(index).cloned()
+    }
+
+    /// Returns the index of the closest non-gagged prompt item before `index`, if any.
+    #[must_use]
+    pub fn prompt_before(&self, index: usize) -> Option<usize> {
+        self.received
+            .range(..index.min(self.received.len()))
+            .rposition(Item::is_visible_prompt)
+    }
+
+    /// Gag the most recent prompt item, returning whether there was one.
+    pub fn gag_last_prompt(&mut self) -> bool {
+        let Some(Item::Prompt { prompt }) = self
+            .received
+            .iter_mut()
+            .rev()
+            .find(|item| matches!(item, Item::Prompt { .. }))
+        else {
+            return false;
+        };
+        prompt.gag = true;
+        true
+    }
+
+    /// Returns the index of the closest non-gagged prompt item after `index`, if any.
+    #[must_use]
+    pub fn prompt_after(&self, index: usize) -> Option<usize> {
+        let start = index.saturating_add(1).min(self.received.len());
+        self.received
+            .range(start..)
+            .position(Item::is_visible_prompt)
+            .map(|pos| start + pos)
+    }
+}
+
+impl Output {
+    /// Add each line of `text` to the output as if it came from the MUD, after converting
+    /// cformat markup (e.g. `<red>`) to colours with [`cformat`].
+    pub fn echo(&mut self, text: &str) {
+        let items = text
+            .lines()
+            .map(|line| Item::Mud {
+                line: MudLine::from(Bytes::from(cformat(line))),
+                received: None,
+            })
+            .collect::<Vec<_>>();
         self.extend(items.into_iter());
     }
+
+    /// Returns the indexes of items from `start` onward with plain text matching `regex`,
+    /// oldest first.
+    #[must_use]
+    pub fn search_regex(&self, regex: &Regex, start: usize) -> Vec<usize> {
+        self.received
+            .iter()
+            .enumerate()
+            .skip(start)
+            .filter(|(_, item)| regex.is_match(&item.plain_text()))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Returns the indexes out of `indexes` of items matching `query`, in the same order.
+    pub(crate) fn search_query(
+        &mut self,
+        query: &Query,
+        indexes: impl IntoIterator<Item = usize>,
+    ) -> Vec<usize> {
+        let index = self.search_index();
+        indexes
+            .into_iter()
+            .filter(|idx| index.get(*idx).is_some_and(|entry| query.matches(entry)))
+            .collect()
+    }
+
+    /// Returns the number of times an item was replaced, or inserted before existing items.
+    /// Item indexes found before this changes may no longer point at the same items.
+    #[must_use]
+    pub fn rewrites(&self) -> usize {
+        self.rewrites
+    }
+
+    /// Returns the number of items evicted since the output was created. Subtracting the
+    /// growth of this count keeps a previously found item index pointing at the same item.
+    #[must_use]
+    pub fn evicted(&self) -> usize {
+        self.evicted
+    }
+
+    fn search_index(&mut self) -> &VecDeque<IndexEntry> {
+        self.search_index
+            .get_or_insert_with(|| self.received.iter().map(IndexEntry::new).collect())
+    }
+
+    fn evict(&mut self) {
+        let excess = self
+            .received
+            .len()
+            .saturating_sub(self.max_len.unwrap_or(usize::MAX));
+        if excess == 0 {
+            return;
+        }
+        self.received.drain(..excess);
+        if let Some(index) = &mut self.search_index {
+            index.drain(..excess);
+        }
+        self.evicted = self.evicted.saturating_add(excess);
+        self.new_data = self.new_data.min(self.received.len());
+    }
+}
+
+/// A lowercased needle, prepared for matching against [`Output`] search index entries.
+#[derive(Debug, Clone)]
+pub(crate) struct Query {
+    finder: Finder<'static>,
+    bigrams: Bigrams,
+}
+
+impl Query {
+    pub(crate) fn new(needle: &str) -> Self {
+        let needle = needle.to_lowercase();
+        Self {
+            bigrams: Bigrams::new(&needle),
+            finder: Finder::new(needle.as_bytes()).into_owned(),
+        }
+    }
+
+    fn matches(&self, entry: &IndexEntry) -> bool {
+        entry.bigrams.contains(self.bigrams) && self.finder.find(entry.text.as_bytes()).is_some()
+    }
+}
+
+/// The lowercased plain text of an item, and the bigrams it contains.
+#[derive(Debug, Clone)]
+struct IndexEntry {
+    text: String,
+    bigrams: Bigrams,
+}
+
+impl IndexEntry {
+    fn new(item: &Item) -> Self {
+        let text = item.search_text();
+        Self {
+            bigrams: Bigrams::new(&text),
+            text,
+        }
+    }
+}
+
+/// A 256 bit signature of the byte pairs in some text. Text can only contain a needle if
+/// its signature contains the needle's, which rules out most items without looking at their
+/// text. Needles shorter than two bytes have an empty signature, matching everything.
+#[derive(Debug, Clone, Copy, Default)]
+struct Bigrams([u64; 4]);
+
+impl Bigrams {
+    fn new(text: &str) -> Self {
+        let mut bigrams = Self::default();
+        for pair in text.as_bytes().windows(2) {
+            let bit = (usize::from(pair[0]) * 31 + usize::from(pair[1])) % 256;
+            bigrams.0[bit / 64] |= 1 << (bit % 64);
+        }
+        bigrams
+    }
+
+    fn contains(self, other: Self) -> bool {
+        self.0
+            .iter()
+            .zip(other.0)
+            .all(|(bits, other)| bits & other == other)
+    }
 }
 
 #[derive(Debug, Clone)]
 #[pyclass(name = "OutputItem")]
 pub enum Item {
     /// An item of output, usually from the MUD server.
-    Mud { line: MudLine },
+    ///
+    /// `received` is when the line was received, if it came from the MUD.
+    #[pyo3(constructor = (line, received=None))]
+    Mud {
+        line: MudLine,
+        received: Option<SystemTime>,
+    },
 
     /// A line of input, usually from the player.
     Input { line: InputLine },
@@ -108,7 +417,10 @@ impl Item {
 
     #[staticmethod]
     fn mud(line: MudLine) -> Self {
-        Item::Mud { line }
+        Item::Mud {
+            line,
+            received: None,
+        }
     }
 
     #[staticmethod]
@@ -158,10 +470,37 @@ impl Item {
     }
 }
 
+impl Item {
+    fn is_visible_prompt(&self) -> bool {
+        matches!(self, Item::Prompt { prompt } if !prompt.gag)
+    }
+
+    /// Lowercased plain text content of the item, used for searching.
+    fn search_text(&self) -> String {
+        self.plain_text().to_lowercase()
+    }
+
+    /// Plain text content of the item, with ANSI escape sequences removed.
+    fn plain_text(&self) -> String {
+        match self {
+            Item::Mud { line, .. }
+            | Item::Prompt { prompt: line }
+            | Item::HeldPrompt { prompt: line }
+            | Item::PreviousSession { line } => line.stripped(),
+            Item::Input { line } => line.to_string(),
+            Item::ConnectionEvent { status } => status.to_string(),
+            Item::CommandResult { message, .. } => {
+                String::from_utf8_lossy(&strip_ansi_escapes::strip(message)).to_string()
+            }
+            Item::Debug { line } => line.clone(),
+        }
+    }
+}
+
 impl Display for Item {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Item::Mud { line } => write!(f, "Line: {line}"),
+            Item::Mud { line, .. } => write!(f, "Line: {line}"),
             Item::Input { line, .. } => write!(f, "Input Line: {line}"),
             Item::Prompt { prompt } => write!(f, "Prompt: {prompt}"),
             Item::HeldPrompt { prompt } => write!(f, "Held Prompt: {prompt}"),
@@ -184,3 +523,321 @@ impl Display for Item {
         }
     }
 }
+
+/// Format `time` as a timestamp in the local time zone. See [`format_timestamp`].
+///
+/// Falls back to UTC if the local time zone can't be determined.
+#[must_use]
+pub fn format_local_timestamp(time: SystemTime, format: &str) -> String {
+    let offset = jiff::Timestamp::try_from(time).map_or(0, |timestamp| {
+        jiff::tz::TimeZone::system().to_offset(timestamp).seconds()
+    });
+    let shift = Duration::from_secs(u64::from(offset.unsigned_abs()));
+    let local = match offset.is_negative() {
+        true => time.checked_sub(shift),
+        false => time.checked_add(shift),
+    };
+    format_timestamp(local.unwrap_or(time), format)
+}
+
+/// Format `time` as a UTC timestamp.
+///
+/// `format` may include `%Y` (year), `%m` (month), `%d` (day), `%H` (hour), `%M` (minute),
+/// `%S` (second) and `%%` (a literal `%`). Other text is kept as-is.
+#[must_use]
+pub fn format_timestamp(time: SystemTime, format: &str) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+
+    // Convert days since the epoch to a civil date. See
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    let mut formatted = String::with_capacity(format.len() + 8);
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            formatted.push(c);
+            continue;
+        }
+        let field = match chars.next() {
+            Some('Y') => format!("{year:04}"),
+            Some('m') => format!("{month:02}"),
+            Some('d') => format!("{day:02}"),
+            Some('H') => format!("{:02}", secs / 3_600),
+            Some('M') => format!("{:02}", secs % 3_600 / 60),
+            Some('S') => format!("{:02}", secs % 60),
+            Some('%') | None => "%".to_string(),
+            Some(other) => format!("%{other}"),
+        };
+        formatted.push_str(&field);
+    }
+    formatted
+}
+
+/// Replace cformat markup tokens like `<bold>`, `<red>` or `<bg_blue>` in `text` with the
+/// matching ANSI escape sequences.
+///
+/// This matches `cformat.cformat()` from the Python `cformat` module. Unknown tokens are
+/// left as-is.
+#[must_use]
+pub fn cformat(text: &str) -> String {
+    let mut formatted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        formatted.push_str(&rest[..start]);
+        let token_rest = &rest[start + 1..];
+        // Like the Python regex, tokens don't span lines.
+        let end = match token_rest.find(['>', '\n']) {
+            Some(end) if token_rest[end..].starts_with('>') => end,
+            _ => {
+                formatted.push('<');
+                rest = token_rest;
+                continue;
+            }
+        };
+        let token = &token_rest[..end];
+        match CFORMAT_CODES.iter().find(|(name, _)| *name == token) {
+            Some((_, code)) => {
+                formatted.push_str("\x1b[");
+                formatted.push_str(code);
+                formatted.push('m');
+            }
+            None => formatted.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &token_rest[end + 1..];
+    }
+    formatted.push_str(rest);
+    formatted
+}
+
+const CFORMAT_CODES: &[(&str, &str)] = &[
+    ("reset", "0"),
+    ("bold", "1"),
+    ("inverted", "7"),
+    ("black", "30"),
+    ("red", "31"),
+    ("green", "32"),
+    ("yellow", "33"),
+    ("blue", "34"),
+    ("magenta", "35"),
+    ("cyan", "36"),
+    ("white", "37"),
+    ("bg_black", "40"),
+    ("bg_red", "41"),
+    ("bg_green", "42"),
+    ("bg_yellow", "43"),
+    ("bg_blue", "44"),
+    ("bg_magenta", "45"),
+    ("bg_cyan", "46"),
+    ("bg_white", "47"),
+];
+
+#[cfg(test)]
+mod tests {
+    use tokio_util::bytes::Bytes;
+
+    use super::*;
+
+    fn mud_item(text: &str) -> Item {
+        Item::Mud {
+            line: MudLine::from(Bytes::copy_from_slice(text.as_bytes())),
+            received: None,
+        }
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        let time = UNIX_EPOCH + std::time::Duration::from_secs(1_709_210_096);
+        assert_eq!(format_timestamp(time, "%H:%M:%S"), "12:34:56");
+        assert_eq!(format_timestamp(time, "[%d/%m %H%%]"), "[29/02 12%]");
+        assert_eq!(format_timestamp(time, "%q %"), "%q %");
+    }
+
+    #[test]
+    fn cformat_markup() {
+        assert_eq!(
+            cformat("<bold><red>HP<reset>: 10"),
+            "\x1b[1m\x1b[31mHP\x1b[0m: 10"
+        );
+        // Unknown tokens and unclosed brackets are left alone.
+        assert_eq!(cformat("<sword> <bg_cyan>"), "<sword> \x1b[46m");
+        assert_eq!(cformat("hp < 5\n<red>"), "hp < 5\n\x1b[31m");
+        assert_eq!(cformat("a <<red>b"), "a <<red>b");
+    }
+
+    #[test]
+    fn echo_adds_coloured_lines() {
+        let mut output = Output::new();
+        output.echo("<green>one\ntwo");
+        assert_eq!(output.len(), 2);
+        let Some(Item::Mud { line, .. }) = output.get(0) else {
+            panic!("expected a MUD item");
+        };
+        assert_eq!(&line.raw[..], b"\x1b[32mone");
+    }
+
+    #[test]
+    fn search_most_recent_first() {
+        let mut output = Output::new();
+        output.push(mud_item("You hit the \x1b[31mORC\x1b[0m."));
+        output.push(mud_item("The orc misses you."));
+        output.push(mud_item("You feel hungry."));
+
+        assert_eq!(output.search("orc", None), vec![1, 0]);
+        assert_eq!(output.search("ORC", Some(1)), vec![1]);
+        assert!(output.search("dragon", None).is_empty());
+        // Short needles have no bigrams to rule items out with.
+        assert_eq!(output.search("Y", None), vec![2, 1, 0]);
+        assert_eq!(output.search("", Some(2)), vec![2, 1]);
+    }
+
+    #[test]
+    fn search_index_stays_consistent() {
+        let mut output = Output::new();
+        output.push(mud_item("first orc"));
+        assert_eq!(output.search("orc", None), vec![0]);
+
+        // Items added after the index was built are searchable.
+        output.push(mud_item("second orc"));
+        output.extend(vec![mud_item("nothing"), mud_item("third orc")].into_iter());
+        assert_eq!(output.search("orc", None), vec![3, 1, 0]);
+
+        // Replacing the items replaces the index.
+        output.set(
+            vec![mud_item("a goblin"), mud_item("an orc")].into_iter(),
+            true,
+        );
+        assert_eq!(output.search("orc", None), vec![1]);
+        assert_eq!(output.search("goblin", None), vec![0]);
+    }
+
+    #[test]
+    fn evicts_oldest_items() {
+        let mut output = Output::new();
+        output.set_max_len(Some(3));
+        for text in ["one orc", "two", "three orc", "four"] {
+            output.push(mud_item(text));
+        }
+        assert_eq!(output.len(), 3);
+        assert_eq!(output.evicted(), 1);
+        assert_eq!(output.search("orc", None), vec![1]);
+
+        // The search index is trimmed along with the items.
+        output.extend(vec![mud_item("five orc"), mud_item("six")].into_iter());
+        assert_eq!(output.evicted(), 3);
+        assert_eq!(output.search("orc", None), vec![1]);
+        assert_eq!(output.read_received().len(), 3);
+        assert_eq!(output.new_data, 0);
+
+        // Lowering the limit evicts right away.
+        output.set_max_len(Some(1));
+        let Some(Item::Mud { line, .. }) = output.get(0) else {
+            panic!("expected a MUD item");
+        };
+        assert_eq!(&line.raw[..], b"six");
+        assert_eq!(output.evicted(), 5);
+
+        // Clearing counts as evicting everything.
+        output.push(mud_item("seven orc"));
+        output.clear();
+        assert!(output.is_empty());
+        assert_eq!(output.evicted(), 7);
+        assert_eq!(output.new_data, 0);
+        assert!(output.search("orc", None).is_empty());
+    }
+
+    #[test]
+    fn reading_clears_activity() {
+        let mut output = Output::new();
+        output.push(mud_item("Ayla tells you 'hi'"));
+        output.important = true;
+        output.push(mud_item("The orc hits you."));
+        assert_eq!(output.new_data, 2);
+        assert!(output.important);
+
+        output.read_received();
+        assert_eq!(output.new_data, 0);
+        assert!(!output.important);
+
+        output.important = true;
+        output.clear();
+        assert!(!output.important);
+    }
+
+    #[test]
+    fn replaces_and_inserts_items() {
+        let mut output = Output::new();
+        output.replace_last(mud_item("loading 10%"));
+        assert_eq!(output.len(), 1);
+
+        output.replace_last(mud_item("loading 50%"));
+        output.insert(0, mud_item("first orc"));
+        output.insert(usize::MAX, mud_item("last orc"));
+        let text = |idx| output.get(idx).unwrap().plain_text();
+        assert_eq!(
+            (0..output.len()).map(text).collect::<Vec<_>>(),
+            vec!["first orc", "loading 50%", "last orc"]
+        );
+
+        // The search index follows the changes.
+        assert_eq!(output.search("orc", None), vec![2, 0]);
+        output.replace_last(mud_item("done"));
+        output.insert(1, mud_item("middle orc"));
+        assert_eq!(output.search("orc", None), vec![1, 0]);
+        assert_eq!(output.search("loading", None), vec![2]);
+    }
+
+    #[test]
+    fn logs_only_appended_items() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut output = Output::new();
+        output.set_log(Some(tx));
+
+        output.push(mud_item("loading 10%"));
+        output.replace_last(mud_item("loading 50%"));
+        output.insert(0, mud_item("earlier"));
+        output.insert(usize::MAX, mud_item("later"));
+
+        let mut logged = Vec::new();
+        while let Ok(item) = rx.try_recv() {
+            logged.push(item.plain_text());
+        }
+        assert_eq!(logged, vec!["loading 10%", "later"]);
+    }
+
+    #[test]
+    fn prompt_navigation() {
+        let mut output = Output::new();
+        let prompt = |gag| Item::Prompt {
+            prompt: MudLine {
+                gag,
+                ..MudLine::from(Bytes::from_static(b"> "))
+            },
+        };
+        output.push(mud_item("round one"));
+        output.push(prompt(false)); // 1
+        output.push(mud_item("round two"));
+        output.push(prompt(true)); // 3, gagged.
+        output.push(prompt(false)); // 4
+        output.push(mud_item("round three"));
+
+        assert_eq!(output.prompt_before(6), Some(4));
+        assert_eq!(output.prompt_before(4), Some(1));
+        assert_eq!(output.prompt_before(1), None);
+        assert_eq!(output.prompt_after(0), Some(1));
+        assert_eq!(output.prompt_after(1), Some(4));
+        assert_eq!(output.prompt_after(4), None);
+        assert_eq!(output.prompt_after(100), None);
+
+        assert_eq!(Output::new().prompt_before(0), None);
+    }
+}
@@ -0,0 +1,201 @@
+use std::time::Duration;
+
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_util::bytes::Bytes;
+use tracing::info;
+
+use crate::client::session_log::{EVENT_MARKER, INPUT_MARKER, NOTE_MARKER, PROMPT_MARKER};
+use crate::net::connection::{self, SessionEvent};
+use crate::net::telnet::codec::Item as TelnetItem;
+
+/// A line of output read from a capture file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Entry {
+    /// When the line was received, in seconds since the Unix epoch, if known.
+    pub(super) at: Option<u64>,
+    pub(super) data: Bytes,
+    /// Whether the line is a prompt.
+    pub(super) prompt: bool,
+}
+
+/// Parse a capture file into the output lines to replay.
+///
+/// Captures are raw output, one line per received line. Lines may be prefixed with a
+/// `[YYYY-MM-DD HH:MM:SS] ` timestamp, as written to session logs. Timestamped lines marked
+/// as input, connection events or command output are skipped, and those marked as prompts
+/// are replayed as prompts. In captures without timestamps, a final line without a line
+/// ending is a prompt.
+pub(super) fn parse(data: &[u8]) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut lines = data.split(|b| *b == b'\n').peekable();
+
+    while let Some(line) = lines.next() {
+        let last = lines.peek().is_none();
+        if last && line.is_empty() {
+            break;
+        }
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+        let (at, line, prompt) = match parse_timestamp(line) {
+            Some((at, rest)) => {
+                if [INPUT_MARKER, EVENT_MARKER, NOTE_MARKER]
+                    .iter()
+                    .any(|marker| rest.starts_with(marker.as_bytes()))
+                {
+                    continue;
+                }
+                match rest.strip_prefix(PROMPT_MARKER.as_bytes()) {
+                    Some(prompt) => (Some(at), prompt, true),
+                    None => (Some(at), rest, false),
+                }
+            }
+            None => (None, line, last),
+        };
+
+        entries.push(Entry {
+            at,
+            data: Bytes::copy_from_slice(line),
+            prompt,
+        });
+    }
+
+    entries
+}
+
+/// Send `entries` to the client for `session_id` as if they were received from a connection.
+///
+/// With an `interval` lines are sent that far apart. Otherwise the time
+/// between timestamped lines is reproduced, and other lines are sent right away.
+pub(super) async fn run(
+    session_id: u32,
+    entries: Vec<Entry>,
+    interval: Option<Duration>,
+    conn_tx: UnboundedSender<connection::Event>,
+) {
+    let mut prev_at = None;
+    let total = entries.len();
+
+    for entry in entries {
+        let delay = match (interval, entry.at, prev_at) {
+            (Some(interval), ..) => interval,
+            (None, Some(at), Some(prev)) => Duration::from_secs(at.saturating_sub(prev)),
+            _ => Duration::ZERO,
+        };
+        prev_at = entry.at.or(prev_at);
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+
+        let event = match entry.prompt {
+            true => SessionEvent::PartialLine(entry.data),
+            false => SessionEvent::Telnet(TelnetItem::Line(entry.data)),
+        };
+        for event in [event, SessionEvent::ReadComplete] {
+            if conn_tx
+                .send(connection::Event { session_id, event })
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+
+    info!("replay of {total} lines finished");
+}
+
+// Parse a `[YYYY-MM-DD HH:MM:SS] ` prefix, returning the time in seconds since the Unix epoch
+// and the rest of the line.
+fn parse_timestamp(line: &[u8]) -> Option<(u64, &[u8])> {
+    let prefix = line.get(..22)?;
+    if prefix[0] != b'[' || &prefix[20..] != b"] " {
+        return None;
+    }
+    let field = |range: std::ops::Range<usize>| -> Option<u64> {
+        let digits = prefix.get(range)?;
+        if !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        std::str::from_utf8(digits).ok()?.parse().ok()
+    };
+    let separators = [(5, b'-'), (8, b'-'), (11, b' '), (14, b':'), (17, b':')];
+    if separators.iter().any(|(idx, sep)| prefix[*idx] != *sep) {
+        return None;
+    }
+
+    let (month, day) = (field(6..8)?, field(9..11)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let days = days_from_civil(field(1..5)?, month, day)?;
+    let secs = days * 86_400 + field(12..14)? * 3_600 + field(15..17)? * 60 + field(18..20)?;
+    Some((secs, &line[22..]))
+}
+
+// Convert a civil date to days since the Unix epoch, or `None` for dates before year 1. The
+// month and day must be in range. See
+// https://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: u64, month: u64, day: u64) -> Option<u64> {
+    let year = if month <= 2 {
+        year.checked_sub(1)?
+    } else {
+        year
+    };
+    let era = year / 400;
+    let yoe = year - era * 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some((era * 146_097 + doe).saturating_sub(719_468))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_captures() {
+        let capture = b"[2024-02-29 12:34:56] You have \x1b[33m42\x1b[0m gold.\r\n\
+            [2024-02-29 12:34:57] > score\n\
+            [2024-02-29 12:34:57] *** Connected\n\
+            [2024-02-29 12:34:59] plain line\n\
+            [2024-02-29 12:34:59] --- Created alias 1\n\
+            [2024-02-29 12:35:00] %%% <90hp> \n\
+            untimed line\n\
+            <100hp> ";
+        let entry = |at, data: &'static [u8], prompt| Entry {
+            at,
+            data: Bytes::from_static(data),
+            prompt,
+        };
+
+        assert_eq!(
+            parse(capture),
+            [
+                entry(
+                    Some(1_709_210_096),
+                    b"You have \x1b[33m42\x1b[0m gold.",
+                    false
+                ),
+                entry(Some(1_709_210_099), b"plain line", false),
+                entry(Some(1_709_210_100), b"<90hp> ", true),
+                entry(None, b"untimed line", false),
+                entry(None, b"<100hp> ", true),
+            ]
+        );
+
+        // Out of range dates aren't timestamps.
+        for line in [
+            &b"[0000-01-01 00:00:00] x"[..],
+            b"[2024-00-10 00:00:00] x",
+            b"[2024-13-10 00:00:00] x",
+            b"[2024-01-00 00:00:00] x",
+        ] {
+            assert_eq!(parse(line), [entry(None, line, true)]);
+        }
+
+        // A trailing line ending doesn't produce an empty prompt.
+        let entries = parse(b"one\ntwo\n");
+        assert_eq!(entries.len(), 2);
+        assert!(!entries[1].prompt);
+    }
+}
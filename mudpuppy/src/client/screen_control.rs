@@ -0,0 +1,124 @@
+use tokio_util::bytes::{Bytes, BytesMut};
+
+const ESC: u8 = 0x1B;
+
+/// The result of removing screen control sequences from received data.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct Filtered {
+    /// The data with screen control sequences removed.
+    pub(super) data: Bytes,
+    /// Whether the data contained a sequence that clears the screen.
+    pub(super) cleared: bool,
+}
+
+/// Remove ANSI sequences that clear the screen or move the cursor from `data`.
+///
+/// These sequences assume a fixed size terminal and garble a scrolling output buffer.
+/// Other escape sequences (e.g. SGR colours) are left intact. Incomplete sequences at
+/// the end of the data are kept as-is.
+pub(super) fn filter(data: Bytes) -> Filtered {
+    if !data.contains(&ESC) {
+        return Filtered {
+            data,
+            cleared: false,
+        };
+    }
+
+    let mut out = BytesMut::with_capacity(data.len());
+    let mut cleared = false;
+    // Whether the previous control sequence moved the cursor to the top left. Erasing
+    // below the cursor after that is the same as clearing the screen.
+    let mut homed = false;
+    let mut i = 0;
+
+    while i < data.len() {
+        if data[i] != ESC {
+            out.extend_from_slice(&data[i..=i]);
+            i += 1;
+            continue;
+        }
+
+        match data.get(i + 1) {
+            // ESC c (full reset) clears the screen.
+            Some(b'c') => {
+                cleared = true;
+                i += 2;
+            }
+            // ESC 7 and ESC 8 save and restore the cursor position.
+            Some(b'7' | b'8') => i += 2,
+            Some(b'[') => {
+                let Some(len) = csi_len(&data[i..]) else {
+                    // Incomplete - keep the remainder.
+                    out.extend_from_slice(&data[i..]);
+                    break;
+                };
+                let seq = &data[i..i + len];
+                let params = &seq[2..len - 1];
+                match seq[len - 1] {
+                    b'J' => {
+                        cleared |= matches!(params, b"2" | b"3") || (homed && is_zero(params));
+                        homed = false;
+                    }
+                    b'H' | b'f' => homed = is_home(params),
+                    b'A'..=b'G' | b'K' | b'S' | b'T' | b'd' | b'r' | b's' | b'u' => {
+                        homed = false;
+                    }
+                    _ => out.extend_from_slice(seq),
+                }
+                i += len;
+            }
+            _ => {
+                out.extend_from_slice(&data[i..=i]);
+                i += 1;
+            }
+        }
+    }
+
+    Filtered {
+        data: out.freeze(),
+        cleared,
+    }
+}
+
+// Returns the length of the CSI sequence at the start of `data`, or `None` if it's incomplete.
+fn csi_len(data: &[u8]) -> Option<usize> {
+    data.iter()
+        .skip(2)
+        .position(|b| (0x40..=0x7E).contains(b))
+        .map(|pos| pos + 3)
+}
+
+fn is_zero(params: &[u8]) -> bool {
+    matches!(params, b"" | b"0")
+}
+
+fn is_home(params: &[u8]) -> bool {
+    params.iter().all(|b| matches!(b, b'1' | b';'))
+        && params.split(|b| *b == b';').all(|p| p.len() <= 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filtered(data: &'static [u8]) -> (Vec<u8>, bool) {
+        let Filtered { data, cleared } = filter(Bytes::from_static(data));
+        (data.to_vec(), cleared)
+    }
+
+    #[test]
+    fn strips_screen_control() {
+        assert_eq!(filtered(b"plain text"), (b"plain text".to_vec(), false));
+        assert_eq!(
+            filtered(b"\x1b[2Jwelcome \x1b[1;32mback\x1b[0m"),
+            (b"welcome \x1b[1;32mback\x1b[0m".to_vec(), true)
+        );
+        assert_eq!(filtered(b"\x1b[H\x1b[Jtitle"), (b"title".to_vec(), true));
+        assert_eq!(
+            filtered(b"\x1b[10;5Hhp: 10\x1b[K\x1b[J"),
+            (b"hp: 10".to_vec(), false)
+        );
+        assert_eq!(filtered(b"\x1b7x\x1b8\x1b[3A"), (b"x".to_vec(), false));
+        assert_eq!(filtered(b"tail \x1b[2"), (b"tail \x1b[2".to_vec(), false));
+    }
+}
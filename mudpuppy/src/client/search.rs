@@ -0,0 +1,293 @@
+use regex::{Regex, RegexBuilder};
+
+use crate::client::output::{Output, Query};
+use crate::error::Error;
+use crate::model::InputLine;
+
+/// A search of the output buffer, edited in the input area.
+#[derive(Debug)]
+pub(super) struct Search {
+    /// The in-progress input, restored when the search is closed.
+    pub(super) stash: InputLine,
+    /// The compiled pattern, if a valid non-empty one has been entered.
+    pub(super) regex: Option<Regex>,
+    /// The lowercased pattern if it's plain text, matched with the output's search index
+    /// instead of `regex`.
+    needle: Option<(String, Query)>,
+    /// Indexes of the output items matching `regex`, oldest first.
+    pub(super) matches: Vec<usize>,
+    /// The output item index of the current match, once one has been jumped to.
+    pub(super) current: Option<usize>,
+    /// Whether enter was pressed, so that `n` and `N` move between matches.
+    pub(super) browsing: bool,
+    /// The output's eviction count when `current` was last updated.
+    evicted: usize,
+    /// The number of output items checked for matches so far, including evicted items.
+    checked: usize,
+    /// The output's rewrite count when the matches were found.
+    rewrites: usize,
+}
+
+impl Search {
+    pub(super) fn new(stash: InputLine) -> Self {
+        Self {
+            stash,
+            regex: None,
+            needle: None,
+            matches: Vec::default(),
+            current: None,
+            browsing: false,
+            evicted: 0,
+            checked: 0,
+            rewrites: 0,
+        }
+    }
+
+    /// Update the pattern, and the matches in `output`.
+    ///
+    /// # Errors
+    /// If the pattern is an invalid regex. The previous pattern is kept in this case.
+    pub(super) fn set_pattern(&mut self, pattern: &str, output: &mut Output) -> Result<(), Error> {
+        let compiled = compile(pattern)?;
+        let needle = compiled
+            .as_ref()
+            .is_some_and(|(_, plain)| *plain)
+            .then(|| pattern.to_lowercase());
+
+        // Plain text containing the previous plain text can only match items that
+        // matched before, so typing more of a pattern narrows the previous matches.
+        let narrow = match (&self.needle, &needle) {
+            (Some((previous, _)), Some(needle)) => needle.contains(previous.as_str()),
+            _ => false,
+        };
+        if !narrow {
+            self.restart(output);
+        }
+
+        self.regex = compiled.map(|(regex, _)| regex);
+        self.needle = needle.map(|needle| {
+            let query = Query::new(&needle);
+            (needle, query)
+        });
+        self.current = None;
+        self.update_matches(output, narrow);
+        Ok(())
+    }
+
+    /// Move to the closest match older (or newer) than the current match, returning its
+    /// output item index.
+    ///
+    /// Without a current match the first older match is the most recent one. Like prompt
+    /// navigation, this stops at the oldest and newest matches rather than wrapping around.
+    pub(super) fn step(&mut self, output: &mut Output, older: bool) -> Option<usize> {
+        // New output may have arrived since the matches were found.
+        self.update_matches(output, false);
+        let from = self.current.unwrap_or(output.len());
+        let target = match older {
+            true => self.matches.iter().rev().find(|idx| **idx < from),
+            false => self.matches.iter().find(|idx| **idx > from),
+        }
+        .copied()?;
+        self.current = Some(target);
+        Some(target)
+    }
+
+    /// Returns a title describing the search, e.g. `search 2/5`.
+    pub(super) fn title(&self) -> String {
+        let position = self
+            .current
+            .and_then(|current| self.matches.iter().position(|idx| *idx == current));
+        match (&self.regex, position) {
+            (None, _) => "search".to_string(),
+            (Some(_), Some(position)) => format!("search {}/{}", position + 1, self.matches.len()),
+            (Some(_), None) => format!("search {} matches", self.matches.len()),
+        }
+    }
+
+    /// Update the matches for output added since they were last updated, first narrowing
+    /// the previous matches down to those still matching if `narrow` is true.
+    fn update_matches(&mut self, output: &mut Output, narrow: bool) {
+        // Old output may have been evicted, moving matches to lower indexes, or removing
+        // them altogether.
+        let evicted = output.evicted().saturating_sub(self.evicted);
+        self.current = self.current.and_then(|idx| idx.checked_sub(evicted));
+        self.matches
+            .retain_mut(|idx| match idx.checked_sub(evicted) {
+                Some(moved) => {
+                    *idx = moved;
+                    true
+                }
+                None => false,
+            });
+        self.evicted = output.evicted();
+
+        // Replaced or inserted items invalidate the matches found so far.
+        let narrow = narrow && output.rewrites() == self.rewrites;
+        if output.rewrites() != self.rewrites {
+            self.restart(output);
+        }
+
+        let start = self.checked.saturating_sub(self.evicted);
+        match (&self.regex, &self.needle) {
+            (None, _) => self.matches.clear(),
+            (Some(_), Some((_, query))) => {
+                if narrow {
+                    self.matches = output.search_query(query, self.matches.iter().copied());
+                }
+                let added = output.search_query(query, start..output.len());
+                self.matches.extend(added);
+            }
+            (Some(regex), None) => self.matches.extend(output.search_regex(regex, start)),
+        }
+        self.checked = self.evicted.saturating_add(output.len());
+    }
+
+    /// Forget the matches found so far, so that the whole output is checked again.
+    fn restart(&mut self, output: &Output) {
+        self.matches.clear();
+        self.checked = output.evicted();
+        self.rewrites = output.rewrites();
+    }
+}
+
+/// Compile a search pattern, returning the regex and whether the pattern is plain text.
+///
+/// Patterns written as `/pattern/` are regular expressions, optionally followed by `i` to
+/// ignore case. Anything else is plain text, matched ignoring case. Empty patterns
+/// match nothing.
+fn compile(pattern: &str) -> Result<Option<(Regex, bool)>, Error> {
+    if pattern.is_empty() {
+        return Ok(None);
+    }
+
+    let (regex, plain) = match pattern.strip_prefix('/').and_then(|rest| {
+        rest.strip_suffix("/i")
+            .map(|regex| (regex, true))
+            .or_else(|| rest.strip_suffix('/').map(|regex| (regex, false)))
+    }) {
+        Some(("", _)) => return Ok(None),
+        Some((regex, ignore_case)) => (
+            RegexBuilder::new(regex)
+                .case_insensitive(ignore_case)
+                .build(),
+            false,
+        ),
+        None => (
+            RegexBuilder::new(&regex::escape(pattern))
+                .case_insensitive(true)
+                .build(),
+            true,
+        ),
+    };
+    regex
+        .map(|regex| Some((regex, plain)))
+        .map_err(Error::Search)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio_util::bytes::Bytes;
+
+    use super::*;
+    use crate::client::output::Item;
+    use crate::model::MudLine;
+
+    fn output(lines: &[&str]) -> Output {
+        let mut output = Output::new();
+        for line in lines {
+            output.push(Item::Mud {
+                line: MudLine::from(Bytes::copy_from_slice(line.as_bytes())),
+                received: None,
+            });
+        }
+        output
+    }
+
+    #[test]
+    fn compiles_patterns() {
+        let is_match = |pattern, text| compile(pattern).unwrap().unwrap().0.is_match(text);
+
+        // Plain text ignores case, and regex characters aren't special.
+        assert!(is_match("ORC", "an orc"));
+        assert!(is_match("a.c", "a.c"));
+        assert!(!is_match("a.c", "abc"));
+
+        // Regexes are case sensitive, unless followed by `i`.
+        assert!(is_match(r"/\d+ gold/", "42 gold"));
+        assert!(!is_match("/Orc/", "orc"));
+        assert!(is_match("/Orc/i", "orc"));
+
+        assert!(compile("").unwrap().is_none());
+        assert!(compile("//").unwrap().is_none());
+        assert!(compile("/(/").is_err());
+        assert!(compile("orc").unwrap().unwrap().1);
+        assert!(!compile("/orc/").unwrap().unwrap().1);
+    }
+
+    #[test]
+    fn steps_between_matches() {
+        let mut output = output(&["an orc", "a goblin", "The orc hits you.", "You flee."]);
+        let mut search = Search::new(InputLine::default());
+        search.set_pattern("orc", &mut output).unwrap();
+        assert_eq!(search.title(), "search 2 matches");
+
+        assert_eq!(search.step(&mut output, true), Some(2));
+        assert_eq!(search.title(), "search 2/2");
+        assert_eq!(search.step(&mut output, true), Some(0));
+        // Stops at the oldest match.
+        assert_eq!(search.step(&mut output, true), None);
+        assert_eq!(search.title(), "search 1/2");
+
+        // Output received while searching is included.
+        output.push(Item::Mud {
+            line: MudLine::from(Bytes::from_static(b"Another orc arrives.")),
+            received: None,
+        });
+        assert_eq!(search.step(&mut output, false), Some(2));
+        assert_eq!(search.step(&mut output, false), Some(4));
+        assert_eq!(search.step(&mut output, false), None);
+        assert_eq!(search.title(), "search 3/3");
+    }
+
+    #[test]
+    fn follows_matches_across_evictions() {
+        let mut output = output(&["an orc", "a goblin", "The orc hits you.", "You flee."]);
+        output.set_max_len(Some(4));
+        let mut search = Search::new(InputLine::default());
+        search.set_pattern("orc", &mut output).unwrap();
+        assert_eq!(search.step(&mut output, true), Some(2));
+
+        output.push(Item::Mud {
+            line: MudLine::from(Bytes::from_static(b"Another orc arrives.")),
+            received: None,
+        });
+        // "The orc hits you." is now at index 1, and the first match was evicted.
+        assert_eq!(search.step(&mut output, true), None);
+        assert_eq!(search.title(), "search 1/2");
+        assert_eq!(search.step(&mut output, false), Some(3));
+    }
+
+    #[test]
+    fn narrows_extended_patterns() {
+        let mut output = output(&["an orc", "an orchid", "a goblin", "The orc hits you."]);
+        let mut search = Search::new(InputLine::default());
+        search.set_pattern("or", &mut output).unwrap();
+        assert_eq!(search.matches, vec![0, 1, 3]);
+        search.set_pattern("orch", &mut output).unwrap();
+        assert_eq!(search.matches, vec![1]);
+
+        // Shortening the pattern (or switching to a regex) searches everything again.
+        search.set_pattern("orc", &mut output).unwrap();
+        assert_eq!(search.matches, vec![0, 1, 3]);
+        search.set_pattern("/orc$/", &mut output).unwrap();
+        assert_eq!(search.matches, vec![0]);
+
+        // Replaced items are searched again too.
+        output.replace_last(Item::Mud {
+            line: MudLine::from(Bytes::from_static(b"You kill the orc")),
+            received: None,
+        });
+        assert_eq!(search.step(&mut output, true), Some(3));
+        assert_eq!(search.matches, vec![0, 3]);
+    }
+}
@@ -0,0 +1,192 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tracing::{info, warn};
+
+use crate::client::output::{self, Item};
+use crate::config;
+use crate::model::LogFormat;
+
+/// Marks a line of input sent to the MUD.
+pub(super) const INPUT_MARKER: &str = "> ";
+/// Marks a connection event.
+pub(super) const EVENT_MARKER: &str = "*** ";
+/// Marks a prompt received from the MUD.
+pub(super) const PROMPT_MARKER: &str = "%%% ";
+/// Marks a line of command result or debug output that didn't come from the MUD.
+pub(super) const NOTE_MARKER: &str = "--- ";
+
+/// A log of everything added to a session's output buffer.
+///
+/// Each line is prefixed with a timestamp. Lines that aren't MUD output, or are prompts,
+/// also start with one of the markers above so that logs can be replayed.
+///
+/// Items are sent to a writer running on a blocking thread, so slow disks don't hold up
+/// the session. Writes are flushed whenever the writer catches up with the session.
+#[derive(Debug)]
+pub(super) struct SessionLog {
+    path: PathBuf,
+    tx: UnboundedSender<Item>,
+}
+
+impl SessionLog {
+    /// Open the session log at `path` for appending, or a new timestamped file named for
+    /// `mud` in the data directory's `logs` directory if no path is given.
+    ///
+    /// Relative paths are resolved against the data directory.
+    pub(super) fn open(mud: &str, path: Option<&Path>, format: LogFormat) -> io::Result<Self> {
+        let path = match path {
+            Some(path) => config::data_dir().join(path),
+            None => config::data_dir().join("logs").join(format!(
+                "{}-{}.log",
+                file_name_safe(mud),
+                timestamp(SystemTime::now()).replace([' ', ':'], "_")
+            )),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let writer_path = path.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(err) = write_items(BufWriter::new(file), rx, format) {
+                warn!(
+                    "failed to write session log {}: {err}",
+                    writer_path.display()
+                );
+            }
+        });
+        info!("session log started: {}", path.display());
+
+        Ok(Self { path, tx })
+    }
+
+    pub(super) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns a sender for items to be logged. The log is closed, after writing any
+    /// outstanding items, once every sender has been dropped.
+    pub(super) fn sender(&self) -> UnboundedSender<Item> {
+        self.tx.clone()
+    }
+}
+
+fn write_items(
+    mut out: impl Write,
+    mut rx: UnboundedReceiver<Item>,
+    format: LogFormat,
+) -> io::Result<()> {
+    while let Some(item) = rx.blocking_recv() {
+        write_item(&mut out, &item, format)?;
+        while let Ok(item) = rx.try_recv() {
+            write_item(&mut out, &item, format)?;
+        }
+        out.flush()?;
+    }
+    out.flush()
+}
+
+fn write_item(out: &mut impl Write, item: &Item, format: LogFormat) -> io::Result<()> {
+    let text = |text: &str| match format {
+        LogFormat::Raw => text.to_string(),
+        LogFormat::Plain => strip_ansi_escapes::strip_str(text),
+    };
+    let now = timestamp(SystemTime::now());
+    let line = match item {
+        Item::Mud { line, .. } => text(&line.to_str()),
+        Item::Prompt { prompt } => format!("{PROMPT_MARKER}{}", text(&prompt.to_str())),
+        Item::Input { line } => format!("{INPUT_MARKER}{line}"),
+        Item::ConnectionEvent { status } => format!("{EVENT_MARKER}{status}"),
+        // Messages may span lines, and each is marked so none are mistaken for MUD output.
+        Item::CommandResult { message, .. } | Item::Debug { line: message } => {
+            for line in text(message).lines() {
+                writeln!(out, "[{now}] {NOTE_MARKER}{line}")?;
+            }
+            return Ok(());
+        }
+        // Held prompts are never added to the buffer, and previous session lines are already
+        // in the log they were loaded from.
+        Item::HeldPrompt { .. } | Item::PreviousSession { .. } => return Ok(()),
+    };
+    writeln!(out, "[{now}] {line}")
+}
+
+// Format a time as a `YYYY-MM-DD HH:MM:SS` UTC timestamp.
+fn timestamp(time: SystemTime) -> String {
+    output::format_timestamp(time, "%Y-%m-%d %H:%M:%S")
+}
+
+pub(super) fn file_name_safe(name: &str) -> String {
+    name.chars()
+        .map(|c| match c.is_alphanumeric() || c == '-' || c == '_' {
+            true => c,
+            false => '_',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use tokio_util::bytes::Bytes;
+
+    use super::*;
+    use crate::model::{InputLine, MudLine};
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!(timestamp(UNIX_EPOCH), "1970-01-01 00:00:00");
+        assert_eq!(
+            timestamp(UNIX_EPOCH + Duration::from_secs(1_709_210_096)),
+            "2024-02-29 12:34:56"
+        );
+    }
+
+    #[test]
+    fn writes_plain_and_raw_items() {
+        let line = Item::Mud {
+            line: MudLine::from(Bytes::from_static(b"You have \x1b[33m42\x1b[0m gold.")),
+            received: None,
+        };
+        let input = Item::Input {
+            line: InputLine::new("score".to_string(), true, false),
+        };
+
+        let prompt = Item::Prompt {
+            prompt: MudLine::from(Bytes::from_static(b"<100hp> ")),
+        };
+        let result = Item::CommandResult {
+            error: false,
+            message: "line one\nline two".to_string(),
+        };
+
+        let written = |format| {
+            let mut out = Vec::new();
+            for item in [&line, &input, &prompt, &result] {
+                write_item(&mut out, item, format).unwrap();
+            }
+            String::from_utf8(out)
+                .unwrap()
+                .lines()
+                .map(|line| line.split_once("] ").unwrap().1.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let rest = ["> score", "%%% <100hp> ", "--- line one", "--- line two"];
+        assert_eq!(
+            written(LogFormat::Plain),
+            [&["You have 42 gold."][..], &rest].concat()
+        );
+        assert_eq!(
+            written(LogFormat::Raw),
+            [&["You have \x1b[33m42\x1b[0m gold."][..], &rest].concat()
+        );
+    }
+}
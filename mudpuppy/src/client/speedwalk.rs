@@ -0,0 +1,121 @@
+use std::iter;
+
+use crate::error::Error;
+
+/// The most times a single speedwalk step can be repeated.
+pub const MAX_REPEAT: usize = 100;
+
+/// Expand a speedwalk, e.g. `3n2e`, into the movement commands it stands for, e.g. `n`,
+/// `n`, `n`, `e`, `e`.
+///
+/// Each step is a direction letter from `directions`, or any command in parentheses (e.g.
+/// `(ne)` or `(enter portal)`), optionally preceded by a count of how many times to repeat
+/// it. Whitespace between steps is ignored.
+///
+/// # Errors
+/// If the speedwalk has a letter that isn't in `directions`, an unclosed parenthesis, an
+/// empty command, a count without a step, or a count of zero or more than [`MAX_REPEAT`].
+pub fn expand(speedwalk: &str, directions: &str) -> Result<Vec<String>, Error> {
+    let mut commands = Vec::new();
+    let mut chars = speedwalk.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut count = String::new();
+        while let Some((_, digit)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+            count.push(digit);
+        }
+        let count = match count.parse::<usize>() {
+            _ if count.is_empty() => 1,
+            Ok(count @ 1..=MAX_REPEAT) => count,
+            _ => {
+                return Err(Error::Speedwalk(format!(
+                    "count {count:?} at position {start} must be between 1 and {MAX_REPEAT}"
+                )))
+            }
+        };
+
+        let command = match chars.next() {
+            Some((pos, '(')) => {
+                let command = chars
+                    .by_ref()
+                    .map(|(_, c)| c)
+                    .take_while(|c| *c != ')')
+                    .collect::<String>();
+                if !speedwalk[pos..].contains(')') {
+                    return Err(Error::Speedwalk(format!(
+                        "unclosed parenthesis at position {pos}"
+                    )));
+                }
+                if command.trim().is_empty() {
+                    return Err(Error::Speedwalk(format!("empty command at position {pos}")));
+                }
+                command.trim().to_string()
+            }
+            Some((_, c)) if directions.contains(c) => c.to_string(),
+            Some((pos, c)) => {
+                return Err(Error::Speedwalk(format!(
+                    "unknown direction {c:?} at position {pos}"
+                )))
+            }
+            None => {
+                return Err(Error::Speedwalk(format!(
+                    "count at position {start} isn't followed by a direction"
+                )))
+            }
+        };
+        commands.extend(iter::repeat(command).take(count));
+    }
+
+    Ok(commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_counts_and_commands() {
+        assert_eq!(
+            expand("3n2e", "nsewud").unwrap(),
+            vec!["n", "n", "n", "e", "e"]
+        );
+        assert_eq!(
+            expand(" s 2(ne) (enter portal) u", "nsewud").unwrap(),
+            vec!["s", "ne", "ne", "enter portal", "u"]
+        );
+        assert_eq!(expand("12w", "w").unwrap().len(), 12);
+        assert!(expand("", "nsewud").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_speedwalks() {
+        let err = |speedwalk| expand(speedwalk, "nsewud").unwrap_err().to_string();
+
+        assert_eq!(
+            err("2nx"),
+            "invalid speedwalk: unknown direction 'x' at position 2"
+        );
+        assert_eq!(
+            err("n3"),
+            "invalid speedwalk: count at position 1 isn't followed by a direction"
+        );
+        assert_eq!(
+            err("0n"),
+            "invalid speedwalk: count \"0\" at position 0 must be between 1 and 100"
+        );
+        assert!(err("1000n").contains("must be between"));
+        assert_eq!(
+            err("n(ne"),
+            "invalid speedwalk: unclosed parenthesis at position 1"
+        );
+        assert_eq!(
+            err("2( )"),
+            "invalid speedwalk: empty command at position 1"
+        );
+    }
+}
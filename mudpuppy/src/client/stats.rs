@@ -0,0 +1,84 @@
+use std::fmt::{self, Display, Formatter};
+use std::time::SystemTime;
+
+use pyo3::{pyclass, pymethods};
+
+/// Throughput statistics for a session's current (or most recent) connection.
+///
+/// Byte counts are of the text sent and received, excluding telnet protocol overhead.
+#[derive(Clone, Debug, Default)]
+#[pyclass]
+pub struct SessionStats {
+    /// The number of bytes of output, and telnet subnegotiation data, received.
+    #[pyo3(get)]
+    pub bytes_received: u64,
+
+    /// The number of bytes of input sent.
+    #[pyo3(get)]
+    pub bytes_sent: u64,
+
+    /// The number of lines of output received, excluding prompts.
+    #[pyo3(get)]
+    pub lines_received: u64,
+
+    /// The number of lines of input sent.
+    #[pyo3(get)]
+    pub lines_sent: u64,
+
+    /// When the session connected, or `None` while it isn't connected.
+    #[pyo3(get)]
+    pub connected_at: Option<SystemTime>,
+}
+
+impl SessionStats {
+    /// Start counting for a new connection.
+    pub(super) fn connected() -> Self {
+        Self {
+            connected_at: Some(SystemTime::now()),
+            ..Self::default()
+        }
+    }
+
+    pub(super) fn received(&mut self, bytes: usize) {
+        self.bytes_received = self.bytes_received.saturating_add(bytes as u64);
+    }
+
+    pub(super) fn sent(&mut self, bytes: usize) {
+        self.bytes_sent = self.bytes_sent.saturating_add(bytes as u64);
+        self.lines_sent = self.lines_sent.saturating_add(1);
+    }
+}
+
+#[pymethods]
+impl SessionStats {
+    /// The number of seconds the session has been connected for, or `None` while it isn't
+    /// connected.
+    #[getter]
+    #[must_use]
+    pub fn uptime(&self) -> Option<f64> {
+        self.connected_at
+            .map(|at| at.elapsed().unwrap_or_default().as_secs_f64())
+    }
+
+    fn __str__(&self) -> String {
+        format!("{self}")
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{self:?}")
+    }
+}
+
+impl Display for SessionStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} bytes ({} lines) received, {} bytes ({} lines) sent",
+            self.bytes_received, self.lines_received, self.bytes_sent, self.lines_sent
+        )?;
+        if let Some(uptime) = self.uptime() {
+            write!(f, ", connected for {uptime:.0}s")?;
+        }
+        Ok(())
+    }
+}
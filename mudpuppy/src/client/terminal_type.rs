@@ -0,0 +1,135 @@
+use tracing::{debug, info};
+
+use crate::net::telnet;
+use crate::net::telnet::codec::Item as TelnetItem;
+use crate::tui::terminal::ColorSupport;
+
+const IS: u8 = 0;
+const SEND: u8 = 1;
+
+// MTTS capability bits.
+const MTTS_ANSI: u32 = 1;
+const MTTS_UTF8: u32 = 4;
+const MTTS_256_COLORS: u32 = 8;
+const MTTS_TRUECOLOR: u32 = 256;
+const MTTS_SSL: u32 = 2048;
+
+/// A terminal type that's replaced with `MTTS` and the bitvector from [`mtts`].
+const MTTS_PLACEHOLDER: &str = "MTTS";
+
+/// Returns the MUD Terminal Type Standard bitvector of mudpuppy's capabilities when
+/// running in a terminal with the given colour support.
+///
+/// ANSI colour, UTF-8 and TLS are always supported. 256 colours and truecolor depend on
+/// the terminal.
+#[must_use]
+pub fn mtts(colors: ColorSupport) -> u32 {
+    let mut bits = MTTS_ANSI | MTTS_UTF8 | MTTS_SSL;
+    if colors >= ColorSupport::Ansi256 {
+        bits |= MTTS_256_COLORS;
+    }
+    if colors == ColorSupport::TrueColor {
+        bits |= MTTS_TRUECOLOR;
+    }
+    bits
+}
+
+/// Answers the telnet TERMINAL-TYPE option (RFC 1091), cycling through the configured
+/// terminal types on successive requests as described by the MUD Terminal Type Standard
+/// (MTTS).
+///
+/// The last type is sent twice to mark the end of the list, and the request after that
+/// starts from the first type again.
+#[derive(Debug)]
+pub(super) struct TerminalType {
+    types: Vec<String>,
+    // The index of the type to send next. One past the last type means the last type is
+    // repeated.
+    next: usize,
+}
+
+impl TerminalType {
+    /// Create a responder for `types`, where a bare `MTTS` type is sent as `MTTS` followed
+    /// by the `mtts` bitvector.
+    pub(super) fn new(types: &[String], mtts: u32) -> Self {
+        Self {
+            types: types
+                .iter()
+                .map(|name| match name.eq_ignore_ascii_case(MTTS_PLACEHOLDER) {
+                    true => format!("{MTTS_PLACEHOLDER} {mtts}"),
+                    false => name.clone(),
+                })
+                .collect(),
+            next: 0,
+        }
+    }
+
+    /// Handle a TERMINAL-TYPE subnegotiation from the MUD, returning the reply to send if any.
+    pub(super) fn handle(&mut self, data: &[u8]) -> Option<TelnetItem> {
+        if data != [SEND] {
+            debug!("ignoring TERMINAL-TYPE subnegotiation {data:?}");
+            return None;
+        }
+        let last = self.types.len().checked_sub(1)?;
+        let name = &self.types[self.next.min(last)];
+        self.next = match self.next > last {
+            true => 0,
+            false => self.next + 1,
+        };
+        info!("sending terminal type {name}");
+
+        let mut reply = vec![IS];
+        reply.extend_from_slice(name.as_bytes());
+        Some(TelnetItem::Subnegotiation(
+            telnet::option::TTYPE,
+            reply.into(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(item: Option<TelnetItem>) -> Vec<u8> {
+        match item {
+            Some(TelnetItem::Subnegotiation(telnet::option::TTYPE, data)) => data.to_vec(),
+            item => panic!("unexpected reply {item:?}"),
+        }
+    }
+
+    #[test]
+    fn cycles_through_types() {
+        let mut ttype = TerminalType::new(
+            &[
+                "MUDPUPPY".to_string(),
+                "XTERM-256COLOR".to_string(),
+                "mtts".to_string(),
+            ],
+            269,
+        );
+        let sent = (0..5)
+            .map(|_| reply(ttype.handle(&[SEND])))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sent,
+            vec![
+                b"\x00MUDPUPPY".to_vec(),
+                b"\x00XTERM-256COLOR".to_vec(),
+                b"\x00MTTS 269".to_vec(),
+                b"\x00MTTS 269".to_vec(),
+                b"\x00MUDPUPPY".to_vec(),
+            ]
+        );
+
+        assert!(ttype.handle(&[IS]).is_none());
+        assert!(TerminalType::new(&[], 0).handle(&[SEND]).is_none());
+    }
+
+    #[test]
+    fn reports_mtts_capabilities() {
+        assert_eq!(mtts(ColorSupport::Ansi), 2053);
+        assert_eq!(mtts(ColorSupport::Ansi256), 2061);
+        assert_eq!(mtts(ColorSupport::TrueColor), 2317);
+    }
+}
@@ -1,19 +1,23 @@
 use std::fmt::Debug;
-use std::fs::OpenOptions;
+use std::fs::{self, OpenOptions};
 use std::io::{Read, Seek, Write};
 use std::sync::{Arc, RwLock, RwLockReadGuard};
+use std::time::Duration;
 
 use crossterm::event::KeyEvent;
 use pyo3::{pyclass, pymethods};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use tokio_rustls::rustls::pki_types;
 use toml_edit::{ArrayOfTables, DocumentMut, Item, Value};
 use tracing::{debug, info, trace, warn};
 
 use super::keybindings::KeyBindings;
+use crate::client::charset::Encoding;
 use crate::config::{config_dir, config_file, data_dir};
 use crate::error::{ConfigError, Error};
-use crate::model::{self, InputMode, Mud, Shortcut, Tls};
+use crate::model::{self, ClearScreen, InputMode, Mud, Shortcut, Tls};
+use crate::net::stream;
 use crate::Result;
 
 /// A [`Config`] that is shared globally for the entire application.
@@ -64,6 +68,12 @@ impl GlobalConfig {
             None,
         )
     }
+
+    /// Returns how screen clearing and cursor movement sequences in output are handled.
+    #[must_use]
+    pub fn handle_clear_screen(&self) -> ClearScreen {
+        self.lookup(|config| config.handle_clear_screen, ClearScreen::default())
+    }
 }
 
 #[pymethods]
@@ -84,6 +94,109 @@ impl GlobalConfig {
             .ok_or(ConfigError::MissingMud(mud_name.to_string()).into())
     }
 
+    /// Returns the threshold over which Python callbacks are logged as slow, or `None` if
+    /// slow callback logging is disabled.
+    #[must_use]
+    pub fn slow_callback_threshold(&self) -> Option<Duration> {
+        self.lookup(
+            |config| match config.slow_callback_threshold_ms {
+                0 => None,
+                ms => Some(Duration::from_millis(ms)),
+            },
+            None,
+        )
+    }
+
+    /// Returns the maximum number of sessions that may be connecting at the same time, or
+    /// `None` if there is no limit.
+    #[must_use]
+    pub fn max_concurrent_connects(&self) -> Option<usize> {
+        self.lookup(
+            |config| match config.max_concurrent_connects {
+                0 => None,
+                limit => Some(limit),
+            },
+            None,
+        )
+    }
+
+    /// Returns whether sending input should scroll the output back to the bottom.
+    #[must_use]
+    pub fn scroll_bottom_on_send(&self) -> bool {
+        self.lookup(|config| config.scroll_bottom_on_send, true)
+    }
+
+    /// Returns the maximum number of lines kept in each session's input history.
+    #[must_use]
+    pub fn history_size(&self) -> usize {
+        self.lookup(|config| config.history_size, default_history_size())
+    }
+
+    /// Returns how often a `GlobalTick` event is emitted, or `None` if it's disabled.
+    #[must_use]
+    pub fn tick_interval(&self) -> Option<Duration> {
+        self.lookup(
+            |config| match config.tick_interval_ms {
+                0 => None,
+                ms => Some(Duration::from_millis(ms)),
+            },
+            Some(Duration::from_millis(default_tick_interval_ms())),
+        )
+    }
+
+    /// Returns whether scripts can ring the terminal bell and show desktop notifications.
+    #[must_use]
+    pub fn notifications_enabled(&self) -> bool {
+        self.lookup(
+            |config| config.notifications_enabled,
+            default_notifications_enabled(),
+        )
+    }
+
+    /// Returns the maximum number of items kept in each output buffer, or `None` if there is
+    /// no limit.
+    #[must_use]
+    pub fn max_buffer_lines(&self) -> Option<usize> {
+        self.lookup(
+            |config| match config.max_buffer_lines {
+                0 => None,
+                limit => Some(limit),
+            },
+            Some(default_max_buffer_lines()),
+        )
+    }
+
+    /// Returns whether MUD output lines are shown with the time they were received.
+    #[must_use]
+    pub fn show_timestamps(&self) -> bool {
+        self.lookup(|config| config.show_timestamps, false)
+    }
+
+    /// Returns the format of output line timestamps.
+    #[must_use]
+    pub fn timestamp_format(&self) -> String {
+        self.lookup(
+            |config| config.timestamp_format.clone(),
+            default_timestamp_format(),
+        )
+    }
+
+    /// Returns the extra indent of wrapped continuation lines, or `None` if they aren't
+    /// indented.
+    #[must_use]
+    pub fn wrap_indent(&self) -> Option<u16> {
+        self.lookup(|config| config.wrap_indent, None)
+    }
+
+    /// Returns the letters that are directions in speedwalks.
+    #[must_use]
+    pub fn speedwalk_directions(&self) -> String {
+        self.lookup(
+            |config| config.speedwalk_directions.clone(),
+            default_speedwalk_directions(),
+        )
+    }
+
     #[must_use]
     pub fn keybindings(&self) -> KeyBindings {
         self.lookup(|config| config.keybindings.clone(), KeyBindings::default())
@@ -97,6 +210,81 @@ pub struct Config {
     pub muds: Vec<Mud>,
     #[serde(default, flatten)]
     pub keybindings: KeyBindings,
+    /// Python callbacks taking longer than this many milliseconds to complete are logged
+    /// with a warning. Zero disables logging slow callbacks.
+    #[serde(default = "default_slow_callback_threshold_ms")]
+    pub slow_callback_threshold_ms: u64,
+    /// The maximum number of sessions that may be connecting at the same time, across all
+    /// MUDs. Further connections wait for a slot to free up. Zero means no limit.
+    #[serde(default)]
+    pub max_concurrent_connects: usize,
+    /// Whether sending input while scrolled back returns the output to the bottom. When false
+    /// the scroll position is kept, and new output is counted instead.
+    #[serde(default = "default_scroll_bottom_on_send")]
+    pub scroll_bottom_on_send: bool,
+    /// The maximum number of lines kept in each session's input history.
+    #[serde(default = "default_history_size")]
+    pub history_size: usize,
+    /// The maximum number of items kept in each output buffer. The oldest items are evicted
+    /// beyond this. Zero means no limit.
+    #[serde(default = "default_max_buffer_lines")]
+    pub max_buffer_lines: usize,
+    /// Whether MUD output lines are shown with the time they were received.
+    #[serde(default)]
+    pub show_timestamps: bool,
+    /// The format of output line timestamps, in local time. See `output::format_timestamp`.
+    #[serde(default = "default_timestamp_format")]
+    pub timestamp_format: String,
+    /// When set, the lines a long line wraps onto are indented to line up under its first
+    /// word, plus this many columns. When unset continuation lines aren't indented.
+    #[serde(default)]
+    pub wrap_indent: Option<u16>,
+    /// The letters that are directions in speedwalks, e.g. `3n2e`. See `speedwalk::expand`.
+    #[serde(default = "default_speedwalk_directions")]
+    pub speedwalk_directions: String,
+    /// How often, in milliseconds, a `GlobalTick` event is emitted for scripts. Zero disables
+    /// the event.
+    #[serde(default = "default_tick_interval_ms")]
+    pub tick_interval_ms: u64,
+    /// Whether scripts can ring the terminal bell and show desktop notifications.
+    #[serde(default = "default_notifications_enabled")]
+    pub notifications_enabled: bool,
+    /// How screen clearing and cursor movement sequences in MUD output are handled. See
+    /// `ClearScreen`.
+    #[serde(default)]
+    pub handle_clear_screen: ClearScreen,
+}
+
+fn default_slow_callback_threshold_ms() -> u64 {
+    5_000
+}
+
+fn default_scroll_bottom_on_send() -> bool {
+    true
+}
+
+fn default_history_size() -> usize {
+    1_000
+}
+
+fn default_max_buffer_lines() -> usize {
+    50_000
+}
+
+fn default_speedwalk_directions() -> String {
+    "nsewud".to_string()
+}
+
+fn default_tick_interval_ms() -> u64 {
+    1_000
+}
+
+fn default_notifications_enabled() -> bool {
+    true
+}
+
+fn default_timestamp_format() -> String {
+    "%H:%M:%S".to_string()
 }
 
 impl Config {
@@ -171,6 +359,86 @@ impl Config {
                 )));
             }
 
+            if mud.ca_file.is_some() && mud.pinned_cert.is_some() {
+                return Err(ConfigError::InvalidMud(format!(
+                    "MUD {:?} ca_file and pinned_cert can't both be set",
+                    mud.name
+                )));
+            }
+
+            // Without verification enabled the certificates would be silently ignored.
+            if (mud.ca_file.is_some() || mud.pinned_cert.is_some())
+                && !matches!(mud.tls, Tls::Enabled)
+            {
+                return Err(ConfigError::InvalidMud(format!(
+                    "MUD {:?} ca_file and pinned_cert require tls to be \"Enabled\"",
+                    mud.name
+                )));
+            }
+
+            for path in [&mud.ca_file, &mud.pinned_cert].into_iter().flatten() {
+                stream::load_certs(path)
+                    .map_err(|e| ConfigError::InvalidMud(format!("MUD {:?} {e}", mud.name)))?;
+            }
+
+            if let Some(name) = mud
+                .charset
+                .iter()
+                .find(|name| Encoding::from_name(name).is_none())
+            {
+                return Err(ConfigError::InvalidMud(format!(
+                    "MUD {:?} charset {name:?} is not supported",
+                    mud.name
+                )));
+            }
+
+            if let Some(name) = mud.terminal_types.iter().find(|name| {
+                name.is_empty() || !name.chars().all(|c| c.is_ascii_graphic() || c == ' ')
+            }) {
+                return Err(ConfigError::InvalidMud(format!(
+                    "MUD {:?} terminal type {name:?} must be non-empty printable ASCII",
+                    mud.name
+                )));
+            }
+
+            if let Some(Err(err)) = mud.banner_end.as_deref().map(Regex::new) {
+                return Err(ConfigError::InvalidMud(format!(
+                    "MUD {:?} banner_end {err}",
+                    mud.name
+                )));
+            }
+
+            if mud.client_cert.is_some() != mud.client_key.is_some() {
+                return Err(ConfigError::InvalidMud(format!(
+                    "MUD {:?} client_cert and client_key must be set together",
+                    mud.name
+                )));
+            }
+
+            if mud.client_cert.is_some() && !matches!(mud.tls, Tls::Enabled) {
+                return Err(ConfigError::InvalidMud(format!(
+                    "MUD {:?} client_cert and client_key require tls to be \"Enabled\"",
+                    mud.name
+                )));
+            }
+
+            if mud
+                .proxy
+                .as_ref()
+                .is_some_and(|proxy| proxy.host.is_empty())
+            {
+                return Err(ConfigError::InvalidMud(format!(
+                    "MUD {:?} proxy host is empty",
+                    mud.name
+                )));
+            }
+
+            for style in [&mud.echo_style, &mud.scripted_echo_style] {
+                style
+                    .color()
+                    .map_err(|e| ConfigError::InvalidMud(format!("MUD {:?} {e}", mud.name)))?;
+            }
+
             if matches!(mud.tls, Tls::Enabled) {
                 pki_types::ServerName::try_from(mud.host.as_str()).map_err(|e| {
                     ConfigError::InvalidMud(format!(
@@ -233,4 +501,32 @@ pub fn edit_mud(name: &str, key: &str, v: impl Into<Value> + Debug) -> Result<()
     Ok(())
 }
 
+/// Returns the keys explicitly set in the config file for the MUD with the given `name`.
+///
+/// Any MUD config fields not returned are using their default values.
+///
+/// # Errors
+///
+/// Returns an error if the config file can't be read, or if it contains invalid TOML content.
+pub fn mud_config_keys(name: &str) -> Result<Vec<String>> {
+    let config_file = config_file();
+    if !config_file.exists() {
+        return Ok(Vec::default());
+    }
+
+    let config_doc = fs::read_to_string(config_file)?
+        .parse::<DocumentMut>()
+        .map_err(|err| Error::Config(ConfigError::TomlEdit(err)))?;
+
+    Ok(config_doc
+        .get("muds")
+        .and_then(Item::as_array_of_tables)
+        .and_then(|muds| {
+            muds.iter()
+                .find(|mud| mud.get("name").and_then(Item::as_str) == Some(name))
+        })
+        .map(|mud| mud.iter().map(|(key, _)| key.to_string()).collect())
+        .unwrap_or_default())
+}
+
 const CONFIG: &str = include_str!("../../../.config/config.toml");
@@ -1,10 +1,12 @@
 mod config_file;
 mod keybindings;
 mod logging;
+mod secrets;
 
 pub use config_file::*;
 pub use keybindings::*;
 pub use logging::*;
+pub use secrets::*;
 
 use std::env;
 use std::fmt::Debug;
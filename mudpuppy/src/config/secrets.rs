@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+use crate::error::ConfigError;
+use crate::model::{split_commands, unescape_separator, InputLine};
+
+const KEYRING_PREFIX: &str = "{keyring:";
+const CREDENTIAL_PREFIX: &str = "{credential:";
+
+/// The keyring service that credentials are stored under.
+const CREDENTIAL_SERVICE: &str = "mudpuppy";
+
+/// A reference to a secret in the OS keyring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Secret<'a> {
+    /// `{keyring:SERVICE:ACCOUNT}`, a password stored by another tool.
+    Keyring { service: &'a str, account: &'a str },
+    /// `{credential:NAME}`, a credential saved with [`set_credential`].
+    Credential(&'a str),
+}
+
+impl Display for Secret<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Secret::Keyring { service, account } => write!(f, "keyring:{service}:{account}"),
+            Secret::Credential(name) => write!(f, "credential:{name}"),
+        }
+    }
+}
+
+/// The secrets referenced by a line of text, read from the OS keyring by [`read_secrets`].
+///
+/// Reading the keyring can block, so secrets are read up front and the line is expanded
+/// with [`Secrets::expand_commands`] once it's ready to send.
+#[derive(Debug, Default)]
+pub struct Secrets(HashMap<String, String>);
+
+impl Secrets {
+    /// Split `text` into commands on `separator`, like user input, and then replace each
+    /// `{keyring:SERVICE:ACCOUNT}` and `{credential:NAME}` reference in each command with
+    /// the secret it refers to. Splitting first means a secret containing the separator is
+    /// still sent whole.
+    ///
+    /// Commands containing a secret are sent like passwords, so they aren't echoed or kept in
+    /// the input history.
+    ///
+    /// # Errors
+    /// If a reference is malformed, or it wasn't read by [`read_secrets`].
+    pub fn expand_commands(
+        &self,
+        text: &str,
+        separator: Option<&str>,
+    ) -> Result<Vec<InputLine>, ConfigError> {
+        let commands = match separator {
+            Some(sep) => split_commands(text, sep)
+                .into_iter()
+                .map(|command| unescape_separator(command, sep))
+                .collect(),
+            None => vec![text.to_string()],
+        };
+        commands
+            .into_iter()
+            .map(|command| {
+                Ok(match self.expand(&command)? {
+                    Some(expanded) => InputLine::new(expanded, false, true),
+                    None => InputLine::new(command, true, true),
+                })
+            })
+            .collect()
+    }
+
+    fn expand(&self, text: &str) -> Result<Option<String>, ConfigError> {
+        expand_with(text, |secret| {
+            self.0
+                .get(&secret.to_string())
+                .cloned()
+                .ok_or_else(|| ConfigError::Secret(format!("secret {secret} wasn't read")))
+        })
+    }
+}
+
+/// Read each secret referenced by `text` from the OS keyring, so that secrets like
+/// passwords don't have to be kept in the config file. See [`Secrets::expand_commands`].
+///
+/// Returns `None` if `text` has no references.
+///
+/// `{credential:NAME}` references are looked up with [`credential`]. For
+/// `{keyring:SERVICE:ACCOUNT}` references the password stored for `SERVICE` and `ACCOUNT`
+/// is read from the OS keyring directly.
+///
+/// # Errors
+/// If a reference is malformed, or its secret can't be read, or mudpuppy was built without
+/// the `keyring` feature.
+pub fn read_secrets(text: &str) -> Result<Option<Secrets>, ConfigError> {
+    read_with(text, |secret| match secret {
+        Secret::Keyring { service, account } => keyring_password(service, account),
+        Secret::Credential(name) => credential(name)?
+            .ok_or_else(|| ConfigError::Secret(format!("no credential named {name:?}"))),
+    })
+}
+
+fn read_with(
+    text: &str,
+    lookup: impl Fn(Secret<'_>) -> Result<String, ConfigError>,
+) -> Result<Option<Secrets>, ConfigError> {
+    let mut secrets = HashMap::new();
+    let expanded = expand_with(text, |secret| {
+        let value = lookup(secret)?;
+        secrets.insert(secret.to_string(), value.clone());
+        Ok(value)
+    })?;
+    Ok(expanded.map(|_| Secrets(secrets)))
+}
+
+/// Returns the credential saved with [`set_credential`] under `name`, or `None` if there
+/// isn't one.
+///
+/// # Errors
+/// If the OS keyring can't be used, or mudpuppy was built without the `keyring` feature.
+pub fn credential(name: &str) -> Result<Option<String>, ConfigError> {
+    store::get(CREDENTIAL_SERVICE, credential_name(name)?)
+}
+
+/// Save `value` in the OS keyring as the credential `name`, replacing any previous value.
+///
+/// # Errors
+/// If the OS keyring can't be used, or mudpuppy was built without the `keyring` feature.
+pub fn set_credential(name: &str, value: &str) -> Result<(), ConfigError> {
+    store::set(CREDENTIAL_SERVICE, credential_name(name)?, value)
+}
+
+/// Remove the credential `name` from the OS keyring, returning false if there wasn't one.
+///
+/// # Errors
+/// If the OS keyring can't be used, or mudpuppy was built without the `keyring` feature.
+pub fn delete_credential(name: &str) -> Result<bool, ConfigError> {
+    store::delete(CREDENTIAL_SERVICE, credential_name(name)?)
+}
+
+fn credential_name(name: &str) -> Result<&str, ConfigError> {
+    match name.trim().is_empty() {
+        true => Err(ConfigError::Secret(
+            "credential name can't be empty".to_string(),
+        )),
+        false => Ok(name),
+    }
+}
+
+fn expand_with(
+    text: &str,
+    mut lookup: impl FnMut(Secret<'_>) -> Result<String, ConfigError>,
+) -> Result<Option<String>, ConfigError> {
+    let next_reference = |text: &str| {
+        [KEYRING_PREFIX, CREDENTIAL_PREFIX]
+            .into_iter()
+            .filter_map(|prefix| text.find(prefix).map(|pos| (pos, prefix)))
+            .min_by_key(|(pos, _)| *pos)
+    };
+
+    if next_reference(text).is_none() {
+        return Ok(None);
+    }
+
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some((pos, prefix)) = next_reference(rest) {
+        expanded.push_str(&rest[..pos]);
+        let (reference, after) = rest[pos + prefix.len()..]
+            .split_once('}')
+            .ok_or_else(|| ConfigError::Secret("unclosed secret reference".to_string()))?;
+        let secret = match prefix {
+            KEYRING_PREFIX => reference
+                .split_once(':')
+                .filter(|(service, account)| !service.is_empty() && !account.is_empty())
+                .map(|(service, account)| Secret::Keyring { service, account })
+                .ok_or_else(|| {
+                    ConfigError::Secret(format!(
+                        "keyring reference {reference:?} must be SERVICE:ACCOUNT"
+                    ))
+                })?,
+            _ => Secret::Credential(credential_name(reference)?),
+        };
+        expanded.push_str(&lookup(secret)?);
+        rest = after;
+    }
+    expanded.push_str(rest);
+    Ok(Some(expanded))
+}
+
+fn keyring_password(service: &str, account: &str) -> Result<String, ConfigError> {
+    store::get(service, account)?.ok_or_else(|| {
+        ConfigError::Secret(format!(
+            "no password in the keyring for service {service:?} and account {account:?}"
+        ))
+    })
+}
+
+#[cfg(feature = "keyring")]
+mod store {
+    use keyring::Entry;
+
+    use crate::error::ConfigError;
+
+    pub(super) fn get(service: &str, account: &str) -> Result<Option<String>, ConfigError> {
+        match entry(service, account)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(err) => Err(keyring_error(&err)),
+        }
+    }
+
+    pub(super) fn set(service: &str, account: &str, value: &str) -> Result<(), ConfigError> {
+        entry(service, account)?
+            .set_password(value)
+            .map_err(|err| keyring_error(&err))
+    }
+
+    pub(super) fn delete(service: &str, account: &str) -> Result<bool, ConfigError> {
+        match entry(service, account)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(err) => Err(keyring_error(&err)),
+        }
+    }
+
+    fn entry(service: &str, account: &str) -> Result<Entry, ConfigError> {
+        Entry::new(service, account).map_err(|err| keyring_error(&err))
+    }
+
+    fn keyring_error(err: &keyring::Error) -> ConfigError {
+        ConfigError::Secret(format!("OS keyring: {err}"))
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+mod store {
+    use crate::error::ConfigError;
+
+    pub(super) fn get(_: &str, _: &str) -> Result<Option<String>, ConfigError> {
+        Err(unsupported())
+    }
+
+    pub(super) fn set(_: &str, _: &str, _: &str) -> Result<(), ConfigError> {
+        Err(unsupported())
+    }
+
+    pub(super) fn delete(_: &str, _: &str) -> Result<bool, ConfigError> {
+        Err(unsupported())
+    }
+
+    fn unsupported() -> ConfigError {
+        ConfigError::Secret("mudpuppy was built without the keyring feature".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::input::EchoState;
+
+    fn lookup(secret: Secret<'_>) -> Result<String, ConfigError> {
+        match secret {
+            Secret::Keyring {
+                service: "dune",
+                account: "ayla",
+            } => Ok("hunter2".to_string()),
+            Secret::Credential("dune") => Ok("swordfish".to_string()),
+            Secret::Credential("split") => Ok("sword;;fish".to_string()),
+            _ => Err(ConfigError::Secret("missing".to_string())),
+        }
+    }
+
+    #[test]
+    fn expands_keyring_references() {
+        assert_eq!(expand_with("connect ayla", lookup).unwrap(), None);
+        assert_eq!(
+            expand_with("connect ayla {keyring:dune:ayla}", lookup).unwrap(),
+            Some("connect ayla hunter2".to_string())
+        );
+        assert_eq!(
+            expand_with("{keyring:dune:ayla}{keyring:dune:ayla}!", lookup).unwrap(),
+            Some("hunter2hunter2!".to_string())
+        );
+
+        assert!(expand_with("{keyring:dune:bob}", lookup).is_err());
+        assert!(expand_with("{keyring:dune}", lookup).is_err());
+        assert!(expand_with("{keyring::ayla}", lookup).is_err());
+        assert!(expand_with("{keyring:dune:ayla", lookup).is_err());
+    }
+
+    #[test]
+    fn expands_credential_references() {
+        assert_eq!(
+            expand_with("{credential:dune} {keyring:dune:ayla}", lookup).unwrap(),
+            Some("swordfish hunter2".to_string())
+        );
+        assert_eq!(
+            expand_with("{keyring:dune:ayla}/{credential:dune}", lookup).unwrap(),
+            Some("hunter2/swordfish".to_string())
+        );
+
+        assert!(expand_with("{credential:moria}", lookup).is_err());
+        assert!(expand_with("{credential:}", lookup).is_err());
+        assert!(expand_with("{credential: }", lookup).is_err());
+    }
+
+    #[test]
+    fn splits_commands_before_expanding() {
+        assert!(read_with("connect ayla", lookup).unwrap().is_none());
+
+        let text = "connect ayla {credential:split};;look";
+        let secrets = read_with(text, lookup).unwrap().unwrap();
+        let commands = secrets.expand_commands(text, Some(";;")).unwrap();
+        let sent = commands
+            .iter()
+            .map(|line| line.sent.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(sent, ["connect ayla sword;;fish", "look"]);
+        assert_eq!(commands[0].echo, EchoState::Password);
+        assert_eq!(commands[1].echo, EchoState::Enabled);
+
+        let commands = secrets.expand_commands(text, None).unwrap();
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].sent, "connect ayla sword;;fish;;look");
+
+        assert!(Secrets::default().expand_commands(text, None).is_err());
+    }
+}
@@ -37,6 +37,9 @@ pub enum Error {
     #[error("timer error: {0}")]
     Timer(#[from] TimerError),
 
+    #[error("line error: {0}")]
+    Line(#[from] LineError),
+
     #[error("layout missing required section named {0:?}")]
     LayoutMissing(String),
 
@@ -46,9 +49,15 @@ pub enum Error {
     #[error("invalid layout section")]
     BadLayout,
 
+    #[error("a select dialog needs at least one option")]
+    NoDialogOptions,
+
     #[error("gmcp error: {0}")]
     Gmcp(#[from] GmcpError),
 
+    #[error("msdp error: {0}")]
+    Msdp(#[from] MsdpError),
+
     #[error("python error: {error}\n{traceback}")]
     Python {
         #[source]
@@ -58,6 +67,48 @@ pub enum Error {
 
     #[error("unknown session: {0}")]
     UnknownSession(u32),
+
+    #[error("unknown buffer: {0}")]
+    UnknownBuffer(u32),
+
+    #[error("unknown gauge: {0}")]
+    UnknownGauge(u32),
+
+    #[error("invalid filter regex pattern: {0}")]
+    Filter(regex::Error),
+
+    #[error("invalid search regex pattern: {0}")]
+    Search(regex::Error),
+
+    #[error("invalid capture regex pattern: {0}")]
+    CapturePattern(regex::Error),
+
+    #[error("importing triggers: {0}")]
+    Import(String),
+
+    #[error("invalid substitution regex pattern: {0}")]
+    SubstitutionPattern(regex::Error),
+
+    #[error("a block capture is already in progress")]
+    CaptureInProgress,
+
+    #[error("invalid speedwalk: {0}")]
+    Speedwalk(String),
+
+    #[error("loading TLS certificate: {0}")]
+    Certificate(String),
+
+    #[error("proxy error: {0}")]
+    Proxy(String),
+
+    #[error("MCCP decompression error: {0}")]
+    Mccp(#[from] flate2::DecompressError),
+
+    #[error("MCCP decompression consumed or produced an invalid amount of data")]
+    MccpLength,
+
+    #[error("connection timed out after {0}s")]
+    ConnectTimeout(u64),
 }
 
 impl Error {
@@ -120,6 +171,9 @@ pub enum ConfigError {
     #[error("configuring logging: {0}")]
     Logging(String),
 
+    #[error("looking up secret: {0}")]
+    Secret(String),
+
     #[error("{0}")]
     Keybinding(#[from] KeyBindingError),
 }
@@ -162,6 +216,23 @@ pub enum AliasError {
 pub enum TimerError {
     #[error("unknown timer ID: {0}")]
     UnknownId(u32),
+
+    #[error("timer {0} is not running")]
+    NotRunning(u32),
+}
+
+#[derive(Debug, Error)]
+#[allow(clippy::module_name_repetitions)]
+pub enum LineError {
+    #[error("invalid color {0:?}")]
+    InvalidColor(String),
+
+    #[error("invalid range {start}..{end} for line of {len} characters")]
+    InvalidRange {
+        start: usize,
+        end: usize,
+        len: usize,
+    },
 }
 
 #[derive(Debug, Error)]
@@ -176,6 +247,19 @@ pub enum GmcpError {
     #[error("error encoding or decoding JSON GMCP data: {0}")]
     BadJson(#[from] serde_json::Error),
 
+    #[error("circular reference in GMCP data")]
+    CircularReference,
+
     #[error("GMCP is not negotiated as ready yet")]
     NotReady,
 }
+
+#[derive(Debug, Error)]
+#[allow(clippy::module_name_repetitions)]
+pub enum MsdpError {
+    #[error("bad MSDP subnegotiation data: {0}")]
+    BadData(String),
+
+    #[error("MSDP is not negotiated as ready yet")]
+    NotReady,
+}
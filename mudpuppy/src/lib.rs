@@ -6,6 +6,7 @@ pub mod error;
 pub mod idmap;
 pub mod model;
 pub mod net;
+pub mod notify;
 pub mod python;
 pub mod tui;
 
@@ -1,18 +1,25 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt::{self, Display, Formatter};
-use std::time::Duration;
+use std::net::IpAddr;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 use pyo3::{pyclass, pymethods, Py, PyAny, PyObject, PyRef, Python};
-use regex::Regex;
+use ratatui::style::Color;
+use regex::{Captures, Regex};
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString};
 use tokio::sync::watch;
 use tokio_util::bytes::Bytes;
 
 use crate::client::input::EchoState;
-use crate::error::{AliasError, Error, KeyBindingError, TriggerError};
+use crate::error::{AliasError, Error, KeyBindingError, LineError, TriggerError};
 use crate::idmap::{self};
 use crate::net::telnet;
+use crate::python;
 
 #[derive(Clone, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
 #[pyclass]
@@ -65,11 +72,85 @@ pub struct Mud {
     #[pyo3(get)]
     pub tls: Tls,
 
+    /// A PEM file of CA certificates trusted to verify the MUD's certificate, instead of the
+    /// built-in web PKI roots.
+    ///
+    /// Relative paths are resolved against the config directory.
+    #[serde(default)]
+    #[pyo3(get)]
+    pub ca_file: Option<PathBuf>,
+
+    /// A PEM file holding the exact certificate the MUD must present, e.g. a self-signed
+    /// certificate. Any other certificate is rejected.
+    ///
+    /// Relative paths are resolved against the config directory.
+    #[serde(default)]
+    #[pyo3(get)]
+    pub pinned_cert: Option<PathBuf>,
+
+    /// A PEM certificate chain presented to the MUD when TLS is used, for MUDs that require
+    /// client certificate authentication. Must be set together with `client_key`.
+    ///
+    /// Relative paths are resolved against the config directory.
+    #[serde(default)]
+    #[pyo3(get)]
+    pub client_cert: Option<PathBuf>,
+
+    /// The PEM private key for `client_cert`.
+    ///
+    /// Relative paths are resolved against the config directory.
+    #[serde(default)]
+    #[pyo3(get)]
+    pub client_key: Option<PathBuf>,
+
+    /// Which IP address family is used to connect. See `AddressFamily`.
+    ///
+    /// When a `proxy` is set this applies to the connection to the proxy, since the proxy
+    /// resolves and connects to the MUD's host itself.
+    #[serde(default)]
+    #[pyo3(get)]
+    pub address_family: AddressFamily,
+
+    /// A SOCKS5 proxy to connect through, if any. See `Proxy`.
+    #[serde(default)]
+    #[pyo3(get)]
+    pub proxy: Option<Proxy>,
+
+    /// How long to wait for a connection to be established, in seconds. This includes
+    /// resolving the host, connecting, and the TLS handshake. Zero disables the timeout.
+    #[serde(default = "default::connect_timeout")]
+    #[pyo3(get)]
+    pub connect_timeout: u64,
+
+    /// Whether to automatically reconnect when the connection is lost unexpectedly.
+    #[serde(default = "default::auto_reconnect")]
+    #[pyo3(get)]
+    pub auto_reconnect: bool,
+
+    /// The maximum number of automatic reconnect attempts in a row. Zero allows unlimited
+    /// attempts.
+    #[serde(default = "default::reconnect_max_attempts")]
+    #[pyo3(get)]
+    pub reconnect_max_attempts: u32,
+
     /// Whether TCP keepalives are configured.
     #[serde(default = "default::no_tcp_keepalive")]
     #[pyo3(get)]
     pub no_tcp_keepalive: bool,
 
+    /// The number of seconds without sending anything before an idle keepalive is sent, for
+    /// MUDs that disconnect idle players regardless of TCP keepalives. Zero disables idle
+    /// keepalives.
+    #[serde(default = "default::idle_keepalive")]
+    #[pyo3(get)]
+    pub idle_keepalive: u64,
+
+    /// The line sent as an idle keepalive, e.g. `""` for a bare newline. When unset a telnet
+    /// NOP command is sent instead.
+    #[serde(default = "default::idle_keepalive_line")]
+    #[pyo3(get)]
+    pub idle_keepalive_line: Option<String>,
+
     /// Whether to hold the most recent prompt line at the bottom of the output buffer.
     ///
     /// You may want to disable this if prompt detection is not working correctly, or if
@@ -78,11 +159,62 @@ pub struct Mud {
     #[pyo3(get)]
     pub hold_prompt: bool,
 
+    /// The number of recent prompts remembered for each session, along with when they were
+    /// received. `0` disables prompt history.
+    #[serde(default = "default::prompt_history_size")]
+    #[pyo3(get)]
+    pub prompt_history_size: usize,
+
+    /// Whether to negotiate the telnet EOR option and use it to detect prompts.
+    ///
+    /// You may want to disable this if the MUD sends EOR incorrectly. Prompts will be
+    /// detected with a timeout instead.
+    #[serde(default = "default::use_eor")]
+    #[pyo3(get)]
+    pub use_eor: bool,
+
+    /// A regex matched against each line of output after connecting. The output before the
+    /// first matching line is kept as the session's banner. Without a pattern the banner
+    /// ends when a password is sent.
+    #[serde(default)]
+    #[pyo3(get)]
+    pub banner_end: Option<String>,
+
+    /// The line ending sent after each line of input. See `LineEnding`.
+    #[serde(default)]
+    #[pyo3(get)]
+    pub line_ending: LineEnding,
+
+    /// The character sets accepted when the MUD negotiates one with the telnet CHARSET option,
+    /// most preferred first. Supported charsets are UTF-8, US-ASCII, ISO-8859-1 (Latin-1) and
+    /// Windows-1252.
+    ///
+    /// Output is decoded with the first charset until the MUD negotiates another.
+    #[serde(default = "default::charset")]
+    #[pyo3(get)]
+    pub charset: Vec<String>,
+
+    /// The terminal types reported, one per request, when the MUD asks with the telnet
+    /// TERMINAL-TYPE option. A bare `MTTS` is reported with mudpuppy's MTTS bitvector. An
+    /// empty list refuses the option.
+    #[serde(default = "default::terminal_types")]
+    #[pyo3(get)]
+    pub terminal_types: Vec<String>,
+
     /// Whether input sent to the MUD is echoed in the output buffer.
     #[serde(default = "default::echo_input")]
     #[pyo3(get)]
     pub echo_input: bool,
 
+    /// How input typed by the user is styled when echoed in the output buffer. See `EchoStyle`.
+    #[serde(default = "default::echo_style")]
+    pub echo_style: EchoStyle,
+
+    /// How input sent by scripts (e.g. triggers and aliases) is styled when echoed in the output
+    /// buffer. Unset fields fall back to `echo_style`.
+    #[serde(default = "default::scripted_echo_style")]
+    pub scripted_echo_style: EchoStyle,
+
     /// Whether output lines are wrapped when they would exceed the width of the output buffer.
     ///
     /// You may want to disable this if you prefer to see truncated, but accurately rendered,
@@ -95,6 +227,19 @@ pub struct Mud {
     #[serde(default = "default::debug_gmcp")]
     pub debug_gmcp: bool,
 
+    /// How received GMCP messages are formatted when `debug_gmcp` is enabled.
+    #[serde(default = "default::debug_gmcp_format")]
+    pub debug_gmcp_format: GmcpDebugFormat,
+
+    /// Whether output lines received in a single read are emitted together as one `Lines` event.
+    #[serde(default = "default::batch_line_events")]
+    pub batch_line_events: bool,
+
+    /// Whether the "split view" for scrolling output history leaves a live strip of new output
+    /// below it. When false the split view covers the whole output area.
+    #[serde(default = "default::split_scroll")]
+    pub split_scroll: bool,
+
     /// The percentage of the screen to use for the "split view" for scrolling output history.
     #[serde(default = "default::splitview_percentage")]
     pub splitview_percentage: u16,
@@ -112,6 +257,70 @@ pub struct Mud {
     /// The command separator to use when sending multiple commands in a single message.
     #[serde(default = "default::command_separator")]
     pub command_separator: Option<String>,
+
+    /// The number of milliseconds to wait between sending commands, e.g. the steps of a
+    /// speedwalk. Commands sent in the meantime are queued. Zero sends commands right away.
+    #[serde(default)]
+    #[pyo3(get)]
+    pub command_interval: u64,
+
+    /// What to do when enter is pressed with no input. See `EmptyEnter`.
+    #[serde(default = "default::empty_enter")]
+    pub empty_enter: EmptyEnter,
+
+    /// What to do with text pasted into the input. See `PasteMode`.
+    #[serde(default = "default::paste_mode")]
+    pub paste_mode: PasteMode,
+
+    /// A file to write connection lifecycle events to, as JSON lines.
+    ///
+    /// Relative paths are resolved against the data directory. Disabled when unset.
+    #[serde(default = "default::connection_log")]
+    pub connection_log: Option<PathBuf>,
+
+    /// Whether to start logging the session's output to a file when it connects.
+    #[serde(default = "default::auto_log")]
+    #[pyo3(get)]
+    pub auto_log: bool,
+
+    /// The format of session logs. See `LogFormat`.
+    #[serde(default = "default::log_format")]
+    #[pyo3(get)]
+    pub log_format: LogFormat,
+
+    /// Whether to save the session's input history when it disconnects or closes, and load it
+    /// for new sessions.
+    #[serde(default = "default::persist_history")]
+    #[pyo3(get)]
+    pub persist_history: bool,
+
+    /// The name of the character played, if any. Persisted input history is kept separately
+    /// for each character of a MUD.
+    #[serde(default)]
+    #[pyo3(get)]
+    pub character: Option<String>,
+
+    /// Commands sent automatically after the session connects.
+    #[serde(default = "default::on_connect_commands")]
+    #[pyo3(get)]
+    pub on_connect_commands: Vec<String>,
+
+    /// Commands sent instead of `on_connect_commands` when the session reconnects. When unset,
+    /// `on_connect_commands` are sent for every connection.
+    #[serde(default = "default::on_reconnect_commands")]
+    #[pyo3(get)]
+    pub on_reconnect_commands: Option<Vec<String>>,
+
+    /// How long to wait before sending each of the on connect commands, in milliseconds.
+    #[serde(default = "default::on_connect_delay_ms")]
+    #[pyo3(get)]
+    pub on_connect_delay_ms: u64,
+
+    /// The name of a credential saved in the OS keyring, substituted for `{credential}` in the
+    /// on connect commands. Only the name is kept in the config, never the secret itself.
+    #[serde(default)]
+    #[pyo3(get)]
+    pub credential: Option<String>,
 }
 
 impl Display for Mud {
@@ -120,6 +329,44 @@ impl Display for Mud {
     }
 }
 
+/// Styling for input lines echoed in the output buffer.
+///
+/// Unset fields use the default appearance.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EchoStyle {
+    /// Text displayed before the echoed input, in place of the default " ↳ " icon.
+    pub prefix: Option<String>,
+
+    /// The colour of the echoed input. Either a colour name (e.g. "lightgreen"), an indexed
+    /// colour (e.g. "42"), or a hex RGB value (e.g. "#ff8800").
+    pub color: Option<String>,
+}
+
+impl EchoStyle {
+    /// Returns the parsed `color`, if one is set.
+    ///
+    /// # Errors
+    /// If the `color` isn't a valid colour description.
+    pub fn color(&self) -> Result<Option<Color>, String> {
+        self.color
+            .as_deref()
+            .map(|color| {
+                Color::from_str(color).map_err(|_| format!("invalid echo color {color:?}"))
+            })
+            .transpose()
+    }
+
+    /// Returns a copy of the style with any unset fields taken from `fallback`.
+    #[must_use]
+    pub fn or(&self, fallback: &EchoStyle) -> EchoStyle {
+        EchoStyle {
+            prefix: self.prefix.clone().or_else(|| fallback.prefix.clone()),
+            color: self.color.clone().or_else(|| fallback.color.clone()),
+        }
+    }
+}
+
 /// Possible TLS states for a `MUD`.
 #[derive(
     Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize,
@@ -133,6 +380,187 @@ pub enum Tls {
     InsecureSkipVerify,
 }
 
+/// A SOCKS5 proxy used to connect to a `MUD`.
+#[derive(Clone, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+#[pyclass]
+#[allow(clippy::unsafe_derive_deserialize)] // No constructor invariants to uphold.
+pub struct Proxy {
+    #[pyo3(get)]
+    pub host: String,
+
+    #[pyo3(get)]
+    pub port: u16,
+
+    /// The username to authenticate with, if the proxy requires authentication.
+    #[serde(default)]
+    #[pyo3(get)]
+    pub username: Option<String>,
+
+    /// The password to authenticate with. Not exposed to Python, and never serialized so
+    /// it can't leak into dumps of the resolved settings.
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+}
+
+impl fmt::Debug for Proxy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // Redact the password so it can't end up in trace logs.
+        f.debug_struct("Proxy")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+/// Possible IP address families to use when connecting to a `MUD`.
+#[derive(
+    Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize,
+)]
+#[pyclass(eq, eq_int)]
+#[allow(clippy::unsafe_derive_deserialize)] // No constructor invariants to uphold.
+pub enum AddressFamily {
+    /// IPv4 and IPv6 addresses are both tried, preferring whichever connects first.
+    #[default]
+    Auto,
+    /// Only IPv4 addresses are used.
+    V4,
+    /// Only IPv6 addresses are used.
+    V6,
+}
+
+impl AddressFamily {
+    /// Returns true if `ip` may be used for connections with this address family.
+    #[must_use]
+    pub fn allows(self, ip: IpAddr) -> bool {
+        match self {
+            AddressFamily::Auto => true,
+            AddressFamily::V4 => ip.is_ipv4(),
+            AddressFamily::V6 => ip.is_ipv6(),
+        }
+    }
+}
+
+impl Display for AddressFamily {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressFamily::Auto => write!(f, "IPv4 or IPv6"),
+            AddressFamily::V4 => write!(f, "IPv4"),
+            AddressFamily::V6 => write!(f, "IPv6"),
+        }
+    }
+}
+
+/// Possible line endings sent after each line of input to a `MUD`.
+#[derive(
+    Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize,
+)]
+#[pyclass(eq, eq_int)]
+#[allow(clippy::unsafe_derive_deserialize)] // No constructor invariants to uphold.
+pub enum LineEnding {
+    /// A carriage return and line feed (`\r\n`), as the telnet spec requires.
+    #[default]
+    Crlf,
+    /// A bare line feed (`\n`).
+    Lf,
+    /// A bare carriage return (`\r`).
+    Cr,
+}
+
+impl LineEnding {
+    /// Returns the bytes sent to end a line.
+    #[must_use]
+    pub fn as_bytes(self) -> &'static [u8] {
+        match self {
+            LineEnding::Crlf => b"\r\n",
+            LineEnding::Lf => b"\n",
+            LineEnding::Cr => b"\r",
+        }
+    }
+}
+
+impl Display for LineEnding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LineEnding::Crlf => write!(f, "CRLF"),
+            LineEnding::Lf => write!(f, "LF"),
+            LineEnding::Cr => write!(f, "CR"),
+        }
+    }
+}
+
+/// Possible formats for session logs.
+#[derive(
+    Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize,
+)]
+#[pyclass(eq, eq_int)]
+#[allow(clippy::unsafe_derive_deserialize)] // No constructor invariants to uphold.
+pub enum LogFormat {
+    /// Plain text, with ANSI colours removed.
+    #[default]
+    Plain,
+    /// Text as it was received, including ANSI colours.
+    Raw,
+}
+
+/// Possible formats for GMCP messages displayed in the output buffer with `debug_gmcp`.
+#[derive(
+    Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize,
+)]
+pub enum GmcpDebugFormat {
+    /// The package name and JSON payload exactly as received.
+    #[default]
+    Raw,
+    /// The package name, followed by the JSON payload indented over multiple lines and
+    /// highlighted with colour. Large payloads are truncated.
+    Pretty,
+}
+
+/// Possible behaviours for pressing enter with no input for a `MUD`.
+#[derive(
+    Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize,
+)]
+pub enum EmptyEnter {
+    /// Send an empty line to the MUD.
+    #[default]
+    SendBlank,
+    /// Don't send anything.
+    Ignore,
+    /// Send the previously sent input again.
+    RepeatLast,
+}
+
+/// Possible ways to handle text pasted into the input for a `MUD`.
+#[derive(
+    Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize,
+)]
+pub enum PasteMode {
+    /// Insert the pasted text into the input. Line breaks are replaced with the MUD's command
+    /// separator, or spaces if it has none.
+    #[default]
+    Input,
+    /// Send each pasted line as if enter was pressed after it. Text after the last line break
+    /// is left in the input.
+    SendLines,
+}
+
+/// Possible ways to handle ANSI sequences that clear the screen or move the cursor in output
+/// from a `MUD`. These assume a fixed size terminal and garble a scrolling output buffer.
+#[derive(
+    Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize,
+)]
+pub enum ClearScreen {
+    /// Remove the sequences from the output.
+    Strip,
+    /// Remove the sequences from the output, and add blank lines to push the existing output
+    /// out of view when the screen is cleared.
+    Blank,
+    /// Leave the sequences in the output untouched.
+    #[default]
+    Passthrough,
+}
+
 #[derive(
     Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd, EnumString, Display,
 )]
@@ -373,14 +801,21 @@ pub enum Shortcut {
 
     ToggleLineWrap,
     ToggleInputEcho,
+    ToggleFilter,
+    ToggleSearch,
 
     HistoryNext,
     HistoryPrevious,
 
+    InputUndo,
+    InputRedo,
+
     ScrollUp,
     ScrollDown,
     ScrollTop,
     ScrollBottom,
+    ScrollPromptPrev,
+    ScrollPromptNext,
 }
 
 #[pymethods]
@@ -409,9 +844,19 @@ pub struct MudLine {
 }
 
 impl MudLine {
-    pub fn to_str(&self) -> Cow<str> {
+    pub fn to_str(&self) -> Cow<'_, str> {
         String::from_utf8_lossy(&self.raw)
     }
+
+    /// Returns the text patterns are matched against: the raw line including ANSI escape
+    /// sequences when `ansi` is true, otherwise the visible text with ANSI stripped.
+    #[must_use]
+    pub fn match_text(&self, ansi: bool) -> Cow<'_, str> {
+        match ansi {
+            true => self.to_str(),
+            false => Cow::Owned(self.stripped()),
+        }
+    }
 }
 
 impl Display for MudLine {
@@ -446,13 +891,95 @@ impl MudLine {
     }
 
     pub fn stripped(&self) -> String {
-        strip_ansi_escapes::strip_str(self.to_str())
+        strip_ansi_with_offsets(&self.to_str()).0
     }
 
     pub fn set(&mut self, value: &str) {
         self.raw = Bytes::copy_from_slice(value.as_bytes());
     }
 
+    /// Colour the visible characters from `start` up to, but not including, `end`.
+    ///
+    /// Offsets are character offsets in the `stripped()` text. Colours are a colour name
+    /// (e.g. "lightgreen"), an indexed colour (e.g. "42"), or a hex RGB value (e.g. "#ff8800").
+    /// Existing colours inside the range are overridden, and the colours in effect after the
+    /// range are restored.
+    ///
+    /// # Errors
+    /// If a colour is invalid, or the range is out of bounds.
+    #[pyo3(signature = (start, end, fg=None, bg=None))]
+    pub fn color_range(
+        &mut self,
+        start: usize,
+        end: usize,
+        fg: Option<&str>,
+        bg: Option<&str>,
+    ) -> Result<(), Error> {
+        let mut params = Vec::new();
+        for (color, background) in [(fg, false), (bg, true)] {
+            if let Some(color) = color {
+                params.push(sgr_color(parse_color(color)?, background));
+            }
+        }
+
+        let text = self.to_str().into_owned();
+        let (stripped, offsets) = strip_ansi_with_offsets(&text);
+        let len = stripped.chars().count();
+        if start > end || end > len {
+            return Err(LineError::InvalidRange { start, end, len }.into());
+        }
+        if start == end || params.is_empty() {
+            return Ok(());
+        }
+
+        let char_offset = |pos: usize| {
+            stripped
+                .char_indices()
+                .nth(pos)
+                .map_or(stripped.len(), |(i, _)| i)
+        };
+        // Map the last visible byte rather than the end, so escape sequences following the
+        // range aren't included in it.
+        let raw_start = offsets[char_offset(start)];
+        let raw_end = offsets[char_offset(end) - 1] + 1;
+        let color = format!("\x1b[{}m", params.join(";"));
+
+        let sgrs = sgr_ranges(&text);
+        let mut colored = String::with_capacity(text.len() + color.len() * 2);
+        colored.push_str(&text[..raw_start]);
+        colored.push_str(&color);
+        // Re-apply the colour after each SGR sequence inside the range.
+        let mut pos = raw_start;
+        for sgr in sgrs
+            .iter()
+            .filter(|sgr| (raw_start..raw_end).contains(&sgr.start))
+        {
+            colored.push_str(&text[pos..sgr.end]);
+            colored.push_str(&color);
+            pos = sgr.end;
+        }
+        colored.push_str(&text[pos..raw_end]);
+
+        // Restore the original attributes: reset, and replay each SGR sequence since the
+        // last reset before the end of the range.
+        colored.push_str("\x1b[0m");
+        let preceding = sgrs
+            .iter()
+            .filter(|sgr| sgr.end <= raw_end)
+            .collect::<Vec<_>>();
+        let since_reset = preceding
+            .iter()
+            .rposition(|sgr| sgr_is_reset(&text[(*sgr).clone()]))
+            .map_or(0, |pos| pos + 1);
+        for sgr in &preceding[since_reset..] {
+            colored.push_str(&text[(*sgr).clone()]);
+        }
+        colored.push_str(&text[raw_end..]);
+
+        self.set(&colored);
+        Ok(())
+    }
+
     fn __repr__(&self) -> String {
         format!("{self:?}")
     }
@@ -475,6 +1002,66 @@ pub struct InputLine {
     pub scripted: bool,
 }
 
+/// Split `text` into commands on each `sep` that isn't escaped by a preceding backslash.
+///
+/// Backslashes right before a separator pair up first, so `\\` is a literal backslash:
+/// `a\\;b` is split into `a\` and `b`, while `a\\\;b` isn't split. Backslashes elsewhere
+/// are left alone.
+///
+/// Escaped separators are kept in the commands, backslash and all, so that the commands
+/// can be split again (e.g. after alias expansion). Use [`unescape_separator`] before
+/// sending a command.
+pub(crate) fn split_commands<'a>(text: &'a str, sep: &str) -> Vec<&'a str> {
+    if sep.is_empty() {
+        return vec![text];
+    }
+    let mut commands = Vec::new();
+    let mut start = 0;
+    for (pos, _) in text.match_indices(sep) {
+        if pos < start {
+            continue;
+        }
+        let slashes = trailing_backslashes(&text[start..pos]);
+        if slashes % 2 == 1 {
+            continue;
+        }
+        // Drop the second backslash of each escaped pair.
+        commands.push(&text[start..pos - slashes / 2]);
+        start = pos + sep.len();
+    }
+    commands.push(&text[start..]);
+    commands
+}
+
+/// Replace each backslash escaped `sep` in `text` with a literal `sep`, along with each
+/// escaped pair of backslashes before it.
+pub(crate) fn unescape_separator(text: &str, sep: &str) -> String {
+    if sep.is_empty() {
+        return text.to_string();
+    }
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find(sep) {
+        let before = &rest[..pos];
+        let slashes = trailing_backslashes(before);
+        match slashes % 2 {
+            1 => {
+                unescaped.push_str(&before[..pos - slashes]);
+                unescaped.push_str(&"\\".repeat(slashes / 2));
+            }
+            _ => unescaped.push_str(before),
+        }
+        unescaped.push_str(sep);
+        rest = &rest[pos + sep.len()..];
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+fn trailing_backslashes(text: &str) -> usize {
+    text.len() - text.trim_end_matches('\\').len()
+}
+
 #[pymethods]
 impl InputLine {
     #[new]
@@ -495,9 +1082,20 @@ impl InputLine {
         self.sent.trim().is_empty()
     }
 
+    /// Returns a copy of the line to record in place of a password, with the content
+    /// replaced by a fixed mask that doesn't reveal its length.
+    pub(crate) fn redacted(&self) -> Self {
+        Self {
+            sent: "********".to_string(),
+            original: None,
+            echo: EchoState::Password,
+            scripted: self.scripted,
+        }
+    }
+
     pub(crate) fn split(&self, sep: &str) -> Vec<Self> {
-        self.sent
-            .split(sep)
+        split_commands(&self.sent, sep)
+            .into_iter()
             .filter_map(|fragment| {
                 if fragment.trim().is_empty() {
                     return None;
@@ -679,12 +1277,15 @@ impl idmap::Identifiable for Trigger {
 
 #[derive(Debug, Clone)]
 #[pyclass]
+#[allow(clippy::struct_excessive_bools)] // Independent flags, mirrored as Python attributes.
 pub struct TriggerConfig {
     #[pyo3(get)]
     pub name: String,
 
+    /// Whether the pattern is matched against the raw line, including ANSI escape sequences,
+    /// instead of the visible text with ANSI stripped.
     #[pyo3(get, set)]
-    pub strip_ansi: bool,
+    pub match_ansi: bool,
 
     #[pyo3(get, set)]
     pub prompt: bool,
@@ -692,93 +1293,295 @@ pub struct TriggerConfig {
     #[pyo3(get, set)]
     pub gag: bool,
 
-    #[pyo3(get, set)]
+    #[pyo3(get)]
     pub callback: Option<Py<PyAny>>, // Must be async. No return.
 
-    #[pyo3(get, set)]
+    #[pyo3(get)]
     pub highlight: Option<Py<PyAny>>, // Must _not_ be async. Must return MudLine.
 
     #[pyo3(get, set)]
     pub expansion: Option<String>, // TODO(XXX): Rename to reaction?
 
+    /// Triggers with a higher priority are evaluated first. Ties are evaluated in the order
+    /// the triggers were created.
+    #[pyo3(get, set)]
+    pub priority: i32,
+
+    /// Whether a match stops lower priority triggers from being evaluated for the line.
+    #[pyo3(get, set)]
+    pub stop: bool,
+
+    /// Whether the callback is invoked once for each non-overlapping match in the line,
+    /// instead of only for the first match.
+    #[pyo3(get, set)]
+    pub multi_match: bool,
+
+    /// Foreground and background colours applied to the matched text, without a Python
+    /// highlight callback. See [`MudLine::color_range`] for the colour syntax.
+    #[pyo3(get, set)]
+    pub recolor: Option<(Option<String>, Option<String>)>,
+
+    /// Whether a match marks the session's tab as having important activity, e.g. for tells,
+    /// so it stands out from ordinary new output.
+    #[pyo3(get, set)]
+    pub important: bool,
+
+    /// A label for enabling and disabling related triggers and aliases together, e.g.
+    /// `"combat"`.
+    #[pyo3(get, set)]
+    pub group: Option<String>,
+
     #[pyo3(get)]
     pub hit_count: u64,
 
     pub regex: Regex,
+
+    /// Whether `callback` accepts the named match groups. Checked when it's set, since
+    /// inspecting its signature for every match is slow.
+    pub callback_named_groups: bool,
+
+    /// Whether `highlight` accepts the named match groups.
+    pub highlight_named_groups: bool,
 }
 
 impl TriggerConfig {
-    /// Check if the input matches the trigger pattern, and return matches if it does.
+    /// Construct a trigger configuration for `pattern` with every option at its default. Set
+    /// other options with struct update syntax.
     ///
-    /// # Panics
-    /// TODO: It shouldn't...
+    /// # Errors
     ///
-    // TODO(XXX): Tidy, remove unwrap.
+    /// If the regex pattern can't be compiled.
+    pub fn with_pattern(pattern: &str, name: String) -> Result<Self, Error> {
+        Self::new(
+            pattern, name, false, None, false, false, None, None, None, 0, false, false, None,
+            false, None,
+        )
+    }
+
+    /// Check if the input matches the trigger pattern, and return the match groups if it does.
+    #[must_use]
+    pub fn matches(&self, line: &MudLine) -> Option<MatchGroups> {
+        self.match_line(line).map(|(_, groups)| groups)
+    }
+
+    /// Check if the input matches the trigger pattern, and return the match groups of each
+    /// non-overlapping match, in order. Empty if there's no match.
     #[must_use]
-    pub fn matches(&self, line: &MudLine) -> (bool, Option<Vec<String>>) {
+    pub fn all_matches(&self, line: &MudLine) -> Vec<MatchGroups> {
         if !line.prompt && self.prompt {
-            return (false, None);
+            return Vec::default();
         }
-        // TODO(XXX): Cleanup with MSRV 1.81 lifetime coolness
-        let stripped_haystack;
-        let haystack;
-        let line = if self.strip_ansi {
-            stripped_haystack = line.stripped();
-            stripped_haystack.as_str()
-        } else {
-            haystack = line.to_str();
-            &haystack
-        };
-        self.regex.captures(line).map_or((false, None), |matches| {
-            let captures = matches
-                .iter()
-                .skip(1)
-                .map(|m| m.unwrap().as_str().to_owned())
-                .collect();
-            (true, Some(captures))
-        })
+        let text = line.match_text(self.match_ansi);
+        let (text, _) = Self::match_input(line, &text);
+        self.regex
+            .captures_iter(text)
+            .map(|captures| MatchGroups::new(&self.regex, &captures))
+            .collect()
     }
-}
 
-#[pymethods]
-impl TriggerConfig {
-    /// Construct a new trigger configuration for a given regex pattern.
+    // Match the pattern against the line, returning the text that was matched against along
+    // with the match groups.
+    fn match_line(&self, line: &MudLine) -> Option<(String, MatchGroups)> {
+        if !line.prompt && self.prompt {
+            return None;
+        }
+        let text = line.match_text(self.match_ansi);
+        let (text, _) = Self::match_input(line, &text);
+        self.regex
+            .captures(text)
+            .map(|captures| (text.to_owned(), MatchGroups::new(&self.regex, &captures)))
+    }
+
+    /// Apply the `recolor` colours to each match of the pattern in `line`.
     ///
     /// # Errors
-    ///
-    /// If the regex pattern can't be compiled.
-    #[new]
-    #[pyo3(signature = (pattern, name, *, strip_ansi=false, prompt=false, gag=false, callback=None, highlight=None, expansion=None))]
-    #[allow(clippy::too_many_arguments)]
-    pub fn new(
-        pattern: &str,
+    /// If a `recolor` colour is invalid.
+    pub fn recolor(&self, line: &mut MudLine) -> Result<(), Error> {
+        let Some((fg, bg)) = &self.recolor else {
+            return Ok(());
+        };
+
+        let text = line.to_str().into_owned();
+        let (stripped, offsets) = strip_ansi_with_offsets(&text);
+        let haystack = match self.match_ansi {
+            true => text.as_str(),
+            false => stripped.as_str(),
+        };
+        let (haystack, skipped) = Self::match_input(line, haystack);
+        let raw_offset = |pos: usize| match self.match_ansi {
+            true => pos,
+            false => offsets[pos],
+        };
+        // Coloring only adds escape sequences, so visible character offsets are unchanged
+        // by each range colored.
+        let char_offset = |raw: usize| {
+            stripped
+                .char_indices()
+                .take_while(|(i, _)| offsets[*i] < raw)
+                .count()
+        };
+
+        for m in self.regex.find_iter(haystack).filter(|m| !m.is_empty()) {
+            let raw_start = raw_offset(skipped + m.start());
+            let raw_end = raw_offset(skipped + m.end() - 1) + 1;
+            line.color_range(
+                char_offset(raw_start),
+                char_offset(raw_end),
+                fg.as_deref(),
+                bg.as_deref(),
+            )?;
+        }
+        Ok(())
+    }
+
+    // Prompts are flushed from whatever was buffered, and may carry line ending bytes left
+    // over from surrounding output. Ignore them so anchored patterns match. Returns the
+    // text to match and the number of bytes skipped from the start.
+    fn match_input<'a>(line: &MudLine, text: &'a str) -> (&'a str, usize) {
+        if !line.prompt {
+            return (text, 0);
+        }
+        let trimmed = text.trim_start_matches(['\r', '\n']);
+        let skipped = text.len() - trimmed.len();
+        (trimmed.trim_end_matches(['\r', '\n']), skipped)
+    }
+}
+
+#[pymethods]
+impl TriggerConfig {
+    /// Construct a new trigger configuration for a given regex pattern.
+    ///
+    /// # Errors
+    ///
+    /// If the regex pattern can't be compiled.
+    #[new]
+    #[pyo3(signature = (pattern, name, *, match_ansi=false, strip_ansi=None, prompt=false, gag=false, callback=None, highlight=None, expansion=None, priority=0, stop=false, multi_match=false, recolor=None, important=false, group=None))]
+    #[allow(clippy::too_many_arguments, clippy::fn_params_excessive_bools)]
+    pub fn new(
+        pattern: &str,
         name: String,
-        strip_ansi: bool,
+        match_ansi: bool,
+        strip_ansi: Option<bool>,
         prompt: bool,
         gag: bool,
         callback: Option<PyObject>,
         highlight: Option<PyObject>,
         expansion: Option<String>,
+        priority: i32,
+        stop: bool,
+        multi_match: bool,
+        recolor: Option<(Option<String>, Option<String>)>,
+        important: bool,
+        group: Option<String>,
     ) -> Result<Self, Error> {
         let regex = Regex::new(pattern).map_err(TriggerError::Pattern)?;
+        if let Some((fg, bg)) = &recolor {
+            for color in fg.iter().chain(bg) {
+                parse_color(color)?;
+            }
+        }
         Ok(Self {
             name,
-            strip_ansi,
+            // `strip_ansi` is the older, inverted, spelling of `match_ansi`.
+            match_ansi: strip_ansi.map_or(match_ansi, |strip_ansi| !strip_ansi),
             prompt,
             gag,
+            callback_named_groups: named_groups(callback.as_ref(), 3),
+            highlight_named_groups: named_groups(highlight.as_ref(), 1),
             callback,
             highlight,
             expansion,
+            priority,
+            stop,
+            multi_match,
+            recolor,
+            important,
+            group,
             hit_count: 0,
             regex,
         })
     }
 
+    #[setter]
+    fn set_callback(&mut self, callback: Option<Py<PyAny>>) {
+        self.callback_named_groups = named_groups(callback.as_ref(), 3);
+        self.callback = callback;
+    }
+
+    #[setter]
+    fn set_highlight(&mut self, highlight: Option<Py<PyAny>>) {
+        self.highlight_named_groups = named_groups(highlight.as_ref(), 1);
+        self.highlight = highlight;
+    }
+
     #[must_use]
     pub fn pattern(&self) -> &str {
         self.regex.as_str()
     }
 
+    /// Returns the `(start, end)` byte offsets of each capture group in the line's text if
+    /// the trigger matches it, or `None` if it doesn't.
+    ///
+    /// Offsets always refer to the line's full UTF-8 text, including any ANSI escape sequences,
+    /// even when the pattern was matched against the stripped text. Groups that didn't
+    /// participate in the match are `None`.
+    #[must_use]
+    pub fn spans(&self, line: &MudLine) -> Option<Vec<Option<(usize, usize)>>> {
+        if !line.prompt && self.prompt {
+            return None;
+        }
+
+        let (text, offsets) = if self.match_ansi {
+            (line.to_str(), None)
+        } else {
+            let (text, offsets) = strip_ansi_with_offsets(&line.to_str());
+            (Cow::Owned(text), Some(offsets))
+        };
+        let (text, skipped) = Self::match_input(line, &text);
+        let raw_offset = |pos: usize| offsets.as_ref().map_or(pos, |offsets| offsets[pos]);
+
+        let captures = self.regex.captures(text)?;
+        Some(
+            captures
+                .iter()
+                .skip(1)
+                .map(|m| {
+                    m.map(|m| {
+                        let (start, end) = (skipped + m.start(), skipped + m.end());
+                        // Map the last matched byte rather than the end, so that escape
+                        // sequences following the group aren't included in its span.
+                        match start == end {
+                            true => (raw_offset(start), raw_offset(start)),
+                            false => (raw_offset(start), raw_offset(end - 1) + 1),
+                        }
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// Test the pattern against `sample` as if it was a line received from the MUD, returning
+    /// the match if there is one. Prompt triggers treat the sample as a prompt.
+    ///
+    /// Unlike a real match the hit count isn't changed, and the callback, expansion and
+    /// highlight aren't used.
+    #[must_use]
+    pub fn test(&self, sample: &str) -> Option<PatternMatch> {
+        let mut line = MudLine::new(sample.as_bytes());
+        line.prompt = self.prompt;
+        self.match_line(&line).map(PatternMatch::from)
+    }
+
+    #[getter]
+    fn strip_ansi(&self) -> bool {
+        !self.match_ansi
+    }
+
+    #[setter]
+    fn set_strip_ansi(&mut self, strip_ansi: bool) {
+        self.match_ansi = !strip_ansi;
+    }
+
     fn __str__(&self) -> String {
         format!("{self}")
     }
@@ -794,6 +1597,130 @@ impl Display for TriggerConfig {
     }
 }
 
+// Returns the byte ranges of each SGR (colour and style) escape sequence in `text`.
+fn sgr_ranges(text: &str) -> Vec<Range<usize>> {
+    const ESC: u8 = 0x1B;
+
+    let bytes = text.as_bytes();
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i + 1 < bytes.len() {
+        if bytes[i] != ESC || bytes[i + 1] != b'[' {
+            i += 1;
+            continue;
+        }
+        let Some(len) = bytes[i + 2..]
+            .iter()
+            .position(|b| (0x40..=0x7E).contains(b))
+        else {
+            break;
+        };
+        let end = i + len + 3;
+        if bytes[end - 1] == b'm' {
+            ranges.push(i..end);
+        }
+        i = end;
+    }
+    ranges
+}
+
+// Returns true if the SGR sequence `sgr` starts by resetting all attributes.
+fn sgr_is_reset(sgr: &str) -> bool {
+    let params = &sgr[2..sgr.len() - 1];
+    let first = params.split([';', ':']).next().unwrap_or_default();
+    first.bytes().all(|b| b == b'0')
+}
+
+fn parse_color(color: &str) -> Result<Color, LineError> {
+    Color::from_str(color).map_err(|_| LineError::InvalidColor(color.into()))
+}
+
+// Returns the SGR parameters selecting `color` as the foreground, or background, colour.
+pub(crate) fn sgr_color(color: Color, background: bool) -> String {
+    let offset = if background { 10 } else { 0 };
+    let (base, bright) = (30 + offset, 90 + offset);
+    match color {
+        Color::Reset => format!("{}", 39 + offset),
+        Color::Black => format!("{base}"),
+        Color::Red => format!("{}", base + 1),
+        Color::Green => format!("{}", base + 2),
+        Color::Yellow => format!("{}", base + 3),
+        Color::Blue => format!("{}", base + 4),
+        Color::Magenta => format!("{}", base + 5),
+        Color::Cyan => format!("{}", base + 6),
+        Color::Gray => format!("{}", base + 7),
+        Color::DarkGray => format!("{bright}"),
+        Color::LightRed => format!("{}", bright + 1),
+        Color::LightGreen => format!("{}", bright + 2),
+        Color::LightYellow => format!("{}", bright + 3),
+        Color::LightBlue => format!("{}", bright + 4),
+        Color::LightMagenta => format!("{}", bright + 5),
+        Color::LightCyan => format!("{}", bright + 6),
+        Color::White => format!("{}", bright + 7),
+        Color::Indexed(i) => format!("{};5;{i}", 38 + offset),
+        Color::Rgb(r, g, b) => format!("{};2;{r};{g};{b}", 38 + offset),
+    }
+}
+
+// Returns true if `callable` accepts the named match groups after `args` leading arguments.
+fn named_groups(callable: Option<&Py<PyAny>>, args: usize) -> bool {
+    callable.is_some_and(|callable| {
+        Python::with_gil(|py| python::accepts_named_groups(callable.bind(py), args))
+    })
+}
+
+// Strip ANSI escape sequences and control characters other than `\n` from `text`, returning
+// the stripped text alongside the offset in `text` of each of its bytes. A final entry holds
+// the length of `text`.
+//
+// This is what `MudLine::stripped()` returns, so offsets into text matched against the
+// stripped line can always be mapped back to the raw line.
+fn strip_ansi_with_offsets(text: &str) -> (String, Vec<usize>) {
+    const ESC: u8 = 0x1B;
+
+    let bytes = text.as_bytes();
+    let mut stripped = Vec::with_capacity(bytes.len());
+    let mut offsets = Vec::with_capacity(bytes.len() + 1);
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i].is_ascii_control() && !matches!(bytes[i], b'\n' | ESC) {
+            i += 1;
+            continue;
+        }
+        if bytes[i] != ESC {
+            stripped.push(bytes[i]);
+            offsets.push(i);
+            i += 1;
+            continue;
+        }
+        let rest = &bytes[i..];
+        i += match rest.get(1) {
+            // CSI: parameters up to a final byte in 0x40..=0x7E.
+            Some(b'[') => rest
+                .iter()
+                .skip(2)
+                .position(|b| (0x40..=0x7E).contains(b))
+                .map_or(rest.len(), |pos| pos + 3),
+            // OSC: terminated by BEL or ST (ESC \).
+            Some(b']') => rest
+                .windows(2)
+                .position(|w| w[0] == 0x07 || w == b"\x1b\\")
+                .map_or(rest.len(), |pos| match rest[pos] {
+                    0x07 => pos + 1,
+                    _ => pos + 2,
+                }),
+            Some(b) if b.is_ascii() => 2,
+            _ => 1,
+        };
+    }
+    offsets.push(bytes.len());
+
+    // Only whole ASCII escape sequences are removed, so the result is still valid UTF-8.
+    let stripped = String::from_utf8(stripped)
+        .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned());
+    (stripped, offsets)
+}
+
 #[derive(Debug, Clone)]
 #[pyclass]
 pub struct Alias {
@@ -843,38 +1770,159 @@ pub struct AliasConfig {
     #[pyo3(get)]
     pub name: String,
 
-    #[pyo3(get, set)]
+    #[pyo3(get)]
     pub callback: Option<Py<PyAny>>, // Must be async. No return.
 
     #[pyo3(get, set)]
     pub expansion: Option<String>, // TODO(XXX): Rename to reaction?
 
+    /// Aliases with a higher priority are evaluated first. Ties are evaluated in the order
+    /// the aliases were created.
+    #[pyo3(get, set)]
+    pub priority: i32,
+
+    /// A label for enabling and disabling related triggers and aliases together, e.g.
+    /// `"combat"`.
+    #[pyo3(get, set)]
+    pub group: Option<String>,
+
     #[pyo3(get)]
     pub hit_count: u64,
 
     pub regex: Regex,
+
+    /// Whether `callback` accepts the named match groups. Checked when it's set, since
+    /// inspecting its signature for every match is slow.
+    pub callback_named_groups: bool,
 }
 
 impl AliasConfig {
-    /// Check if the input matches the alias pattern, and return matches if it does.
-    ///
-    /// # Panics
-    /// TODO: It shouldn't...
+    /// Check if the input matches the alias pattern, and return the match groups if it does.
+    #[must_use]
+    pub fn matches(&self, input: &str) -> Option<MatchGroups> {
+        self.regex
+            .captures(input)
+            .map(|captures| MatchGroups::new(&self.regex, &captures))
+    }
+}
+
+/// The capture groups of a trigger or alias pattern match.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct MatchGroups {
+    /// The text matched by the whole pattern.
+    pub matched: String,
+    /// Each capture group in order. Groups that didn't participate in the match are empty.
+    pub positional: Vec<String>,
+    /// Named capture groups, like `(?P<amount>\d+)`. Groups that didn't participate in the
+    /// match are `None`, like Python's `re.Match.groupdict()`.
+    pub named: HashMap<String, Option<String>>,
+}
+
+/// The result of testing a trigger or alias pattern against sample text.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[pyclass]
+pub struct PatternMatch {
+    /// The text the pattern was matched against, e.g. with ANSI escape sequences stripped.
+    #[pyo3(get)]
+    pub text: String,
+
+    /// Each capture group in order. Groups that didn't participate in the match are empty.
+    #[pyo3(get)]
+    pub groups: Vec<String>,
+
+    /// Named capture groups. Groups that didn't participate in the match are `None`.
+    #[pyo3(get)]
+    pub named_groups: HashMap<String, Option<String>>,
+}
+
+impl From<(String, MatchGroups)> for PatternMatch {
+    fn from((text, groups): (String, MatchGroups)) -> Self {
+        Self {
+            text,
+            groups: groups.positional,
+            named_groups: groups.named,
+        }
+    }
+}
+
+#[pymethods]
+impl PatternMatch {
+    fn __str__(&self) -> String {
+        format!("{self}")
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{self:?}")
+    }
+}
+
+impl Display for PatternMatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "matched {:?} groups: {:?}", self.text, self.groups)
+    }
+}
+
+impl MatchGroups {
+    fn new(regex: &Regex, captures: &Captures<'_>) -> Self {
+        let matched = captures
+            .get(0)
+            .map_or_else(String::new, |m| m.as_str().to_owned());
+        let positional = captures
+            .iter()
+            .skip(1)
+            .map(|m| m.map_or_else(String::new, |m| m.as_str().to_owned()))
+            .collect();
+        let named = regex
+            .capture_names()
+            .flatten()
+            .map(|name| {
+                let value = captures.name(name).map(|m| m.as_str().to_owned());
+                (name.to_owned(), value)
+            })
+            .collect();
+        Self {
+            matched,
+            positional,
+            named,
+        }
+    }
+
+    /// Substitute the groups into `template`, e.g. an alias expansion like `cast %1 at %2`.
     ///
-    // TODO(XXX): Tidy, remove unwrap.
+    /// `%0` is the whole match, `%1` to `%9` are capture groups in order, and `%<name>` is a
+    /// named group. Groups that didn't participate in the match are replaced with nothing.
+    /// `%%` is a literal `%`, and a `%` that isn't followed by a known group is kept as-is.
     #[must_use]
-    pub fn matches(&self, input: &str) -> (bool, Option<Vec<String>>) {
-        match self.regex.captures(input) {
-            Some(matches) => {
-                let captures = matches
-                    .iter()
-                    .skip(1)
-                    .map(|m| m.unwrap().as_str().to_owned())
-                    .collect();
-                (true, Some(captures))
+    pub fn expand(&self, template: &str) -> String {
+        let mut expanded = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(pos) = rest.find('%') {
+            expanded.push_str(&rest[..pos]);
+            rest = &rest[pos + 1..];
+
+            let group = match rest.chars().next() {
+                Some('%') => Some(("%", 1)),
+                Some(digit @ '0'..='9') => match digit.to_digit(10).unwrap_or_default() {
+                    0 => Some(self.matched.as_str()),
+                    n => self.positional.get(n as usize - 1).map(String::as_str),
+                }
+                .map(|value| (value, 1)),
+                Some('<') => rest[1..].split_once('>').and_then(|(name, _)| {
+                    let value = self.named.get(name)?.as_deref().unwrap_or_default();
+                    Some((value, name.len() + 2))
+                }),
+                _ => None,
+            };
+            match group {
+                Some((value, len)) => {
+                    expanded.push_str(value);
+                    rest = &rest[len..];
+                }
+                None => expanded.push('%'),
             }
-            None => (false, None),
         }
+        expanded.push_str(rest);
+        expanded
     }
 }
 
@@ -886,29 +1934,51 @@ impl AliasConfig {
     ///
     /// If the regex pattern can't be compiled.
     #[new]
-    #[pyo3(signature = (pattern, name, *, callback=None, expansion=None))]
+    #[pyo3(signature = (pattern, name, *, callback=None, expansion=None, priority=0, group=None))]
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         pattern: &str,
         name: String,
         callback: Option<Py<PyAny>>,
         expansion: Option<String>,
+        priority: i32,
+        group: Option<String>,
     ) -> Result<Self, Error> {
         let regex = Regex::new(pattern).map_err(AliasError::Pattern)?;
         Ok(Self {
             name,
+            callback_named_groups: named_groups(callback.as_ref(), 3),
             callback,
             expansion,
+            priority,
+            group,
             hit_count: 0,
             regex,
         })
     }
 
+    #[setter]
+    fn set_callback(&mut self, callback: Option<Py<PyAny>>) {
+        self.callback_named_groups = named_groups(callback.as_ref(), 3);
+        self.callback = callback;
+    }
+
     #[must_use]
     pub fn pattern(&self) -> &str {
         self.regex.as_str()
     }
 
+    /// Test the pattern against `sample` as if it was input, returning the match if there
+    /// is one.
+    ///
+    /// Unlike a real match the hit count isn't changed, and the callback and expansion
+    /// aren't used.
+    #[must_use]
+    pub fn test(&self, sample: &str) -> Option<PatternMatch> {
+        self.matches(sample)
+            .map(|groups| PatternMatch::from((sample.to_owned(), groups)))
+    }
+
     fn __str__(&self) -> String {
         format!("{self}")
     }
@@ -933,7 +2003,15 @@ pub struct Timer {
     #[pyo3(get)]
     pub running: bool,
 
-    pub stop_tx: watch::Sender<bool>,
+    /// Whether the timer is running, but paused. The countdown resumes from where it was
+    /// paused.
+    #[pyo3(get)]
+    pub paused: bool,
+
+    pub control_tx: watch::Sender<TimerControl>,
+
+    /// When the timer's task will next fire, as published by the task.
+    pub countdown: watch::Receiver<Countdown>,
 
     #[pyo3(get)]
     pub module: String,
@@ -949,6 +2027,16 @@ impl Timer {
         format!("Timer({}) - config: {}", self.id, *config)
     }
 
+    /// Returns the time remaining until the timer next fires, or `None` if it isn't running.
+    #[must_use]
+    pub fn remaining(&self) -> Option<Duration> {
+        match *self.countdown.borrow() {
+            Countdown::Until(deadline) => Some(deadline.saturating_duration_since(Instant::now())),
+            Countdown::Paused(remaining) => Some(remaining),
+            Countdown::Stopped => None,
+        }
+    }
+
     fn __repr__(&self) -> String {
         format!("{self:?}")
     }
@@ -960,12 +2048,152 @@ impl Display for Timer {
     }
 }
 
+/// Signals sent to a running timer's task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerControl {
+    Run,
+    Pause,
+    Stop,
+    /// Restart the countdown to the next tick from the full duration, and run the timer if
+    /// it was paused.
+    Reset,
+}
+
+/// The state of a timer's countdown to its next tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Countdown {
+    /// The timer will fire at the given instant.
+    Until(Instant),
+    /// The timer is paused, with the given time remaining.
+    Paused(Duration),
+    /// The timer's task isn't running.
+    Stopped,
+}
+
 impl idmap::Identifiable for Timer {
     fn id(&self) -> u32 {
         self.id
     }
 }
 
+/// A Python callback registered to handle subnegotiation data for a specific telnet option.
+#[derive(Debug, Clone)]
+pub struct SubnegotiationHandler {
+    pub option: u8,
+    pub module: String,
+    pub callback: Py<PyAny>,
+}
+
+/// A Python callback registered to transform input lines before they're sent.
+#[derive(Debug, Clone)]
+pub struct InputTransform {
+    pub module: String,
+    pub callback: Py<PyAny>, // Must not be async. Returns an InputLine, or None.
+}
+
+/// A search-and-replace applied to each line of output, without calling into Python.
+#[derive(Debug, Clone)]
+#[pyclass]
+pub struct Substitution {
+    #[pyo3(get)]
+    pub id: u32,
+
+    /// The text each match is replaced with. Match groups are substituted in like an alias
+    /// expansion: `%0` is the whole match, `%1` to `%9` are the capture groups, and
+    /// `%<name>` is a named group.
+    #[pyo3(get)]
+    pub replacement: String,
+
+    /// Whether the pattern is matched against the raw line, including ANSI escape sequences,
+    /// instead of the visible text with ANSI stripped.
+    #[pyo3(get)]
+    pub match_ansi: bool,
+
+    pub regex: Regex,
+}
+
+impl Substitution {
+    /// Replace each match in `text`, returning `None` if nothing matched.
+    ///
+    /// Unless `match_ansi` is set, the pattern is matched against the visible text and each
+    /// match replaces the raw text it spans. Escape sequences inside the match are kept after
+    /// the replacement, so the colours in effect after it are unchanged.
+    #[must_use]
+    pub fn apply(&self, text: &str) -> Option<String> {
+        let expand = |captures: &Captures<'_>| {
+            MatchGroups::new(&self.regex, captures).expand(&self.replacement)
+        };
+        if self.match_ansi {
+            return match self.regex.replace_all(text, expand) {
+                Cow::Borrowed(_) => None,
+                Cow::Owned(replaced) => Some(replaced),
+            };
+        }
+
+        let (stripped, offsets) = strip_ansi_with_offsets(text);
+        let mut replaced = String::with_capacity(text.len());
+        let mut pos = 0;
+        let mut matched = false;
+        for captures in self.regex.captures_iter(&stripped) {
+            // Safety: group 0 is always the whole match.
+            let m = captures.get(0).unwrap();
+            matched = true;
+            // Map the last matched byte rather than the end, so escape sequences following
+            // the match aren't replaced.
+            let (raw_start, raw_end) = match m.is_empty() {
+                true => (offsets[m.start()], offsets[m.start()]),
+                false => (offsets[m.start()], offsets[m.end() - 1] + 1),
+            };
+            replaced.push_str(&text[pos..raw_start]);
+            replaced.push_str(&expand(&captures));
+            // Keep what was stripped between the matched characters. The bytes of a
+            // multibyte character are never split by an escape sequence.
+            for (i, _) in stripped[m.range()].char_indices().skip(1) {
+                let i = m.start() + i;
+                replaced.push_str(&text[offsets[i - 1] + 1..offsets[i]]);
+            }
+            pos = raw_end;
+        }
+        replaced.push_str(&text[pos..]);
+        matched.then_some(replaced)
+    }
+}
+
+#[pymethods]
+impl Substitution {
+    #[must_use]
+    pub fn pattern(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    fn __str__(&self) -> String {
+        format!("{self}")
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{self:?}")
+    }
+}
+
+impl Display for Substitution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Substitution({}, {:?} -> {:?}{})",
+            self.id,
+            self.regex.as_str(),
+            self.replacement,
+            if self.match_ansi { ", ANSI" } else { "" }
+        )
+    }
+}
+
+impl idmap::Identifiable for Substitution {
+    fn id(&self) -> u32 {
+        self.id
+    }
+}
+
 #[derive(Debug, Clone)]
 #[pyclass]
 pub struct TimerConfig {
@@ -983,6 +2211,15 @@ pub struct TimerConfig {
 
     #[pyo3(get, set)]
     pub max_ticks: u64,
+
+    /// Whether the callback runs only once, after `duration`, before the timer is removed.
+    #[pyo3(get, set)]
+    pub once: bool,
+
+    /// Whether the time remaining until the timer next fires is shown in the session's input
+    /// area while it's running.
+    #[pyo3(get, set)]
+    pub display: bool,
 }
 
 #[pymethods]
@@ -993,12 +2230,14 @@ impl TimerConfig {
     ///
     /// If the duration pattern can't be recognized.
     #[new]
-    #[pyo3(signature = (name, duration_ms, callback, session_id=None))]
+    #[pyo3(signature = (name, duration_ms, callback, session_id=None, *, once=false, display=false))]
     pub fn new(
         name: String,
         duration_ms: u64,
         callback: PyObject,
         session_id: Option<u32>,
+        once: bool,
+        display: bool,
     ) -> Result<Self, Error> {
         let duration = Duration::from_millis(duration_ms);
         Ok(Self {
@@ -1006,6 +2245,8 @@ impl TimerConfig {
             session_id,
             callback,
             max_ticks: 0,
+            once,
+            display,
             duration,
         })
     }
@@ -1027,14 +2268,48 @@ impl Display for TimerConfig {
 
 // 🤷 https://github.com/serde-rs/serde/issues/368
 mod default {
+    use crate::tui::terminal::ColorSupport;
+
     pub(super) fn hold_prompt() -> bool {
         true
     }
 
+    pub(super) fn prompt_history_size() -> usize {
+        100
+    }
+
+    pub(super) fn use_eor() -> bool {
+        true
+    }
+
+    pub(super) fn charset() -> Vec<String> {
+        vec!["UTF-8".to_string(), "US-ASCII".to_string()]
+    }
+
+    pub(super) fn terminal_types() -> Vec<String> {
+        vec![
+            "MUDPUPPY".to_string(),
+            ColorSupport::detect().terminal_type().to_string(),
+            "MTTS".to_string(),
+        ]
+    }
+
+    pub(super) fn batch_line_events() -> bool {
+        false
+    }
+
     pub(super) fn echo_input() -> bool {
         true
     }
 
+    pub(super) fn echo_style() -> super::EchoStyle {
+        super::EchoStyle::default()
+    }
+
+    pub(super) fn scripted_echo_style() -> super::EchoStyle {
+        super::EchoStyle::default()
+    }
+
     pub(super) fn no_line_wrap() -> bool {
         false
     }
@@ -1043,6 +2318,14 @@ mod default {
         false
     }
 
+    pub(super) fn debug_gmcp_format() -> super::GmcpDebugFormat {
+        super::GmcpDebugFormat::Raw
+    }
+
+    pub(super) fn split_scroll() -> bool {
+        true
+    }
+
     pub(super) fn splitview_percentage() -> u16 {
         70
     }
@@ -1059,8 +2342,444 @@ mod default {
         false
     }
 
+    pub(super) fn idle_keepalive() -> u64 {
+        0
+    }
+
+    pub(super) fn idle_keepalive_line() -> Option<String> {
+        None
+    }
+
     #[allow(clippy::unnecessary_wraps)] // Matching config field.
     pub(super) fn command_separator() -> Option<String> {
         Some(";;".to_string())
     }
+
+    pub(super) fn empty_enter() -> super::EmptyEnter {
+        super::EmptyEnter::SendBlank
+    }
+
+    pub(super) fn paste_mode() -> super::PasteMode {
+        super::PasteMode::Input
+    }
+
+    pub(super) fn connect_timeout() -> u64 {
+        30
+    }
+
+    pub(super) fn auto_reconnect() -> bool {
+        false
+    }
+
+    pub(super) fn reconnect_max_attempts() -> u32 {
+        5
+    }
+
+    pub(super) fn connection_log() -> Option<std::path::PathBuf> {
+        None
+    }
+
+    pub(super) fn auto_log() -> bool {
+        false
+    }
+
+    pub(super) fn log_format() -> super::LogFormat {
+        super::LogFormat::Plain
+    }
+
+    pub(super) fn persist_history() -> bool {
+        false
+    }
+
+    pub(super) fn on_connect_commands() -> Vec<String> {
+        Vec::default()
+    }
+
+    pub(super) fn on_reconnect_commands() -> Option<Vec<String>> {
+        None
+    }
+
+    pub(super) fn on_connect_delay_ms() -> u64 {
+        500
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proxy_password_not_serialized() {
+        let mud = Mud {
+            proxy: Some(Proxy {
+                host: "localhost".to_string(),
+                port: 1080,
+                username: Some("user".to_string()),
+                password: Some("hunter2".to_string()),
+            }),
+            ..Mud::default()
+        };
+        let dumped = serde_json::to_string(&mud).unwrap();
+        assert!(dumped.contains("\"username\":\"user\""));
+        assert!(!dumped.contains("hunter2"));
+        assert!(!format!("{mud:?}").contains("hunter2"));
+    }
+
+    fn coloured_line() -> MudLine {
+        MudLine::from(Bytes::from_static(b"You have \x1b[33m42\x1b[0m gold."))
+    }
+
+    #[test]
+    fn substitutes_matches() {
+        let substitution = Substitution {
+            id: 1,
+            replacement: "<%<item>:%2>".to_string(),
+            match_ansi: false,
+            regex: Regex::new(r"\[(?P<item>\w+) (\d+)\]").unwrap(),
+        };
+        assert_eq!(substitution.apply("You see a sword."), None);
+        assert_eq!(
+            substitution.apply("[sword 1] and [shield 2]").as_deref(),
+            Some("<sword:1> and <shield:2>")
+        );
+
+        // Matches in coloured text keep its escape sequences intact.
+        let substitution = |pattern, replacement: &str, match_ansi| Substitution {
+            id: 1,
+            replacement: replacement.to_string(),
+            match_ansi,
+            regex: Regex::new(pattern).unwrap(),
+        };
+        let line = coloured_line().to_str().into_owned();
+        assert_eq!(
+            substitution(r"\d+", "N", false).apply(&line).as_deref(),
+            Some("You have \x1b[33mN\x1b[0m gold.")
+        );
+        assert_eq!(
+            substitution(r"have (\d+) gold", "own %1 coins", false)
+                .apply(&line)
+                .as_deref(),
+            Some("You own 42 coins\x1b[33m\x1b[0m.")
+        );
+        assert_eq!(
+            substitution(r"\x1b\[33m", "\x1b[31m", true)
+                .apply(&line)
+                .as_deref(),
+            Some("You have \x1b[31m42\x1b[0m gold.")
+        );
+        assert_eq!(
+            substitution("café", "inn", false)
+                .apply("The \x1b[1mcafé\x1b[0m is open.")
+                .as_deref(),
+            Some("The \x1b[1minn\x1b[0m is open.")
+        );
+        assert_eq!(
+            substitution("é ü", "-", false)
+                .apply("caf\x1b[32mé \x1b[33mü\x1b[0m")
+                .as_deref(),
+            Some("caf\x1b[32m-\x1b[33m\x1b[0m")
+        );
+    }
+
+    #[test]
+    fn redacts_password_lines() {
+        let line = InputLine::new("hunter2".to_string(), false, true).redacted();
+        assert_eq!(line.sent, "********");
+        assert_eq!(line.echo, EchoState::Password);
+        assert!(line.scripted);
+        assert!(!line.to_string().contains("hunter2"));
+    }
+
+    #[test]
+    fn expands_match_groups() {
+        let alias = AliasConfig::new(
+            r"^cast (\w+)(?: at (?P<target>\w+))?$",
+            "cast".to_string(),
+            None,
+            None,
+            0,
+            None,
+        )
+        .unwrap();
+
+        let groups = alias.matches("cast fireball at orc").unwrap();
+        assert_eq!(
+            groups.expand("cast '%1' %2 (%0) %<target>"),
+            "cast 'fireball' orc (cast fireball at orc) orc"
+        );
+        // Escapes, and anything that isn't a known group, are kept.
+        assert_eq!(
+            groups.expand("100%% %3 %<nope> %x 50%"),
+            "100% %3 %<nope> %x 50%"
+        );
+
+        // Groups that didn't participate are empty.
+        let groups = alias.matches("cast heal").unwrap();
+        assert_eq!(
+            groups.expand("cast %1 at [%2] [%<target>]"),
+            "cast heal at [] []"
+        );
+    }
+
+    fn trigger(pattern: &str) -> TriggerConfig {
+        TriggerConfig::with_pattern(pattern, "test".to_string()).unwrap()
+    }
+
+    #[test]
+    fn trigger_matches_stripped_text_by_default() {
+        let trigger = trigger(r"^You have (\d+) gold\.$");
+        assert!(!trigger.match_ansi);
+
+        let groups = trigger.matches(&coloured_line()).unwrap();
+        assert_eq!(groups.positional, vec!["42".to_string()]);
+    }
+
+    #[test]
+    fn trigger_matches_raw_text_with_match_ansi() {
+        let new_trigger = |pattern| TriggerConfig {
+            match_ansi: true,
+            ..trigger(pattern)
+        };
+
+        let colour_sensitive = new_trigger(r"\x1b\[33m(\d+)\x1b\[0m");
+        let groups = colour_sensitive.matches(&coloured_line()).unwrap();
+        assert_eq!(groups.positional, vec!["42".to_string()]);
+
+        // The plain text pattern no longer matches once ANSI is included.
+        let plain = new_trigger(r"^You have (\d+) gold\.$");
+        assert!(plain.matches(&coloured_line()).is_none());
+
+        // The older strip_ansi spelling is the inverse of match_ansi.
+        let strip_ansi = |match_ansi, strip_ansi| {
+            TriggerConfig::new(
+                ".*",
+                "test".to_string(),
+                match_ansi,
+                Some(strip_ansi),
+                false,
+                false,
+                None,
+                None,
+                None,
+                0,
+                false,
+                false,
+                None,
+                false,
+                None,
+            )
+            .unwrap()
+            .match_ansi
+        };
+        assert!(strip_ansi(false, false));
+        assert!(!strip_ansi(true, true));
+    }
+
+    #[test]
+    fn alias_matches_named_groups() {
+        let alias = AliasConfig::new(
+            r"^give (?P<amount>\d+) (?P<item>\w+)(?: to (?P<target>\w+))?$",
+            "give".to_string(),
+            None,
+            None,
+            0,
+            None,
+        )
+        .unwrap();
+
+        let groups = alias.matches("give 10 gold").unwrap();
+        assert_eq!(groups.positional, vec!["10", "gold", ""]);
+        assert_eq!(
+            groups.named,
+            HashMap::from([
+                ("amount".to_string(), Some("10".to_string())),
+                ("item".to_string(), Some("gold".to_string())),
+                ("target".to_string(), None),
+            ])
+        );
+        assert!(alias.matches("take 10 gold").is_none());
+    }
+
+    #[test]
+    fn trigger_spans_map_to_raw_offsets() {
+        let new_trigger = |pattern, match_ansi| TriggerConfig {
+            match_ansi,
+            ..trigger(pattern)
+        };
+        let line = coloured_line();
+
+        let spans = new_trigger(r"^You have (\d+) (gold)(!)?", false).spans(&line);
+        assert_eq!(spans, Some(vec![Some((14, 16)), Some((21, 25)), None]));
+        assert_eq!(&line.raw[14..16], b"42");
+        assert_eq!(&line.raw[21..25], b"gold");
+
+        let spans = new_trigger(r"\x1b\[33m(\d+)", true).spans(&line);
+        assert_eq!(spans, Some(vec![Some((14, 16))]));
+
+        // Control characters are stripped before matching, and spans still refer to the
+        // raw text.
+        let line = MudLine::from(Bytes::from_static(b"\tYou have \x1b[33m42\x1b[0m gold."));
+        let spans = new_trigger(r"^You have (\d+)", false).spans(&line);
+        assert_eq!(spans, Some(vec![Some((15, 17))]));
+        assert_eq!(&line.raw[15..17], b"42");
+
+        assert_eq!(new_trigger("silver", false).spans(&line), None);
+    }
+
+    #[test]
+    fn trigger_all_matches() {
+        let trigger = TriggerConfig {
+            multi_match: true,
+            ..trigger(r"(\d+) (\w+)")
+        };
+
+        let line = MudLine::from(Bytes::from_static(
+            b"You pick up 3 apples, 2 pears, and \x1b[35m5 plums\x1b[0m.",
+        ));
+        let groups = trigger
+            .all_matches(&line)
+            .into_iter()
+            .map(|groups| groups.positional)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            groups,
+            [["3", "apples"], ["2", "pears"], ["5", "plums"]].map(|g| g.map(String::from))
+        );
+        assert!(trigger
+            .all_matches(&MudLine::from(Bytes::from_static(b"Nothing here.")))
+            .is_empty());
+    }
+
+    #[test]
+    fn trigger_test_reports_matched_text() {
+        let trigger = |prompt| TriggerConfig {
+            prompt,
+            ..trigger(r"^HP: (\d+)$")
+        };
+
+        let found = trigger(false).test("\x1b[32mHP: 42\x1b[0m").unwrap();
+        assert_eq!(found.text, "HP: 42");
+        assert_eq!(found.groups, vec!["42"]);
+        // Prompt triggers treat the sample as a prompt.
+        assert!(trigger(true).test("HP: 42\r\n").is_some());
+        assert!(trigger(false).test("SP: 42").is_none());
+        assert_eq!(trigger(false).hit_count, 0);
+    }
+
+    #[test]
+    fn trigger_recolors_each_match() {
+        let new_trigger = |pattern, match_ansi, fg: &str| TriggerConfig {
+            match_ansi,
+            recolor: Some((Some(fg.to_string()), None)),
+            ..trigger(pattern)
+        };
+
+        let mut line = MudLine::from(Bytes::from_static(b"DANGER! \x1b[1mDANGER\x1b[0m!"));
+        new_trigger("DANGER", false, "red")
+            .recolor(&mut line)
+            .unwrap();
+        assert_eq!(
+            line.to_str(),
+            "\x1b[31mDANGER\x1b[0m! \x1b[1m\x1b[31mDANGER\x1b[0m\x1b[1m\x1b[0m!"
+        );
+
+        // Control characters before a match don't shift the recoloured range.
+        let mut line = MudLine::from(Bytes::from_static(b"\x07\tbeware: DANGER!"));
+        new_trigger("DANGER", false, "red")
+            .recolor(&mut line)
+            .unwrap();
+        assert_eq!(line.to_str(), "\x07\tbeware: \x1b[31mDANGER\x1b[0m!");
+
+        // Patterns matching ANSI recolour the visible text they matched.
+        let mut line = coloured_line();
+        new_trigger(r"\x1b\[33m\d+", true, "red")
+            .recolor(&mut line)
+            .unwrap();
+        assert_eq!(line.stripped(), "You have 42 gold.");
+        assert_eq!(
+            line.to_str(),
+            "You have \x1b[33m\x1b[31m42\x1b[0m\x1b[33m\x1b[0m gold."
+        );
+
+        let mut line = coloured_line();
+        assert!(new_trigger("gold", false, "nope")
+            .recolor(&mut line)
+            .is_err());
+    }
+
+    #[test]
+    fn color_range_overrides_and_restores_colours() {
+        let mut line = coloured_line();
+        line.color_range(4, 11, Some("red"), None).unwrap();
+        assert_eq!(
+            line.to_str(),
+            "You \x1b[31mhave \x1b[33m\x1b[31m42\x1b[0m\x1b[33m\x1b[0m gold."
+        );
+        assert_eq!(line.stripped(), "You have 42 gold.");
+
+        let mut line = MudLine::from(Bytes::from_static("\x1b[1mkänguru\x1b[0m!".as_bytes()));
+        line.color_range(1, 3, Some("#ff8800"), Some("42")).unwrap();
+        assert_eq!(
+            line.to_str(),
+            "\x1b[1mk\x1b[38;2;255;136;0;48;5;42män\x1b[0m\x1b[1mguru\x1b[0m!"
+        );
+
+        // Offsets are into the `stripped()` text, which drops control characters.
+        let mut line = MudLine::from(Bytes::from_static(b"\tYou have 42\x07 gold."));
+        line.color_range(9, 11, Some("red"), None).unwrap();
+        assert_eq!(line.to_str(), "\tYou have \x1b[31m42\x1b[0m\x07 gold.");
+        assert_eq!(line.stripped(), "You have 42 gold.");
+
+        let mut line = coloured_line();
+        assert!(line.color_range(4, 18, Some("red"), None).is_err());
+        assert!(line.color_range(0, 1, Some("nope"), None).is_err());
+        assert_eq!(line, coloured_line());
+    }
+
+    #[test]
+    fn prompt_trigger_matches_prompts_only() {
+        let trigger = TriggerConfig {
+            prompt: true,
+            gag: true,
+            ..trigger(r"^<(\d+)hp> $")
+        };
+
+        let mut prompt = MudLine::from(Bytes::from_static(b"\r<\x1b[32m100\x1b[0mhp> "));
+        assert!(trigger.matches(&prompt).is_none());
+
+        prompt.prompt = true;
+        let groups = trigger.matches(&prompt).unwrap();
+        assert_eq!(groups.positional, vec!["100".to_string()]);
+    }
+
+    #[test]
+    fn splits_commands_on_unescaped_separators() {
+        assert_eq!(split_commands("n;;e;;look", ";;"), vec!["n", "e", "look"]);
+        assert_eq!(
+            split_commands(r"say a\;;b;;wave", ";;"),
+            vec![r"say a\;;b", "wave"]
+        );
+        assert_eq!(split_commands(";;n;;", ";;"), vec!["", "n", ""]);
+        assert_eq!(split_commands("n;;e", ""), vec!["n;;e"]);
+
+        assert_eq!(unescape_separator(r"say a\;;b", ";;"), "say a;;b");
+        // Backslashes elsewhere are left alone.
+        assert_eq!(unescape_separator(r"say a\b\;", ";;"), r"say a\b\;");
+
+        // Escaped backslashes before a separator don't escape it.
+        assert_eq!(
+            split_commands(r"say a\\;;b;;say c\\\;;d", ";;"),
+            vec![r"say a\", "b", r"say c\\\;;d"]
+        );
+        assert_eq!(unescape_separator(r"say c\\\;;d", ";;"), r"say c\;;d");
+        assert_eq!(split_commands(r"a\\\\;;b\\", ";;"), vec![r"a\\", r"b\\"]);
+
+        let line = InputLine::new(r"say hi\;;there;;  ;;smile".to_string(), true, false);
+        let fragments = line
+            .split(";;")
+            .into_iter()
+            .map(|line| line.sent)
+            .collect::<Vec<_>>();
+        assert_eq!(fragments, vec![r"say hi\;;there", "smile"]);
+    }
 }
@@ -28,10 +28,10 @@ pub async fn connect(
     event_tx: UnboundedSender<Event>,
 ) -> Result<(Handle, stream::Info), Error> {
     let stream = Stream::connect(mud).await?;
-    let info: stream::Info = (&stream).into();
+    let info = stream::Info::new(&stream, mud.proxy.is_some());
     let (action_tx, action_rx) = unbounded_channel();
 
-    let codec = telnet::codec::Codec::default();
+    let codec = telnet::codec::Codec::new().with_line_ending(mud.line_ending);
     let task = tokio::spawn(
         Connection {
             session_id,
@@ -121,6 +121,9 @@ pub enum SessionEvent {
 
     /// Partial line content that was flushed from the line buffer.
     PartialLine(Bytes),
+
+    /// All of the telnet items decoded from a single read have been emitted.
+    ReadComplete,
 }
 
 /// An active connection to a MUD server.
@@ -188,6 +191,13 @@ impl Connection {
             if let Err(err) = self.emit_event(SessionEvent::Telnet(item)) {
                 return ControlFlow::Break(Some(err));
             }
+
+            // Once the read buffer is drained, the next item requires another read.
+            if self.stream.read_buffer().is_empty() && !self.stream.codec().pending() {
+                if let Err(err) = self.emit_event(SessionEvent::ReadComplete) {
+                    return ControlFlow::Break(Some(err));
+                }
+            }
         }
 
         trace!("stream ended - breaking control flow");
@@ -232,3 +242,249 @@ impl Connection {
             .map_err(Into::into)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+    use tokio_rustls::rustls::pki_types::{CertificateDer, PrivatePkcs8KeyDer};
+    use tokio_rustls::rustls::server::WebPkiClientVerifier;
+    use tokio_rustls::rustls::{RootCertStore, ServerConfig};
+    use tokio_rustls::TlsAcceptor;
+
+    use super::*;
+    use crate::model::{AddressFamily, Tls};
+
+    #[tokio::test]
+    async fn tls_close_notify_disconnects_gracefully() {
+        let (_handle, mut events, server) = tls_session(|mut tls_stream| async move {
+            tls_stream.write_all(b"hello\r\n").await.unwrap();
+            // Sends a close_notify alert before closing the TCP stream.
+            tls_stream.shutdown().await.unwrap();
+        })
+        .await;
+
+        let mut saw_line = false;
+        loop {
+            match events.recv().await.unwrap().event {
+                SessionEvent::Telnet(telnet::codec::Item::Line(line)) => {
+                    assert_eq!(line.as_ref(), b"hello");
+                    saw_line = true;
+                }
+                SessionEvent::Disconnected => break,
+                SessionEvent::Error(err) => panic!("unexpected error event: {err}"),
+                _ => {}
+            }
+        }
+        assert!(saw_line);
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn tls_close_without_close_notify_is_an_error() {
+        let (_handle, mut events, server) = tls_session(|tls_stream| async move {
+            // Drop the TCP stream without sending a close_notify alert.
+            let (tcp_stream, _) = tls_stream.into_inner();
+            drop(tcp_stream);
+        })
+        .await;
+
+        loop {
+            match events.recv().await.unwrap().event {
+                SessionEvent::Error(err) => {
+                    assert!(err.to_string().contains("without sending close_notify"));
+                    break;
+                }
+                SessionEvent::Disconnected => panic!("unexpected graceful disconnect"),
+                _ => {}
+            }
+        }
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn connects_with_address_family() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut mud = Mud {
+            name: "test".to_string(),
+            host: "127.0.0.1".to_string(),
+            port,
+            address_family: AddressFamily::V6,
+            no_tcp_keepalive: true,
+            ..Mud::default()
+        };
+
+        // There are no IPv6 addresses for an IPv4 host.
+        let err = connect(1, &mud, unbounded_channel().0).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("no IPv6 addresses found for 127.0.0.1"));
+
+        mud.address_family = AddressFamily::V4;
+        let (_handle, info) = connect(1, &mud, unbounded_channel().0).await.unwrap();
+        assert!(!info.is_ipv6());
+        assert_eq!(info.to_string(), format!("telnet://127.0.0.1:{port}"));
+
+        let info = stream::Info::Tcp {
+            ip: "::1".to_string(),
+            port: 4000,
+            proxied: true,
+        };
+        assert!(info.is_ipv6());
+        assert_eq!(info.to_string(), "telnet://[::1]:4000 via proxy");
+    }
+
+    #[tokio::test]
+    async fn tls_presents_client_certificate() {
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = CertificateParams::new(Vec::new()).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca_cert = ca_params.self_signed(&ca_key).unwrap();
+        let client_key = KeyPair::generate().unwrap();
+        let client_cert = CertificateParams::new(vec!["player".to_string()])
+            .unwrap()
+            .signed_by(&client_key, &ca_cert, &ca_key)
+            .unwrap();
+
+        let dir = std::env::temp_dir().join(format!("mudpuppy-client-cert-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (cert_path, key_path) = (dir.join("client.pem"), dir.join("client.key"));
+        std::fs::write(&cert_path, client_cert.pem()).unwrap();
+        std::fs::write(&key_path, client_key.serialize_pem()).unwrap();
+
+        // The server only accepts clients with a certificate issued by the CA.
+        let mut roots = RootCertStore::empty();
+        roots.add(ca_cert.der().clone()).unwrap();
+        let verifier = WebPkiClientVerifier::builder(Arc::new(roots))
+            .build()
+            .unwrap();
+        let server_cert =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let server_config = ServerConfig::builder()
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(
+                vec![CertificateDer::from(server_cert.cert.der().to_vec())],
+                PrivatePkcs8KeyDer::from(server_cert.key_pair.serialize_der()).into(),
+            )
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(server_config));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (tcp_stream, _) = listener.accept().await.unwrap();
+            let tls_stream = acceptor.accept(tcp_stream).await.unwrap();
+            let (_, server_conn) = tls_stream.get_ref();
+            assert_eq!(server_conn.peer_certificates().unwrap().len(), 1);
+        });
+
+        let mud = Mud {
+            name: "test".to_string(),
+            host: "127.0.0.1".to_string(),
+            port,
+            tls: Tls::InsecureSkipVerify,
+            client_cert: Some(cert_path),
+            client_key: Some(key_path),
+            no_tcp_keepalive: true,
+            ..Mud::default()
+        };
+        let result = connect(1, &mud, unbounded_channel().0).await;
+        std::fs::remove_dir_all(&dir).unwrap();
+        result.unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn tls_verifies_pinned_certificate() {
+        let server_cert =
+            rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let other_cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let dir = std::env::temp_dir().join(format!("mudpuppy-pinned-cert-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (pinned_path, other_path) = (dir.join("pinned.pem"), dir.join("other.pem"));
+        std::fs::write(&pinned_path, server_cert.cert.pem()).unwrap();
+        std::fs::write(&other_path, other_cert.cert.pem()).unwrap();
+
+        let server_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![CertificateDer::from(server_cert.cert.der().to_vec())],
+                PrivatePkcs8KeyDer::from(server_cert.key_pair.serialize_der()).into(),
+            )
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(server_config));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            for _ in 0..2 {
+                let (tcp_stream, _) = listener.accept().await.unwrap();
+                let _ = acceptor.accept(tcp_stream).await;
+            }
+        });
+
+        // The pinned certificate is accepted, even though it's self-signed and doesn't match
+        // the host.
+        let mut mud = Mud {
+            name: "test".to_string(),
+            host: "127.0.0.1".to_string(),
+            port,
+            tls: Tls::Enabled,
+            pinned_cert: Some(pinned_path),
+            no_tcp_keepalive: true,
+            ..Mud::default()
+        };
+        let pinned = connect(1, &mud, unbounded_channel().0).await;
+
+        // Any other certificate is rejected.
+        mud.pinned_cert = Some(other_path);
+        let other = connect(1, &mud, unbounded_channel().0).await;
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        pinned.unwrap();
+        assert!(other.is_err());
+        server.await.unwrap();
+    }
+
+    // Start a local TLS server that runs `serve` for one accepted connection, and connect
+    // a session to it. Returns the session's connection handle, event receiver and the server
+    // task. The connection is closed when the handle is dropped.
+    async fn tls_session<F, Fut>(serve: F) -> (Handle, UnboundedReceiver<Event>, JoinHandle<()>)
+    where
+        F: FnOnce(tokio_rustls::server::TlsStream<tokio::net::TcpStream>) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = ()> + Send,
+    {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let server_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![CertificateDer::from(cert.cert.der().to_vec())],
+                PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der()).into(),
+            )
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(server_config));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (tcp_stream, _) = listener.accept().await.unwrap();
+            serve(acceptor.accept(tcp_stream).await.unwrap()).await;
+        });
+
+        let mud = Mud {
+            name: "test".to_string(),
+            host: "127.0.0.1".to_string(),
+            port,
+            tls: Tls::InsecureSkipVerify,
+            no_tcp_keepalive: true,
+            ..Mud::default()
+        };
+        let (event_tx, event_rx) = unbounded_channel();
+        let (handle, _) = connect(1, &mud, event_tx).await.unwrap();
+
+        (handle, event_rx, server)
+    }
+}
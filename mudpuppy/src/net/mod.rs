@@ -1,3 +1,4 @@
 pub mod connection;
+mod socks;
 pub mod stream;
 pub mod telnet;
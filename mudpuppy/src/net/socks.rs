@@ -0,0 +1,231 @@
+//! A minimal SOCKS5 client (RFC 1928), with optional username/password authentication
+//! (RFC 1929).
+
+use std::net::IpAddr;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tracing::{debug, instrument, Level};
+
+use crate::error::Error;
+use crate::model::Proxy;
+
+const VERSION: u8 = 5;
+
+const METHOD_NO_AUTH: u8 = 0;
+const METHOD_USER_PASS: u8 = 2;
+const METHOD_NONE_ACCEPTABLE: u8 = 0xFF;
+
+const USER_PASS_VERSION: u8 = 1;
+
+const CMD_CONNECT: u8 = 1;
+
+const ATYP_IPV4: u8 = 1;
+const ATYP_DOMAIN: u8 = 3;
+const ATYP_IPV6: u8 = 4;
+
+/// Ask the SOCKS5 proxy at the other end of `stream` to connect to `host` and `port`.
+///
+/// Once this returns the stream is connected to the destination through the proxy, and
+/// anything else (e.g. a TLS handshake) is negotiated end-to-end with the destination.
+/// Host names are resolved by the proxy.
+///
+/// # Errors
+/// If the proxy can't be spoken to, refuses the credentials, or can't connect to the
+/// destination.
+#[instrument(level = Level::TRACE, skip(stream, proxy))]
+pub(super) async fn handshake<S>(
+    stream: &mut S,
+    proxy: &Proxy,
+    host: &str,
+    port: u16,
+) -> Result<(), Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    // Only offer username/password authentication when there are credentials to send.
+    let greeting: &[u8] = match proxy.username {
+        Some(_) => &[VERSION, 2, METHOD_NO_AUTH, METHOD_USER_PASS],
+        None => &[VERSION, 1, METHOD_NO_AUTH],
+    };
+    stream.write_all(greeting).await?;
+
+    let mut reply = [0; 2];
+    stream.read_exact(&mut reply).await?;
+    check_version(reply[0], VERSION)?;
+    match reply[1] {
+        METHOD_NO_AUTH => {}
+        METHOD_USER_PASS if proxy.username.is_some() => authenticate(stream, proxy).await?,
+        METHOD_NONE_ACCEPTABLE => {
+            return Err(Error::Proxy(
+                "no acceptable authentication method".to_string(),
+            ))
+        }
+        method => {
+            return Err(Error::Proxy(format!(
+                "unsupported authentication method {method}"
+            )))
+        }
+    }
+
+    let mut request = vec![VERSION, CMD_CONNECT, 0];
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            request.push(ATYP_IPV4);
+            request.extend_from_slice(&ip.octets());
+        }
+        Ok(IpAddr::V6(ip)) => {
+            request.push(ATYP_IPV6);
+            request.extend_from_slice(&ip.octets());
+        }
+        Err(_) => {
+            request.push(ATYP_DOMAIN);
+            request.push(field_len(host, "host name")?);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut reply = [0; 4];
+    stream.read_exact(&mut reply).await?;
+    check_version(reply[0], VERSION)?;
+    if reply[1] != 0 {
+        return Err(Error::Proxy(reply_message(reply[1])));
+    }
+
+    // The address the proxy bound for the connection isn't needed, but has to be consumed.
+    let addr_len = match reply[3] {
+        ATYP_IPV4 => 4,
+        ATYP_IPV6 => 16,
+        ATYP_DOMAIN => usize::from(stream.read_u8().await?),
+        atyp => return Err(Error::Proxy(format!("unknown address type {atyp}"))),
+    };
+    let mut bound = vec![0; addr_len + 2];
+    stream.read_exact(&mut bound).await?;
+
+    debug!("connected to {host}:{port} through proxy");
+    Ok(())
+}
+
+async fn authenticate<S>(stream: &mut S, proxy: &Proxy) -> Result<(), Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let username = proxy.username.as_deref().unwrap_or_default();
+    let password = proxy.password.as_deref().unwrap_or_default();
+
+    let mut request = vec![USER_PASS_VERSION, field_len(username, "username")?];
+    request.extend_from_slice(username.as_bytes());
+    request.push(field_len(password, "password")?);
+    request.extend_from_slice(password.as_bytes());
+    stream.write_all(&request).await?;
+
+    let mut reply = [0; 2];
+    stream.read_exact(&mut reply).await?;
+    check_version(reply[0], USER_PASS_VERSION)?;
+    match reply[1] {
+        0 => Ok(()),
+        _ => Err(Error::Proxy("authentication failed".to_string())),
+    }
+}
+
+fn check_version(version: u8, expected: u8) -> Result<(), Error> {
+    match version == expected {
+        true => Ok(()),
+        false => Err(Error::Proxy(format!(
+            "unexpected version {version}, expected {expected}"
+        ))),
+    }
+}
+
+// SOCKS5 strings are prefixed with a one byte length.
+fn field_len(value: &str, name: &str) -> Result<u8, Error> {
+    u8::try_from(value.len()).map_err(|_| Error::Proxy(format!("{name} is too long")))
+}
+
+fn reply_message(reply: u8) -> String {
+    match reply {
+        1 => "general failure",
+        2 => "connection not allowed by ruleset",
+        3 => "network unreachable",
+        4 => "host unreachable",
+        5 => "connection refused",
+        6 => "TTL expired",
+        7 => "command not supported",
+        8 => "address type not supported",
+        _ => return format!("unknown reply {reply}"),
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::duplex;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn connects_with_credentials() {
+        let (mut client, mut server) = duplex(1024);
+        let proxy = Proxy {
+            host: "proxy.example.com".to_string(),
+            port: 1080,
+            username: Some("user".to_string()),
+            password: Some("hunter2".to_string()),
+        };
+
+        let server = tokio::spawn(async move {
+            let mut greeting = [0; 4];
+            server.read_exact(&mut greeting).await.unwrap();
+            assert_eq!(greeting, [5, 2, 0, 2]);
+            server.write_all(&[5, 2]).await.unwrap();
+
+            let mut auth = [0; 14];
+            server.read_exact(&mut auth).await.unwrap();
+            assert_eq!(&auth, b"\x01\x04user\x07hunter2");
+            server.write_all(&[1, 0]).await.unwrap();
+
+            let mut request = [0; 18];
+            server.read_exact(&mut request).await.unwrap();
+            assert_eq!(&request, b"\x05\x01\x00\x03\x0bdunemud.net\x0f\xa0");
+            // Bound to a domain name, which the client has to skip over.
+            server
+                .write_all(b"\x05\x00\x00\x03\x05proxy\x04\x38hello")
+                .await
+                .unwrap();
+        });
+
+        handshake(&mut client, &proxy, "dunemud.net", 4000)
+            .await
+            .unwrap();
+        // Anything after the reply is the destination's data.
+        let mut data = [0; 5];
+        client.read_exact(&mut data).await.unwrap();
+        assert_eq!(&data, b"hello");
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn reports_failures() {
+        let (mut client, mut server) = duplex(1024);
+        let proxy = Proxy::default();
+
+        let server = tokio::spawn(async move {
+            let mut greeting = [0; 3];
+            server.read_exact(&mut greeting).await.unwrap();
+            assert_eq!(greeting, [5, 1, 0]);
+            server.write_all(&[5, 0]).await.unwrap();
+
+            let mut request = [0; 10];
+            server.read_exact(&mut request).await.unwrap();
+            assert_eq!(request, [5, 1, 0, 1, 127, 0, 0, 1, 0x0f, 0xa0]);
+            server.write_all(&[5, 5, 0, 1]).await.unwrap();
+        });
+
+        let err = handshake(&mut client, &proxy, "127.0.0.1", 4000)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "proxy error: connection refused");
+        server.await.unwrap();
+    }
+}
@@ -1,5 +1,7 @@
 use std::fmt::{Display, Formatter};
 use std::io;
+use std::net::IpAddr;
+use std::path::Path;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
@@ -8,18 +10,22 @@ use std::time::Duration;
 use pyo3::{pyclass, pymethods};
 use socket2::{Socket, TcpKeepalive};
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
-use tokio::net::TcpStream;
+use tokio::net::{lookup_host, TcpStream};
 use tokio_rustls::client::TlsStream;
-use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
 use tokio_rustls::rustls::{ClientConfig, RootCertStore};
 use tokio_rustls::TlsConnector;
 use tracing::{info, instrument, Level};
 
+use crate::config;
 use crate::error::Error;
-use crate::model::{Mud, Tls};
+use crate::model::{AddressFamily, Mud, Tls};
+use crate::net::socks;
 
 /// A TCP stream to a MUD server that may be TLS encrypted.
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum Stream {
     /// A vanilla TCP stream.
     Tcp(TcpStream),
@@ -36,7 +42,21 @@ impl Stream {
     #[instrument(level = Level::TRACE, skip(mud))]
     pub async fn connect(mud: &Mud) -> Result<Stream, Error> {
         info!("connecting");
-        let mut tcp_stream = happy_eyeballs::tokio::connect((mud.host.as_str(), mud.port)).await?;
+        // With a proxy, the TCP connection is made to the proxy and then extended to the MUD.
+        // Any TLS handshake happens afterwards, so it's end-to-end with the MUD.
+        let (host, port) = mud
+            .proxy
+            .as_ref()
+            .map_or((mud.host.as_str(), mud.port), |proxy| {
+                (proxy.host.as_str(), proxy.port)
+            });
+        let mut tcp_stream = match mud.address_family {
+            AddressFamily::Auto => happy_eyeballs::tokio::connect((host, port)).await?,
+            family => Self::connect_family(host, port, family).await?,
+        };
+        if let Some(proxy) = &mud.proxy {
+            socks::handshake(&mut tcp_stream, proxy, &mud.host, mud.port).await?;
+        }
 
         if !mud.no_tcp_keepalive {
             tcp_stream = Self::configure_keepalive(tcp_stream)?;
@@ -46,7 +66,7 @@ impl Stream {
             .peer_addr()
             .map(|addr| addr.ip().to_string())
             .unwrap_or_default();
-        info!("connected to {ip_addr}:{}", mud.port);
+        info!("connected to {ip_addr}:{port}");
 
         Ok(match mud.tls {
             Tls::Disabled => Stream::Tcp(tcp_stream),
@@ -57,22 +77,78 @@ impl Stream {
         })
     }
 
+    // Connect to the first resolved address of `host` in `family` that accepts the
+    // connection, trying them in the order they were resolved.
+    async fn connect_family(
+        host: &str,
+        port: u16,
+        family: AddressFamily,
+    ) -> Result<TcpStream, Error> {
+        let mut last_err = None;
+        for addr in lookup_host((host, port))
+            .await?
+            .filter(|addr| family.allows(addr.ip()))
+        {
+            match TcpStream::connect(addr).await {
+                Ok(tcp_stream) => return Ok(tcp_stream),
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(last_err
+            .unwrap_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no {family} addresses found for {host}"),
+                )
+            })
+            .into())
+    }
+
     // TODO(XXX): support choosing crypto provider?
     // TODO(XXX): use rustls-platform-verifier.
     async fn connect_tls(mud: &Mud, tcp_stream: TcpStream) -> Result<TlsStream<TcpStream>, Error> {
-        let config = match mud.tls {
-            Tls::Enabled => ClientConfig::builder().with_root_certificates(RootCertStore {
-                roots: webpki_roots::TLS_SERVER_ROOTS.into(),
-            }),
-            Tls::InsecureSkipVerify => ClientConfig::builder()
+        let config = match (mud.tls, &mud.pinned_cert, &mud.ca_file) {
+            (Tls::Enabled, Some(pinned_cert), _) => {
+                let pinned = load_certs(pinned_cert).map_err(Error::Certificate)?;
+                ClientConfig::builder()
+                    .dangerous()
+                    .with_custom_certificate_verifier(Arc::new(pinned::PinnedCertVerifier::new(
+                        pinned,
+                    )))
+            }
+            (Tls::Enabled, None, Some(ca_file)) => {
+                let mut roots = RootCertStore::empty();
+                for cert in load_certs(ca_file).map_err(Error::Certificate)? {
+                    roots.add(cert).map_err(|err| {
+                        Error::Certificate(format!("{}: {err}", ca_file.display()))
+                    })?;
+                }
+                ClientConfig::builder().with_root_certificates(roots)
+            }
+            (Tls::Enabled, None, None) => {
+                ClientConfig::builder().with_root_certificates(RootCertStore {
+                    roots: webpki_roots::TLS_SERVER_ROOTS.into(),
+                })
+            }
+            (Tls::InsecureSkipVerify, ..) => ClientConfig::builder()
                 .dangerous()
                 .with_custom_certificate_verifier(Arc::new(
                     danger::NoCertificateVerification::new(),
                 )),
-            Tls::Disabled => unreachable!("connect_tls should not be called with Tls::None"),
+            (Tls::Disabled, ..) => unreachable!("connect_tls should not be called with Tls::None"),
+        };
+        let config = match (&mud.client_cert, &mud.client_key) {
+            (Some(cert), Some(key)) => {
+                let (cert_chain, key) = Self::load_client_cert(cert, key)?;
+                config
+                    .with_client_auth_cert(cert_chain, key)
+                    .map_err(|err| Error::Certificate(err.to_string()))?
+            }
+            // Config validation ensures both or neither are set.
+            _ => config.with_no_client_auth(),
         };
 
-        TlsConnector::from(Arc::new(config.with_no_client_auth()))
+        TlsConnector::from(Arc::new(config))
             .connect(
                 // Safety: config verifiers mud host up-front.
                 ServerName::try_from(mud.host.as_str()).unwrap().to_owned(),
@@ -82,6 +158,19 @@ impl Stream {
             .map_err(Into::into)
     }
 
+    // Load a PEM certificate chain and private key, resolving relative paths against the
+    // config directory.
+    fn load_client_cert(
+        cert: &Path,
+        key: &Path,
+    ) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>), Error> {
+        let cert_chain = load_certs(cert).map_err(Error::Certificate)?;
+        let key = config::config_dir().join(key);
+        let key = PrivateKeyDer::from_pem_file(&key)
+            .map_err(|err| Error::Certificate(format!("{}: {err}", key.display())))?;
+        Ok((cert_chain, key))
+    }
+
     fn configure_keepalive(tcp_stream: TcpStream) -> Result<TcpStream, Error> {
         // Convert the Tokio TCP stream into a std::net::TcpStream, and then a socket2::Socket.
         let tcp_stream = tcp_stream.into_std()?;
@@ -115,6 +204,23 @@ impl Stream {
     }
 }
 
+/// Load the certificates in a PEM file, resolving a relative `path` against the config
+/// directory.
+///
+/// # Errors
+/// If the file can't be read, isn't valid PEM, or holds no certificates. The error
+/// describes the problem, including the file's path.
+pub fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, String> {
+    let path = config::config_dir().join(path);
+    let certs = CertificateDer::pem_file_iter(&path)
+        .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+        .map_err(|err| format!("{}: {err}", path.display()))?;
+    match certs.is_empty() {
+        true => Err(format!("{}: no certificates found", path.display())),
+        false => Ok(certs),
+    }
+}
+
 impl AsyncRead for Stream {
     fn poll_read(
         self: Pin<&mut Self>,
@@ -123,11 +229,28 @@ impl AsyncRead for Stream {
     ) -> Poll<io::Result<()>> {
         match self.get_mut() {
             Stream::Tcp(tcp_stream) => Pin::new(tcp_stream).poll_read(cx, buf),
-            Stream::Tls { tls_stream, .. } => Pin::new(tls_stream).poll_read(cx, buf),
+            Stream::Tls { tls_stream, .. } => match Pin::new(tls_stream).poll_read(cx, buf) {
+                // A clean close_notify from the server is surfaced as a normal EOF (Ok with no
+                // data read) and results in a graceful disconnect. Other errors are given a more
+                // descriptive message.
+                Poll::Ready(Err(err)) => Poll::Ready(Err(describe_tls_error(&err))),
+                res => res,
+            },
         }
     }
 }
 
+/// Describe a TLS read error that occurred mid-stream.
+fn describe_tls_error(err: &io::Error) -> io::Error {
+    let msg = match err.kind() {
+        io::ErrorKind::UnexpectedEof => {
+            "TLS connection closed by server without sending close_notify".to_string()
+        }
+        _ => format!("TLS connection error: {err}"),
+    };
+    io::Error::new(err.kind(), msg)
+}
+
 impl AsyncWrite for Stream {
     fn poll_write(
         self: Pin<&mut Self>,
@@ -155,18 +278,21 @@ impl AsyncWrite for Stream {
     }
 }
 
-impl From<&Stream> for Info {
-    fn from(value: &Stream) -> Self {
+impl Info {
+    /// Describe the connection information of `stream`, noting whether it was established
+    /// through a proxy.
+    #[must_use]
+    pub fn new(stream: &Stream, proxied: bool) -> Self {
         fn ip_and_port(stream: &TcpStream) -> (String, u16) {
             stream
                 .peer_addr()
                 .map(|addr| (addr.ip().to_string(), addr.port()))
                 .unwrap_or_default()
         }
-        match value {
+        match stream {
             Stream::Tcp(stream) => {
                 let (ip, port) = ip_and_port(stream);
-                Info::Tcp { ip, port }
+                Info::Tcp { ip, port, proxied }
             }
             Stream::Tls {
                 tls_stream,
@@ -177,6 +303,7 @@ impl From<&Stream> for Info {
                 Info::Tls {
                     ip,
                     port,
+                    proxied,
                     protocol: tls_conn
                         .protocol_version()
                         .map(|proto| proto.as_str().unwrap_or_default().into())
@@ -199,17 +326,23 @@ pub enum Info {
     /// The stream is an unencrypted TCP stream.
     Tcp {
         /// The resolved IP address of the MUD server that was used for the connection stream.
+        /// The proxy's address if the connection is proxied.
         ip: String,
         /// The port of the MUD server that was used for the connection stream.
         port: u16,
+        /// Whether the connection was established through a proxy.
+        proxied: bool,
     },
 
     /// The stream is a TLS encrypted TCP stream.
     Tls {
         /// The resolved IP address of the MUD server that was used for the connection stream.
+        /// The proxy's address if the connection is proxied.
         ip: String,
         /// The port of the MUD server that was used for the connection stream.
         port: u16,
+        /// Whether the connection was established through a proxy.
+        proxied: bool,
         /// The TLS protocol name.
         protocol: String,
         /// The TLS ciphersuite name.
@@ -221,6 +354,13 @@ pub enum Info {
 
 #[pymethods]
 impl Info {
+    /// Returns true if the stream is connected over IPv6, or false for IPv4.
+    #[must_use]
+    pub fn is_ipv6(&self) -> bool {
+        let (Info::Tcp { ip, .. } | Info::Tls { ip, .. }) = self;
+        ip.parse::<IpAddr>().is_ok_and(|ip| ip.is_ipv6())
+    }
+
     fn __str__(&self) -> String {
         format!("{self}")
     }
@@ -232,16 +372,22 @@ impl Info {
 
 impl Display for Info {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // IPv6 addresses are bracketed, so they can be told apart from the port.
+        let (Info::Tcp { ip, proxied, .. } | Info::Tls { ip, proxied, .. }) = self;
+        let ip = match self.is_ipv6() {
+            true => format!("[{ip}]"),
+            false => ip.clone(),
+        };
         match self {
-            Info::Tcp { ip, port } => {
-                write!(f, "telnet://{ip}:{port}")
+            Info::Tcp { port, .. } => {
+                write!(f, "telnet://{ip}:{port}")?;
             }
             Info::Tls {
-                ip,
                 port,
                 protocol,
                 ciphersuite,
                 verify_skipped,
+                ..
             } => {
                 write!(
                     f,
@@ -251,9 +397,13 @@ impl Display for Info {
                     } else {
                         ""
                     },
-                )
+                )?;
             }
         }
+        match proxied {
+            true => write!(f, " via proxy"),
+            false => Ok(()),
+        }
     }
 }
 
@@ -330,3 +480,88 @@ mod danger {
         }
     }
 }
+
+mod pinned {
+    use tokio_rustls::rustls::client::danger::{
+        HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+    };
+    use tokio_rustls::rustls::crypto::ring::default_provider;
+    use tokio_rustls::rustls::crypto::{
+        verify_tls12_signature, verify_tls13_signature, CryptoProvider,
+    };
+    use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+    use tokio_rustls::rustls::{CertificateError, DigitallySignedStruct, Error, SignatureScheme};
+
+    /// `PinnedCertVerifier` is a [`ServerCertVerifier`] that only accepts a server presenting
+    /// one of a set of pinned certificates.
+    ///
+    /// The server's certificate must be byte-for-byte identical to a pinned one. Its name and
+    /// validity period aren't checked, but the handshake signatures are still verified, so the
+    /// server must hold the certificate's private key.
+    #[derive(Debug)]
+    pub struct PinnedCertVerifier {
+        pinned: Vec<CertificateDer<'static>>,
+        provider: CryptoProvider,
+    }
+
+    impl PinnedCertVerifier {
+        pub fn new(pinned: Vec<CertificateDer<'static>>) -> Self {
+            Self {
+                pinned,
+                provider: default_provider(),
+            }
+        }
+    }
+
+    impl ServerCertVerifier for PinnedCertVerifier {
+        fn verify_server_cert(
+            &self,
+            end_entity: &CertificateDer<'_>,
+            _intermediates: &[CertificateDer<'_>],
+            _server_name: &ServerName,
+            _ocsp: &[u8],
+            _now: UnixTime,
+        ) -> Result<ServerCertVerified, Error> {
+            match self.pinned.iter().any(|pinned| pinned == end_entity) {
+                true => Ok(ServerCertVerified::assertion()),
+                false => Err(Error::InvalidCertificate(
+                    CertificateError::ApplicationVerificationFailure,
+                )),
+            }
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            verify_tls12_signature(
+                message,
+                cert,
+                dss,
+                &self.provider.signature_verification_algorithms,
+            )
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            verify_tls13_signature(
+                message,
+                cert,
+                dss,
+                &self.provider.signature_verification_algorithms,
+            )
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            self.provider
+                .signature_verification_algorithms
+                .supported_schemes()
+        }
+    }
+}
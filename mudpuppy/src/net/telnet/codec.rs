@@ -4,16 +4,22 @@
 
 use std::mem;
 
+use flate2::{Decompress, FlushDecompress, Status};
 use tokio_util::bytes::{Buf, BufMut, Bytes, BytesMut};
 use tokio_util::codec::{Decoder, Encoder};
 
 use crate::error::Error;
+use crate::model::LineEnding;
 use crate::net::telnet;
 
 #[derive(Debug, Default)]
 pub struct Codec {
     state: State,
     line_buffer: BytesMut,
+    /// Decompression state, once the server has started an MCCP2 compressed stream.
+    mccp: Option<Mccp>,
+    /// The line ending appended to each encoded [`Item::Line`].
+    line_ending: LineEnding,
 }
 
 impl Codec {
@@ -22,9 +28,29 @@ impl Codec {
         Codec {
             state: State::default(),
             line_buffer: BytesMut::with_capacity(1024),
+            mccp: None,
+            line_ending: LineEnding::default(),
         }
     }
 
+    /// Use `line_ending` to terminate encoded [`Item::Line`]s, instead of `\r\n`.
+    #[must_use]
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Returns true if the codec holds decompressed data that hasn't been decoded yet.
+    ///
+    /// When MCCP2 is active the read buffer is drained into the decompressor, so an empty
+    /// read buffer doesn't mean there are no more items to decode.
+    #[must_use]
+    pub fn pending(&self) -> bool {
+        self.mccp
+            .as_ref()
+            .is_some_and(|mccp| !mccp.inflated.is_empty())
+    }
+
     /// If the decoder is buffering a line, return the partial line, clearing the buffer.
     ///
     /// Returns `None` if there is no partial line content, or if the codec is not presently
@@ -72,6 +98,34 @@ impl Decoder for Codec {
     type Error = Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let Some(mut mccp) = self.mccp.take() else {
+            let item = self.decode_data(src)?;
+            // Everything the server sends after the MCCP2 subnegotiation is compressed.
+            if let Some(Item::Subnegotiation(telnet::option::MCCP2, _)) = &item {
+                self.mccp = Some(Mccp::default());
+            }
+            return Ok(item);
+        };
+
+        // On error the decompression state isn't put back, leaving the codec uncompressed.
+        mccp.inflate(src)?;
+        let item = self.decode_data(&mut mccp.inflated)?;
+        if item.is_some() || !mccp.finished {
+            self.mccp = Some(mccp);
+            return Ok(item);
+        }
+
+        // The server ended the compressed stream. Anything left over is decoded as normal,
+        // uncompressed, data.
+        let mut remaining = mccp.inflated;
+        remaining.unsplit(src.split());
+        *src = remaining;
+        self.decode(src)
+    }
+}
+
+impl Codec {
+    fn decode_data(&mut self, src: &mut BytesMut) -> Result<Option<Item>, Error> {
         loop {
             if src.is_empty() {
                 return Ok(None);
@@ -129,7 +183,7 @@ impl Encoder<Item> for Codec {
     type Error = Error;
 
     fn encode(&mut self, item: Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        item.encode(dst);
+        item.encode(dst, self.line_ending);
         Ok(())
     }
 }
@@ -143,6 +197,12 @@ pub enum Item {
     /// Telnet stream. The terminating `\r\n` is stripped from the line.
     Line(Bytes),
 
+    /// Raw data, without a terminating `\r\n`.
+    ///
+    /// The decoder never produces this item type, it's used to transmit data that shouldn't
+    /// be sent as a line. IAC bytes in the data are escaped when encoded.
+    Data(Bytes),
+
     /// A telnet negotiation command.
     Negotiation(Negotiation),
 
@@ -160,12 +220,15 @@ pub enum Item {
 }
 
 impl Item {
-    /// Encode the telnet item into the given buffer.
-    fn encode(&self, buf: &mut BytesMut) {
+    /// Encode the telnet item into the given buffer, ending lines with `line_ending`.
+    fn encode(&self, buf: &mut BytesMut, line_ending: LineEnding) {
         match self {
             Item::Line(line) => {
                 buf.put_slice(&escape_iac(line));
-                buf.put_slice(b"\r\n");
+                buf.put_slice(line_ending.as_bytes());
+            }
+            Item::Data(data) => {
+                buf.put_slice(&escape_iac(data));
             }
             Item::Negotiation(Negotiation::Will(opt)) => {
                 buf.put_slice(&[telnet::command::IAC, telnet::command::WILL, *opt]);
@@ -261,6 +324,57 @@ impl From<Negotiation> for Item {
     }
 }
 
+/// MCCP2 decompression state.
+///
+/// See <https://tintin.mudhalla.net/protocols/mccp/> for more information.
+#[derive(Debug)]
+struct Mccp {
+    inflater: Decompress,
+    /// Decompressed data that hasn't been decoded yet.
+    inflated: BytesMut,
+    /// Whether the server has ended the compressed stream.
+    finished: bool,
+}
+
+impl Default for Mccp {
+    fn default() -> Self {
+        Self {
+            inflater: Decompress::new(true),
+            inflated: BytesMut::with_capacity(4096),
+            finished: false,
+        }
+    }
+}
+
+impl Mccp {
+    /// Decompress as much of `src` as possible, consuming it from the buffer.
+    fn inflate(&mut self, src: &mut BytesMut) -> Result<(), Error> {
+        let mut out = [0; 8192];
+        while !self.finished && !src.is_empty() {
+            let (total_in, total_out) = (self.inflater.total_in(), self.inflater.total_out());
+            let status = self
+                .inflater
+                .decompress(src, &mut out, FlushDecompress::None)?;
+            let (Ok(consumed), Ok(produced)) = (
+                usize::try_from(self.inflater.total_in() - total_in),
+                usize::try_from(self.inflater.total_out() - total_out),
+            ) else {
+                return Err(Error::MccpLength);
+            };
+            src.advance(consumed);
+            self.inflated.put_slice(&out[..produced]);
+
+            match status {
+                Status::StreamEnd => self.finished = true,
+                Status::BufError => break,
+                Status::Ok if consumed == 0 && produced == 0 => break,
+                Status::Ok => {}
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Default)]
 enum State {
     #[default]
@@ -422,3 +536,89 @@ fn escape_iac(data: &Bytes) -> Bytes {
     }
     res.freeze()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+
+    use super::*;
+
+    #[test]
+    fn encodes_line_endings() {
+        for (line_ending, expected) in [
+            (LineEnding::Crlf, b"look\r\n".as_slice()),
+            (LineEnding::Lf, b"look\n"),
+            (LineEnding::Cr, b"look\r"),
+        ] {
+            let mut codec = Codec::new().with_line_ending(line_ending);
+            let mut dst = BytesMut::new();
+            codec
+                .encode(Item::Line(Bytes::from_static(b"look")), &mut dst)
+                .unwrap();
+            assert_eq!(dst.as_ref(), expected);
+        }
+    }
+
+    #[test]
+    fn mccp2_decompression() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"compressed\r\nlines\r\n").unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut src = BytesMut::new();
+        src.put_slice(b"before\r\n");
+        src.put_slice(&[
+            telnet::command::IAC,
+            telnet::command::SB,
+            telnet::option::MCCP2,
+            telnet::command::IAC,
+            telnet::command::SE,
+        ]);
+        src.put_slice(&compressed);
+        src.put_slice(b"after\r\n");
+
+        let mut codec = Codec::new();
+        let mut items = Vec::new();
+        while let Some(item) = codec.decode(&mut src).unwrap() {
+            items.push(item);
+        }
+
+        let lines = items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Line(line) => Some(line.as_ref()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [&b"before"[..], b"compressed", b"lines", b"after"].to_vec()
+        );
+        assert!(matches!(
+            items[1],
+            Item::Subnegotiation(telnet::option::MCCP2, _)
+        ));
+        assert!(codec.mccp.is_none());
+    }
+
+    #[test]
+    fn mccp2_corrupt_stream() {
+        let mut src = BytesMut::new();
+        src.put_slice(&[
+            telnet::command::IAC,
+            telnet::command::SB,
+            telnet::option::MCCP2,
+            telnet::command::IAC,
+            telnet::command::SE,
+        ]);
+        src.put_slice(b"not zlib data");
+
+        let mut codec = Codec::new();
+        assert!(codec.decode(&mut src).unwrap().is_some());
+        assert!(matches!(codec.decode(&mut src), Err(Error::Mccp(_))));
+        assert!(codec.mccp.is_none());
+    }
+}
@@ -66,6 +66,8 @@ pub mod option {
     u8_const!(AUTHENTICATION, 37);
     u8_const!(ENCRYPT, 38);
     u8_const!(NEWENVIRON, 39);
+    u8_const!(CHARSET, 42);
+    u8_const!(MSDP, 69);
     u8_const!(MSSP, 70);
     u8_const!(ZMP, 93);
     u8_const!(EXOPL, 255);
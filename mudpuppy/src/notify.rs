@@ -0,0 +1,112 @@
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use tracing::{trace, warn};
+
+/// The shortest time between terminal bells. Bells requested sooner are skipped.
+const BELL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The shortest time between notifications. Notifications requested sooner are skipped.
+const NOTIFICATION_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Rings the terminal bell, and shows desktop notifications, for scripts to flag important
+/// events.
+///
+/// Both are rate limited so that e.g. a trigger matching every line of a fight doesn't
+/// spam alerts.
+#[derive(Debug, Default)]
+pub struct Notifier {
+    last_bell: Option<Instant>,
+    last_notification: Option<Instant>,
+}
+
+impl Notifier {
+    /// Ring the terminal bell, returning false if it was skipped because a bell rang too
+    /// recently.
+    pub fn beep(&mut self) -> bool {
+        if !rate_limit(&mut self.last_bell, Instant::now(), BELL_INTERVAL) {
+            trace!("skipping rate limited bell");
+            return false;
+        }
+        if let Err(err) = io::stdout()
+            .write_all(b"\x07")
+            .and_then(|()| io::stdout().flush())
+        {
+            warn!("ringing terminal bell: {err}");
+        }
+        true
+    }
+
+    /// Ring the terminal bell and, where supported, show a desktop notification. Returns false
+    /// if the notification was skipped because one was shown too recently.
+    pub fn notify(&mut self, title: &str, body: &str) -> bool {
+        if !rate_limit(
+            &mut self.last_notification,
+            Instant::now(),
+            NOTIFICATION_INTERVAL,
+        ) {
+            trace!("skipping rate limited notification {title:?}");
+            return false;
+        }
+        self.beep();
+        show_notification(title, body);
+        true
+    }
+}
+
+// Returns true, and updates `last` to `now`, if at least `interval` has passed since `last`.
+fn rate_limit(last: &mut Option<Instant>, now: Instant, interval: Duration) -> bool {
+    if last.is_some_and(|last| now.saturating_duration_since(last) < interval) {
+        return false;
+    }
+    *last = Some(now);
+    true
+}
+
+#[cfg(feature = "notify-rust")]
+fn show_notification(title: &str, body: &str) {
+    let (title, body) = (title.to_string(), body.to_string());
+    // Talking to the desktop's notification service can block.
+    tokio::task::spawn_blocking(move || {
+        if let Err(err) = notify_rust::Notification::new()
+            .appname(crate::CRATE_NAME)
+            .summary(&title)
+            .body(&body)
+            .show()
+        {
+            warn!("showing desktop notification: {err}");
+        }
+    });
+}
+
+#[cfg(not(feature = "notify-rust"))]
+fn show_notification(title: &str, _body: &str) {
+    trace!("not showing desktop notification {title:?}, built without notify-rust");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limits_alerts() {
+        let start = Instant::now();
+        let interval = Duration::from_secs(1);
+        let mut last = None;
+
+        assert!(rate_limit(&mut last, start, interval));
+        assert!(!rate_limit(
+            &mut last,
+            start + Duration::from_millis(999),
+            interval
+        ));
+        assert!(rate_limit(&mut last, start + interval, interval));
+        // Skipped alerts don't push back the next allowed one.
+        assert!(!rate_limit(
+            &mut last,
+            start + Duration::from_millis(1500),
+            interval
+        ));
+        assert!(rate_limit(&mut last, start + interval * 2, interval));
+    }
+}
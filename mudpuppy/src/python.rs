@@ -4,31 +4,40 @@ use std::fmt::{Display, Formatter};
 use std::fs;
 use std::future::Future;
 use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use futures::stream::FuturesUnordered;
-use pyo3::exceptions::PyTypeError;
+use futures::stream::{self, FuturesUnordered, Stream, StreamExt};
+use pyo3::exceptions::{PyTypeError, PyValueError};
 use pyo3::ffi::c_str;
 use pyo3::types::{
     PyAnyMethods, PyBool, PyBoolMethods, PyFunction, PyList, PyListMethods, PyModule,
     PyModuleMethods, PyStringMethods, PyTuple,
 };
 use pyo3::{
-    pyclass, pymethods, pymodule, Bound, Py, PyAny, PyErr, PyObject, PyRef, PyResult, Python,
+    pyclass, pymethods, pymodule, Bound, IntoPyObjectExt, Py, PyAny, PyErr, PyObject, PyRef,
+    PyResult, Python,
 };
 use ratatui::style::Color;
 use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::{watch, RwLock};
 use tracing::{debug, error, info, instrument, trace, warn};
 
-use crate::app::{State, UiState};
-use crate::config::{config_dir, data_dir, GlobalConfig, KeyBindings};
-use crate::error::{AliasError, Error, TimerError, TriggerError};
+use crate::app::{State, TabAction, UiState};
+use crate::client::import::ImportFormat;
+use crate::config::{self, config_dir, data_dir, mud_config_keys, GlobalConfig, KeyBindings};
+use crate::error::{AliasError, Error, GmcpError, TimerError, TriggerError};
 use crate::model::{
-    Alias, AliasConfig, InputLine, KeyEvent, Mud, MudLine, PromptMode, PromptSignal, SessionInfo,
-    Shortcut, Timer, TimerConfig, Tls, Trigger, TriggerConfig,
+    AddressFamily, Alias, AliasConfig, Countdown, InputLine, InputTransform, KeyEvent, LineEnding,
+    LogFormat, MatchGroups, Mud, MudLine, PatternMatch, PromptMode, PromptSignal, Proxy,
+    SessionInfo, Shortcut, SubnegotiationHandler, Substitution, Timer, TimerConfig, TimerControl,
+    Tls, Trigger, TriggerConfig,
 };
+use crate::tui::dialog::{Dialog, DialogKind};
+use crate::tui::mudbuffer::OUTPUT_SECTION_NAME;
 use crate::{client, net, tui, Result, CRATE_NAME, GIT_COMMIT_HASH};
 
 /// Low level types and APIs for interacting with Mudpuppy.
@@ -45,6 +54,10 @@ pub fn mudpuppy_core(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<SessionInfo>()?;
     m.add_class::<Mud>()?;
     m.add_class::<Tls>()?;
+    m.add_class::<LogFormat>()?;
+    m.add_class::<AddressFamily>()?;
+    m.add_class::<LineEnding>()?;
+    m.add_class::<Proxy>()?;
     m.add_class::<MudLine>()?;
     m.add_class::<InputLine>()?;
     m.add_class::<Event>()?;
@@ -54,6 +67,8 @@ pub fn mudpuppy_core(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<TriggerConfig>()?;
     m.add_class::<Alias>()?;
     m.add_class::<AliasConfig>()?;
+    m.add_class::<PatternMatch>()?;
+    m.add_class::<Substitution>()?;
     m.add_class::<TimerConfig>()?;
     m.add_class::<Timer>()?;
     m.add_class::<PromptSignal>()?;
@@ -61,6 +76,8 @@ pub fn mudpuppy_core(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<client::Status>()?;
     m.add_class::<net::stream::Info>()?;
     m.add_class::<client::output::Output>()?;
+    m.add_class::<client::stats::SessionStats>()?;
+    m.add_class::<client::import::ImportReport>()?;
     m.add_class::<client::output::Item>()?;
     m.add_class::<client::input::Input>()?;
     m.add_class::<client::input::EchoState>()?;
@@ -74,8 +91,14 @@ pub fn mudpuppy_core(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     Ok(())
 }
 
+/// Initialize the Python environment, loading the built-in modules and then any user modules.
+///
+/// In `safe_mode` user modules aren't discovered or loaded at all.
+///
+/// # Errors
+/// If the Python environment can't be set up, or a module fails to load.
 #[instrument(level = "trace", skip(py_app))]
-pub fn init(py_app: PyApp) -> Result<(Py<EventHandlers>, Vec<PyObject>), Error> {
+pub fn init(py_app: PyApp, safe_mode: bool) -> Result<(Py<EventHandlers>, Vec<PyObject>), Error> {
     // Bind a rust backend to the Python logging module.
     pyo3_pylogger::register(CRATE_NAME);
 
@@ -129,9 +152,7 @@ builtins.print = mudpuppy_core.mudpuppy_core.print
         "cformat",
         "layout",
         "commands",
-        "telnet_charset",
-        "telnet_naws",
-        "history",
+        "on_connect",
         "cmd_misc",
         "cmd_py",
         "cmd_status",
@@ -142,7 +163,13 @@ builtins.print = mudpuppy_core.mudpuppy_core.print
     );
     debug!("found {} built-in py modules", builtin_modules.len());
 
-    let user_modules = user_modules()?;
+    let user_modules = match safe_mode {
+        true => {
+            warn!("safe mode: skipping user modules");
+            Vec::default()
+        }
+        false => user_modules()?,
+    };
     debug!("loaded {} user modules", user_modules.len());
 
     let all_modules: Vec<PyObject> = Python::with_gil(|_| {
@@ -176,6 +203,93 @@ pub fn reload(user_modules: &[PyObject]) -> Result<()> {
 
 pub type PyFuture = Pin<Box<dyn Future<Output = PyResult<PyObject>> + Send + 'static>>;
 
+/// Convert a Python awaitable into a [`PyFuture`] that logs a warning naming `label` if it
+/// takes longer than the configured slow callback threshold to complete.
+///
+/// # Errors
+/// If the awaitable can't be converted into a Rust future.
+pub fn timed_future(
+    config: &GlobalConfig,
+    label: String,
+    awaitable: Bound<'_, PyAny>,
+) -> PyResult<PyFuture> {
+    let threshold = config.slow_callback_threshold();
+    let future = pyo3_async_runtimes::tokio::into_future(awaitable)?;
+    Ok(Box::pin(async move {
+        let start = Instant::now();
+        let res = future.await;
+        let elapsed = start.elapsed();
+        if threshold.is_some_and(|threshold| elapsed > threshold) {
+            warn!("slow callback: {label} took {elapsed:?}");
+        }
+        res
+    }))
+}
+
+/// Returns a label describing a Python callable for logging, e.g. `module.function`.
+#[must_use]
+pub fn callable_label(callable: &Bound<'_, PyAny>) -> String {
+    let attr = |name| {
+        callable
+            .getattr(name)
+            .and_then(|value| value.extract::<String>())
+            .ok()
+    };
+    match (attr("__module__"), attr("__qualname__")) {
+        (Some(module), Some(name)) => format!("{module}.{name}"),
+        (None, Some(name)) => name,
+        _ => callable.to_string(),
+    }
+}
+
+/// Call a trigger, alias or highlight `callable` with `args` followed by the positional match
+/// `groups`.
+///
+/// The named match groups are passed as a further dict argument only if `named` is true, so
+/// callables written to take just the positional groups keep working. See
+/// [`accepts_named_groups`].
+///
+/// # Errors
+/// If calling the callable raises an exception.
+pub fn call_with_groups<'py>(
+    callable: &Bound<'py, PyAny>,
+    args: impl IntoIterator<Item = PyObject>,
+    groups: &MatchGroups,
+    named: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    let py = callable.py();
+    let mut args = args.into_iter().collect::<Vec<_>>();
+    args.push(groups.positional.clone().into_py_any(py)?);
+    if named {
+        args.push(groups.named.clone().into_py_any(py)?);
+    }
+    callable.call1(PyTuple::new(py, args)?)
+}
+
+/// Returns true if `callable` accepts the dict of named match groups after `args` leading
+/// arguments and the list of positional groups.
+///
+/// This inspects the callable's signature, so is done once when a callable is set rather
+/// than for each match.
+#[must_use]
+pub fn accepts_named_groups(callable: &Bound<'_, PyAny>, args: usize) -> bool {
+    accepts_positional_args(callable, args + 2)
+}
+
+// Returns true if `callable` can be called with `count` positional arguments. Callables
+// without an inspectable signature are assumed not to accept them.
+fn accepts_positional_args(callable: &Bound<'_, PyAny>, count: usize) -> bool {
+    let py = callable.py();
+    let bind = || -> PyResult<()> {
+        let signature = py
+            .import("inspect")?
+            .call_method1("signature", (callable,))?;
+        signature.call_method1("bind", PyTuple::new(py, vec![py.None(); count])?)?;
+        Ok(())
+    };
+    bind().is_ok()
+}
+
 #[derive(Debug, Clone)]
 #[pyclass(name = "MudpuppyCore")]
 #[allow(clippy::module_name_repetitions)]
@@ -235,6 +349,63 @@ impl PyApp {
         })
     }
 
+    fn open_dialog<'py>(
+        &self,
+        py: Python<'py>,
+        dialog: Dialog,
+        timeout_ms: Option<u64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        Self::require_coroutine(py, "dialog callback", &dialog.callback)?;
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id(dialog.session_id)
+                .ok_or(Error::UnknownSession(dialog.session_id))?;
+            debug!(
+                "opening dialog for {}: {:?}",
+                dialog.session_id, dialog.message
+            );
+            state.dialogs.push_back(Dialog {
+                deadline: timeout_ms
+                    .map(|ms| tokio::time::Instant::now() + Duration::from_millis(ms)),
+                ..dialog
+            });
+            Ok(())
+        })
+    }
+
+    fn toggle_group<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        group: String,
+        enabled: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        debug!("setting group {group} for {session_id} enabled: {enabled}");
+        with_state!(self, py, |mut state| {
+            let client = state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?;
+
+            Python::with_gil(|py| {
+                let in_group = |config: Option<&String>| config == Some(&group);
+                let mut toggled = 0;
+                for (_, trigger) in &mut client.triggers {
+                    if in_group(trigger.config.borrow(py).group.as_ref()) {
+                        trigger.enabled = enabled;
+                        toggled += 1;
+                    }
+                }
+                for (_, alias) in &mut client.aliases {
+                    if in_group(alias.config.borrow(py).group.as_ref()) {
+                        alias.enabled = enabled;
+                        toggled += 1;
+                    }
+                }
+                Ok(toggled)
+            })
+        })
+    }
+
     // Verify that a callback is an async coroutine function.
     fn require_coroutine(py: Python<'_>, name: &str, callback: &Py<PyAny>) -> PyResult<()> {
         // TODO(XXX): possible optimization - cache ref to this fn?
@@ -351,10 +522,84 @@ impl PyApp {
         with_state!(self, py, |state| Ok(state.active_session_id))
     }
 
+    fn notify<'py>(
+        &self,
+        py: Python<'py>,
+        title: String,
+        body: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let enabled = self.config.notifications_enabled();
+        with_state!(self, py, |mut state| Ok(
+            enabled && state.notifier.notify(&title, &body)
+        ))
+    }
+
+    fn beep<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let enabled = self.config.notifications_enabled();
+        with_state!(self, py, |mut state| Ok(enabled && state.notifier.beep()))
+    }
+
+    fn active_tab<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| Ok(state.active_tab))
+    }
+
+    fn switch_to_tab<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?;
+            state
+                .tab_requests
+                .push_back(TabAction::Focus { session_id });
+            Ok(())
+        })
+    }
+
+    fn next_tab<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state.tab_requests.push_back(TabAction::Next);
+            Ok(())
+        })
+    }
+
+    fn previous_tab<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state.tab_requests.push_back(TabAction::Prev);
+            Ok(())
+        })
+    }
+
     fn sessions<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |state| Ok(state.all_client_info()))
     }
 
+    fn sessions_for_mud<'py>(
+        &self,
+        py: Python<'py>,
+        mud_name: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            let sessions = state
+                .clients_for_mud(&mud_name)
+                .map(|client| client.info.as_ref().clone())
+                .collect::<Vec<_>>();
+            Ok(sessions)
+        })
+    }
+
+    fn is_mud_connected<'py>(
+        &self,
+        py: Python<'py>,
+        mud_name: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            let connected = state
+                .clients_for_mud(&mud_name)
+                .any(client::Client::connected);
+            Ok(connected)
+        })
+    }
+
     fn session_info<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |state| {
             Python::with_gil(|_| {
@@ -377,22 +622,234 @@ impl PyApp {
         })
     }
 
+    #[pyo3(signature = (session_id, message, callback, timeout_ms=None))]
+    fn confirm<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        message: String,
+        callback: Py<PyAny>,
+        timeout_ms: Option<u64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let dialog = Dialog {
+            session_id,
+            message,
+            kind: DialogKind::Confirm,
+            callback,
+            deadline: None,
+        };
+        self.open_dialog(py, dialog, timeout_ms)
+    }
+
+    #[pyo3(signature = (session_id, message, callback, timeout_ms=None))]
+    fn prompt<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        message: String,
+        callback: Py<PyAny>,
+        timeout_ms: Option<u64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let dialog = Dialog {
+            session_id,
+            message,
+            kind: DialogKind::Prompt {
+                input: String::new(),
+            },
+            callback,
+            deadline: None,
+        };
+        self.open_dialog(py, dialog, timeout_ms)
+    }
+
+    #[pyo3(signature = (session_id, message, options, callback, timeout_ms=None))]
+    fn select<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        message: String,
+        options: Vec<String>,
+        callback: Py<PyAny>,
+        timeout_ms: Option<u64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        if options.is_empty() {
+            return Err(Error::NoDialogOptions.into());
+        }
+        let dialog = Dialog {
+            session_id,
+            message,
+            kind: DialogKind::Select {
+                options,
+                selected: 0,
+            },
+            callback,
+            deadline: None,
+        };
+        self.open_dialog(py, dialog, timeout_ms)
+    }
+
+    #[pyo3(signature = (session_id, until_pattern, callback, max_lines=500))]
+    fn capture_block<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        until_pattern: String,
+        callback: Py<PyAny>,
+        max_lines: usize,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            Ok(state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .capture_block(&until_pattern, max_lines, callback)?)
+        })
+    }
+
+    fn cancel_capture<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            Ok(state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .cancel_capture())
+        })
+    }
+
+    fn session_data<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            Python::with_gil(|py| {
+                Ok(state
+                    .client_for_id(session_id)
+                    .ok_or(Error::UnknownSession(session_id))?
+                    .data
+                    .clone_ref(py))
+            })
+        })
+    }
+
+    fn banner<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            Ok(state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .banner
+                .clone())
+        })
+    }
+
+    fn session_stats<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            Ok(state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .stats())
+        })
+    }
+
     fn mud_config(&self, id: &SessionInfo) -> Option<Mud> {
         self.config.lookup_mud(&id.mud_name)
     }
 
+    #[staticmethod]
+    fn mtts() -> u32 {
+        client::terminal_type::mtts(tui::terminal::ColorSupport::detect())
+    }
+
+    #[pyo3(signature = (speedwalk, directions=None))]
+    fn expand_speedwalk(
+        &self,
+        speedwalk: &str,
+        directions: Option<String>,
+    ) -> Result<Vec<String>, Error> {
+        let directions = directions.unwrap_or_else(|| self.config.speedwalk_directions());
+        client::speedwalk::expand(speedwalk, &directions)
+    }
+
+    fn dump_resolved_settings(&self, py: Python<'_>, mud_name: &str) -> PyResult<PyObject> {
+        let mud = self.config.must_lookup_mud(mud_name)?;
+        let explicit_keys = mud_config_keys(mud_name)?;
+
+        let serde_json::Value::Object(fields) = serde_json::to_value(&mud)
+            .map_err(|e| Error::Internal(format!("serializing MUD config: {e}")))?
+        else {
+            return Err(Error::Internal("MUD config didn't serialize to a map".to_string()).into());
+        };
+
+        // Annotate each resolved value with where it came from.
+        let resolved = fields
+            .into_iter()
+            .map(|(key, value)| {
+                let source = match explicit_keys.contains(&key) {
+                    true => "config",
+                    false => "default",
+                };
+                (key, serde_json::json!({"value": value, "source": source}))
+            })
+            .collect::<serde_json::Map<_, _>>();
+
+        Ok(py
+            .import("json")?
+            .call_method1("loads", (serde_json::Value::Object(resolved).to_string(),))?
+            .unbind())
+    }
+
+    #[staticmethod]
+    fn get_credential(py: Python<'_>, name: &str) -> Result<Option<String>, Error> {
+        py.allow_threads(|| config::credential(name))
+            .map_err(Into::into)
+    }
+
+    #[staticmethod]
+    fn set_credential(py: Python<'_>, name: &str, value: &str) -> Result<(), Error> {
+        py.allow_threads(|| config::set_credential(name, value))
+            .map_err(Into::into)
+    }
+
+    #[staticmethod]
+    fn delete_credential(py: Python<'_>, name: &str) -> Result<bool, Error> {
+        py.allow_threads(|| config::delete_credential(name))
+            .map_err(Into::into)
+    }
+
+    #[pyo3(signature = (session_id, line, *, split=true, expand_secrets=false))]
     fn send_line<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
         line: String,
+        split: bool,
+        expand_secrets: bool,
     ) -> PyResult<Bound<'py, PyAny>> {
+        // Secrets are read before taking the state lock, since the OS keyring may block.
+        let secrets = match expand_secrets {
+            true => py
+                .allow_threads(|| config::read_secrets(&line))
+                .map_err(Error::from)?,
+            false => None,
+        };
         with_state!(self, py, |mut state| {
-            state
+            let client = state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?;
+            match (secrets, split) {
+                (Some(secrets), split) => client.send_line_with_secrets(&line, &secrets, split),
+                (None, true) => client.send_line(InputLine::new(line, true, true)),
+                (None, false) => client.send_line_unsplit(InputLine::new(line, true, true)),
+            }
+            .map_err(Into::into)
+        })
+    }
+
+    fn send_password<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        password: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            Ok(state
                 .client_for_id_mut(session_id)
                 .ok_or(Error::UnknownSession(session_id))?
-                .send_line(InputLine::new(line, true, true))
-                .map_err(Into::into)
+                .send_password(password)?)
         })
     }
 
@@ -413,476 +870,652 @@ impl PyApp {
         })
     }
 
-    fn connect<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
-        with_state!(self, py, |mut state| {
-            state
-                .client_for_id_mut(session_id)
-                .ok_or(Error::UnknownSession(session_id))?
-                .connect()
-                .await
-                .map_err(Into::into)
-        })
-    }
-
-    fn disconnect<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+    fn queue_commands<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        lines: Vec<String>,
+        interval: f64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let interval = Duration::try_from_secs_f64(interval)
+            .map_err(|e| PyValueError::new_err(format!("invalid interval: {e}")))?;
         with_state!(self, py, |mut state| {
             state
                 .client_for_id_mut(session_id)
                 .ok_or(Error::UnknownSession(session_id))?
-                .disconnect()
-                .await
+                .queue_lines(
+                    lines
+                        .into_iter()
+                        .map(|line| InputLine::new(line, true, true))
+                        .collect(),
+                    interval,
+                )
                 .map_err(Into::into)
         })
     }
 
-    fn request_enable_option<'py>(
+    fn queued_commands<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        option: u8,
     ) -> PyResult<Bound<'py, PyAny>> {
-        with_state!(self, py, |mut state| {
-            state
-                .client_for_id_mut(session_id)
+        with_state!(self, py, |state| {
+            Ok(state
+                .client_for_id(session_id)
                 .ok_or(Error::UnknownSession(session_id))?
-                .request_enable_option(option)
-                .map_err(Into::into)
+                .queued_commands())
         })
     }
 
-    fn request_disable_option<'py>(
+    fn cancel_queued_commands<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        option: u8,
     ) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |mut state| {
-            state
+            Ok(state
                 .client_for_id_mut(session_id)
                 .ok_or(Error::UnknownSession(session_id))?
-                .request_disable_option(option)
-                .map_err(Into::into)
+                .cancel_queued_commands())
         })
     }
 
-    fn send_subnegotiation<'py>(
+    fn sent_commands<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        option: u8,
-        data: Vec<u8>,
+        n: usize,
     ) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |state| {
-            state
+            Ok(state
                 .client_for_id(session_id)
                 .ok_or(Error::UnknownSession(session_id))?
-                .send_subnegotiation(option, data)
-                .map_err(Into::into)
+                .sent_lines(n))
         })
     }
 
-    fn new_trigger<'py>(
+    fn input_history<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            Ok(state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .input
+                .history())
+        })
+    }
+
+    #[pyo3(signature = (session_id, path=None, format=None))]
+    fn start_logging<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        config: Py<TriggerConfig>,
-        module: String,
+        path: Option<PathBuf>,
+        format: Option<LogFormat>,
     ) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |mut state| {
-            let triggers = &mut state
+            Ok(state
                 .client_for_id_mut(session_id)
                 .ok_or(Error::UnknownSession(session_id))?
-                .triggers;
-
-            Python::with_gil(|pyy| {
-                let new_config: PyRef<'_, TriggerConfig> = config.extract(pyy)?;
-                for (_, t) in &mut *triggers {
-                    let old_config: PyRef<'_, TriggerConfig> = t.config.extract(pyy)?;
-                    if new_config.name == old_config.name {
-                        warn!("trigger with name {} already exists", new_config.name);
-                        return Ok::<_, PyErr>(None);
-                    }
-                }
+                .start_logging(path.as_deref(), format)?
+                .display()
+                .to_string())
+        })
+    }
 
-                if let Some(callback) = &new_config.callback {
-                    Self::require_coroutine(pyy, "trigger callback", callback)?;
-                }
-
-                if let Some(highlight) = &new_config.highlight {
-                    Self::require_callable(pyy, "trigger highlight callback", highlight)?;
-                }
+    fn stop_logging<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            Ok(state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .stop_logging()
+                .map(|path| path.display().to_string()))
+        })
+    }
 
-                Ok(Some(triggers.construct(|id| Trigger {
-                    id,
-                    enabled: true,
-                    module,
-                    config,
-                })))
-            })
+    fn log_path<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            let path = state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .log_path()
+                .map(|path| path.display().to_string());
+            Ok(path)
         })
     }
 
-    fn get_trigger<'py>(
+    #[pyo3(signature = (session_id, n=None))]
+    fn prompt_history<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        trig_id: u32,
+        n: Option<usize>,
     ) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |state| {
-            Python::with_gil(|_| {
-                Ok(state
-                    .client_for_id(session_id)
-                    .ok_or(Error::UnknownSession(session_id))?
-                    .triggers
-                    .get(trig_id)
-                    .cloned())
-            })
+            let history = state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .prompt_history(n.unwrap_or(usize::MAX))
+                .into_iter()
+                .map(|(at, prompt)| {
+                    let at = at.duration_since(UNIX_EPOCH).unwrap_or_default();
+                    (at.as_secs_f64(), prompt)
+                })
+                .collect::<Vec<_>>();
+            Ok(history)
         })
     }
 
-    fn disable_trigger<'py>(
+    fn gag_prompt<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            Ok(state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .gag_prompt())
+        })
+    }
+
+    #[pyo3(signature = (session_id, data, newline=true))]
+    fn send_bytes<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        trig_id: u32,
+        data: Vec<u8>,
+        newline: bool,
     ) -> PyResult<Bound<'py, PyAny>> {
-        self.toggle_trigger(py, session_id, trig_id, false)
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .send_bytes(data.into(), newline)
+                .map_err(Into::into)
+        })
     }
 
-    fn enable_trigger<'py>(
+    fn send_raw<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        trig_id: u32,
+        data: Vec<u8>,
     ) -> PyResult<Bound<'py, PyAny>> {
-        self.toggle_trigger(py, session_id, trig_id, true)
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .send_raw(data.into())
+                .map_err(Into::into)
+        })
     }
 
-    fn remove_trigger<'py>(
+    fn connect<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .connect()
+                .map_err(Into::into)
+        })
+    }
+
+    fn disconnect<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .disconnect()
+                .await
+                .map_err(Into::into)
+        })
+    }
+
+    fn connect_all<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            let connects = state
+                .clients_mut()
+                .map(|client| (client.info.id, client.connect()))
+                .collect::<Vec<_>>();
+            Ok(batch_results(stream::iter(connects)).await)
+        })
+    }
+
+    fn disconnect_all<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            let disconnects = state
+                .clients_mut()
+                .map(|client| async move { (client.info.id, client.disconnect().await) })
+                .collect::<FuturesUnordered<_>>();
+            Ok(batch_results(disconnects).await)
+        })
+    }
+
+    #[pyo3(signature = (session_id, at, reconnect=false))]
+    fn schedule_connect<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        trig_id: u32,
+        at: f64,
+        reconnect: bool,
     ) -> PyResult<Bound<'py, PyAny>> {
+        let at = UNIX_EPOCH
+            + Duration::try_from_secs_f64(at)
+                .map_err(|e| PyValueError::new_err(format!("invalid timestamp: {e}")))?;
+        let task_state = self.state.clone();
+        let task_waker = self.waker.clone();
         with_state!(self, py, |mut state| {
-            state
+            let client = state
                 .client_for_id_mut(session_id)
-                .ok_or(Error::UnknownSession(session_id))?
-                .triggers
-                .remove(trig_id);
+                .ok_or(Error::UnknownSession(session_id))?;
+            client.cancel_scheduled_connect();
+            info!("scheduling connect for {at:?}");
+            client.scheduled_connect = Some(tokio::spawn(scheduled_connect(
+                session_id, at, reconnect, task_state, task_waker,
+            )));
             Ok(())
         })
     }
 
-    fn remove_module_triggers<'py>(
+    fn cancel_scheduled_connect<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        module: String,
     ) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |mut state| {
-            let triggers = &mut state
+            Ok(state
                 .client_for_id_mut(session_id)
                 .ok_or(Error::UnknownSession(session_id))?
-                .triggers;
-            let triggers_to_remove = triggers
-                .iter()
-                .filter_map(|(id, trigger)| {
-                    if trigger.module == module {
-                        Some(*id)
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<_>>();
-            debug!(
-                "removing {} triggers that were added by module {}",
-                triggers_to_remove.len(),
-                module
-            );
-
-            for trigger_id in triggers_to_remove {
-                triggers.remove(trigger_id);
-            }
-            Ok(())
+                .cancel_scheduled_connect())
         })
     }
 
-    fn triggers<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
-        with_state!(self, py, |state| {
-            Python::with_gil(|_| {
-                Ok(state
-                    .client_for_id(session_id)
-                    .ok_or(Error::UnknownSession(session_id))?
-                    .triggers
-                    .iter()
-                    .map(|(_, a)| a.clone())
-                    .collect::<Vec<_>>())
-            })
+    fn cancel_reconnect<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            Ok(state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .cancel_reconnect())
         })
     }
 
-    fn new_alias<'py>(
+    fn request_enable_option<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        config: Py<AliasConfig>,
-        module: String,
+        option: u8,
     ) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |mut state| {
-            let aliases = &mut state
+            state
                 .client_for_id_mut(session_id)
                 .ok_or(Error::UnknownSession(session_id))?
-                .aliases;
-
-            Python::with_gil(|pyy| {
-                let new_config: PyRef<'_, AliasConfig> = config.extract(pyy)?;
-                for (_, t) in &mut *aliases {
-                    let old_config: PyRef<'_, AliasConfig> = t.config.extract(pyy)?;
-                    if new_config.name == old_config.name {
-                        warn!("alias with name {} already exists", new_config.name);
-                        return Ok::<_, PyErr>(None);
-                    }
-                }
-
-                if let Some(callback) = &new_config.callback {
-                    Self::require_coroutine(pyy, "alias callback", callback)?;
-                }
+                .request_enable_option(option)
+                .map_err(Into::into)
+        })
+    }
 
-                Ok(Some(aliases.construct(|id| Alias {
-                    id,
-                    enabled: true,
-                    config,
-                    module,
-                })))
-            })
+    fn request_disable_option<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        option: u8,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .request_disable_option(option)
+                .map_err(Into::into)
         })
     }
 
-    fn get_alias<'py>(
+    fn send_subnegotiation<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        alias_id: u32,
+        option: u8,
+        data: Vec<u8>,
     ) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |state| {
-            Python::with_gil(|_| {
-                Ok(state
-                    .client_for_id(session_id)
-                    .ok_or(Error::UnknownSession(session_id))?
-                    .aliases
-                    .get(alias_id)
-                    .cloned())
-            })
+            state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .send_subnegotiation(option, data)
+                .map_err(Into::into)
         })
     }
 
-    fn aliases<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+    fn prompt_mode<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |state| {
-            Python::with_gil(|_| {
-                Ok(state
-                    .client_for_id(session_id)
-                    .ok_or(Error::UnknownSession(session_id))?
-                    .aliases
-                    .iter()
-                    .map(|(_, a)| a.clone())
-                    .collect::<Vec<_>>())
-            })
+            Ok(state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .prompt_mode())
         })
     }
 
-    fn disable_alias<'py>(
+    fn set_prompt_mode<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        alias_id: u32,
+        mode: PromptMode,
     ) -> PyResult<Bound<'py, PyAny>> {
-        self.toggle_alias(py, session_id, alias_id, false)
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .set_prompt_mode(mode);
+            Ok(())
+        })
     }
 
-    fn remove_alias<'py>(
+    fn on_subnegotiation<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        alias_id: u32,
+        option: u8,
+        callback: Py<PyAny>,
+        module: String,
     ) -> PyResult<Bound<'py, PyAny>> {
+        Self::require_coroutine(py, "subnegotiation callback", &callback)?;
         with_state!(self, py, |mut state| {
+            debug!("adding option {option} subnegotiation handler for module {module}");
             state
                 .client_for_id_mut(session_id)
                 .ok_or(Error::UnknownSession(session_id))?
-                .aliases
-                .remove(alias_id);
+                .subnegotiation_handlers
+                .push(SubnegotiationHandler {
+                    option,
+                    module,
+                    callback,
+                });
             Ok(())
         })
     }
 
-    fn remove_module_aliases<'py>(
+    fn add_input_transform<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
+        callback: Py<PyAny>,
         module: String,
     ) -> PyResult<Bound<'py, PyAny>> {
+        Self::require_callable(py, "input transform callback", &callback)?;
         with_state!(self, py, |mut state| {
-            let aliases = &mut state
+            debug!("adding input transform for module {module}");
+            state
                 .client_for_id_mut(session_id)
                 .ok_or(Error::UnknownSession(session_id))?
-                .aliases;
-            let aliases_to_remove = aliases
-                .iter()
-                .filter_map(|(id, alias)| {
-                    if alias.module == module {
-                        Some(*id)
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<_>>();
-            debug!(
-                "removing {} aliases that were added by module {}",
-                aliases_to_remove.len(),
-                module
-            );
-
-            for alias_id in aliases_to_remove {
-                aliases.remove(alias_id);
-            }
+                .input_transforms
+                .push(InputTransform { module, callback });
             Ok(())
         })
     }
 
-    fn enable_alias<'py>(
+    fn remove_module_input_transforms<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        alias_id: u32,
+        module: String,
     ) -> PyResult<Bound<'py, PyAny>> {
-        self.toggle_alias(py, session_id, alias_id, true)
+        with_state!(self, py, |mut state| {
+            let transforms = &mut state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .input_transforms;
+            let before = transforms.len();
+            transforms.retain(|transform| transform.module != module);
+            debug!(
+                "removed {} input transforms that were added by module {}",
+                before - transforms.len(),
+                module
+            );
+            Ok(())
+        })
     }
 
-    fn new_timer<'py>(
+    #[pyo3(signature = (session_id, path, module, format="json"))]
+    fn import_triggers<'py>(
         &self,
         py: Python<'py>,
-        config: Py<TimerConfig>,
+        session_id: u32,
+        path: &str,
         module: String,
+        format: &str,
     ) -> PyResult<Bound<'py, PyAny>> {
+        let format = format
+            .parse::<ImportFormat>()
+            .map_err(|_| Error::Import(format!("unknown import format {format:?}")))?;
+        let text = fs::read_to_string(path).map_err(Error::from)?;
         with_state!(self, py, |mut state| {
-            let timers = &mut state.timers;
+            Ok(state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .import_triggers(&text, format, &module)?)
+        })
+    }
 
-            trace!("setting up a new timer for module {module}");
+    fn new_trigger<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        config: Py<TriggerConfig>,
+        module: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            let triggers = &mut state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .triggers;
 
             Python::with_gil(|pyy| {
-                let new_config: PyRef<'_, TimerConfig> = config.extract(pyy)?;
-                for (_, t) in &mut *timers {
-                    let old_config: PyRef<'_, TimerConfig> = t.config.extract(pyy)?;
+                let new_config: PyRef<'_, TriggerConfig> = config.extract(pyy)?;
+                for (_, t) in &mut *triggers {
+                    let old_config: PyRef<'_, TriggerConfig> = t.config.extract(pyy)?;
                     if new_config.name == old_config.name {
-                        warn!("timer with name {} already exists", new_config.name);
+                        warn!("trigger with name {} already exists", new_config.name);
                         return Ok::<_, PyErr>(None);
                     }
                 }
-                let (stop_tx, stop_rx) = watch::channel(false);
 
-                Self::require_coroutine(pyy, "timer callback", &new_config.callback)?;
+                if let Some(callback) = &new_config.callback {
+                    Self::require_coroutine(pyy, "trigger callback", callback)?;
+                }
 
-                let timer_id = timers.construct(|id| Timer {
+                if let Some(highlight) = &new_config.highlight {
+                    Self::require_callable(pyy, "trigger highlight callback", highlight)?;
+                }
+
+                Ok(Some(triggers.construct(|id| Trigger {
                     id,
-                    running: true,
-                    stop_tx,
+                    enabled: true,
                     module,
                     config,
-                });
-
-                let task_locals = Python::with_gil(pyo3_async_runtimes::tokio::get_current_locals)?;
-                tokio::spawn(pyo3_async_runtimes::tokio::scope(
-                    task_locals,
-                    run_timer(timer_id, new_config.clone(), stop_rx),
-                ));
+                })))
+            })
+        })
+    }
 
-                Ok(Some(timer_id))
+    fn get_trigger<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        trig_id: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            Python::with_gil(|_| {
+                Ok(state
+                    .client_for_id(session_id)
+                    .ok_or(Error::UnknownSession(session_id))?
+                    .triggers
+                    .get(trig_id)
+                    .cloned())
             })
         })
     }
 
-    fn start_timer<'py>(&self, py: Python<'py>, timer_id: u32) -> PyResult<Bound<'py, PyAny>> {
-        with_state!(self, py, |mut state| {
-            let timers = &mut state.timers;
-            let timer = timers
-                .get_mut(timer_id)
-                .ok_or(Error::Timer(TimerError::UnknownId(timer_id)))?;
+    fn test_trigger<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        trig_id: u32,
+        sample: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            let trigger = state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .triggers
+                .get(trig_id)
+                .ok_or(Error::Trigger(TriggerError::UnknownId(trig_id)))?;
+            Ok(Python::with_gil(|py| {
+                trigger.config.borrow(py).test(&sample)
+            }))
+        })
+    }
 
-            if timer.running {
-                warn!("timer {} is already running", timer.id);
-                return Ok(());
-            }
+    fn disable_trigger<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        trig_id: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.toggle_trigger(py, session_id, trig_id, false)
+    }
 
-            Python::with_gil(|pyy| {
-                let config: PyRef<'_, TimerConfig> = timer.config.extract(pyy)?;
-                let (stop_tx, stop_rx) = watch::channel(false);
+    fn enable_trigger<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        trig_id: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.toggle_trigger(py, session_id, trig_id, true)
+    }
 
-                timer.stop_tx = stop_tx;
-                timer.running = true;
+    fn enable_group<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        group: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.toggle_group(py, session_id, group, true)
+    }
 
-                let task_locals = Python::with_gil(pyo3_async_runtimes::tokio::get_current_locals)?;
-                tokio::spawn(pyo3_async_runtimes::tokio::scope(
-                    task_locals,
-                    run_timer(timer_id, config.clone(), stop_rx),
-                ));
+    fn disable_group<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        group: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.toggle_group(py, session_id, group, false)
+    }
 
-                Ok(())
-            })
+    fn remove_trigger<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        trig_id: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .triggers
+                .remove(trig_id);
+            Ok(())
         })
     }
 
-    fn stop_timer<'py>(&self, py: Python<'py>, id: u32) -> PyResult<Bound<'py, PyAny>> {
+    #[pyo3(signature = (session_id, pattern, replacement, *, match_ansi=false))]
+    fn add_substitution<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        pattern: String,
+        replacement: String,
+        match_ansi: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |mut state| {
-            match state.timers.get_mut(id) {
-                Some(timer) => {
-                    if timer.running {
-                        timer.running = false;
-                        timer.stop_tx.send(true).ok();
-                    } else {
-                        warn!("timer {} is already stopped", timer.id);
-                    }
+            Ok(state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .add_substitution(&pattern, replacement, match_ansi)?)
+        })
+    }
 
-                    Ok(())
-                }
-                None => Err(Error::Timer(TimerError::UnknownId(id)).into()),
-            }
+    fn remove_substitution<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        substitution_id: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .remove_substitution(substitution_id);
+            Ok(())
         })
     }
 
-    fn get_timer<'py>(&self, py: Python<'py>, id: u32) -> PyResult<Bound<'py, PyAny>> {
-        with_state!(self, py, |state| Python::with_gil(|_| {
-            Ok(state.timers.get(id).cloned())
-        }))
+    fn substitutions<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            let mut substitutions = state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .substitutions
+                .iter()
+                .map(|(_, s)| s.clone())
+                .collect::<Vec<_>>();
+            substitutions.sort_by_key(|s| s.id);
+            Ok(substitutions)
+        })
     }
 
-    fn remove_timer<'py>(&self, py: Python<'py>, id: u32) -> PyResult<Bound<'py, PyAny>> {
+    fn set_triggers_enabled<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        enabled: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |mut state| {
-            let timer = state
-                .timers
-                .get_mut(id)
-                .ok_or(Error::Timer(TimerError::UnknownId(id)))?;
-            if timer.running {
-                timer.running = false;
-                timer.stop_tx.send(true).ok();
-            }
-            info!("removed timer {id}");
-            state.timers.remove(id);
+            debug!("setting triggers enabled to {enabled} for session {session_id}");
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .triggers_enabled = enabled;
             Ok(())
         })
     }
 
-    fn remove_module_timers<'py>(
+    fn triggers_enabled<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            Ok(state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .triggers_enabled)
+        })
+    }
+
+    fn remove_module_triggers<'py>(
         &self,
         py: Python<'py>,
+        session_id: u32,
         module: String,
     ) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |mut state| {
-            let timers = &mut state.timers;
-            let timers_to_remove = timers
+            let triggers = &mut state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .triggers;
+            let triggers_to_remove = triggers
                 .iter()
-                .filter_map(|(id, timer)| {
-                    if timer.module == module {
+                .filter_map(|(id, trigger)| {
+                    if trigger.module == module {
                         Some(*id)
                     } else {
                         None
@@ -890,23 +1523,25 @@ impl PyApp {
                 })
                 .collect::<Vec<_>>();
             debug!(
-                "removing {} timers that were added by module {}",
-                timers_to_remove.len(),
+                "removing {} triggers that were added by module {}",
+                triggers_to_remove.len(),
                 module
             );
 
-            for timer_id in timers_to_remove {
-                timers.remove(timer_id);
+            for trigger_id in triggers_to_remove {
+                triggers.remove(trigger_id);
             }
             Ok(())
         })
     }
 
-    fn timers<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    fn triggers<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |state| {
             Python::with_gil(|_| {
                 Ok(state
-                    .timers
+                    .client_for_id(session_id)
+                    .ok_or(Error::UnknownSession(session_id))?
+                    .triggers
                     .iter()
                     .map(|(_, a)| a.clone())
                     .collect::<Vec<_>>())
@@ -914,215 +1549,1037 @@ impl PyApp {
         })
     }
 
-    fn get_input<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
-        with_state!(self, py, |state| {
-            Ok(state
-                .client_for_id(session_id)
-                .ok_or(Error::UnknownSession(session_id))?
-                .input
-                .value())
-        })
-    }
+    fn new_alias<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        config: Py<AliasConfig>,
+        module: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            let aliases = &mut state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .aliases;
+
+            Python::with_gil(|pyy| {
+                let new_config: PyRef<'_, AliasConfig> = config.extract(pyy)?;
+                for (_, t) in &mut *aliases {
+                    let old_config: PyRef<'_, AliasConfig> = t.config.extract(pyy)?;
+                    if new_config.name == old_config.name {
+                        warn!("alias with name {} already exists", new_config.name);
+                        return Ok::<_, PyErr>(None);
+                    }
+                }
+
+                if let Some(callback) = &new_config.callback {
+                    Self::require_coroutine(pyy, "alias callback", callback)?;
+                }
+
+                Ok(Some(aliases.construct(|id| Alias {
+                    id,
+                    enabled: true,
+                    config,
+                    module,
+                })))
+            })
+        })
+    }
+
+    fn get_alias<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        alias_id: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            Python::with_gil(|_| {
+                Ok(state
+                    .client_for_id(session_id)
+                    .ok_or(Error::UnknownSession(session_id))?
+                    .aliases
+                    .get(alias_id)
+                    .cloned())
+            })
+        })
+    }
+
+    fn test_alias<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        alias_id: u32,
+        sample: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            let alias = state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .aliases
+                .get(alias_id)
+                .ok_or(Error::Alias(AliasError::UnknownId(alias_id)))?;
+            Ok(Python::with_gil(|py| alias.config.borrow(py).test(&sample)))
+        })
+    }
+
+    fn aliases<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            Python::with_gil(|_| {
+                Ok(state
+                    .client_for_id(session_id)
+                    .ok_or(Error::UnknownSession(session_id))?
+                    .aliases
+                    .iter()
+                    .map(|(_, a)| a.clone())
+                    .collect::<Vec<_>>())
+            })
+        })
+    }
+
+    fn disable_alias<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        alias_id: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.toggle_alias(py, session_id, alias_id, false)
+    }
+
+    fn remove_alias<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        alias_id: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .aliases
+                .remove(alias_id);
+            Ok(())
+        })
+    }
+
+    fn set_aliases_enabled<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        enabled: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            debug!("setting aliases enabled to {enabled} for session {session_id}");
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .aliases_enabled = enabled;
+            Ok(())
+        })
+    }
+
+    fn aliases_enabled<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            Ok(state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .aliases_enabled)
+        })
+    }
+
+    fn remove_module_aliases<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        module: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            let aliases = &mut state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .aliases;
+            let aliases_to_remove = aliases
+                .iter()
+                .filter_map(|(id, alias)| {
+                    if alias.module == module {
+                        Some(*id)
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>();
+            debug!(
+                "removing {} aliases that were added by module {}",
+                aliases_to_remove.len(),
+                module
+            );
+
+            for alias_id in aliases_to_remove {
+                aliases.remove(alias_id);
+            }
+            Ok(())
+        })
+    }
+
+    fn enable_alias<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        alias_id: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.toggle_alias(py, session_id, alias_id, true)
+    }
+
+    fn new_timer<'py>(
+        &self,
+        py: Python<'py>,
+        config: Py<TimerConfig>,
+        module: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let global_config = self.config.clone();
+        let task_state = self.state.clone();
+        let task_waker = self.waker.clone();
+        with_state!(self, py, |mut state| {
+            let timers = &mut state.timers;
+
+            trace!("setting up a new timer for module {module}");
+
+            Python::with_gil(|pyy| {
+                let new_config: PyRef<'_, TimerConfig> = config.extract(pyy)?;
+                for (_, t) in &mut *timers {
+                    let old_config: PyRef<'_, TimerConfig> = t.config.extract(pyy)?;
+                    if new_config.name == old_config.name {
+                        warn!("timer with name {} already exists", new_config.name);
+                        return Ok::<_, PyErr>(None);
+                    }
+                }
+                Self::require_coroutine(pyy, "timer callback", &new_config.callback)?;
+
+                let (control_tx, control_rx) = watch::channel(TimerControl::Run);
+                let (countdown_tx, countdown) = watch::channel(Countdown::Stopped);
+                let timer_id = timers.construct(|id| Timer {
+                    id,
+                    running: true,
+                    paused: false,
+                    control_tx,
+                    countdown,
+                    module,
+                    config,
+                });
+
+                let task_locals = Python::with_gil(pyo3_async_runtimes::tokio::get_current_locals)?;
+                tokio::spawn(pyo3_async_runtimes::tokio::scope(
+                    task_locals,
+                    run_timer(
+                        timer_id,
+                        new_config.clone(),
+                        global_config.clone(),
+                        TimerChannels {
+                            control_rx,
+                            countdown_tx,
+                        },
+                        task_state,
+                        task_waker,
+                    ),
+                ));
+
+                Ok(Some(timer_id))
+            })
+        })
+    }
+
+    fn start_timer<'py>(&self, py: Python<'py>, timer_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        let global_config = self.config.clone();
+        let task_state = self.state.clone();
+        let task_waker = self.waker.clone();
+        with_state!(self, py, |mut state| {
+            let timers = &mut state.timers;
+            let timer = timers
+                .get_mut(timer_id)
+                .ok_or(Error::Timer(TimerError::UnknownId(timer_id)))?;
+
+            if timer.running {
+                warn!("timer {} is already running", timer.id);
+                return Ok(());
+            }
+
+            Python::with_gil(|pyy| {
+                let config: PyRef<'_, TimerConfig> = timer.config.extract(pyy)?;
+                let (control_tx, control_rx) = watch::channel(TimerControl::Run);
+                let (countdown_tx, countdown) = watch::channel(Countdown::Stopped);
+
+                timer.control_tx = control_tx;
+                timer.countdown = countdown;
+                timer.running = true;
+                timer.paused = false;
+
+                let task_locals = Python::with_gil(pyo3_async_runtimes::tokio::get_current_locals)?;
+                tokio::spawn(pyo3_async_runtimes::tokio::scope(
+                    task_locals,
+                    run_timer(
+                        timer_id,
+                        config.clone(),
+                        global_config.clone(),
+                        TimerChannels {
+                            control_rx,
+                            countdown_tx,
+                        },
+                        task_state,
+                        task_waker,
+                    ),
+                ));
+
+                Ok(())
+            })
+        })
+    }
+
+    fn stop_timer<'py>(&self, py: Python<'py>, id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            match state.timers.get_mut(id) {
+                Some(timer) => {
+                    if timer.running {
+                        timer.running = false;
+                        timer.paused = false;
+                        timer.control_tx.send(TimerControl::Stop).ok();
+                    } else {
+                        warn!("timer {} is already stopped", timer.id);
+                    }
+
+                    Ok(())
+                }
+                None => Err(Error::Timer(TimerError::UnknownId(id)).into()),
+            }
+        })
+    }
+
+    fn pause_timer<'py>(&self, py: Python<'py>, id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            let timer = state
+                .timers
+                .get_mut(id)
+                .ok_or(Error::Timer(TimerError::UnknownId(id)))?;
+            if !timer.running {
+                return Err(Error::Timer(TimerError::NotRunning(id)).into());
+            }
+            if timer.paused {
+                warn!("timer {id} is already paused");
+                return Ok(());
+            }
+            timer.paused = true;
+            timer.control_tx.send(TimerControl::Pause).ok();
+            Ok(())
+        })
+    }
+
+    fn resume_timer<'py>(&self, py: Python<'py>, id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            let timer = state
+                .timers
+                .get_mut(id)
+                .ok_or(Error::Timer(TimerError::UnknownId(id)))?;
+            if !timer.running {
+                return Err(Error::Timer(TimerError::NotRunning(id)).into());
+            }
+            if !timer.paused {
+                warn!("timer {id} is not paused");
+                return Ok(());
+            }
+            timer.paused = false;
+            timer.control_tx.send(TimerControl::Run).ok();
+            Ok(())
+        })
+    }
+
+    fn reset_timer<'py>(&self, py: Python<'py>, id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            let timer = state
+                .timers
+                .get_mut(id)
+                .ok_or(Error::Timer(TimerError::UnknownId(id)))?;
+            if !timer.running {
+                return Err(Error::Timer(TimerError::NotRunning(id)).into());
+            }
+            timer.paused = false;
+            timer.control_tx.send(TimerControl::Reset).ok();
+            Ok(())
+        })
+    }
+
+    fn get_timer<'py>(&self, py: Python<'py>, id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| Python::with_gil(|_| {
+            Ok(state.timers.get(id).cloned())
+        }))
+    }
+
+    fn remove_timer<'py>(&self, py: Python<'py>, id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            let timer = state
+                .timers
+                .get_mut(id)
+                .ok_or(Error::Timer(TimerError::UnknownId(id)))?;
+            if timer.running {
+                timer.running = false;
+                timer.control_tx.send(TimerControl::Stop).ok();
+            }
+            info!("removed timer {id}");
+            state.timers.remove(id);
+            Ok(())
+        })
+    }
+
+    fn remove_module_timers<'py>(
+        &self,
+        py: Python<'py>,
+        module: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            let timers = &mut state.timers;
+            let timers_to_remove = timers
+                .iter()
+                .filter_map(|(id, timer)| {
+                    if timer.module == module {
+                        Some(*id)
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>();
+            debug!(
+                "removing {} timers that were added by module {}",
+                timers_to_remove.len(),
+                module
+            );
+
+            for timer_id in timers_to_remove {
+                timers.remove(timer_id);
+            }
+            Ok(())
+        })
+    }
+
+    fn timers<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            Python::with_gil(|_| {
+                Ok(state
+                    .timers
+                    .iter()
+                    .map(|(_, a)| a.clone())
+                    .collect::<Vec<_>>())
+            })
+        })
+    }
+
+    fn get_input<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            Ok(state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .input
+                .value())
+        })
+    }
+
+    fn get_input_echo<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            Ok(state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .input
+                .telnet_echo())
+        })
+    }
+
+    fn set_input<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        input: InputLine,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .input
+                .set_value(input);
+            Ok(())
+        })
+    }
+
+    fn clear_input<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .input
+                .reset();
+            Ok(())
+        })
+    }
+
+    fn echo<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        text: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .echo(&text);
+            Ok(())
+        })
+    }
+
+    fn add_output<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        output: client::output::Item,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .output
+                .push(output);
+            Ok(())
+        })
+    }
 
-    fn get_input_echo<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+    fn replace_last_line<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        output: client::output::Item,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .output
+                .replace_last(output);
+            Ok(())
+        })
+    }
+
+    fn insert_output<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        index: usize,
+        output: client::output::Item,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .output
+                .insert(index, output);
+            Ok(())
+        })
+    }
+
+    fn add_outputs<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        output: Vec<client::output::Item>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .output
+                .extend(output.into_iter());
+            Ok(())
+        })
+    }
+
+    #[pyo3(signature = (session_id, needle, max_results=None))]
+    fn search_output<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        needle: String,
+        max_results: Option<usize>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            let output = &mut state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .output;
+            Ok(output
+                .search(&needle, max_results)
+                .into_iter()
+                .filter_map(|idx| output.get(idx).map(|item| (idx, item)))
+                .collect::<Vec<_>>())
+        })
+    }
+
+    fn dimensions<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |state| {
             Ok(state
                 .client_for_id(session_id)
                 .ok_or(Error::UnknownSession(session_id))?
-                .input
-                .telnet_echo())
+                .buffer_dimensions)
+        })
+    }
+
+    fn layout<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            Python::with_gil(|_| {
+                Ok(state
+                    .client_for_id(session_id)
+                    .ok_or(Error::UnknownSession(session_id))?
+                    .layout
+                    .clone())
+            })
+        })
+    }
+
+    fn new_buffer<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        config: Py<tui::buffer::BufferConfig>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        // Buffers without their own size limit get the global one.
+        let mut output = config.borrow(py).output.bind(py).borrow_mut();
+        if output.max_len().is_none() {
+            output.set_max_len(self.config.max_buffer_lines());
+        }
+        drop(output);
+        with_state!(self, py, |mut state| {
+            Ok(state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .extra_buffers
+                .construct(|id| tui::extrabuffer::ExtraBuffer { id, config }))
+        })
+    }
+
+    fn get_buffer<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        buffer_id: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            Python::with_gil(|_| {
+                Ok(state
+                    .client_for_id(session_id)
+                    .ok_or(Error::UnknownSession(session_id))?
+                    .extra_buffers
+                    .get(buffer_id)
+                    .cloned())
+            })
+        })
+    }
+
+    fn buffers<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            Python::with_gil(|_| {
+                Ok(state
+                    .client_for_id(session_id)
+                    .ok_or(Error::UnknownSession(session_id))?
+                    .extra_buffers
+                    .iter()
+                    .map(|(_, a)| a.clone())
+                    .collect::<Vec<_>>())
+            })
+        })
+    }
+
+    #[pyo3(signature = (session_id, buffer_id=None))]
+    fn clear_buffer<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        buffer_id: Option<u32>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            Ok(state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .clear_buffer(buffer_id)?)
+        })
+    }
+
+    fn remove_buffer<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        buffer_id: u32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .extra_buffers
+                .remove(buffer_id);
+            Ok(())
+        })
+    }
+
+    fn move_buffer<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        buffer_id: u32,
+        direction: i32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            let client = state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?;
+            let buffer = client
+                .extra_buffers
+                .get(buffer_id)
+                .ok_or(Error::UnknownBuffer(buffer_id))?;
+            Python::with_gil(|py| {
+                let layout_name = buffer.config.borrow(py).layout_name.clone();
+                client
+                    .layout
+                    .borrow(py)
+                    .move_section(py, &layout_name, direction)
+                    .map_err(Into::into)
+            })
+        })
+    }
+
+    fn resize_buffer<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        buffer_id: u32,
+        constraint: tui::layout::PyConstraint,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            let client = state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?;
+            let buffer = client
+                .extra_buffers
+                .get(buffer_id)
+                .ok_or(Error::UnknownBuffer(buffer_id))?;
+            Python::with_gil(|py| {
+                let layout_name = buffer.config.borrow(py).layout_name.clone();
+                client
+                    .layout
+                    .borrow(py)
+                    .resize_section(py, &layout_name, constraint)
+                    .map_err(Into::into)
+            })
+        })
+    }
+
+    #[pyo3(signature = (session_id, pattern=None))]
+    fn set_filter<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        pattern: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .set_output_filter(pattern.as_deref())
+                .map_err(Into::into)
+        })
+    }
+
+    fn get_filter<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            Ok(state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .output_filter()
+                .map(ToString::to_string))
+        })
+    }
+
+    #[pyo3(signature = (session_id, *, title=None, layout_name=None, value=None, max=None, rgb=None, thresholds=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn new_gauge<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        title: Option<String>,
+        layout_name: Option<String>,
+        value: Option<f64>,
+        max: Option<f64>,
+        rgb: Option<(u8, u8, u8)>,
+        thresholds: Option<Vec<tui::gauge::Threshold>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            let color = rgb.map(|(r, g, b)| Color::Rgb(r, g, b)).unwrap_or_default();
+
+            let new_id = state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .gauges
+                .construct(|id| {
+                    Python::with_gil(|pyy| {
+                        let mut gauge = tui::gauge::Gauge {
+                            id,
+                            layout_name: layout_name.unwrap_or_default(),
+                            value: value.unwrap_or_default(),
+                            max: max.unwrap_or_default(),
+                            title: title.unwrap_or_default(),
+                            show_values: false,
+                            color,
+                            thresholds: Vec::default(),
+                        };
+                        gauge.set_thresholds(thresholds.unwrap_or_default());
+                        Py::new(pyy, gauge).unwrap()
+                    })
+                });
+
+            debug!("constructed new gauge with ID {new_id} for session {session_id}");
+
+            Python::with_gil(|_pyy| {
+                Ok(state
+                    .client_for_id_mut(session_id)
+                    .ok_or(Error::UnknownSession(session_id))?
+                    .gauges
+                    .get(new_id)
+                    .cloned())
+            })
+        })
+    }
+
+    #[pyo3(signature = (session_id, buffer_name, raw=false))]
+    fn visible_lines<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        buffer_name: String,
+        raw: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            let client = state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?;
+
+            if buffer_name == OUTPUT_SECTION_NAME {
+                return Ok(tui::buffer::visible_text(&client.visible_output, raw));
+            }
+
+            Python::with_gil(|py| {
+                client
+                    .extra_buffers
+                    .iter()
+                    .map(|(_, buffer)| buffer.config.borrow(py))
+                    .find(|config| config.layout_name == buffer_name)
+                    .map(|config| config.visible_lines(raw))
+                    .ok_or(Error::LayoutMissing(buffer_name).into())
+            })
         })
     }
 
-    fn set_input<'py>(
+    fn scroll<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        input: InputLine,
+        buffer_name: String,
+        lines: isize,
     ) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |mut state| {
-            state
-                .client_for_id_mut(session_id)
-                .ok_or(Error::UnknownSession(session_id))?
-                .input
-                .set_value(input);
-            Ok(())
-        })
-    }
-
-    fn clear_input<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
-        with_state!(self, py, |mut state| {
-            state
+            Ok(state
                 .client_for_id_mut(session_id)
                 .ok_or(Error::UnknownSession(session_id))?
-                .input
-                .reset();
-            Ok(())
+                .scroll_buffer(&buffer_name, client::ScrollTarget::Lines(lines))?)
         })
     }
 
-    fn add_output<'py>(
+    fn scroll_to_item<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        output: client::output::Item,
+        buffer_name: String,
+        index: usize,
     ) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |mut state| {
-            state
+            Ok(state
                 .client_for_id_mut(session_id)
                 .ok_or(Error::UnknownSession(session_id))?
-                .output
-                .push(output);
-            Ok(())
+                .scroll_buffer(&buffer_name, client::ScrollTarget::Item(index))?)
         })
     }
 
-    fn add_outputs<'py>(
+    #[pyo3(signature = (session_id, previous=true))]
+    fn scroll_to_prompt<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        output: Vec<client::output::Item>,
+        previous: bool,
     ) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |mut state| {
-            state
+            Ok(state
                 .client_for_id_mut(session_id)
                 .ok_or(Error::UnknownSession(session_id))?
-                .output
-                .extend(output.into_iter());
-            Ok(())
+                .scroll_to_prompt(previous)?)
         })
     }
 
-    fn dimensions<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+    fn scroll_position<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        buffer_name: String,
+    ) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |state| {
-            Ok(state
+            let client = state
                 .client_for_id(session_id)
-                .ok_or(Error::UnknownSession(session_id))?
-                .buffer_dimensions)
-        })
-    }
+                .ok_or(Error::UnknownSession(session_id))?;
 
-    fn layout<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
-        with_state!(self, py, |state| {
-            Python::with_gil(|_| {
-                Ok(state
-                    .client_for_id(session_id)
-                    .ok_or(Error::UnknownSession(session_id))?
-                    .layout
-                    .clone())
+            if buffer_name == OUTPUT_SECTION_NAME {
+                return Ok(client.output_scroll);
+            }
+
+            Python::with_gil(|py| {
+                client
+                    .extra_buffers
+                    .iter()
+                    .map(|(_, buffer)| buffer.config.borrow(py))
+                    .find(|config| config.layout_name == buffer_name)
+                    .map(|config| config.scroll_pos)
+                    .ok_or(Error::LayoutMissing(buffer_name).into())
             })
         })
     }
 
-    fn new_buffer<'py>(
+    fn get_wrap<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        config: Py<tui::buffer::BufferConfig>,
+        buffer_name: String,
     ) -> PyResult<Bound<'py, PyAny>> {
-        with_state!(self, py, |mut state| {
-            Ok(state
-                .client_for_id_mut(session_id)
-                .ok_or(Error::UnknownSession(session_id))?
-                .extra_buffers
-                .construct(|id| tui::extrabuffer::ExtraBuffer { id, config }))
+        with_state!(self, py, |state| {
+            let client = state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?;
+
+            if buffer_name == OUTPUT_SECTION_NAME {
+                return Ok(client.line_wrap());
+            }
+
+            Python::with_gil(|py| {
+                client
+                    .extra_buffers
+                    .iter()
+                    .map(|(_, buffer)| buffer.config.borrow(py))
+                    .find(|config| config.layout_name == buffer_name)
+                    .map(|config| config.line_wrap)
+                    .ok_or(Error::LayoutMissing(buffer_name).into())
+            })
         })
     }
 
-    fn get_buffer<'py>(
+    fn set_wrap<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        buffer_id: u32,
+        buffer_name: String,
+        wrap: bool,
     ) -> PyResult<Bound<'py, PyAny>> {
-        with_state!(self, py, |state| {
-            Python::with_gil(|_| {
-                Ok(state
-                    .client_for_id(session_id)
-                    .ok_or(Error::UnknownSession(session_id))?
+        with_state!(self, py, |mut state| {
+            let client = state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?;
+
+            if buffer_name == OUTPUT_SECTION_NAME {
+                client.set_line_wrap(Some(wrap));
+                return Ok(());
+            }
+
+            Python::with_gil(|py| {
+                let mut config = client
                     .extra_buffers
-                    .get(buffer_id)
-                    .cloned())
+                    .iter()
+                    .map(|(_, buffer)| buffer.config.borrow_mut(py))
+                    .find(|config| config.layout_name == buffer_name)
+                    .ok_or(Error::LayoutMissing(buffer_name))?;
+                config.line_wrap = wrap;
+                Ok(())
             })
         })
     }
 
-    fn buffers<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+    fn get_timestamps<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |state| {
-            Python::with_gil(|_| {
-                Ok(state
-                    .client_for_id(session_id)
-                    .ok_or(Error::UnknownSession(session_id))?
-                    .extra_buffers
-                    .iter()
-                    .map(|(_, a)| a.clone())
-                    .collect::<Vec<_>>())
-            })
+            Ok(state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .show_timestamps())
         })
     }
 
-    fn remove_buffer<'py>(
+    fn set_timestamps<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        buffer_id: u32,
+        show: bool,
     ) -> PyResult<Bound<'py, PyAny>> {
         with_state!(self, py, |mut state| {
             state
                 .client_for_id_mut(session_id)
                 .ok_or(Error::UnknownSession(session_id))?
-                .extra_buffers
-                .remove(buffer_id);
+                .set_show_timestamps(Some(show));
             Ok(())
         })
     }
 
-    #[pyo3(signature = (session_id, *, title=None, layout_name=None, value=None, max=None, rgb=None))]
-    #[allow(clippy::too_many_arguments)]
-    fn new_gauge<'py>(
+    fn set_gauge<'py>(
         &self,
         py: Python<'py>,
         session_id: u32,
-        title: Option<String>,
-        layout_name: Option<String>,
-        value: Option<f64>,
-        max: Option<f64>,
-        rgb: Option<(u8, u8, u8)>,
+        gauge_id: u32,
+        current: f64,
+        max: f64,
     ) -> PyResult<Bound<'py, PyAny>> {
-        with_state!(self, py, |mut state| {
-            let color = rgb.map(|(r, g, b)| Color::Rgb(r, g, b)).unwrap_or_default();
-
-            let new_id = state
-                .client_for_id_mut(session_id)
+        with_state!(self, py, |state| {
+            let gauge = state
+                .client_for_id(session_id)
                 .ok_or(Error::UnknownSession(session_id))?
                 .gauges
-                .construct(|id| {
-                    Python::with_gil(|pyy| {
-                        Py::new(
-                            pyy,
-                            tui::gauge::Gauge {
-                                id,
-                                layout_name: layout_name.unwrap_or_default(),
-                                value: value.unwrap_or_default(),
-                                max: max.unwrap_or_default(),
-                                title: title.unwrap_or_default(),
-                                color,
-                            },
-                        )
-                        .unwrap()
-                    })
-                });
-
-            debug!("constructed new gauge with ID {new_id} for session {session_id}");
+                .get(gauge_id)
+                .ok_or(Error::UnknownGauge(gauge_id))?;
+            Python::with_gil(|py| gauge.borrow_mut(py).set(current, max));
+            Ok(())
+        })
+    }
 
-            Python::with_gil(|_pyy| {
-                Ok(state
-                    .client_for_id_mut(session_id)
-                    .ok_or(Error::UnknownSession(session_id))?
-                    .gauges
-                    .get(new_id)
-                    .cloned())
-            })
+    fn update_gauges<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        updates: HashMap<u32, tui::gauge::GaugeUpdate>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |state| {
+            let gauges = &state
+                .client_for_id(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .gauges;
+
+            Python::with_gil(|py| {
+                for (gauge_id, update) in updates {
+                    let Some(gauge) = gauges.get(gauge_id) else {
+                        warn!(
+                            "skipping update for unknown gauge {gauge_id} in session {session_id}"
+                        );
+                        continue;
+                    };
+                    gauge.borrow_mut(py).apply(update);
+                }
+            });
+            Ok(())
         })
     }
 
@@ -1135,9 +2592,6 @@ impl PyApp {
         })
     }
 
-    // TODO(XXX): it would be nice to take PyObject as data arg and handle JSON
-    //   serialization in Rust, but PyObject doesn't impl Serialized even with the
-    //   serde feature of PyO3 active. Hmmm. Needs more investigation!
     fn gmcp_send<'py>(
         &self,
         py: Python<'py>,
@@ -1154,6 +2608,26 @@ impl PyApp {
         })
     }
 
+    fn gmcp_send_obj<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        package: String,
+        data: &Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let value = client::gmcp::to_json(data).map_err(|err| match err {
+            Error::Gmcp(GmcpError::CircularReference) => PyValueError::new_err(err.to_string()),
+            err => err.into(),
+        })?;
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .gmcp_send(&package, value)
+                .map_err(Into::into)
+        })
+    }
+
     fn gmcp_register<'py>(
         &self,
         py: Python<'py>,
@@ -1184,6 +2658,30 @@ impl PyApp {
         })
     }
 
+    fn msdp_enabled<'py>(&self, py: Python<'py>, session_id: u32) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            Ok(state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .msdp_enabled())
+        })
+    }
+
+    fn msdp_report<'py>(
+        &self,
+        py: Python<'py>,
+        session_id: u32,
+        variables: Vec<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        with_state!(self, py, |mut state| {
+            state
+                .client_for_id_mut(session_id)
+                .ok_or(Error::UnknownSession(session_id))?
+                .msdp_report(&variables)
+                .map_err(Into::into)
+        })
+    }
+
     #[pyo3(signature = (custom_type, data, session_id=None))]
     fn emit_event<'py>(
         &self,
@@ -1231,6 +2729,7 @@ impl PyApp {
 //  to use from Python.
 #[derive(Debug, Clone)]
 #[pyclass]
+#[allow(clippy::large_enum_variant)] // NewSession is rare, and boxing the Mud is awkward for Python.
 pub enum Event {
     NewSession {
         // Having a separate ID field is duplicative with 'SessionInfo.id', but makes it easier to
@@ -1247,6 +2746,14 @@ pub enum Event {
         id: u32,
         prompt: MudLine,
     },
+    Lines {
+        id: u32,
+        lines: Vec<MudLine>,
+    },
+    OutputLine {
+        id: u32,
+        line: MudLine,
+    },
     Iac {
         id: u32,
         command: u8,
@@ -1268,6 +2775,10 @@ pub enum Event {
         id: u32,
         dimensions: (u16, u16),
     },
+    BufferCleared {
+        id: u32,
+        buffer_id: Option<u32>,
+    },
     InputLine {
         id: u32,
         input: InputLine,
@@ -1290,6 +2801,12 @@ pub enum Event {
         id: u32,
         package: String,
         json: String,
+        data: PyObject,
+    },
+    MsdpMessage {
+        id: u32,
+        variable: String,
+        value: PyObject,
     },
     Python {
         id: Option<u32>,
@@ -1301,6 +2818,20 @@ pub enum Event {
     ResumeSession {
         id: u32,
     },
+    Reconnecting {
+        id: u32,
+        attempt: u32,
+    },
+    TimerExpired {
+        id: Option<u32>,
+        timer_id: u32,
+    },
+    Focus {
+        focused: bool,
+    },
+    GlobalTick {
+        count: u64,
+    },
 }
 
 #[pymethods]
@@ -1311,12 +2842,15 @@ impl Event {
             Self::NewSession { .. } => EventType::NewSession {},
             Self::Connection { .. } => EventType::Connection {},
             Self::Prompt { .. } => EventType::Prompt {},
+            Self::Lines { .. } => EventType::Lines {},
+            Self::OutputLine { .. } => EventType::OutputLine {},
             Self::ConfigReloaded {} => EventType::ConfigReloaded {},
             Self::Iac { .. } => EventType::Iac {},
             Self::OptionEnabled { .. } => EventType::OptionEnabled {},
             Self::OptionDisabled { .. } => EventType::OptionDisabled {},
             Self::Subnegotiation { .. } => EventType::Subnegotiation {},
             Self::BufferResized { .. } => EventType::BufferResized {},
+            Self::BufferCleared { .. } => EventType::BufferCleared {},
             Self::InputLine { .. } => EventType::InputLine {},
             Self::Shortcut { .. } => EventType::Shortcut {},
             Self::KeyPress { .. } => EventType::KeyPress {},
@@ -1324,8 +2858,13 @@ impl Event {
             Self::GmcpEnabled { .. } => EventType::GmcpEnabled {},
             Self::GmcpDisabled { .. } => EventType::GmcpDisabled {},
             Self::GmcpMessage { .. } => EventType::GmcpMessage {},
+            Self::MsdpMessage { .. } => EventType::MsdpMessage {},
             Self::PythonReloaded { .. } => EventType::PythonReloaded {},
             Self::ResumeSession { .. } => EventType::ResumeSession {},
+            Self::Reconnecting { .. } => EventType::Reconnecting {},
+            Self::TimerExpired { .. } => EventType::TimerExpired {},
+            Self::Focus { .. } => EventType::Focus {},
+            Self::GlobalTick { .. } => EventType::GlobalTick {},
         }
     }
 
@@ -1334,20 +2873,28 @@ impl Event {
             Event::NewSession { id, .. }
             | Event::Connection { id, .. }
             | Event::Prompt { id, .. }
+            | Event::Lines { id, .. }
+            | Event::OutputLine { id, .. }
             | Event::OptionEnabled { id, .. }
             | Event::OptionDisabled { id, .. }
             | Event::Subnegotiation { id, .. }
             | Event::Iac { id, .. }
             | Event::BufferResized { id, .. }
+            | Event::BufferCleared { id, .. }
             | Event::InputLine { id, .. }
             | Event::Shortcut { id, .. }
             | Event::KeyPress { id, .. }
             | Event::GmcpEnabled { id, .. }
             | Event::GmcpDisabled { id, .. }
             | Event::GmcpMessage { id, .. }
-            | Event::ResumeSession { id, .. } => Some(*id),
-            Event::Python { id, .. } => *id,
-            Event::ConfigReloaded { .. } | Event::PythonReloaded { .. } => None,
+            | Event::MsdpMessage { id, .. }
+            | Event::ResumeSession { id, .. }
+            | Event::Reconnecting { id, .. } => Some(*id),
+            Event::Python { id, .. } | Event::TimerExpired { id, .. } => *id,
+            Event::ConfigReloaded { .. }
+            | Event::PythonReloaded { .. }
+            | Event::Focus { .. }
+            | Event::GlobalTick { .. } => None,
         }
     }
 
@@ -1361,6 +2908,7 @@ impl Event {
 }
 
 impl Display for Event {
+    #[allow(clippy::too_many_lines)] // One arm per event type.
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Event::NewSession { info, .. } => {
@@ -1372,6 +2920,16 @@ impl Display for Event {
             Event::Prompt { id, prompt } => {
                 write!(f, "event: connection ID {id} received prompt {prompt:?}")
             }
+            Event::Lines { id, lines } => {
+                write!(
+                    f,
+                    "event: connection ID {id} received {} lines",
+                    lines.len()
+                )
+            }
+            Event::OutputLine { id, line } => {
+                write!(f, "event: connection ID {id} output line {line:?}")
+            }
             Event::Iac { id, command } => {
                 write!(f, "event: connection ID {id} received telnet IAC {command}")
             }
@@ -1406,6 +2964,12 @@ impl Display for Event {
                     dimensions.0, dimensions.1
                 )
             }
+            Event::BufferCleared { id, buffer_id } => match buffer_id {
+                Some(buffer_id) => {
+                    write!(f, "event: connection ID {id} buffer {buffer_id} cleared")
+                }
+                None => write!(f, "event: connection ID {id} output cleared"),
+            },
             Event::InputLine { id, input } => {
                 write!(f, "event: connection ID {id} sent input line {input}")
             }
@@ -1435,12 +2999,29 @@ impl Display for Event {
             Event::GmcpMessage { id, package, .. } => {
                 write!(f, "event: connection ID {id} GMCP message {package}")
             }
+            Event::MsdpMessage { id, variable, .. } => {
+                write!(f, "event: connection ID {id} MSDP variable {variable}")
+            }
             Event::ResumeSession { id, .. } => {
                 write!(f, "event: connection ID {id} resumed")
             }
             Event::PythonReloaded { .. } => {
                 write!(f, "event: python code reloaded")
             }
+            Event::Reconnecting { id, attempt } => {
+                write!(
+                    f,
+                    "event: connection ID {id} reconnecting (attempt {attempt})"
+                )
+            }
+            Event::TimerExpired { id, timer_id } => {
+                write!(f, "event: connection ID {id:?} timer {timer_id} expired")
+            }
+            Event::Focus { focused } => match focused {
+                true => write!(f, "event: terminal gained focus"),
+                false => write!(f, "event: terminal lost focus"),
+            },
+            Event::GlobalTick { count } => write!(f, "event: global tick {count}"),
         }
     }
 }
@@ -1451,6 +3032,8 @@ pub enum EventType {
     NewSession,
     Connection,
     Prompt,
+    Lines,
+    OutputLine,
     ConfigReloaded,
     PythonReloaded,
     Iac,
@@ -1458,6 +3041,7 @@ pub enum EventType {
     OptionDisabled,
     Subnegotiation,
     BufferResized,
+    BufferCleared,
     InputLine,
     Shortcut,
     KeyPress,
@@ -1465,7 +3049,12 @@ pub enum EventType {
     GmcpEnabled,
     GmcpDisabled,
     GmcpMessage,
+    MsdpMessage,
     ResumeSession,
+    Reconnecting,
+    TimerExpired,
+    Focus,
+    GlobalTick,
 }
 
 #[pymethods]
@@ -1482,12 +3071,15 @@ impl EventType {
             Self::NewSession { .. } => "event type: new session",
             Self::Connection { .. } => "event type: connection",
             Self::Prompt { .. } => "event type: prompt",
+            Self::Lines { .. } => "event type: lines",
+            Self::OutputLine { .. } => "event type: output line",
             Self::ConfigReloaded { .. } => "event type: config reloaded",
             Self::Iac { .. } => "event type: telnet IAC",
             Self::OptionEnabled { .. } => "event type: telnet option enabled",
             Self::OptionDisabled { .. } => "event type: telnet option disabled",
             Self::Subnegotiation { .. } => "event type: telnet subnegotiation",
             Self::BufferResized { .. } => "event type: buffer resized",
+            Self::BufferCleared { .. } => "event type: buffer cleared",
             Self::InputLine { .. } => "event type: input line",
             Self::Shortcut { .. } => "event type: keyboard shortcut",
             Self::KeyPress { .. } => "event type: key press",
@@ -1495,8 +3087,13 @@ impl EventType {
             Self::GmcpEnabled { .. } => "event type: GMCP enabled",
             Self::GmcpDisabled { .. } => "event type: GMCP disabled",
             Self::GmcpMessage { .. } => "event type: GMCP message",
+            Self::MsdpMessage { .. } => "event type: MSDP message",
             Self::PythonReloaded { .. } => "event type: python reloaded",
             Self::ResumeSession { .. } => "event type: session resumed",
+            Self::Reconnecting { .. } => "event type: reconnecting",
+            Self::TimerExpired { .. } => "event type: timer expired",
+            Self::Focus { .. } => "event type: focus",
+            Self::GlobalTick { .. } => "event type: global tick",
         }
         .to_string()
     }
@@ -1508,6 +3105,19 @@ impl EventType {
 
 type HandlerMap = HashMap<EventType, Py<PyList>>;
 
+// Set once an `OutputLine` handler is added. Handlers are never removed, so this only
+// changes once.
+static OUTPUT_LINE_HANDLED: AtomicBool = AtomicBool::new(false);
+
+/// Returns true if any handler for `OutputLine` events has been added.
+///
+/// Output lines are only sent as events when this is true, to avoid waking the event loop
+/// for every line of busy output nobody is listening to.
+#[must_use]
+pub fn output_line_handled() -> bool {
+    OUTPUT_LINE_HANDLED.load(Ordering::Relaxed)
+}
+
 #[derive(Debug, Default, Clone)]
 #[pyclass]
 pub struct EventHandlers {
@@ -1526,6 +3136,7 @@ impl EventHandlers {
     pub fn dispatch(
         &self,
         py: Python<'_>,
+        config: &GlobalConfig,
         event: &Event,
         futures: &mut FuturesUnordered<PyFuture>,
     ) -> Result<(), Error> {
@@ -1537,9 +3148,11 @@ impl EventHandlers {
                         traceback: String::default(),
                     })?;
                 let handler = handler_tuple.get_item(0)?;
-                futures.push(Box::pin(pyo3_async_runtimes::tokio::into_future(
+                futures.push(timed_future(
+                    config,
+                    format!("event handler {}", callable_label(&handler)),
                     handler.call1((event.clone(),))?,
-                )?));
+                )?);
             }
         }
         Ok(())
@@ -1571,9 +3184,11 @@ impl EventHandlers {
             module,
             handler
                 .getattr(py, "__qualname__")
-                .map(|x| x.to_string())
-                .unwrap_or("unknown".to_string())
+                .map_or("unknown".to_string(), |x| x.to_string())
         );
+        if event_type == EventType::OutputLine {
+            OUTPUT_LINE_HANDLED.store(true, Ordering::Relaxed);
+        }
         self.handlers
             .entry(event_type)
             .or_insert_with(|| PyList::empty(py).into())
@@ -1589,6 +3204,31 @@ impl EventHandlers {
     fn get_handler_events(&self) -> Vec<EventType> {
         self.handlers.keys().cloned().collect()
     }
+
+    /// Describe the registered handlers for each event type as `(module, qualname)` tuples,
+    /// in the order they are dispatched.
+    ///
+    /// # Errors
+    /// If a registered handler isn't a `(handler, module)` tuple.
+    pub fn get_handlers_detail(
+        &self,
+        py: Python<'_>,
+    ) -> PyResult<HashMap<EventType, Vec<(String, String)>>> {
+        let mut detail = HashMap::with_capacity(self.handlers.len());
+        for (event_type, handlers) in &self.handlers {
+            let mut described = Vec::new();
+            for handler_tuple in handlers.bind(py) {
+                let (handler, module): (Bound<'_, PyAny>, String) = handler_tuple.extract()?;
+                let qualname = handler
+                    .getattr("__qualname__")
+                    .and_then(|name| name.extract())
+                    .unwrap_or_else(|_| callable_label(&handler));
+                described.push((module, qualname));
+            }
+            detail.insert(event_type.clone(), described);
+        }
+        Ok(detail)
+    }
 }
 
 fn user_modules() -> Result<Vec<PyObject>, Error> {
@@ -1628,22 +3268,39 @@ fn user_modules() -> Result<Vec<PyObject>, Error> {
     })
 }
 
-async fn run_timer(timer_id: u32, config: TimerConfig, mut stop_rx: watch::Receiver<bool>) {
-    let mut interval = tokio::time::interval(config.duration);
+/// The channels connecting a timer's task with its [`Timer`].
+struct TimerChannels {
+    control_rx: watch::Receiver<TimerControl>,
+    countdown_tx: watch::Sender<Countdown>,
+}
+
+async fn run_timer(
+    timer_id: u32,
+    config: TimerConfig,
+    global_config: GlobalConfig,
+    channels: TimerChannels,
+    state: Arc<RwLock<State>>,
+    waker: UnboundedSender<()>,
+) {
+    let TimerChannels {
+        mut control_rx,
+        countdown_tx,
+    } = channels;
+    // Deadlines advance by the duration from the previous deadline, not from when the
+    // callback finished, so a timer doesn't drift.
+    let mut deadline = tokio::time::Instant::now() + config.duration;
     let mut ticks = 0;
-    let mut first_tick = true;
 
     loop {
+        countdown_tx.send_replace(Countdown::Until(deadline.into_std()));
         tokio::select! {
-            _ = interval.tick() => {
-                // The interval is always immediately ready for the first tick - we prefer
-                // to skip that tick and run the callback only after the duration expired.
-                if first_tick {
-                    first_tick = false;
-                    continue;
-                }
+            () = tokio::time::sleep_until(deadline) => {
                 let awaitable = Python::with_gil(|py|{
-                    pyo3_async_runtimes::tokio::into_future(config.callback.bind(py).call1((timer_id, config.session_id,))?)
+                    timed_future(
+                        &global_config,
+                        format!("timer '{}' callback", config.name),
+                        config.callback.bind(py).call1((timer_id, config.session_id,))?,
+                    )
                 });
                 match awaitable {
                     // TODO(XXX): method for passing error back for ui state...
@@ -1657,21 +3314,122 @@ async fn run_timer(timer_id: u32, config: TimerConfig, mut stop_rx: watch::Recei
                     }
                 }
 
-                // TODO(XXX): method for passing back that the timer is expired.
-                if config.max_ticks > 0 && ticks >= config.max_ticks {
-                    info!("Timer '{}' reached max ticks ({}).", config.name, config.max_ticks);
+                ticks += 1;
+                if config.once || (config.max_ticks > 0 && ticks >= config.max_ticks) {
+                    info!("Timer '{}' expired after {ticks} tick(s).", config.name);
+                    timer_expired(timer_id, config.session_id, &state, &waker).await;
                     break;
                 }
-
-                ticks += 1;
-
+                deadline += config.duration;
             }
-            _ = stop_rx.changed() => {
-                info!("Timer '{}' was stopped.", config.name);
-                break;
+            res = control_rx.changed() => {
+                let control = *control_rx.borrow_and_update();
+                if res.is_err() || control == TimerControl::Stop {
+                    info!("Timer '{}' was stopped.", config.name);
+                    break;
+                }
+                let mut reset = control == TimerControl::Reset;
+                if control == TimerControl::Pause {
+                    let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+                    info!("Timer '{}' was paused with {remaining:?} remaining.", config.name);
+                    countdown_tx.send_replace(Countdown::Paused(remaining));
+                    let Some(control) = wait_for_resume(&mut control_rx).await else {
+                        info!("Timer '{}' was stopped.", config.name);
+                        break;
+                    };
+                    reset = control == TimerControl::Reset;
+                    deadline = tokio::time::Instant::now() + remaining;
+                }
+                if reset {
+                    info!("Timer '{}' was reset.", config.name);
+                    deadline = tokio::time::Instant::now() + config.duration;
+                }
             }
         }
     }
+    countdown_tx.send_replace(Countdown::Stopped);
+}
+
+// Wait for a paused timer to be resumed or reset, returning the control that resumed it.
+// Returns `None` if it was stopped instead.
+async fn wait_for_resume(control_rx: &mut watch::Receiver<TimerControl>) -> Option<TimerControl> {
+    loop {
+        if control_rx.changed().await.is_err() {
+            return None;
+        }
+        match *control_rx.borrow_and_update() {
+            control @ (TimerControl::Run | TimerControl::Reset) => return Some(control),
+            TimerControl::Stop => return None,
+            TimerControl::Pause => {}
+        }
+    }
+}
+
+// Remove an expired timer, and let scripts know it's gone.
+async fn timer_expired(
+    timer_id: u32,
+    session_id: Option<u32>,
+    state: &RwLock<State>,
+    waker: &UnboundedSender<()>,
+) {
+    let _ = waker.send(());
+    let mut state = state.write().await;
+    if state.timers.get(timer_id).is_none() {
+        // Removed while the final callback was running.
+        return;
+    }
+    state.timers.remove(timer_id);
+    if let Err(err) = state.event_tx.send(Event::TimerExpired {
+        id: session_id,
+        timer_id,
+    }) {
+        warn!("failed to send timer expired event: {err}");
+    }
+}
+
+async fn scheduled_connect(
+    session_id: u32,
+    at: SystemTime,
+    reconnect: bool,
+    state: Arc<RwLock<State>>,
+    waker: UnboundedSender<()>,
+) {
+    // Sleep in bounded increments, re-checking the wall clock each time. This keeps the
+    // schedule accurate if the system clock changes, or the machine sleeps, in the meantime.
+    while let Ok(remaining) = at.duration_since(SystemTime::now()) {
+        if remaining.is_zero() {
+            break;
+        }
+        tokio::time::sleep(remaining.min(Duration::from_secs(30))).await;
+    }
+
+    let _ = waker.send(());
+    let mut state = state.write().await;
+    let Some(client) = state.client_for_id_mut(session_id) else {
+        return;
+    };
+    // We're running, so there's nothing left to cancel. Drop our own handle.
+    client.scheduled_connect = None;
+
+    if client.connected() {
+        if !reconnect {
+            info!("scheduled connect skipped: already connected");
+            return;
+        }
+        info!("scheduled reconnect: disconnecting");
+        if let Err(err) = client.disconnect().await {
+            warn!("scheduled reconnect failed to disconnect: {err}");
+        }
+    }
+
+    info!("scheduled connect running");
+    if let Err(err) = client.connect() {
+        warn!("scheduled connect failed: {err}");
+        client.output.push(client::output::Item::CommandResult {
+            error: true,
+            message: format!("scheduled connect failed: {err}"),
+        });
+    }
 }
 
 // TODO(XXX): I tried, and tried to pull out the common boilerplate in these macros to a fn
@@ -1724,3 +3482,16 @@ macro_rules! builtin_modules {
 }
 
 pub(crate) use builtin_modules;
+
+/// Collect the results of connecting or disconnecting several sessions, as each session ID
+/// with an error message, or `None` if it succeeded. Results are ordered by session ID.
+async fn batch_results(
+    results: impl Stream<Item = (u32, Result<(), Error>)>,
+) -> Vec<(u32, Option<String>)> {
+    let mut results = results
+        .map(|(id, result)| (id, result.err().map(|err| err.to_string())))
+        .collect::<Vec<_>>()
+        .await;
+    results.sort_by_key(|(id, _)| *id);
+    results
+}
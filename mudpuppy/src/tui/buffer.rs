@@ -1,19 +1,23 @@
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use pyo3::{pyclass, pymethods, Py, Python};
 use ratatui::layout::{Alignment, Rect};
-use ratatui::style::Style;
-use ratatui::text::{Span, StyledGrapheme, Text};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, StyledGrapheme, Text};
 use ratatui::widgets::{
     Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState,
 };
 use ratatui::Frame;
+use regex::Regex;
 use tracing::trace;
+use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 use crate::client::output::{self, Output};
 use crate::error::Error;
+use crate::model::{self, EchoStyle, Mud};
 use crate::tui::reflow::{LineComposer, LineTruncator, WordWrapper, WrappedLine};
 use crate::Result;
 
@@ -60,7 +64,7 @@ where
         DrawScrollbar::Always => true,
     };
 
-    render_visible(
+    buffer.visible = render_visible(
         buffer,
         f,
         data_source,
@@ -96,16 +100,18 @@ pub enum DrawScrollbar {
     Always,
 }
 
+/// Returns the rendered rows, in top to bottom order.
+///
 /// # Errors
 /// TODO(XXX): docs.
 // A hacked up combination of `Paragraph::render_paragraph` and `Paragraph::render_text`.
 fn render_visible<'a, List, I, Filter>(
-    buffer: &'a BufferConfig,
+    buffer: &BufferConfig,
     f: &mut Frame<'_>,
     items: List,
     filter: Filter,
     area: Rect,
-) -> Result<()>
+) -> Result<Vec<Line<'static>>>
 where
     List: DoubleEndedIterator<Item = &'a I> + 'a,
     Filter: Fn(&&I) -> bool,
@@ -123,10 +129,32 @@ where
         BufferDirection::TopToBottom => 0,
         BufferDirection::BottomToTop => area.height,
     };
+    let mut rows = Vec::new();
 
-    for item in items {
+    'items: for item_ref in items {
         // TODO(XXX): Possible optimization, memoization.
-        let item = item.to_text()?;
+        let mut item = item_ref.to_text(buffer)?;
+
+        // When a live filter is active, hide items whose text content doesn't match. This
+        // is purely a view filter, the underlying buffer data is left untouched.
+        if let Some(filter) = &buffer.filter {
+            if !filter.is_match(&plain_text(&item)) {
+                continue;
+            }
+        }
+
+        // Search matches are highlighted, without changing the underlying buffer data.
+        if let Some(highlight) = &buffer.highlight {
+            item.lines = item
+                .lines
+                .into_iter()
+                .map(|line| highlight_matches(line, highlight))
+                .collect();
+        }
+
+        // The gutter is added after wrapping, so continuation lines are indented past it.
+        let gutter = TimestampGutter::new(item_ref.received(), buffer.timestamps.as_deref());
+        let text_width = area.width.saturating_sub(gutter.width());
 
         let styled = item.lines.iter().map(|line| {
             let graphemes = line
@@ -142,10 +170,11 @@ where
         let mut word_wrapper;
         let mut line_truncator;
         let line_composer: &mut dyn LineComposer = if buffer.line_wrap {
-            word_wrapper = WordWrapper::new(styled, area.width, false);
+            word_wrapper = WordWrapper::new(styled, text_width, false);
+            word_wrapper.set_hanging_indent(buffer.wrap_indent);
             &mut word_wrapper
         } else {
-            line_truncator = LineTruncator::new(styled, area.width);
+            line_truncator = LineTruncator::new(styled, text_width);
             &mut line_truncator
         };
 
@@ -158,13 +187,14 @@ where
         {
             lines.push((line.to_vec(), width, alignment));
         }
+        gutter.prefix(&mut lines);
         lines.reverse();
 
         for (line, width, alignment) in lines {
             if buffer.direction == BufferDirection::BottomToTop && pos == 0
                 || buffer.direction == BufferDirection::TopToBottom && pos == area.height
             {
-                return Ok(()); // No more space, exit early
+                break 'items; // No more space, exit early
             }
 
             let y = if buffer.direction == BufferDirection::BottomToTop {
@@ -173,6 +203,7 @@ where
                 pos
             };
             let mut x = get_line_offset(width, area.width, alignment);
+            let mut row = Line::default();
             for StyledGrapheme { symbol, style } in line {
                 let width = symbol.width();
                 if width == 0 {
@@ -182,10 +213,16 @@ where
                 buf[(area.left() + x, area.top() + y)]
                     .set_symbol(symbol)
                     .set_style(style);
+                match row.spans.last_mut() {
+                    Some(span) if span.style == style => span.content.to_mut().push_str(symbol),
+                    _ => row.spans.push(Span::styled(symbol.to_string(), style)),
+                }
                 x += u16::try_from(width)
                     .map_err(|e| Error::Internal(format!("bad symbol width for {symbol}: {e}")))?;
             }
 
+            rows.push(row);
+
             pos = match buffer.direction {
                 BufferDirection::TopToBottom => pos + 1,
                 BufferDirection::BottomToTop => pos.saturating_sub(1),
@@ -193,7 +230,54 @@ where
         }
     }
 
-    Ok(()) // Rendered all available lines.
+    if buffer.direction == BufferDirection::BottomToTop {
+        rows.reverse();
+    }
+    Ok(rows)
+}
+
+/// Convert rendered rows to text. When `raw` is true, the style of the text is included as
+/// ANSI SGR escape sequences.
+#[must_use]
+pub fn visible_text(rows: &[Line<'_>], raw: bool) -> Vec<String> {
+    rows.iter()
+        .map(|row| {
+            row.spans
+                .iter()
+                .map(|span| match sgr_params(span.style) {
+                    params if raw && !params.is_empty() => {
+                        format!("\x1b[{}m{}\x1b[0m", params.join(";"), span.content)
+                    }
+                    _ => span.content.to_string(),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn sgr_params(style: Style) -> Vec<String> {
+    const MODIFIERS: [(Modifier, &str); 7] = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::CROSSED_OUT, "9"),
+    ];
+
+    let mut params = MODIFIERS
+        .iter()
+        .filter(|(modifier, _)| style.add_modifier.contains(*modifier))
+        .map(|(_, param)| (*param).to_string())
+        .collect::<Vec<_>>();
+    // Reset colours are the default, so they're left out rather than reset explicitly.
+    for (color, background) in [(style.fg, false), (style.bg, true)] {
+        if let Some(color) = color.filter(|color| *color != Color::Reset) {
+            params.push(model::sgr_color(color, background));
+        }
+    }
+    params
 }
 
 pub trait Item: Debug + Send + Sync {
@@ -201,7 +285,125 @@ pub trait Item: Debug + Send + Sync {
 
     /// # Errors
     /// If the item can't be converted to text.
-    fn to_text(&self) -> Result<Text<'static>>;
+    fn to_text(&self, buffer: &BufferConfig) -> Result<Text<'static>>;
+
+    /// Returns when the item was received, if known.
+    fn received(&self) -> Option<SystemTime> {
+        None
+    }
+}
+
+// A gutter prefixing the rows of an item with the local time it was `received`, formatted with
+// `format`. Only the first row shows the time, and items without a received time get a blank
+// gutter, to keep the text aligned. Without a format there's no gutter.
+struct TimestampGutter {
+    stamp: Option<String>,
+    blank: String,
+}
+
+impl TimestampGutter {
+    fn new(received: Option<SystemTime>, format: Option<&str>) -> Self {
+        let Some(format) = format else {
+            return Self {
+                stamp: None,
+                blank: String::new(),
+            };
+        };
+        let stamp = format!(
+            "{} ",
+            output::format_local_timestamp(received.unwrap_or(UNIX_EPOCH), format)
+        );
+        Self {
+            blank: " ".repeat(stamp.width()),
+            stamp: received.map(|_| stamp),
+        }
+    }
+
+    fn width(&self) -> u16 {
+        u16::try_from(self.blank.len()).unwrap_or(u16::MAX)
+    }
+
+    fn prefix<'a>(&'a self, rows: &mut [(Vec<StyledGrapheme<'a>>, u16, Alignment)]) {
+        if self.blank.is_empty() {
+            return;
+        }
+        let style = Style::default().fg(Color::DarkGray);
+        for (idx, (row, width, _)) in rows.iter_mut().enumerate() {
+            let text = match (&self.stamp, idx) {
+                (Some(stamp), 0) => stamp,
+                _ => &self.blank,
+            };
+            row.splice(
+                0..0,
+                text.graphemes(true)
+                    .map(|symbol| StyledGrapheme::new(symbol, style)),
+            );
+            *width = width.saturating_add(self.width());
+        }
+    }
+}
+
+// Split the spans of `line` at the matches of `regex`, patching the style of the matched
+// text so it stands out.
+fn highlight_matches(line: Line<'static>, regex: &Regex) -> Line<'static> {
+    let text = line
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect::<String>();
+    let matches = regex
+        .find_iter(&text)
+        .filter(|m| !m.is_empty())
+        .map(|m| m.range())
+        .collect::<Vec<_>>();
+    if matches.is_empty() {
+        return line;
+    }
+
+    let highlight = Style::new().fg(Color::Black).bg(Color::Yellow);
+    let mut spans = Vec::new();
+    let mut offset = 0;
+    for span in line.spans {
+        let end = offset + span.content.len();
+        // Byte offsets within the span where highlighting starts or stops.
+        let mut bounds = vec![0, span.content.len()];
+        for range in &matches {
+            for pos in [range.start, range.end] {
+                if pos > offset && pos < end {
+                    bounds.push(pos - offset);
+                }
+            }
+        }
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        for pair in bounds.windows(2) {
+            let (start, stop) = (pair[0], pair[1]);
+            let highlighted = matches
+                .iter()
+                .any(|range| range.start <= offset + start && offset + start < range.end);
+            let style = match highlighted {
+                true => span.style.patch(highlight),
+                false => span.style,
+            };
+            spans.push(Span::styled(span.content[start..stop].to_string(), style));
+        }
+        offset = end;
+    }
+    Line { spans, ..line }
+}
+
+fn plain_text(text: &Text<'_>) -> String {
+    text.lines
+        .iter()
+        .map(|line| {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 const fn get_line_offset(line_width: u16, text_area_width: u16, alignment: Alignment) -> u16 {
@@ -222,6 +424,10 @@ pub struct BufferConfig {
     #[pyo3(get, set)]
     pub line_wrap: bool,
 
+    /// The hanging indent of wrapped lines. See [`WordWrapper::set_hanging_indent`].
+    #[pyo3(get, set)]
+    pub wrap_indent: Option<u16>,
+
     #[pyo3(get, set)]
     pub border_top: bool,
 
@@ -245,9 +451,36 @@ pub struct BufferConfig {
 
     #[pyo3(get)]
     pub max_scroll: usize,
+
+    /// An optional live filter. When set, only items with text matching the regex are
+    /// rendered. For the main output buffer this is the session's filter, see
+    /// `Client::set_output_filter`.
+    pub filter: Option<Regex>,
+
+    /// An optional search pattern. When set, text matching the regex is highlighted.
+    pub highlight: Option<Regex>,
+
+    /// An optional timestamp format. When set, a gutter showing when each item was received
+    /// is drawn to the left of the items.
+    pub timestamps: Option<String>,
+
+    /// Styling for echoed input items typed by the user.
+    pub echo_style: EchoStyle,
+
+    /// Styling for echoed input items sent by scripts.
+    pub scripted_echo_style: EchoStyle,
+
+    /// The rows of text rendered by the most recent draw, top to bottom.
+    pub visible: Vec<Line<'static>>,
 }
 
 impl BufferConfig {
+    /// Configure the styling of echoed input items from the given `Mud` config.
+    pub fn set_echo_styles(&mut self, mud: &Mud) {
+        self.echo_style = mud.echo_style.clone();
+        self.scripted_echo_style = mud.scripted_echo_style.or(&mud.echo_style);
+    }
+
     #[must_use]
     pub fn area_inside_borders(&self, mut area: Rect, scrollbar: bool) -> Rect {
         if self.border_top {
@@ -314,6 +547,7 @@ impl BufferConfig {
         Ok(Self {
             layout_name,
             line_wrap: false,
+            wrap_indent: None,
             output,
             border_top: false,
             border_bottom: false,
@@ -322,9 +556,23 @@ impl BufferConfig {
             direction: BufferDirection::default(),
             scroll_pos: 0,
             max_scroll: 0,
+            filter: None,
+            highlight: None,
+            timestamps: None,
+            echo_style: EchoStyle::default(),
+            scripted_echo_style: EchoStyle::default(),
+            visible: Vec::default(),
         })
     }
 
+    /// Returns the rows of text rendered by the most recent draw, top to bottom. When `raw` is
+    /// true, text styles are included as ANSI escape sequences.
+    #[must_use]
+    #[pyo3(signature = (raw=false))]
+    pub fn visible_lines(&self, raw: bool) -> Vec<String> {
+        visible_text(&self.visible, raw)
+    }
+
     #[must_use]
     pub fn scroll(&self) -> usize {
         self.scroll_pos
@@ -418,3 +666,101 @@ impl Display for BufferDirection {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Stylize;
+
+    use super::*;
+
+    #[test]
+    fn visible_text_plain_and_raw() {
+        let rows = vec![
+            Line::from(vec![
+                Span::raw("You have "),
+                "42".yellow().bold(),
+                Span::raw(" gold."),
+            ]),
+            Line::from("Rgb".fg(Color::Rgb(1, 2, 3))),
+        ];
+
+        assert_eq!(visible_text(&rows, false), vec!["You have 42 gold.", "Rgb"]);
+        assert_eq!(
+            visible_text(&rows, true),
+            vec![
+                "You have \x1b[1;33m42\x1b[0m gold.",
+                "\x1b[38;2;1;2;3mRgb\x1b[0m",
+            ]
+        );
+    }
+
+    #[test]
+    fn highlight_matches_across_spans() {
+        let line = Line::from(vec![
+            Span::raw("an o"),
+            "rc hits".red(),
+            Span::raw(" an orc"),
+        ]);
+        let highlight = Style::new().fg(Color::Black).bg(Color::Yellow);
+
+        let line = highlight_matches(line, &Regex::new("orc").unwrap());
+        assert_eq!(
+            line.spans,
+            vec![
+                Span::raw("an "),
+                Span::styled("o", highlight),
+                Span::styled("rc", Style::new().red().patch(highlight)),
+                " hits".red(),
+                Span::raw(" an "),
+                Span::styled("orc", highlight),
+            ]
+        );
+
+        let line = Line::from("no match");
+        assert_eq!(
+            highlight_matches(line.clone(), &Regex::new("orc").unwrap()),
+            line
+        );
+    }
+
+    #[test]
+    fn timestamp_gutter_prefixes_wrapped_rows() {
+        let text = |row: &[StyledGrapheme<'_>]| row.iter().map(|g| g.symbol).collect::<String>();
+        let rows = |line: &'static str| {
+            line.split('|')
+                .map(|row| {
+                    let graphemes = row
+                        .graphemes(true)
+                        .map(|symbol| StyledGrapheme::new(symbol, Style::default()));
+                    (
+                        graphemes.collect::<Vec<_>>(),
+                        u16::try_from(row.len()).unwrap(),
+                        Alignment::Left,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Only the first row shows the time, continuation rows are indented past it.
+        let gutter = TimestampGutter::new(Some(UNIX_EPOCH), Some("[%%]"));
+        assert_eq!(gutter.width(), 4);
+        let mut wrapped = rows("You hit|the orc.");
+        gutter.prefix(&mut wrapped);
+        assert_eq!(text(&wrapped[0].0), "[%] You hit");
+        assert_eq!(text(&wrapped[1].0), "    the orc.");
+        assert_eq!(wrapped[1].1, 12);
+
+        // Items without a received time get a blank gutter.
+        let gutter = TimestampGutter::new(None, Some("[%%]"));
+        let mut wrapped = rows("You flee.");
+        gutter.prefix(&mut wrapped);
+        assert_eq!(text(&wrapped[0].0), "    You flee.");
+
+        // Without a format there's no gutter.
+        let gutter = TimestampGutter::new(Some(UNIX_EPOCH), None);
+        assert_eq!(gutter.width(), 0);
+        let mut wrapped = rows("You flee.");
+        gutter.prefix(&mut wrapped);
+        assert_eq!(text(&wrapped[0].0), "You flee.");
+    }
+}
@@ -0,0 +1,223 @@
+use pyo3::{IntoPyObjectExt, Py, PyAny, PyObject, PyResult, Python};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Text};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+use tokio::time::Instant;
+
+/// A dialog shown on top of the UI to ask the user a question on behalf of a Python
+/// script. The answer is delivered to the script's callback.
+#[derive(Debug)]
+pub struct Dialog {
+    pub session_id: u32,
+    pub message: String,
+    pub kind: DialogKind,
+    pub callback: Py<PyAny>, // Async. Called with the session ID and the answer.
+    /// When the dialog is dismissed without an answer, if ever.
+    pub deadline: Option<Instant>,
+}
+
+impl Dialog {
+    /// Returns true if the dialog's deadline has passed.
+    #[must_use]
+    pub fn expired(&self, now: Instant) -> bool {
+        self.deadline.is_some_and(|deadline| now >= deadline)
+    }
+
+    /// Call the dialog's callback with `answer`, returning the awaitable result.
+    ///
+    /// # Errors
+    /// If the callback raises an exception.
+    pub fn answer(self, py: Python<'_>, answer: Answer) -> PyResult<PyObject> {
+        let answer = match answer {
+            Answer::Confirm(confirmed) => confirmed.into_py_any(py)?,
+            Answer::Prompt(text) => text.into_py_any(py)?,
+            Answer::Select(index) => index.into_py_any(py)?,
+            Answer::Cancel => py.None(),
+        };
+        self.callback.call1(py, (self.session_id, answer))
+    }
+
+    pub fn draw(&self, frame: &mut Frame<'_>, area: Rect) {
+        let mut text = Text::from(self.message.as_str());
+        text.push_line(Line::default());
+        // Room for the message, border, help text and blank lines.
+        let max_options = usize::from(area.height).saturating_sub(text.height() + 5);
+        match &self.kind {
+            DialogKind::Confirm => {
+                text.push_line(Line::from("Press 'y' for yes, 'n' for no or Esc to cancel"));
+            }
+            DialogKind::Prompt { input } => {
+                text.push_line(Line::styled(
+                    format!("> {input}_"),
+                    Style::default().fg(Color::LightGreen),
+                ));
+                text.push_line(Line::from("Press Enter to answer or Esc to cancel"));
+            }
+            DialogKind::Select { options, selected } => {
+                // Scroll so the selected option stays visible.
+                let first = selected.saturating_sub(max_options.saturating_sub(1));
+                for (index, option) in options.iter().enumerate().skip(first).take(max_options) {
+                    text.push_line(match index == *selected {
+                        true => Line::styled(
+                            format!("> {option} <"),
+                            Style::default().fg(Color::LightGreen),
+                        ),
+                        false => Line::from(option.as_str()),
+                    });
+                }
+                text.push_line(Line::default());
+                text.push_line(Line::from(
+                    "Use the arrow keys to choose, Enter to select or Esc to cancel",
+                ));
+            }
+        }
+
+        let width = (area.width * 3 / 5).max(20).min(area.width);
+        // Account for the border, and give wrapped messages some room.
+        let height = u16::try_from(text.height())
+            .unwrap_or(u16::MAX)
+            .saturating_add(4)
+            .min(area.height);
+        let popup_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(
+            Paragraph::new(text)
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: false })
+                .block(
+                    Block::default()
+                        .borders(Borders::all())
+                        .border_style(Color::Yellow),
+                ),
+            popup_area,
+        );
+    }
+}
+
+/// The kind of question a [`Dialog`] asks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DialogKind {
+    /// A yes or no question.
+    Confirm,
+    /// A question answered with a line of text, and the text entered so far.
+    Prompt { input: String },
+    /// A choice between options, and the index of the option currently selected.
+    Select {
+        options: Vec<String>,
+        selected: usize,
+    },
+}
+
+impl DialogKind {
+    /// Handle a key press, returning the answer once the user has given one.
+    pub fn key(&mut self, key: &KeyEvent) -> Option<Answer> {
+        if key.code == KeyCode::Esc {
+            return Some(Answer::Cancel);
+        }
+        match self {
+            DialogKind::Confirm => match key.code {
+                KeyCode::Char('y' | 'Y') => Some(Answer::Confirm(true)),
+                KeyCode::Char('n' | 'N') => Some(Answer::Confirm(false)),
+                _ => None,
+            },
+            DialogKind::Prompt { input } => {
+                match key.code {
+                    KeyCode::Enter => return Some(Answer::Prompt(std::mem::take(input))),
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        input.push(c);
+                    }
+                    _ => {}
+                }
+                None
+            }
+            DialogKind::Select { options, selected } => {
+                let last = options.len().saturating_sub(1);
+                match key.code {
+                    KeyCode::Enter => return Some(Answer::Select(*selected)),
+                    KeyCode::Up => *selected = selected.saturating_sub(1),
+                    KeyCode::Down => *selected = (*selected + 1).min(last),
+                    KeyCode::PageUp => *selected = selected.saturating_sub(10),
+                    KeyCode::PageDown => *selected = (*selected + 10).min(last),
+                    KeyCode::Home => *selected = 0,
+                    KeyCode::End => *selected = last,
+                    _ => {}
+                }
+                None
+            }
+        }
+    }
+}
+
+/// The user's answer to a [`Dialog`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Confirm(bool),
+    Prompt(String),
+    /// The index of the chosen option.
+    Select(usize),
+    /// The dialog was cancelled, or timed out.
+    Cancel,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(kind: &mut DialogKind, code: KeyCode) -> Option<Answer> {
+        kind.key(&KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn answers_dialogs() {
+        let mut confirm = DialogKind::Confirm;
+        assert_eq!(press(&mut confirm, KeyCode::Char('x')), None);
+        assert_eq!(
+            press(&mut confirm, KeyCode::Char('Y')),
+            Some(Answer::Confirm(true))
+        );
+        assert_eq!(
+            press(&mut confirm, KeyCode::Char('n')),
+            Some(Answer::Confirm(false))
+        );
+        assert_eq!(press(&mut confirm, KeyCode::Esc), Some(Answer::Cancel));
+
+        let mut prompt = DialogKind::Prompt {
+            input: String::new(),
+        };
+        for c in "yess".chars() {
+            assert_eq!(press(&mut prompt, KeyCode::Char(c)), None);
+        }
+        assert_eq!(press(&mut prompt, KeyCode::Backspace), None);
+        assert_eq!(
+            press(&mut prompt, KeyCode::Enter),
+            Some(Answer::Prompt("yes".to_string()))
+        );
+        assert_eq!(press(&mut prompt, KeyCode::Esc), Some(Answer::Cancel));
+
+        let mut select = DialogKind::Select {
+            options: vec!["Alice".to_string(), "Bob".to_string(), "Carol".to_string()],
+            selected: 0,
+        };
+        assert_eq!(press(&mut select, KeyCode::Up), None);
+        assert_eq!(press(&mut select, KeyCode::Enter), Some(Answer::Select(0)));
+        for _ in 0..3 {
+            assert_eq!(press(&mut select, KeyCode::Down), None);
+        }
+        assert_eq!(press(&mut select, KeyCode::Enter), Some(Answer::Select(2)));
+        assert_eq!(press(&mut select, KeyCode::Home), None);
+        assert_eq!(press(&mut select, KeyCode::Down), None);
+        assert_eq!(press(&mut select, KeyCode::Enter), Some(Answer::Select(1)));
+        assert_eq!(press(&mut select, KeyCode::Esc), Some(Answer::Cancel));
+    }
+}
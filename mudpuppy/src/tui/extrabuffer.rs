@@ -55,7 +55,15 @@ impl ExtraBuffer {
                 buffer_area,
                 // TODO(XXX): config for scroll bar render...
                 DrawScrollbar::Always,
-            )
+            )?;
+
+            // Remember what was rendered, and the clamped scroll position, so they can be
+            // queried from Python.
+            let mut shared = self.config.borrow_mut(py);
+            shared.visible = config.visible;
+            shared.scroll_pos = config.scroll_pos;
+            shared.max_scroll = config.max_scroll;
+            Ok(())
         })
     }
 }
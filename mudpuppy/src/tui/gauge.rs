@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
-use pyo3::types::PyAnyMethods;
-use pyo3::{pyclass, pymethods, Py, Python};
+use pyo3::types::{PyAnyMethods, PyDict, PyDictMethods};
+use pyo3::{pyclass, pymethods, Bound, FromPyObject, Py, PyAny, PyResult, Python};
 use ratatui::layout::{Alignment, Rect};
 use ratatui::style::{Color, Style, Stylize};
 use ratatui::text::{Line, Span};
@@ -13,6 +13,9 @@ use crate::error::Error;
 use crate::idmap::Identifiable;
 use crate::Result;
 
+/// A colour threshold given from Python, as a `(percentage, (r, g, b))` pair.
+pub type Threshold = (f64, (u8, u8, u8));
+
 #[derive(Debug, Clone)]
 #[pyclass]
 pub struct Gauge {
@@ -31,11 +34,35 @@ pub struct Gauge {
     #[pyo3(get, set)]
     pub title: String,
 
+    /// Whether the label shows the value and max, like `450/500`, instead of a percentage.
+    #[pyo3(get, set)]
+    pub show_values: bool,
+
     pub color: Color,
+
+    /// Colours used instead of `color` while the gauge is low, as `(percentage, color)` pairs
+    /// sorted by percentage. The first threshold the gauge is below applies.
+    pub thresholds: Vec<(f64, Color)>,
 }
 
 #[pymethods]
 impl Gauge {
+    /// Returns the ratio of the value to the max, between 0 and 1.
+    #[must_use]
+    pub fn ratio(&self) -> f64 {
+        safe_ratio(self.value, self.max)
+    }
+
+    /// Set the colour thresholds from `(percentage, (r, g, b))` pairs. While the gauge is
+    /// below a threshold's percentage it's drawn in that colour instead of its own.
+    pub fn set_thresholds(&mut self, thresholds: Vec<Threshold>) {
+        self.thresholds = thresholds
+            .into_iter()
+            .map(|(percentage, (r, g, b))| (percentage, Color::Rgb(r, g, b)))
+            .collect();
+        self.thresholds.sort_by(|a, b| a.0.total_cmp(&b.0));
+    }
+
     fn set_colour(&mut self, r: u8, g: u8, b: u8) {
         self.color = Color::Rgb(r, g, b);
     }
@@ -45,6 +72,64 @@ impl Gauge {
     }
 }
 
+impl Gauge {
+    /// Set the value and max, labelling the gauge with both, e.g. `450/500`.
+    pub fn set(&mut self, value: f64, max: f64) {
+        self.value = value;
+        self.max = max;
+        self.show_values = true;
+    }
+
+    fn label(&self) -> String {
+        match self.show_values {
+            true => format!("{}/{}", self.value, self.max),
+            false => format!("{:.1}%", self.ratio() * 100.0),
+        }
+    }
+
+    // The colour of the first threshold the gauge is below, or its own colour.
+    fn current_color(&self) -> Color {
+        let percentage = self.ratio() * 100.0;
+        self.thresholds
+            .iter()
+            .find(|(threshold, _)| percentage < *threshold)
+            .map_or(self.color, |(_, color)| *color)
+    }
+
+    /// Apply the fields that are set in `update`, leaving the others unchanged.
+    pub fn apply(&mut self, update: GaugeUpdate) {
+        if let Some(value) = update.value {
+            self.value = value;
+        }
+        if let Some(max) = update.max {
+            self.max = max;
+        }
+        if let Some(title) = update.title {
+            self.title = title;
+        }
+    }
+}
+
+/// A partial update for a [`Gauge`], extracted from a Python dict with optional `value`,
+/// `max` and `title` keys.
+#[derive(Debug, Clone, Default)]
+pub struct GaugeUpdate {
+    pub value: Option<f64>,
+    pub max: Option<f64>,
+    pub title: Option<String>,
+}
+
+impl<'py> FromPyObject<'py> for GaugeUpdate {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let dict = ob.downcast::<PyDict>()?;
+        Ok(Self {
+            value: dict.get_item("value")?.map(|v| v.extract()).transpose()?,
+            max: dict.get_item("max")?.map(|v| v.extract()).transpose()?,
+            title: dict.get_item("title")?.map(|v| v.extract()).transpose()?,
+        })
+    }
+}
+
 impl Identifiable for Py<Gauge> {
     fn id(&self) -> u32 {
         Python::with_gil(|py| {
@@ -82,15 +167,15 @@ pub fn draw_gauge(
             .fg(Color::White);
 
         let label = Span::styled(
-            format!("{:.1}%", (gauge.value / gauge.max) * 100.0),
+            gauge.label(),
             Style::new().italic().bold().fg(Color::Yellow),
         );
 
         let gauge_widget = RatatuiGauge::default()
             .block(gauge_block)
             .label(label)
-            .gauge_style(gauge.color)
-            .ratio(safe_ratio(gauge.value, gauge.max));
+            .gauge_style(gauge.current_color())
+            .ratio(gauge.ratio());
         f.render_widget(gauge_widget, *gauge_area);
 
         Ok(())
@@ -104,3 +189,36 @@ fn safe_ratio(value: f64, max: f64) -> f64 {
         (value / max).clamp(0.0, 1.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_and_colours() {
+        let mut gauge = Gauge {
+            id: 1,
+            layout_name: "hp".to_string(),
+            value: 450.0,
+            max: 500.0,
+            title: "HP".to_string(),
+            show_values: false,
+            color: Color::Green,
+            thresholds: Vec::default(),
+        };
+        assert_eq!(gauge.label(), "90.0%");
+        gauge.set_thresholds(vec![(50.0, (255, 255, 0)), (25.0, (255, 0, 0))]);
+        assert_eq!(gauge.current_color(), Color::Green);
+
+        gauge.set(120.0, 500.0);
+        assert_eq!(gauge.label(), "120/500");
+        assert_eq!(gauge.current_color(), Color::Rgb(255, 0, 0));
+        gauge.set(200.5, 500.0);
+        assert_eq!(gauge.label(), "200.5/500");
+        assert_eq!(gauge.current_color(), Color::Rgb(255, 255, 0));
+
+        // A zero max is empty, rather than dividing by zero.
+        gauge.set(10.0, 0.0);
+        assert!(gauge.ratio().abs() < f64::EPSILON);
+    }
+}
@@ -1,5 +1,6 @@
 use ratatui::layout::{Position, Rect};
 use ratatui::prelude::{Color, Style};
+use ratatui::text::Line;
 use ratatui::widgets::{Block, Borders, Paragraph};
 use ratatui::Frame;
 use std::collections::HashMap;
@@ -15,6 +16,8 @@ pub(super) struct Input {}
 impl Input {
     pub fn draw(
         input: &mut client_input::Input,
+        title: Option<&str>,
+        status: Option<&str>,
         frame: &mut Frame<'_>,
         sections: &HashMap<String, Rect>,
     ) -> Result<()> {
@@ -24,22 +27,30 @@ impl Input {
 
         let content = input.value();
         let mut content_str = content.sent;
-        if content_str.is_empty() && content.original.is_some() {
-            content_str = content.original.unwrap();
+        if content_str.is_empty() {
+            if let Some(original) = content.original {
+                content_str = original;
+            }
         }
 
         if content.echo == EchoState::Password {
             content_str = "*".repeat(content_str.len());
         }
 
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Magenta));
+        if let Some(title) = title {
+            block = block.title(title);
+        }
+        if let Some(status) = status {
+            block = block.title(Line::from(status).right_aligned());
+        }
+
         let width = area.width.max(3) - 3;
         let scroll = input.visual_scroll(width as usize);
         let input_text = Paragraph::new(content_str.as_str())
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Magenta)),
-            )
+            .block(block)
             .style(Style::default().fg(Color::White))
             .scroll((0, u16::try_from(scroll).unwrap_or_default()));
 
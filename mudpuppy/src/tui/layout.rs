@@ -80,6 +80,72 @@ impl LayoutNode {
         Ok(())
     }
 
+    /// Move the section named `name` by `offset` positions within its parent's sections.
+    ///
+    /// Negative offsets move the section towards the start of the parent's sections, positive
+    /// offsets towards the end. The new position is clamped to the bounds of the parent.
+    ///
+    /// # Errors
+    /// If the section can't be found, or the layout contains invalid types.
+    pub fn move_section(&self, py: Python<'_>, name: &str, offset: i32) -> Result<()> {
+        let (sections, idx) = self.find_parent_sections(py, name)?;
+        let last = sections.len().saturating_sub(1);
+        let new_idx = match offset.is_negative() {
+            true => idx.saturating_sub(offset.unsigned_abs() as usize),
+            false => idx.saturating_add(offset.unsigned_abs() as usize).min(last),
+        };
+        if new_idx == idx {
+            return Ok(());
+        }
+
+        let section = sections.get_item(idx)?;
+        sections.del_item(idx)?;
+        sections.insert(new_idx, section)?;
+        Ok(())
+    }
+
+    /// Replace the constraint of the section named `name`.
+    ///
+    /// The constraint is clamped so that the section can't be sized to nothing.
+    ///
+    /// # Errors
+    /// If the section can't be found, or the layout contains invalid types.
+    pub fn resize_section(
+        &self,
+        py: Python<'_>,
+        name: &str,
+        constraint: PyConstraint,
+    ) -> Result<()> {
+        let section = self.find_section(py, name)?;
+        let existing = section.get_item(0)?;
+        let existing: &Bound<'_, PyConstraint> =
+            existing.downcast().map_err(|_| Error::BadLayout)?;
+        existing.borrow_mut().set_from(constraint.clamped());
+        Ok(())
+    }
+
+    fn find_parent_sections<'py>(
+        &self,
+        py: Python<'py>,
+        name: &str,
+    ) -> Result<(Bound<'py, PyList>, usize)> {
+        let sections = self.sections.bind(py);
+
+        for (idx, section) in sections.iter().enumerate() {
+            let tuple: Bound<'_, PyTuple> =
+                section.downcast_into().map_err(|_| Error::BadLayout)?;
+            let layout: LayoutNode = tuple.get_item(1)?.extract()?;
+
+            if layout.name == name {
+                return Ok((sections.clone(), idx));
+            } else if let Ok(found) = layout.find_parent_sections(py, name) {
+                return Ok(found);
+            }
+        }
+
+        Err(Error::LayoutMissing(name.to_string()))
+    }
+
     fn collect_all_layouts(&self, py: Python<'_>, result: &Bound<PyDict>) -> Result<()> {
         let sections = self.sections.bind(py);
         for section in sections {
@@ -210,6 +276,23 @@ impl PyConstraint {
             Constraint::Min(0)
         }
     }
+
+    /// Returns a copy of the constraint adjusted so that it always yields a visible area.
+    fn clamped(self) -> Self {
+        if let Some(percentage) = self.percentage {
+            Self::with_percentage(percentage.clamp(1, 100))
+        } else if let Some((a, b)) = self.ratio {
+            Self::with_ratio((a.max(1), b.max(a.max(1))))
+        } else if let Some(length) = self.length {
+            Self::with_length(length.max(1))
+        } else if let Some(max) = self.max {
+            Self::with_max(max.max(1))
+        } else if let Some(min) = self.min {
+            Self::with_min(min.max(1))
+        } else {
+            Self::with_min(1)
+        }
+    }
 }
 
 #[pymethods]
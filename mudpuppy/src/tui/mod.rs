@@ -1,4 +1,5 @@
 pub mod buffer;
+pub mod dialog;
 pub(crate) mod extrabuffer;
 pub(crate) mod gauge;
 mod input;
@@ -8,3 +9,4 @@ pub(super) mod mudlist;
 mod reflow;
 pub(super) mod session;
 mod splitview;
+pub mod terminal;
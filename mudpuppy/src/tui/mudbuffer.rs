@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::time::SystemTime;
 
 use ansi_to_tui::IntoText;
 use deref_derive::{Deref, DerefMut};
@@ -26,7 +27,7 @@ pub(super) struct MudBuffer {
 impl MudBuffer {
     pub(super) fn new(mud: Mud, session_id: u32) -> Result<Self> {
         let mut buff = BufferConfig::new(OUTPUT_SECTION_NAME.to_string())?;
-        buff.line_wrap = !mud.no_line_wrap;
+        buff.set_echo_styles(&mud);
         Ok(Self {
             session_id,
             mud,
@@ -35,7 +36,7 @@ impl MudBuffer {
     }
 
     pub(super) fn reload_config(&mut self, mud: Mud) {
-        self.buff.line_wrap = !mud.no_line_wrap;
+        self.buff.set_echo_styles(&mud);
         self.mud = mud;
     }
 
@@ -50,11 +51,11 @@ impl MudBuffer {
             .get(OUTPUT_SECTION_NAME)
             .ok_or(Error::LayoutMissing(OUTPUT_SECTION_NAME.to_string()))?;
 
-        // Handle sending a resize event if the area we're rendering into
-        // has changed size since the last render.
+        // Handle sending a resize event, and telling the MUD with NAWS, if the area we're
+        // rendering into has changed size since the last render.
         let current_dimensions = (area.width, area.height);
         if session.buffer_dimensions != current_dimensions {
-            session.buffer_dimensions = current_dimensions;
+            session.set_buffer_dimensions(current_dimensions);
             info!(
                 "session buffer resized to {}x{}",
                 current_dimensions.0, current_dimensions.1
@@ -65,6 +66,15 @@ impl MudBuffer {
             })?;
         }
 
+        // Any live output filter the session has set is applied at render time.
+        self.buff.filter = session.output_filter().cloned();
+        self.buff.highlight = session.search_highlight().cloned();
+        // Items are wrapped as they're rendered, so a changed wrap setting reflows the
+        // existing output without moving the (item based) scroll position.
+        self.buff.line_wrap = session.line_wrap();
+        self.buff.wrap_indent = session.wrap_indent();
+        self.buff.timestamps = session.timestamp_gutter();
+
         // We may display a held prompt at the bottom of all the normal output.
         let prompt = if self.mud.hold_prompt {
             session
@@ -88,7 +98,7 @@ impl MudBuffer {
                 match item {
                     // Hide gagged MUD items
                     // TODO(XXX): Offer a way to disable gagging for troubleshooting?
-                    output::Item::Mud { line } if line.gag => false,
+                    output::Item::Mud { line, .. } if line.gag => false,
                     // Hide input items when echo_input is disabled. The user doesn't want to see
                     // their own input displayed in the output buff.
                     output::Item::Input { .. } if !self.mud.echo_input => false,
@@ -103,7 +113,11 @@ impl MudBuffer {
             },
             area,
             DrawScrollbar::Never,
-        )
+        )?;
+
+        // Remember what was rendered so it can be queried from Python.
+        session.visible_output.clone_from(&self.buff.visible);
+        Ok(())
     }
 }
 
@@ -208,9 +222,16 @@ impl buffer::Item for output::Item {
         }
     }
 
-    fn to_text(&self) -> Result<Text<'static>> {
+    fn received(&self) -> Option<SystemTime> {
+        match self {
+            Self::Mud { received, .. } => *received,
+            _ => None,
+        }
+    }
+
+    fn to_text(&self, buffer: &BufferConfig) -> Result<Text<'static>> {
         Ok(match self {
-            Self::Mud { line: text }
+            Self::Mud { line: text, .. }
             | Self::Prompt { prompt: text }
             | Self::HeldPrompt { prompt: text } => String::from_utf8_lossy(&text.raw)
                 .to_string()
@@ -222,7 +243,26 @@ impl buffer::Item for output::Item {
                 .into_text()?
                 .style(Style::default().add_modifier(Modifier::DIM)),
             Self::Input { line, .. } => {
-                vec![Line::from([self.icon().unwrap(), line.into()].concat())].into()
+                let style = match line.scripted {
+                    true => &buffer.scripted_echo_style,
+                    false => &buffer.echo_style,
+                };
+                // Colours are validated when the config is loaded.
+                let color = style.color().ok().flatten();
+                let mut icon = self.icon().unwrap();
+                if let Some(prefix) = &style.prefix {
+                    icon = vec![Span::styled(
+                        prefix.clone(),
+                        Style::default().fg(Color::LightBlue),
+                    )];
+                }
+                let mut spans = [icon, line.into()].concat();
+                if let Some(color) = color {
+                    for span in &mut spans {
+                        span.style = span.style.fg(color);
+                    }
+                }
+                vec![Line::from(spans)].into()
             }
             Self::ConnectionEvent { status } => {
                 vec![Line::from([self.icon().unwrap(), status.into()].concat())].into()
@@ -15,6 +15,7 @@ use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 use ratatui::layout::Alignment;
+use ratatui::style::Style;
 use ratatui::text::StyledGrapheme;
 
 // NOTE(@cpu): lifted from grapheme.rs since it is unexported.
@@ -58,6 +59,8 @@ where
     current_line: Vec<StyledGrapheme<'a>>,
     /// Removes the leading whitespace from lines
     trim: bool,
+    /// Indents continuation lines under the first word of the line, plus this many columns
+    hanging_indent: Option<u16>,
 
     // These are cached allocations that hold no state across next_line invocations
     pending_word: Vec<StyledGrapheme<'a>>,
@@ -78,6 +81,7 @@ where
             current_alignment: Alignment::Left,
             current_line: vec![],
             trim,
+            hanging_indent: None,
 
             pending_word: Vec::new(),
             pending_line_pool: Vec::new(),
@@ -85,6 +89,26 @@ where
         }
     }
 
+    /// Indent the lines a long line wraps onto so they line up under its first word, plus
+    /// `extra` columns. `None` leaves continuation lines unindented.
+    ///
+    /// The indent is limited to half of the line width, so that there's always room left
+    /// for the wrapped text.
+    pub fn set_hanging_indent(&mut self, extra: Option<u16>) {
+        self.hanging_indent = extra;
+    }
+
+    // The width available to continuation lines of a line with the given leading indentation.
+    fn continuation_width(&self, leading_width: u16) -> u16 {
+        let Some(extra) = self.hanging_indent else {
+            return self.max_line_width;
+        };
+        let indent = leading_width
+            .saturating_add(extra)
+            .min(self.max_line_width / 2);
+        self.max_line_width - indent
+    }
+
     /// Split an input line (`line_symbols`) into wrapped lines
     /// and cache them to be emitted later
     fn process_input(&mut self, line_symbols: impl IntoIterator<Item = StyledGrapheme<'a>>) {
@@ -93,6 +117,11 @@ where
         let mut word_width = 0;
         let mut whitespace_width = 0;
         let mut non_whitespace_previous = false;
+        // Continuation lines may be narrower than the first line when there's a hanging indent.
+        let mut max_line_width = self.max_line_width;
+        let mut leading_width = 0;
+        let mut leading = true;
+        let first_wrapped = self.wrapped_lines.len();
 
         self.pending_word.clear();
         self.pending_whitespace.clear();
@@ -101,25 +130,28 @@ where
         for grapheme in line_symbols {
             let is_whitespace = is_whitespace(&grapheme);
             let symbol_width = grapheme.symbol.width() as u16;
+            leading &= is_whitespace;
+            if leading {
+                leading_width += symbol_width;
+            }
 
             // ignore symbols wider than line limit
-            if symbol_width > self.max_line_width {
+            if symbol_width > max_line_width {
                 continue;
             }
 
             let word_found = non_whitespace_previous && is_whitespace;
             // current word would overflow after removing whitespace
-            let trimmed_overflow = pending_line.is_empty()
-                && self.trim
-                && word_width + symbol_width > self.max_line_width;
+            let trimmed_overflow =
+                pending_line.is_empty() && self.trim && word_width + symbol_width > max_line_width;
             // separated whitespace would overflow on its own
             let whitespace_overflow = pending_line.is_empty()
                 && self.trim
-                && whitespace_width + symbol_width > self.max_line_width;
+                && whitespace_width + symbol_width > max_line_width;
             // current full word (including whitespace) would overflow
             let untrimmed_overflow = pending_line.is_empty()
                 && !self.trim
-                && word_width + whitespace_width + symbol_width > self.max_line_width;
+                && word_width + whitespace_width + symbol_width > max_line_width;
 
             // append finished segment to current line
             if word_found || trimmed_overflow || whitespace_overflow || untrimmed_overflow {
@@ -137,17 +169,18 @@ where
             }
 
             // pending line fills up limit
-            let line_full = line_width >= self.max_line_width;
+            let line_full = line_width >= max_line_width;
             // pending word would overflow line limit
-            let pending_word_overflow = symbol_width > 0
-                && line_width + whitespace_width + word_width >= self.max_line_width;
+            let pending_word_overflow =
+                symbol_width > 0 && line_width + whitespace_width + word_width >= max_line_width;
 
             // add finished wrapped line to remaining lines
             if line_full || pending_word_overflow {
-                let mut remaining_width = u16::saturating_sub(self.max_line_width, line_width);
+                let mut remaining_width = u16::saturating_sub(max_line_width, line_width);
 
                 self.wrapped_lines.push_back(mem::take(&mut pending_line));
                 line_width = 0;
+                max_line_width = self.continuation_width(leading_width);
 
                 // remove whitespace up to the end of line
                 while let Some(grapheme) = self.pending_whitespace.front() {
@@ -201,6 +234,16 @@ where
         if self.wrapped_lines.is_empty() {
             self.wrapped_lines.push_back(vec![]);
         }
+
+        let indent = self.max_line_width - max_line_width;
+        if indent > 0 {
+            for line in self.wrapped_lines.iter_mut().skip(first_wrapped + 1) {
+                line.splice(
+                    0..0,
+                    (0..indent).map(|_| StyledGrapheme::new(" ", Style::default())),
+                );
+            }
+        }
     }
 
     fn replace_current_line(&mut self, line: Vec<StyledGrapheme<'a>>) {
@@ -371,6 +414,7 @@ mod test {
     #[derive(Clone, Copy)]
     enum Composer {
         WordWrapper { trim: bool },
+        HangingIndent { extra: u16 },
         LineTruncator,
     }
 
@@ -392,6 +436,11 @@ mod test {
             Composer::WordWrapper { trim } => {
                 Box::new(WordWrapper::new(styled_lines, text_area_width, trim))
             }
+            Composer::HangingIndent { extra } => {
+                let mut wrapper = WordWrapper::new(styled_lines, text_area_width, false);
+                wrapper.set_hanging_indent(Some(extra));
+                Box::new(wrapper)
+            }
             Composer::LineTruncator => Box::new(LineTruncator::new(styled_lines, text_area_width)),
         };
         let mut lines = vec![];
@@ -736,4 +785,89 @@ mod test {
         let (word_wrapper, _, _) = run_composer(Composer::WordWrapper { trim: true }, line, width);
         assert_eq!(word_wrapper, ["foo", "bar"]);
     }
+
+    #[test]
+    fn line_composer_word_wrapper_hanging_indent() {
+        let width = 12;
+        let text = "You see: a sword, a shield\n  > an orc and a goblin";
+        let (word_wrapper, widths, _) =
+            run_composer(Composer::HangingIndent { extra: 0 }, text, width);
+        assert_eq!(
+            word_wrapper,
+            [
+                "You see: a",
+                "sword, a",
+                "shield",
+                "  > an orc",
+                "  and a",
+                "  goblin"
+            ]
+        );
+        assert_eq!(widths, [10, 8, 6, 10, 7, 8]);
+
+        // Extra columns are added to the line's own indentation.
+        let (word_wrapper, _, _) = run_composer(Composer::HangingIndent { extra: 2 }, text, width);
+        assert_eq!(
+            word_wrapper,
+            [
+                "You see: a",
+                "  sword, a",
+                "  shield",
+                "  > an orc",
+                "    and a",
+                "    goblin"
+            ]
+        );
+
+        // The indent leaves room for the wrapped text.
+        let (word_wrapper, _, _) = run_composer(Composer::HangingIndent { extra: 20 }, text, width);
+        assert_eq!(
+            word_wrapper,
+            ["You see: a", "      sword,", "      a", "      shield"]
+                .into_iter()
+                .chain(["  > an orc", "      and a", "      goblin"])
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn line_composer_word_wrapper_keeps_ansi_colours() {
+        use ansi_to_tui::IntoText;
+        use ratatui::style::Color;
+
+        // A 256 colour run, then a truecolor run that both wrap onto continuation lines.
+        let text = "\x1b[38;5;208mthe orange dragon\x1b[0m \x1b[38;2;10;20;30mbreathes fire\x1b[0m"
+            .into_text()
+            .unwrap();
+        let styled_lines = text.iter().map(|line| {
+            (
+                line.iter()
+                    .flat_map(|span| span.styled_graphemes(Style::default())),
+                Alignment::Left,
+            )
+        });
+        let mut wrapper = WordWrapper::new(styled_lines, 10, false);
+        wrapper.set_hanging_indent(Some(2));
+
+        let mut lines = Vec::new();
+        while let Some(WrappedLine { line, .. }) = wrapper.next_line() {
+            lines.push(line.to_vec());
+        }
+        let text = |line: &[StyledGrapheme]| line.iter().map(|g| g.symbol).collect::<String>();
+        let colour = |line: &[StyledGrapheme]| line.iter().rev().find_map(|g| g.style.fg);
+        assert_eq!(
+            lines.iter().map(|line| text(line)).collect::<Vec<_>>(),
+            ["the orange", "  dragon", "  breathes", "  fire"]
+        );
+
+        // Each continuation line keeps the colour of the run it continues, and the indent
+        // is unstyled.
+        for line in &lines[..2] {
+            assert_eq!(colour(line), Some(Color::Indexed(208)));
+        }
+        for line in &lines[2..] {
+            assert_eq!(colour(line), Some(Color::Rgb(10, 20, 30)));
+            assert_eq!(line[0].style, Style::default());
+        }
+    }
 }
@@ -3,14 +3,14 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use futures::stream::FuturesUnordered;
 use pyo3::{Py, PyErr, PyRef, Python};
-use ratatui::crossterm::event::Event as TermEvent;
+use ratatui::crossterm::event::{Event as TermEvent, KeyCode};
 use ratatui::layout::Rect;
 use ratatui::text::Line;
 use ratatui::Frame;
 use tracing::{debug, warn};
 
 use crate::app::{State, Tab, TabAction, TabKind};
-use crate::client::output;
+use crate::client::{output, ScrollRequest};
 use crate::config::{edit_mud, GlobalConfig};
 use crate::error::Error;
 use crate::model::{InputMode, SessionInfo, Shortcut};
@@ -57,7 +57,7 @@ impl Tab for Widget {
     }
 
     // TODO(XXX): Text styling.
-    fn title(&self) -> Line {
+    fn title(&self) -> Line<'_> {
         self.session.mud_name.clone().into()
     }
 
@@ -80,7 +80,10 @@ impl Tab for Widget {
 
         match shortcut {
             Shortcut::ToggleLineWrap => {
-                let no_line_wrap = !mud.no_line_wrap;
+                // Toggling from the keyboard saves the setting, replacing any transient
+                // override set with `/wrap`.
+                let no_line_wrap = client.line_wrap();
+                client.set_line_wrap(None);
                 edit_mud(&mud.name, "no_line_wrap", no_line_wrap)?;
                 client.output.push(output::Item::CommandResult {
                     error: false,
@@ -101,10 +104,20 @@ impl Tab for Widget {
                     ),
                 });
             }
+            Shortcut::ToggleFilter => client.toggle_filter_editing(),
+            Shortcut::ToggleSearch => client.toggle_search(),
+            Shortcut::InputUndo => {
+                client.input.undo();
+            }
+            Shortcut::InputRedo => {
+                client.input.redo();
+            }
+            Shortcut::HistoryPrevious => client.input.history_previous(),
+            Shortcut::HistoryNext => client.input.history_next(),
             _ => {}
         }
 
-        self.scroll_window.handle_shortcut(shortcut);
+        self.scroll_window.handle_shortcut(shortcut, &client.output);
 
         state.event_tx.send(python::Event::Shortcut {
             id: self.session.id,
@@ -124,15 +137,34 @@ impl Tab for Widget {
             return Ok(None);
         };
 
-        let TermEvent::Key(key_event) = event else {
-            return Ok(None);
+        let key_event = match event {
+            TermEvent::Key(key_event) => key_event,
+            // Pasted text isn't matched against key bindings.
+            TermEvent::Paste(text) => return client.paste(futures, text).map(|()| None),
+            _ => return Ok(None),
         };
 
-        client.key_event(futures, key_event).map(|()| None)
+        // Sending input returns a scrolled back output to the bottom, unless configured to
+        // keep the scroll position. This takes precedence over keeping the scroll position
+        // fixed as new output arrives.
+        if key_event.code == KeyCode::Enter
+            && !client.filter_editing()
+            && !client.searching()
+            && self.config.scroll_bottom_on_send()
+        {
+            self.scroll_window.scroll_to(0);
+        }
+
+        client.key_event(futures, key_event)?;
+        if let Some(idx) = client.take_search_jump() {
+            self.scroll_window.scroll_to_item(&client.output, idx);
+        }
+        Ok(None)
     }
 
     fn draw(&mut self, state: &mut State, frame: &mut Frame<'_>, area: Rect) -> Result<()> {
         let event_tx = state.event_tx.clone();
+        let timers = display_timers(state, self.session.id);
         // Retrieve the client for the session.
         let Some(client) = state.client_for_id_mut(self.session.id) else {
             warn!("missing client for session tab: {}", self.session);
@@ -152,12 +184,30 @@ impl Tab for Widget {
         // We do this _before_ drawing the output buffer because the act of draining the
         // new data to draw will clear the new data count.
         if self.scroll_window.scroll_pos != 0 && client.output.new_data > 0 {
-            self.scroll_window
-                .scroll_up(u16::try_from(client.output.new_data).unwrap_or(u16::MAX));
+            self.scroll_window.new_output(client.output.new_data);
+        }
+
+        // Apply any scroll requested by a script. Requests are anchored to an output item, so
+        // output received since the request doesn't throw them off.
+        match client.take_scroll_request() {
+            Some(ScrollRequest::Bottom) => self.scroll_window.scroll_to(0),
+            Some(ScrollRequest::Item(idx)) => {
+                self.scroll_window.scroll_to_item(&client.output, idx);
+            }
+            None => {}
         }
 
         // Draw the input area.
-        Input::draw(&mut client.input, frame, &sections)?;
+        let input_title = client
+            .search_title()
+            .or_else(|| client.filter_editing().then(|| "filter".to_string()));
+        Input::draw(
+            &mut client.input,
+            input_title.as_deref(),
+            timers.as_deref(),
+            frame,
+            &sections,
+        )?;
 
         // Draw the main output buffer.
         self.mud_buffer
@@ -166,7 +216,10 @@ impl Tab for Widget {
         // Draw the scroll window if applicable.
         if self.scroll_window.scroll_pos != 0 {
             self.scroll_window.draw_buffer(client, frame, &sections)?;
+        } else {
+            self.scroll_window.clear_unseen();
         }
+        client.output_scroll = self.scroll_window.scroll_pos;
 
         // Draw any extra buffers.
         for (_, buf) in &mut client.extra_buffers {
@@ -181,6 +234,37 @@ impl Tab for Widget {
     }
 }
 
+// Returns the countdowns of the running timers displayed for the session, e.g. `tick 42s`,
+// oldest timer first. Timers without a session are displayed for every session.
+fn display_timers(state: &State, session_id: u32) -> Option<String> {
+    let mut timers = Python::with_gil(|py| {
+        state
+            .timers
+            .iter()
+            .filter_map(|(id, timer)| {
+                let config = timer.config.borrow(py);
+                if !config.display || config.session_id.is_some_and(|id| id != session_id) {
+                    return None;
+                }
+                let secs = timer.remaining()?.as_millis().div_ceil(1000);
+                Some((*id, format!("{} {secs}s", config.name)))
+            })
+            .collect::<Vec<_>>()
+    });
+    if timers.is_empty() {
+        return None;
+    }
+    timers.sort_unstable_by_key(|(id, _)| *id);
+    Some(format!(
+        " {} ",
+        timers
+            .into_iter()
+            .map(|(_, timer)| timer)
+            .collect::<Vec<_>>()
+            .join(" | ")
+    ))
+}
+
 pub fn initial_layout() -> Py<LayoutNode> {
     Python::with_gil(|py| {
         debug!("configuring initial layout");
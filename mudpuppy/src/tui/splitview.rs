@@ -1,5 +1,7 @@
 use deref_derive::{Deref, DerefMut};
 use ratatui::layout::{Constraint, Direction, Layout, Margin, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::text::Line;
 use ratatui::widgets::Clear;
 use ratatui::Frame;
 use std::collections::HashMap;
@@ -16,24 +18,43 @@ pub(super) struct ScrollWindow {
     mud: Mud,
     #[deref]
     buff: BufferConfig,
+    /// The number of items received since the window was last scrolled to the bottom.
+    unseen: usize,
 }
 
 impl ScrollWindow {
     pub(super) fn new(mud: Mud) -> Result<Self> {
         let mut buff = BufferConfig::new("split_view".to_string())?;
-        buff.line_wrap = !mud.no_line_wrap;
+        buff.set_echo_styles(&mud);
         buff.border_left = true;
         buff.border_right = true;
         buff.border_bottom = true;
 
-        Ok(Self { mud, buff })
+        Ok(Self {
+            mud,
+            buff,
+            unseen: 0,
+        })
     }
 
     pub(super) fn reload_config(&mut self, mud: Mud) {
-        self.buff.line_wrap = !mud.no_line_wrap;
+        self.buff.set_echo_styles(&mud);
         self.mud = mud;
     }
 
+    /// Keep the scroll window at the same point relative to where it was before `count`
+    /// new items were received, and count them as unseen.
+    pub(super) fn new_output(&mut self, count: usize) {
+        self.buff
+            .scroll_up(u16::try_from(count).unwrap_or(u16::MAX));
+        self.unseen = self.unseen.saturating_add(count);
+    }
+
+    /// Forget about unseen output, e.g. because the output was scrolled to the bottom.
+    pub(super) fn clear_unseen(&mut self) {
+        self.unseen = 0;
+    }
+
     pub(super) fn draw_buffer(
         &mut self,
         session: &mut client::Client,
@@ -44,16 +65,20 @@ impl ScrollWindow {
             .get(OUTPUT_SECTION_NAME)
             .ok_or(Error::LayoutMissing(OUTPUT_SECTION_NAME.to_string()))?;
 
-        // Create a sub area of the overall buffer area where we can draw the scroll window.
-        // We don't create this as a fixed layout section because we want it sized relative
-        // to the existing fixed `MudBuffer` output section.
-        let area = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Percentage(self.mud.splitview_percentage),
-                Constraint::Min(1),
-            ])
-            .split(*area)[0];
+        // Create a sub area of the overall buffer area where we can draw the scroll window,
+        // leaving the rest of the output area to show new output live. We don't create this
+        // as a fixed layout section because we want it sized relative to the existing fixed
+        // `MudBuffer` output section.
+        let area = match self.mud.split_scroll {
+            true => Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Percentage(self.mud.splitview_percentage),
+                    Constraint::Min(1),
+                ])
+                .split(*area)[0],
+            false => *area,
+        };
 
         // Render the scrollback content and the scrollbar inside a viewport offset within the
         // overall area.
@@ -65,6 +90,11 @@ impl ScrollWindow {
         // normal buffer content.
         f.render_widget(Clear, viewport);
 
+        self.buff.line_wrap = session.line_wrap();
+        self.buff.wrap_indent = session.wrap_indent();
+        self.buff.highlight = session.search_highlight().cloned();
+        self.buff.timestamps = session.timestamp_gutter();
+
         // We don't use a HeldPromptIterator here because we don't want to hold a prompt in
         // the scrollback buffer.
         let items = session.output.read_received().iter();
@@ -76,10 +106,25 @@ impl ScrollWindow {
             |item| filter_item(item, self.mud.echo_input),
             &viewport,
             DrawScrollbar::Always,
-        )
+        )?;
+
+        // Let the user know about output that arrived while they were scrolled back, on the
+        // bottom border of the scroll window.
+        if self.unseen > 0 && viewport.height > 0 {
+            let indicator = Line::from(format!(" {} new below ", self.unseen))
+                .style(Style::default().fg(Color::Yellow))
+                .right_aligned();
+            let bottom_border = Rect {
+                y: viewport.bottom().saturating_sub(1),
+                height: 1,
+                ..viewport
+            };
+            f.render_widget(indicator, bottom_border);
+        }
+        Ok(())
     }
 
-    pub(super) fn handle_shortcut(&mut self, shortcut: Shortcut) {
+    pub(super) fn handle_shortcut(&mut self, shortcut: Shortcut, output: &output::Output) {
         // TODO(XXX): look up scroll line config.
         let scroll_lines = default::SCROLL_LINES;
 
@@ -96,16 +141,47 @@ impl ScrollWindow {
             Shortcut::ScrollBottom => {
                 self.buff.scroll_bottom();
             }
+            Shortcut::ScrollPromptPrev | Shortcut::ScrollPromptNext => {
+                self.scroll_to_prompt(output, shortcut == Shortcut::ScrollPromptPrev);
+            }
             _ => {}
         }
     }
+
+    /// Scroll so the closest prompt before (or after) the bottom-most visible item is
+    /// shown at the bottom of the scroll window.
+    ///
+    /// Navigation stops at the oldest and newest prompts rather than wrapping around, and
+    /// does nothing when there are no prompts to move to. Moving past the newest prompt
+    /// isn't possible, but reaching it when it's the last item closes the scroll window.
+    fn scroll_to_prompt(&mut self, output: &output::Output, previous: bool) {
+        // Scroll positions count items back from the most recent item.
+        let Some(last) = output.len().checked_sub(1) else {
+            return;
+        };
+        let bottom = last.saturating_sub(self.buff.scroll_pos);
+        let target = match previous {
+            true => output.prompt_before(bottom),
+            false => output.prompt_after(bottom),
+        };
+        if let Some(idx) = target {
+            self.buff.scroll_to(last - idx);
+        }
+    }
+
+    /// Scroll so the output item at `idx` is shown at the bottom of the scroll window.
+    pub fn scroll_to_item(&mut self, output: &output::Output, idx: usize) {
+        if let Some(last) = output.len().checked_sub(1) {
+            self.buff.scroll_to(last.saturating_sub(idx));
+        }
+    }
 }
 
 fn filter_item(item: &output::Item, echo_input: bool) -> bool {
     match item {
         // Hide gagged MUD items
         // TODO(XXX): Offer a way to disable gagging for troubleshooting?
-        output::Item::Mud { line } if line.gag => false,
+        output::Item::Mud { line, .. } if line.gag => false,
 
         // Hide input items when echo_input is disabled. The user doesn't want to see their own
         // input displayed in the output buff.
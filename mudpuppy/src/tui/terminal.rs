@@ -0,0 +1,78 @@
+use std::env;
+
+/// The colours the terminal that mudpuppy is running in can display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    /// The 16 ANSI colours.
+    Ansi,
+    /// The 256 colour xterm palette.
+    Ansi256,
+    /// 24-bit RGB colour.
+    TrueColor,
+}
+
+impl ColorSupport {
+    /// Detect the terminal's colour support from the `TERM` and `COLORTERM` environment
+    /// variables.
+    #[must_use]
+    pub fn detect() -> Self {
+        Self::from_env(
+            env::var("TERM").ok().as_deref(),
+            env::var("COLORTERM").ok().as_deref(),
+        )
+    }
+
+    /// The terminal type name reported to MUDs for this level of colour support.
+    #[must_use]
+    pub fn terminal_type(self) -> &'static str {
+        match self {
+            Self::Ansi => "ANSI",
+            Self::Ansi256 => "XTERM-256COLOR",
+            Self::TrueColor => "XTERM-TRUECOLOR",
+        }
+    }
+
+    fn from_env(term: Option<&str>, colorterm: Option<&str>) -> Self {
+        let term = term.unwrap_or_default();
+        if matches!(colorterm, Some("truecolor" | "24bit")) || term.ends_with("-direct") {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else {
+            Self::Ansi
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_color_support() {
+        assert_eq!(ColorSupport::from_env(None, None), ColorSupport::Ansi);
+        assert_eq!(
+            ColorSupport::from_env(Some("xterm"), None),
+            ColorSupport::Ansi
+        );
+        assert_eq!(
+            ColorSupport::from_env(Some("screen-256color"), None),
+            ColorSupport::Ansi256
+        );
+        assert_eq!(
+            ColorSupport::from_env(Some("xterm-256color"), Some("truecolor")),
+            ColorSupport::TrueColor
+        );
+        assert_eq!(
+            ColorSupport::from_env(Some("xterm-direct"), None),
+            ColorSupport::TrueColor
+        );
+    }
+
+    #[test]
+    fn terminal_type_follows_color_support() {
+        assert_eq!(ColorSupport::Ansi.terminal_type(), "ANSI");
+        assert_eq!(ColorSupport::Ansi256.terminal_type(), "XTERM-256COLOR");
+        assert_eq!(ColorSupport::TrueColor.terminal_type(), "XTERM-TRUECOLOR");
+    }
+}
@@ -137,6 +137,8 @@ class TestCustomLayout:
                 layout_name=HP_GAUGE_SECTION,
                 title="HP",
                 rgb=(0, 255, 0),
+                # Turn red when below 25% health.
+                thresholds=[(25, (255, 0, 0))],
             )
             logging.debug(f"health gauge id: {health_gauge.id}")
 
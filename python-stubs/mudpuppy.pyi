@@ -313,6 +313,7 @@ def alias(
     mud_name: Optional[Union[str, list[str]]] = None,
     module: Optional[str] = None,
     max_hits: Optional[int] = None,
+    priority: int = 0,
 ) -> Callable[[mudpuppy_core.AliasCallable], mudpuppy_core.AliasCallable]:
     """
     Decorator to register an async `mudpuppy_core.AliasCallable` function as an alias handler for
@@ -341,6 +342,9 @@ def alias(
     invoked `max_hits` times before being automatically disabled with
     `mudpuppy_core.MudpuppyCore.disable_alias()`.
 
+    Aliases with a higher `priority` are evaluated first. See
+    `mudpuppy_core.AliasConfig.priority` for more information.
+
     If a `mud_name`, or list of `mud_name`'s are provided then the alias will only be
     registered for sessions with the specified `mud_name`'s.
 
@@ -380,6 +384,10 @@ def trigger(
     mud_name: Optional[Union[str, list[str]]] = None,
     module: Optional[str] = None,
     max_hits: Optional[int] = None,
+    priority: int = 0,
+    stop: bool = False,
+    multi_match: bool = False,
+    important: bool = False,
 ) -> Callable[[mudpuppy_core.TriggerCallable], mudpuppy_core.TriggerCallable]:
     """
     Decorator to register an async `mudpuppy_core.TriggerCallable` function as a trigger handler for
@@ -426,6 +434,17 @@ def trigger(
     invoked `max_hits` times before being automatically disabled with
     `mudpuppy_core.MudpuppyCore.disable_trigger()`.
 
+    Triggers with a higher `priority` are evaluated first. If `stop` is `True`, then
+    triggers evaluated after this one are skipped when it matches. See
+    `mudpuppy_core.TriggerConfig.priority` for more information.
+
+    If `multi_match` is `True`, then the decorated function is invoked once for each
+    match of the `pattern` in a line, with the groups of that match. See
+    `mudpuppy_core.TriggerConfig.multi_match` for more information.
+
+    If `important` is `True`, then a match highlights the session's tab until it's
+    shown. See `mudpuppy_core.TriggerConfig.important` for more information.
+
     If a `mud_name`, or list of `mud_name`'s are provided then the trigger will only be
     registered for sessions with the specified `mud_name`'s.
 
@@ -460,6 +479,7 @@ def highlight(
     strip_ansi: bool = True,
     mud_name: Optional[Union[str, list[str]]] = None,
     module: Optional[str] = None,
+    priority: int = 0,
 ) -> Callable[[mudpuppy_core.HighlightCallable], mudpuppy_core.HighlightCallable]:
     """
     Decorator to register a **non-async** `mudpuppy_core.HighlightCallable` function as a highlight
@@ -550,6 +570,8 @@ def timer(
     minutes: int = 0,
     hours: int = 0,
     max_ticks: Optional[int] = None,
+    once: bool = False,
+    display: bool = False,
     mud_name: Optional[Union[str, list[str]]] = None,
     module: Optional[str] = None,
 ) -> Callable[[TimerCallable], TimerCallable]:
@@ -563,7 +585,9 @@ def timer(
     A duration is calculated based on the `milliseconds`, `seconds`, `minutes`, and `hours`
     arguments. After the duration has expired the `TimerCallable` is invoked. This will
     happen over and over until the timer is stopped with `mudpuppy_core.MudpuppyCore.stop_timer()`
-    or until the optional `max_ticks` value is reached.
+    or until the optional `max_ticks` value is reached. If `once` is `True` the `TimerCallable`
+    is invoked only once, after the duration has expired. If `display` is `True` the
+    time until the timer next fires is shown in the input area.
 
     If a `mud_name`, or list of `mud_name`'s are provided then the timer will only be
     registered for sessions with the specified `mud_name`'s.
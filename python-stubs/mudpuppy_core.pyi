@@ -73,6 +73,82 @@ class Tls(StrEnum):
     or if you're using self-signed test certificates.
     """
 
+class Proxy:
+    """
+    A SOCKS5 proxy used to connect to a `Mud`.
+    """
+
+    host: str
+    """
+    The proxy's host name or IP address.
+    """
+
+    port: int
+    """
+    The proxy's port.
+    """
+
+    username: Optional[str]
+    """
+    The username used to authenticate with the proxy, or `None` if no authentication
+    is used. The password isn't available.
+    """
+
+class AddressFamily(StrEnum):
+    """
+    Describes which IP address family is used when connecting to a `Mud`.
+    """
+
+    Auto = auto()
+    """
+    IPv4 and IPv6 addresses are both tried, using whichever connects first.
+    """
+
+    V4 = auto()
+    """
+    Only IPv4 addresses are used.
+    """
+
+    V6 = auto()
+    """
+    Only IPv6 addresses are used.
+    """
+
+class LineEnding(StrEnum):
+    """
+    Describes the line ending sent after each line of input to a `Mud`.
+    """
+
+    Crlf = auto()
+    """
+    A carriage return and line feed (`\\r\\n`), as the telnet spec requires.
+    """
+
+    Lf = auto()
+    """
+    A bare line feed (`\\n`).
+    """
+
+    Cr = auto()
+    """
+    A bare carriage return (`\\r`).
+    """
+
+class LogFormat(StrEnum):
+    """
+    Describes how session output is written to a log file.
+    """
+
+    Plain = auto()
+    """
+    ANSI colours are removed, leaving plain text.
+    """
+
+    Raw = auto()
+    """
+    Output is written as it was received, including ANSI colours.
+    """
+
 class Mud:
     """
     Information about a MUD and its configuration.
@@ -111,11 +187,165 @@ class Mud:
     Describes the TLS configuration for the MUD.
     """
 
+    ca_file: Optional[str]
+    """
+    The path of a PEM file of CA certificates trusted to verify the MUD's certificate,
+    or `None` to use the built-in roots.
+    """
+
+    pinned_cert: Optional[str]
+    """
+    The path of a PEM file holding the exact certificate the MUD must present, or `None`.
+    """
+
+    client_cert: Optional[str]
+    """
+    The path of a PEM certificate chain presented to the MUD when TLS is used, or `None`
+    if no client certificate is used.
+    """
+
+    client_key: Optional[str]
+    """
+    The path of the PEM private key for `client_cert`, or `None`.
+    """
+
+    address_family: AddressFamily
+    """
+    Describes which IP address family is used to connect to the MUD.
+
+    When a `proxy` is set this applies to the connection to the proxy instead.
+    """
+
+    proxy: Optional[Proxy]
+    """
+    The SOCKS5 proxy used to connect to the MUD, or `None` to connect directly.
+    """
+
+    connect_timeout: int
+    """
+    How long to wait for a connection to be established, in seconds. `0` means no timeout.
+    """
+
+    auto_reconnect: bool
+    """
+    Whether to automatically reconnect when the connection is lost unexpectedly.
+
+    Each attempt emits an `EventType.Reconnecting` event.
+    """
+
+    reconnect_max_attempts: int
+    """
+    The maximum number of automatic reconnect attempts in a row. `0` means unlimited.
+    """
+
+    idle_keepalive: int
+    """
+    The number of seconds without sending anything before an idle keepalive is sent.
+    `0` disables idle keepalives.
+    """
+
+    idle_keepalive_line: Optional[str]
+    """
+    The line sent as an idle keepalive, e.g. `""` for a bare newline. When `None` a
+    telnet NOP command is sent instead.
+    """
+
     command_separator: Optional[str]
     """
     An optional command separator to use when sending multiple commands in a single line.
     """
 
+    command_interval: int
+    """
+    The number of milliseconds to wait between sending commands. Commands sent in the
+    meantime are queued. `0` sends commands right away.
+
+    See `MudpuppyCore.queue_commands()`.
+    """
+
+    use_eor: bool
+    """
+    Whether the telnet EOR option should be negotiated and used to detect prompts.
+    """
+
+    banner_end: Optional[str]
+    """
+    A regex matched against output after connecting. Output before the first matching
+    line is kept as the session's banner. See `MudpuppyCore.banner()`.
+    """
+
+    line_ending: LineEnding
+    """
+    The line ending sent after each line of input.
+    """
+
+    charset: list[str]
+    """
+    The character sets accepted when the MUD negotiates one with the telnet CHARSET
+    option, most preferred first. Output is decoded with the first until the MUD
+    negotiates another.
+    """
+
+    terminal_types: list[str]
+    """
+    The terminal types reported, one per request, when the MUD asks with the telnet
+    TERMINAL-TYPE option. A bare `"MTTS"` is reported with the bitvector from
+    `MudpuppyCore.mtts()`. An empty list refuses the option.
+    """
+
+    auto_log: bool
+    """
+    Whether the session's output is logged to a file automatically when it connects.
+
+    See `MudpuppyCore.start_logging()`.
+    """
+
+    log_format: LogFormat
+    """
+    The format used for session logs.
+    """
+
+    persist_history: bool
+    """
+    Whether the session's input history is saved when it disconnects or closes, and loaded
+    for new sessions.
+    """
+
+    character: Optional[str]
+    """
+    The name of the character played, if any. Persisted input history is kept separately
+    for each character of the MUD.
+    """
+
+    prompt_history_size: int
+    """
+    The number of recent prompts remembered for `MudpuppyCore.prompt_history()`. `0`
+    disables prompt history.
+    """
+
+    on_connect_commands: list[str]
+    """
+    Commands sent automatically after the session connects.
+    """
+
+    on_reconnect_commands: Optional[list[str]]
+    """
+    Commands sent instead of `on_connect_commands` when the session reconnects.
+
+    When `None`, `on_connect_commands` are sent for every connection.
+    """
+
+    on_connect_delay_ms: int
+    """
+    How long to wait before sending each of the on connect commands, in milliseconds.
+    """
+
+    credential: Optional[str]
+    """
+    The name of a credential saved with `MudpuppyCore.set_credential()`, substituted
+    for `{credential}` in the on connect commands.
+    """
+
 class KeyEvent:
     """
     A key press event.
@@ -194,6 +424,12 @@ class Shortcut(StrEnum):
     A shortcut to toggle whether `InputLine`s are displayed in the output buffer.
     """
 
+    ToggleFilter = auto()
+    ToggleSearch = auto()
+    """
+    A shortcut to toggle editing a live filter pattern for the output buffer.
+    """
+
     HistoryNext = auto()
     """
     A shortcut to navigate to the next line in the input history.
@@ -204,6 +440,16 @@ class Shortcut(StrEnum):
     A shortcut to navigate to the previous line in the input history.
     """
 
+    InputUndo = auto()
+    """
+    A shortcut to undo the last edit to the input area.
+    """
+
+    InputRedo = auto()
+    """
+    A shortcut to redo the last undone edit to the input area.
+    """
+
     ScrollUp = auto()
     """
     A shortcut to scroll the output buffer up.
@@ -224,6 +470,16 @@ class Shortcut(StrEnum):
     A shortcut to scroll the output buffer to the bottom.
     """
 
+    ScrollPromptPrev = auto()
+    """
+    A shortcut to scroll the output buffer back to the previous prompt line.
+    """
+
+    ScrollPromptNext = auto()
+    """
+    A shortcut to scroll the output buffer forward to the next prompt line.
+    """
+
 class KeyBindings:
     """
     Read-only Key binding configuration.
@@ -301,6 +557,12 @@ class StreamInfo:
     Information about a connection stream.
     """
 
+    def is_ipv6(self) -> bool:
+        """
+        Returns `True` if the stream is connected over IPv6, or `False` for IPv4.
+        """
+        ...
+
     class Tcp:
         """
         A normal Telnet TCP stream without any encryption or authentication.
@@ -318,6 +580,12 @@ class StreamInfo:
         The port the stream is connected to.
         """
 
+        proxied: bool
+        """
+        Whether the stream was established through a proxy. When `True` the `ip` and
+        `port` are those of the proxy.
+        """
+
     class Tls:
         """
         A TLS encrypted stream.
@@ -335,6 +603,12 @@ class StreamInfo:
         The port the stream is connected to.
         """
 
+        proxied: bool
+        """
+        Whether the stream was established through a proxy. When `True` the `ip` and
+        `port` are those of the proxy.
+        """
+
         protocol: str
         """
         The TLS protocol version in use, in string format.
@@ -353,6 +627,67 @@ class StreamInfo:
         any certificate errors (dangerous!).
         """
 
+class SessionStats:
+    """
+    Throughput statistics for a session's current, or most recent, connection.
+
+    Byte counts are of the text sent and received, excluding telnet protocol overhead.
+    See `MudpuppyCore.session_stats()`.
+    """
+
+    bytes_received: int
+    """
+    The number of bytes of output, and telnet subnegotiation data, received.
+    """
+
+    bytes_sent: int
+    """
+    The number of bytes of input sent.
+    """
+
+    lines_received: int
+    """
+    The number of lines of output received, excluding prompts.
+    """
+
+    lines_sent: int
+    """
+    The number of lines of input sent.
+    """
+
+    connected_at: Optional[datetime.datetime]
+    """
+    When the session connected, or `None` while it isn't connected.
+    """
+
+    uptime: Optional[float]
+    """
+    The number of seconds the session has been connected for, or `None` while it isn't
+    connected.
+    """
+
+class ImportReport:
+    """
+    The outcome of importing triggers and aliases from a file.
+
+    See `MudpuppyCore.import_triggers()`.
+    """
+
+    triggers: list[int]
+    """
+    The IDs of the triggers that were created.
+    """
+
+    aliases: list[int]
+    """
+    The IDs of the aliases that were created.
+    """
+
+    failures: list[tuple[str, str]]
+    """
+    The name of each entry that couldn't be imported, and why.
+    """
+
 class Status:
     """
     Connection status information.
@@ -443,6 +778,37 @@ class MudLine:
         Sets the `MudLine`'s `raw` value to the UTF-8 bytes of the string `new`.
         """
 
+    def color_range(
+        self,
+        start: int,
+        end: int,
+        fg: Optional[str] = None,
+        bg: Optional[str] = None,
+    ):
+        """
+        Colours the visible characters from `start` up to, but not including, `end` with
+        the `fg` foreground and `bg` background colours.
+
+        The offsets are character offsets in the `stripped()` text, so you don't need to
+        account for ANSI control sequences in `raw`. Colours are a colour name
+        (e.g. `"lightgreen"`), an indexed colour (e.g. `"42"`), or a hex RGB value
+        (e.g. `"#ff8800"`).
+
+        Existing colours inside the range are overridden, and the colours in effect after
+        the range are restored. This is handy in a trigger `highlight` callback:
+
+        ```python
+        def highlight(line: MudLine, groups: list[str]) -> MudLine:
+            start = line.stripped().find(groups[0])
+            line.color_range(start, start + len(groups[0]), fg="lightred")
+            return line
+        ```
+
+        To hide the line instead, set `gag` to `True`.
+
+        Raises an exception if a colour is invalid, or the range is out of bounds.
+        """
+
 type TriggerCallable = Callable[[int, int, str, list[str]], Awaitable[None]]
 """
 An async function that is called when output sent from a MUD matches a trigger pattern.
@@ -456,6 +822,11 @@ The handler is called with:
 * the `str` output that matched the trigger pattern, and
 * a `list[str]` of captured groups from the trigger pattern (if any).
 
+If the handler accepts a fifth argument, it's also called with a
+`dict[str, Optional[str]]` of the named groups from the pattern, like
+`(?P<amount>\d+)`. Named groups that didn't participate in the match are `None`.
+Groups in the positional list that didn't participate are empty strings.
+
 Example:
 ```python
 from mudpuppy_core import mudpuppy_core
@@ -482,6 +853,9 @@ The handler is called with:
 * a `MudLine` object representing the line of output from the MUD
 * a `list[str]` of captured groups from the highlight pattern (if any)
 
+If the handler accepts a third argument, it's also called with a `dict[str, Optional[str]]`
+of the named groups from the pattern (see `TriggerCallable`).
+
 It **must** return a `MudLine` to display. This can be the same line
 object passed in, or a new line object.
 
@@ -506,6 +880,9 @@ def example_highlight_callable(line: MudLine, groups):
     line.set(new_line)
     return line
 ```
+
+Alternatively use `MudLine.color_range()` to colour part of the line without editing
+its ANSI control sequences yourself.
 """
 
 class TriggerConfig:
@@ -525,12 +902,20 @@ class TriggerConfig:
     A friendly name to identify the trigger.
     """
 
+    match_ansi: bool
+    """
+    Whether the trigger `pattern` is matched against the raw line, including ANSI escape
+    sequences like colour codes. Defaults to `False`, matching only the visible text.
+
+    Typically you will want this to be `False` unless you want to write a `pattern` that matches
+    on specific colours.
+    """
+
     strip_ansi: bool
     """
     Whether or not ANSI colours should be stripped **before** the trigger `pattern` is matched.
 
-    Typically you will want this to be `True` unless you want to write a `pattern` that matches
-    on specific colours.
+    This is the inverse of `match_ansi`, and setting one updates the other.
     """
 
     prompt: bool
@@ -539,7 +924,9 @@ class TriggerConfig:
     addition to the `pattern` matching for the trigger to fire.
 
     Set this to `True` if you only want the trigger to match lines that generate a `EventType.Prompt`
-    event.
+    event. Prompts are partial lines, so any stray line ending characters around the prompt text
+    are ignored when matching. Match groups are passed to the `callback` the same way as for
+    normal lines, and setting `gag` hides the matched prompt, including a held prompt.
     """
 
     gag: bool
@@ -575,9 +962,66 @@ class TriggerConfig:
     have been called.
     """
 
+    priority: int
+    """
+    The evaluation order of the trigger. Triggers with a higher `priority` are evaluated
+    before those with a lower one. Triggers with the same `priority` are evaluated in the
+    order they were created. Defaults to `0`.
+
+    Use a negative `priority` for catch-all triggers that should only run after all
+    the more specific triggers.
+    """
+
+    recolor: Optional[tuple[Optional[str], Optional[str]]] = None
+    """
+    An optional `(fg, bg)` tuple of colours applied to each match of the `pattern`, without
+    needing a `highlight` callback. Either colour may be `None` to leave it unchanged. See
+    `MudLine.color_range()` for the supported colour formats.
+
+    The colours are applied before any `highlight` callback is invoked.
+    """
+
+    stop: bool
+    """
+    Whether the trigger stops the evaluation of further triggers when it matches a line.
+    Defaults to `False`.
+
+    Triggers that would have been evaluated after this one (i.e. those with a lower
+    `priority`) don't see the matched line. Combined with `priority` this gives "first match
+    wins" behaviour.
+    """
+
+    multi_match: bool
+    """
+    Whether the `callback` is invoked once for each non-overlapping match of the `pattern`
+    in a line, instead of only for the first match. Defaults to `False`.
+
+    Each invocation receives the groups of its own match. The `highlight`, `gag` and
+    `expansion` still apply once per line, and the `highlight` receives the groups of the
+    first match.
+    """
+
+    important: bool
+    """
+    Whether a match marks the session's tab as having important activity, e.g. for tells.
+    Defaults to `False`.
+
+    While the session's tab isn't shown, its title is highlighted and its count of new
+    output is followed by a `!`, until the tab is shown again.
+    """
+
+    group: Optional[str]
+    """
+    A label for enabling and disabling related triggers and aliases together, e.g.
+    `"combat"`. Defaults to `None`.
+
+    See `MudpuppyCore.enable_group()` and `MudpuppyCore.disable_group()`.
+    """
+
     hit_count: int
     """
     The number of times `OutputLine`s have matched this `Trigger` since it was created.
+    With `multi_match` each match in a line is counted.
     """
 
     def __init__(
@@ -585,17 +1029,28 @@ class TriggerConfig:
         pattern: str,
         name: str,
         *,
-        strip_ansi: bool = False,
+        match_ansi: bool = False,
+        strip_ansi: Optional[bool] = None,
         prompt: bool = False,
         gag: bool = False,
         callback: Optional[TriggerCallable] = None,
         highlight: Optional[HighlightCallable] = None,
         expansion: Optional[str] = None,
+        priority: int = 0,
+        stop: bool = False,
+        multi_match: bool = False,
+        recolor: Optional[tuple[Optional[str], Optional[str]]] = None,
+        important: bool = False,
+        group: Optional[str] = None,
     ):
         """
         Create a new `TriggerConfig` with a `pattern` and a `name`.
 
-        Optionally you may specify `strip_ansi`, `prompt`, `gag`, `callback`, `highlight`, and `expansion`.
+        Optionally you may specify `match_ansi`, `prompt`, `gag`, `callback`, `highlight`, `expansion`,
+        `priority`, `stop`, `multi_match`, `recolor`, `important`, and `group`. Raises an error if a `recolor` colour is invalid.
+
+        `strip_ansi` is accepted for compatibility. When provided it takes precedence over
+        `match_ansi`, with the opposite meaning.
         """
         ...
 
@@ -604,26 +1059,99 @@ class TriggerConfig:
         Return a string representation of the `TriggerConfig` regexp pattern.
         """
 
-class Trigger:
+    def spans(self, line: MudLine) -> Optional[list[Optional[tuple[int, int]]]]:
+        """
+        Returns the `(start, end)` byte offsets of each of the `pattern`'s capture groups in
+        `line`, or `None` if the trigger doesn't match `line`.
+
+        Offsets always refer to the full `MudLine.raw` bytes, including ANSI escape sequences,
+        even when `match_ansi` is `False` and the pattern matched the stripped text.
+        This lets a `HighlightCallable` recolour exactly the matched text. Groups that didn't
+        participate in the match are `None`.
+        """
+
+    def test(self, sample: str) -> Optional["PatternMatch"]:
+        """
+        Tests the `pattern` against `sample` as if it was a line received from the MUD,
+        returning a `PatternMatch` if it matches, or `None` if it doesn't.
+
+        Prompt triggers treat the sample as a prompt. The `hit_count` isn't changed, and the
+        callback, expansion and highlight aren't used.
+        """
+        ...
+
+class PatternMatch:
     """
-    A `TriggerConfig` associated with a `int` trigger ID after being created with `MudpuppyCore.new_trigger()`
+    The result of testing a trigger or alias pattern against sample text with
+    `TriggerConfig.test()` or `AliasConfig.test()`.
     """
 
-    id: int
+    text: str
     """
-    The `Trigger`'s ID.
+    The text the pattern was matched against. For triggers that don't `match_ansi`
+    this is the sample with ANSI escape sequences stripped.
     """
 
-    enabled: bool
+    groups: list[str]
     """
-    Whether the `Trigger` is currently enabled.
-
-    Mutate using `MudpuppyCore.enable_trigger()`.
+    Each capture group in order. Groups that didn't participate in the match are empty.
     """
 
-    module: bool
+    named_groups: dict[str, Optional[str]]
+    """
+    Named capture groups, like `(?P<amount>\\d+)`. Groups that didn't participate in
+    the match are `None`.
     """
-    The module that created the `Trigger`.
+
+class Substitution:
+    """
+    A search-and-replace applied to each line of output, created with
+    `MudpuppyCore.add_substitution()`.
+    """
+
+    id: int
+    """
+    The `Substitution`'s ID.
+    """
+
+    replacement: str
+    """
+    The text each match is replaced with. `%0` is the whole match, `%1` to `%9` are
+    capture groups, and `%<name>` is a named group.
+    """
+
+    match_ansi: bool
+    """
+    Whether the pattern is matched against the raw line, including ANSI escape
+    sequences, instead of the visible text with ANSI stripped.
+    """
+
+    def pattern(self) -> str:
+        """
+        Returns the regex pattern the `Substitution` matches.
+        """
+        ...
+
+class Trigger:
+    """
+    A `TriggerConfig` associated with a `int` trigger ID after being created with `MudpuppyCore.new_trigger()`
+    """
+
+    id: int
+    """
+    The `Trigger`'s ID.
+    """
+
+    enabled: bool
+    """
+    Whether the `Trigger` is currently enabled.
+
+    Mutate using `MudpuppyCore.enable_trigger()`.
+    """
+
+    module: bool
+    """
+    The module that created the `Trigger`.
 
     Used in association with `MudpuppyCore.remove_module_triggers()`.
     """
@@ -646,6 +1174,9 @@ The handler is called with:
 * the `str` input that matched the alias pattern, and
 * a `list[str]` of captured groups from the alias pattern (if any).
 
+If the handler accepts a fifth argument, it's also called with a
+`dict[str, Optional[str]]` of the named groups from the pattern (see `TriggerCallable`).
+
 Example:
 ```python
 from mudpuppy_core import mudpuppy_core, Alias
@@ -679,6 +1210,32 @@ class AliasConfig:
     An optional **async** `AliasCallable` to invoke when the alias matches.
     """
 
+    expansion: Optional[str]
+    """
+    An optional line to send in place of the matched input.
+
+    The pattern's match groups are substituted in: `%0` is the whole match, `%1` to `%9`
+    are the capture groups, and `%<name>` is a named group. Use `%%` for a literal `%`.
+    """
+
+    priority: int
+    """
+    The evaluation order of the alias. Aliases with a higher `priority` are evaluated
+    before those with a lower one. Aliases with the same `priority` are evaluated in the
+    order they were created. Defaults to `0`.
+
+    A matching alias without an `expansion` "eats" the input, and aliases evaluated after
+    it are skipped.
+    """
+
+    group: Optional[str]
+    """
+    A label for enabling and disabling related triggers and aliases together, e.g.
+    `"combat"`. Defaults to `None`.
+
+    See `MudpuppyCore.enable_group()` and `MudpuppyCore.disable_group()`.
+    """
+
     hit_count: int
     """
     The number of times `InputLine`s have matched this `AliasConfig` since it was created.
@@ -688,13 +1245,17 @@ class AliasConfig:
         self,
         pattern: str,
         name: str,
+        *,
         callback: Optional[AliasCallable] = None,
         expansion: Optional[str] = None,
+        priority: int = 0,
+        group: Optional[str] = None,
     ):
         """
         Create a new `AliasConfig` with a `pattern` and a `name`.
 
-        You can optionally provide a `callback` and an `expansion` string.
+        You can optionally provide a `callback`, an `expansion` string, a `priority`, and a
+        `group`.
         """
         ...
 
@@ -704,6 +1265,15 @@ class AliasConfig:
         """
         ...
 
+    def test(self, sample: str) -> Optional[PatternMatch]:
+        """
+        Tests the `pattern` against `sample` as if it was input, returning a `PatternMatch`
+        if it matches, or `None` if it doesn't.
+
+        The `hit_count` isn't changed, and the callback and expansion aren't used.
+        """
+        ...
+
     @property
     def expansion(self) -> Optional[str]:
         """
@@ -796,14 +1366,33 @@ class TimerConfig:
         duration_ms: int,
         callback: TimerCallable,
         session: Optional[int] = None,
+        *,
+        once: bool = False,
+        display: bool = False,
     ):
         """
         Create a new `TimerConfig` with a `name` that will be run every `duration_ms`
         milliseconds, invoking `callback`. The timer may optionally be associated
         with an `int` session ID.
+
+        If `once` is `True` the `callback` is invoked a single time, `duration_ms`
+        milliseconds after the timer is created, and then the timer is removed.
+
+        If `display` is `True` the seconds remaining until the timer next fires are
+        shown in the input area, e.g. `tick 42s`.
         """
         ...
 
+    display: bool
+    """
+    Whether the seconds remaining until the timer next fires are shown on the input
+    area's border while the timer is running. Timers with a session ID are only shown
+    for that session. Can be both read and set.
+
+    Use `MudpuppyCore.reset_timer()` to restart the countdown, e.g. when a trigger
+    matches a MUD's tick message.
+    """
+
     @property
     def session_id(self) -> Optional[int]:
         """
@@ -823,6 +1412,8 @@ class TimerConfig:
         """
         An optional maximum number of times the `callback` should be invoked before
         the timer is automatically removed. Can be both read and set.
+
+        An `EventType.TimerExpired` event is emitted when the timer is removed.
         """
         ...
 
@@ -833,6 +1424,12 @@ class TimerConfig:
         """
         ...
 
+    once: bool
+    """
+    Whether the `callback` is invoked only once, after `duration`, before the timer is
+    automatically removed. An `EventType.TimerExpired` event is emitted when it's removed.
+    """
+
 class Timer:
     """
     A `TimerConfig` associated with an `int` timer ID after being created with `MudpuppyCore.new_timer()`
@@ -850,6 +1447,13 @@ class Timer:
     Mutate using `MudpuppyCore.start_timer()` and `MudpuppyCore.stop_timer()`.
     """
 
+    paused: bool
+    """
+    Whether the running `Timer` is currently paused.
+
+    Mutate using `MudpuppyCore.pause_timer()` and `MudpuppyCore.resume_timer()`.
+    """
+
     module: str
     """
     The module that created the `Timer`.
@@ -862,6 +1466,13 @@ class Timer:
     The `TimerConfig` for the `Timer`.
     """
 
+    def remaining(self) -> Optional[datetime.timedelta]:
+        """
+        Returns the time remaining until the `Timer` next fires, or `None` if it isn't
+        running. For a paused `Timer` this is the time that was left when it was paused.
+        """
+        ...
+
 class EchoState(StrEnum):
     """
     The echo state for an `InputLine`
@@ -965,6 +1576,12 @@ class OutputItem:
         The `MudLine` to be displayed.
         """
 
+        received: Optional[datetime.datetime]
+        """
+        When the line was received from the MUD, or `None` for lines that didn't come
+        from the MUD (e.g. ones added by scripts).
+        """
+
     class Input:
         """
         A line of text from the user.
@@ -1094,6 +1711,15 @@ class Output:
         """
         ...
 
+    max_len: Optional[int]
+    """
+    The maximum number of `OutputItem` instances kept, or `None` for no limit. When
+    more are added the oldest are discarded.
+
+    Buffers added with `MudpuppyCore.new_buffer()` without a limit use the
+    `max_buffer_lines` config setting.
+    """
+
     def push(self, item: OutputItem):
         """
         Appends an `OutputItem` to the collection.
@@ -1102,6 +1728,34 @@ class Output:
         of the collection are rendered first, or last.
         """
 
+    def clear(self):
+        """
+        Removes all `OutputItem` instances from the collection.
+
+        See also `MudpuppyCore.clear_buffer()`, which also resets the buffer's scroll
+        position.
+        """
+        ...
+
+    def replace_last(self, item: OutputItem):
+        """
+        Replaces the most recent `OutputItem` in the collection with `item`, or appends
+        `item` if the collection is empty.
+
+        The number of items doesn't change, so the scroll position is kept. Replacements
+        aren't written to the session log, which keeps the item as it was first added.
+        """
+        ...
+
+    def insert(self, index: int, item: OutputItem):
+        """
+        Inserts an `OutputItem` before the item at `index`.
+
+        Indexes past the end of the collection append the item, like `push()`. Only
+        appended items are written to the session log, to keep it in order.
+        """
+        ...
+
     def set(self, items: list[OutputItem]):
         """
         Sets the collection of `OutputItem` instances to `items`.
@@ -1110,6 +1764,39 @@ class Output:
         """
         ...
 
+    def search(self, needle: str, max_results: Optional[int] = None) -> list[int]:
+        """
+        Returns the indexes of `OutputItem` instances with text containing `needle`,
+        ignoring case and ANSI colour.
+
+        The most recent matches are returned first, up to `max_results` if provided.
+
+        The first search builds an index of the collection's text that is kept up to
+        date as items are added, making repeated searches of large collections fast.
+        """
+        ...
+
+    def get(self, index: int) -> Optional[OutputItem]:
+        """
+        Returns the `OutputItem` at `index` in the collection, or `None` if the index
+        is out of range.
+        """
+        ...
+
+    def prompt_before(self, index: int) -> Optional[int]:
+        """
+        Returns the index of the closest prompt `OutputItem` before `index`, or `None`
+        if there isn't one. Gagged prompts are skipped.
+        """
+        ...
+
+    def prompt_after(self, index: int) -> Optional[int]:
+        """
+        Returns the index of the closest prompt `OutputItem` after `index`, or `None`
+        if there isn't one. Gagged prompts are skipped.
+        """
+        ...
+
 class Constraint:
     """
     A `LayoutNode` constraint.
@@ -1356,6 +2043,13 @@ class BufferConfig:
     Whether the content in the `ExtraBuffer` should be line-wrapped.
     """
 
+    wrap_indent: Optional[int]
+    """
+    When set, wrapped lines are indented to line up under the first word of the line they
+    continue, plus this many columns. When `None` (the default) wrapped lines aren't
+    indented.
+    """
+
     border_top: bool
     """
     Whether the top border of the `ExtraBuffer` should be displayed.
@@ -1402,6 +2096,16 @@ class BufferConfig:
         """
         ...
 
+    def visible_lines(self, raw: bool = False) -> list[str]:
+        """
+        Returns the rows of text rendered for the buffer by the most recent draw, from top
+        to bottom.
+
+        By default the plain text is returned. When `raw` is `True` the text styles are
+        included as ANSI escape sequences.
+        """
+        ...
+
 class ExtraBuffer:
     """
     A `BufferConfig` associated with an `int` buffer ID after being created with `MudpuppyCore.new_buffer()`
@@ -1455,6 +2159,37 @@ class Gauge:
     The title label for the gauge.
     """
 
+    show_values: bool
+    """
+    Whether the gauge is labelled with its value and max, like `450/500`, instead of a
+    percentage. Set by `MudpuppyCore.set_gauge()`.
+
+    Can be both read and set.
+    """
+
+    def ratio(self) -> float:
+        """
+        Returns the ratio of `value` to `max`, between `0.0` and `1.0`.
+
+        A gauge with a `max` of `0` has a ratio of `0.0`.
+        """
+        ...
+
+    def set_thresholds(self, thresholds: list[Tuple[float, Tuple[int, int, int]]]):
+        """
+        Set colours used while the gauge is low, as a list of `(percentage, (r, g, b))`
+        tuples. While the gauge is below a threshold's percentage it's drawn in the colour
+        of the lowest such threshold, instead of its own colour. For example, to draw
+        the gauge yellow below 50% and red below 25%:
+
+        ```python
+        gauge.set_thresholds([(50, (255, 255, 0)), (25, (255, 0, 0))])
+        ```
+
+        Replaces any previously set thresholds.
+        """
+        ...
+
     def set_colour(self, r: int, g: int, b: int):
         """
         Set the colour of the gauge to the RGB values provided.
@@ -1530,163 +2265,832 @@ class MudpuppyCore:
         """
         ...
 
-    async def sessions(self) -> list[SessionInfo]:
+    async def notify(self, title: str, body: str) -> bool:
         """
-        Returns a list of `SessionInfo` instances for all sessions.
+        Rings the terminal bell and, where supported, shows a desktop notification with
+        the given `title` and `body`.
+
+        Notifications are rate limited to one every five seconds. Returns `False` if the
+        notification was skipped because of this, or because the
+        `notifications_enabled` config setting is `false`.
         """
         ...
 
-    async def session_info(self, session_id: int) -> SessionInfo:
+    async def beep(self) -> bool:
         """
-        Returns a `SessionInfo` instance for the given session ID.
+        Rings the terminal bell.
+
+        The bell is rate limited to once a second. Returns `False` if it was skipped
+        because of this, or because the `notifications_enabled` config setting is
+        `false`.
         """
         ...
 
-    async def status(self, session_id: int) -> Status:
+    async def active_tab(self) -> Optional[int]:
         """
-        Returns connection `Status` information for the given session ID.
+        Returns the session ID of the tab being shown, or `None` if the MUD list tab
+        is shown.
+
+        Unlike `MudpuppyCore.active_session_id()` this doesn't change when a session is
+        created without switching to its tab.
         """
         ...
 
-    async def mud_config(self, session_id: int) -> Optional[Mud]:
+    async def switch_to_tab(self, session_id: int):
         """
-        Returns the `Mud` configuration for the given session ID, if it exists.
+        Shows the tab for the given session ID.
+
+        Raises an exception if there is no session with the given ID.
         """
         ...
 
-    async def send_line(self, session_id: int, line: str):
+    async def next_tab(self):
         """
-        Sends a line of text to the given session ID as if it were input sent by the user.
-
-        The input will be marked as "scripted" to differentiate it from true user input
-        typed at the keyboard.
+        Shows the next tab, wrapping around to the first tab after the last one.
+        """
+        ...
 
-        [Command splitting](https://mudpuppy-rs.github.io/mudpuppy/user-guide/input.html#command-splitting)
-        works the same as for normal user input.
+    async def previous_tab(self):
+        """
+        Shows the previous tab, wrapping around to the last tab before the first one.
+        """
+        ...
 
-        Unlike true user input, aliases are **not** evaluated for `send_line()`
-        input. This also means it isn't possible to send slash
-        [command](https://mudpuppy-rs.github.io/mudpuppy/user-guide/commands.html)
-        input in this manner.
+    async def sessions(self) -> list[SessionInfo]:
+        """
+        Returns a list of `SessionInfo` instances for all sessions.
+        """
+        ...
 
-        Prefer using `MudpuppyCore.send_lines()` for sending multiple lines.
+    async def sessions_for_mud(self, mud_name: str) -> list[SessionInfo]:
+        """
+        Returns a list of `SessionInfo` instances for all sessions of the MUD with
+        the given `Mud.name`.
+        """
+        ...
 
-        Example:
+    async def is_mud_connected(self, mud_name: str) -> bool:
+        """
+        Returns `True` if any session of the MUD with the given `Mud.name` is connected.
 
-        ```python
-        from mudpuppy_core import mudpuppy_core
-        session_id = ...
-        mudpuppy_core.send_line(session_id, "hello") # Sends 'hello'
-        mudpuppy_core.send_line(session_id, "hello;;wave") # Sends 'hello' and then 'wave'
-        ```
+        This is useful to avoid connecting a second session to the same MUD.
         """
         ...
 
-    async def send_lines(self, session_id: int, lines: list[str]):
+    async def session_info(self, session_id: int) -> SessionInfo:
         """
-        Sends a list of lines of text to the given session ID as if they were input sent by the user.
-
-        The input will be marked as "scripted" to differentiate it from true user input
-        typed at the keyboard.
+        Returns a `SessionInfo` instance for the given session ID.
+        """
+        ...
 
-        Prefer using `MudpuppyCore.send_line()` for sending a single line.
+    async def status(self, session_id: int) -> Status:
+        """
+        Returns connection `Status` information for the given session ID.
         """
         ...
 
-    async def connect(self, session_id: int):
+    async def banner(self, session_id: int) -> list[OutputItem]:
         """
-        Connects the given session ID if it isn't already connected.
+        Returns the output received from the MUD after the given session ID last
+        connected, before logging in. This usually holds the MUD's banner and MOTD.
 
-        You can use `MudpuppyCore.status()` to determine a session's connection `Status`
-        before calling `connect()`.
+        The banner ends when a password is sent, or at the first line matching the
+        MUD's `banner_end` pattern, which isn't included.
 
-        A `EventType.Connection` event will be emitted with the new `Status`.
+        To show the banner, set it as the output of a `BufferConfig` and create a
+        buffer with `MudpuppyCore.new_buffer()`.
         """
         ...
 
-    async def disconnect(self, session_id: int):
+    async def session_data(self, session_id: int) -> dict[Any, Any]:
         """
-        Disconnects the given session ID if it isn't already disconnected.
-
-        You can use `MudpuppyCore.status()` to determine a session's connection `Status`
-        before calling `connect()`.
+        Returns a `dict` for scripts to keep state in for the given session ID, e.g. the
+        current target.
 
-        A `EventType.Connection` event will be emitted with the new `Status`.
+        The same `dict` is returned for every call with the session ID. It's kept across
+        disconnects and reconnects, and dropped when the session is closed. Changing it
+        doesn't emit any events.
         """
         ...
 
-    async def request_enable_option(self, session_id: int, option: int):
+    async def session_stats(self, session_id: int) -> SessionStats:
         """
-        Requests that the MUD server enable a telnet option for the given session ID.
+        Returns `SessionStats` for the given session ID, describing how much has been
+        sent and received since the session connected.
 
-        If the option is enabled by the server a `EventType.OptionEnabled` event will be
-        emitted with the same session ID.
+        The counts are reset each time the session connects.
         """
         ...
 
-    async def request_disable_option(self, session_id: int, option: int):
+    async def mud_config(self, session_id: int) -> Optional[Mud]:
         """
-        Requests that the MUD server disable a telnet option for the given session ID.
-
-        If the option is disabled by the server a `EventType.OptionDisabled` event will be
-        emitted with the same session ID.
+        Returns the `Mud` configuration for the given session ID, if it exists.
         """
         ...
 
-    async def send_subnegotiation(self, session_id: int, option: int, data: bytes):
+    def mtts(self) -> int:
         """
-        Sends a telnet subnegotiation to the given session.
+        Returns the MUD Terminal Type Standard (MTTS) bitvector reported to MUDs that ask
+        with the telnet TERMINAL-TYPE option.
 
-        The data should be the raw bytes of the subnegotiation payload for the given option
-        code.
+        ANSI colour (1), UTF-8 (4) and TLS (2048) are always set. 256 colours (8) and
+        truecolor (256) are set when the terminal supports them, as detected from the
+        `TERM` and `COLORTERM` environment variables.
         """
         ...
 
-    async def new_trigger(
-        self, session_id: int, config: TriggerConfig, module: str
-    ) -> int:
+    def expand_speedwalk(
+        self, speedwalk: str, directions: Optional[str] = None
+    ) -> list[str]:
         """
-        Creates a new trigger for the given session ID for the given `TriggerConfig`.
+        Expands a speedwalk like `"3n2e"` into the movement commands it stands for, e.g.
+        `["n", "n", "n", "e", "e"]`.
 
-        Returns an `int` trigger ID that can be used with `MudpuppyCore.get_trigger()`,
-        `MudpuppyCore.disable_trigger()` and `MudpuppyCore.remove_trigger()`.
+        Each step is a direction letter, or any command in parentheses (e.g. `"(ne)"` or
+        `"(open door)"`), optionally preceded by a count of how many times to repeat it,
+        up to 100. Whitespace between steps is ignored.
 
-        The `module` str is used to associate the trigger with a specific Python module that created
-        it so that if the module is reloaded, the trigger will be deleted first to avoid duplicates
-        when the module re-creates it at load.
+        The direction letters are `directions` if provided, or the
+        `speedwalk_directions` config setting (`"nsewud"` by default) otherwise.
+
+        Raises an exception if the speedwalk has a letter that isn't a direction, an
+        unclosed parenthesis, an empty command, or an invalid count.
         """
         ...
 
-    async def get_trigger(self, session_id: int, trigger_id: int) -> Optional[Trigger]:
+    def dump_resolved_settings(self, mud_name: str) -> dict[str, dict[str, Any]]:
         """
-        Returns the `Trigger` for the given trigger ID if it exists for the provided session ID.
+        Returns the fully resolved configuration for the MUD with the given name.
 
-        See `MudpuppyCore.new_trigger()` for creating triggers.
+        Each config field name maps to a `dict` with a `"value"` key holding the
+        resolved value, and a `"source"` key describing where the value came from:
+        `"config"` if it was set in the config file, or `"default"` otherwise.
+
+        Raises an exception if there is no MUD with the given name.
         """
         ...
 
-    async def disable_trigger(self, session_id: int, trigger_id: int):
+    @staticmethod
+    def get_credential(name: str) -> Optional[str]:
         """
-        Disables the trigger with the given trigger ID for the given session ID if it
-        is currently enabled.
-
-        The trigger will no longer be evaluated when new input is received, even
-        if it matches the trigger's pattern.
+        Returns the credential saved with `MudpuppyCore.set_credential()` under `name`
+        in the OS keyring, or `None` if there isn't one.
 
-        You can use `MudpuppyCore.get_trigger()` to get a `Trigger` to determine if
-        it is currently enabled or disabled. Use `MudpuppyCore.enable_trigger()` to
-        enable the trigger again.
+        Raises an exception if the OS keyring can't be used.
         """
         ...
 
-    async def enable_trigger(self, session_id: int, trigger_id: int):
+    @staticmethod
+    def set_credential(name: str, value: str):
         """
-        Enables the trigger with the given trigger ID for the given session ID if it
-        was previously disabled.
+        Saves `value` (e.g. a password) in the OS keyring as the credential `name`,
+        replacing any previous value. It can then be sent without keeping it in the
+        config file by writing `{credential:NAME}` in a line sent with
+        `expand_secrets=True`.
 
-        You can use `MudpuppyCore.get_trigger()` to get a `Trigger` to determine if
-        it is currently enabled or disabled. Use `MudpuppyCore.disable_trigger()` to
-        disable the trigger again.
+        Raises an exception if the OS keyring can't be used.
+        """
+        ...
+
+    @staticmethod
+    def delete_credential(name: str) -> bool:
+        """
+        Removes the credential `name` from the OS keyring. Returns `False` if there
+        wasn't one.
+
+        Raises an exception if the OS keyring can't be used.
+        """
+        ...
+
+    async def send_line(
+        self,
+        session_id: int,
+        line: str,
+        *,
+        split: bool = True,
+        expand_secrets: bool = False,
+    ):
+        """
+        Sends a line of text to the given session ID as if it were input sent by the user.
+
+        The input will be marked as "scripted" to differentiate it from true user input
+        typed at the keyboard.
+
+        [Command splitting](https://mudpuppy-rs.github.io/mudpuppy/user-guide/input.html#command-splitting)
+        works the same as for normal user input, including backslash escaped separators.
+        Pass `split=False` to send the line as a single command, exactly as given.
+
+        Unlike true user input, aliases are **not** evaluated for `send_line()`
+        input. This also means it isn't possible to send slash
+        [command](https://mudpuppy-rs.github.io/mudpuppy/user-guide/commands.html)
+        input in this manner.
+
+        Prefer using `MudpuppyCore.send_lines()` for sending multiple lines.
+
+        If `expand_secrets` is `True`, each `{keyring:SERVICE:ACCOUNT}` reference in
+        the line is replaced with the password stored for it in the OS keyring, and
+        each `{credential:NAME}` reference with the credential saved by
+        `MudpuppyCore.set_credential()`. The line is split into commands before the
+        secrets are replaced, so a secret containing the command separator is still
+        sent as part of one command. Commands with secrets are sent like passwords, so
+        they aren't echoed or kept in the input history. An exception is raised if a
+        password can't be found.
+
+        Example:
+
+        ```python
+        from mudpuppy_core import mudpuppy_core
+        session_id = ...
+        mudpuppy_core.send_line(session_id, "hello") # Sends 'hello'
+        mudpuppy_core.send_line(session_id, "hello;;wave") # Sends 'hello' and then 'wave'
+        ```
+        """
+        ...
+
+    async def send_password(self, session_id: int, password: str):
+        """
+        Sends `password` to the given session ID as a single scripted line, without
+        command splitting.
+
+        Unlike `MudpuppyCore.send_line()` the line is always treated as a password,
+        whether or not the MUD has turned off echo: the output buffer, session log and
+        `Event.InputLine` only see a `********` mask, and it isn't kept in the input
+        history. Use this for auto-login scripts that may run before the MUD asks for a
+        password.
+        """
+        ...
+
+    async def send_lines(self, session_id: int, lines: list[str]):
+        """
+        Sends a list of lines of text to the given session ID as if they were input sent by the user.
+
+        The input will be marked as "scripted" to differentiate it from true user input
+        typed at the keyboard.
+
+        Prefer using `MudpuppyCore.send_line()` for sending a single line.
+        """
+        ...
+
+    async def queue_commands(
+        self, session_id: int, lines: list[str], interval: float
+    ):
+        """
+        Sends a list of lines of text to the given session ID like `send_lines()`, waiting
+        `interval` seconds after each line before sending the next. Useful for speedwalks
+        on MUDs that reject commands sent too quickly.
+
+        Lines are queued behind any lines still waiting to be sent. Lines sent while the
+        queue is waiting, e.g. by the user or by triggers, are queued after them.
+
+        Queued lines are discarded when the session disconnects, or when
+        `MudpuppyCore.cancel_queued_commands()` is called.
+
+        See also the `Mud.command_interval` config setting, which paces all commands.
+
+        Raises a `ValueError` if `interval` is negative or not a finite number.
+        """
+        ...
+
+    async def queued_commands(self, session_id: int) -> int:
+        """
+        Returns the number of lines waiting to be sent by the given session ID's command
+        queue.
+        """
+        ...
+
+    async def cancel_queued_commands(self, session_id: int) -> int:
+        """
+        Discards the lines waiting to be sent by the given session ID's command queue,
+        returning how many there were.
+        """
+        ...
+
+    async def sent_commands(self, session_id: int, n: int) -> list[InputLine]:
+        """
+        Returns up to the `n` most recent lines transmitted to the MUD for the given
+        session ID, oldest first.
+
+        Unlike input history, these are the lines exactly as they were sent, after
+        aliases were expanded and commands were split. Lines sent by scripts are included,
+        and can be told apart from user input with `InputLine.scripted`.
+
+        The most recent 100 lines are remembered, including lines sent before reconnecting.
+        """
+        ...
+
+    async def input_history(self, session_id: int) -> list[InputLine]:
+        """
+        Returns the input history of the given session ID, oldest first.
+
+        These are the lines as they were typed, before aliases were expanded. Lines sent by
+        scripts, password lines and immediate repeats of the previous line aren't included.
+
+        The number of lines kept is set by the global `history_size` setting. If
+        `Mud.persist_history` is enabled the history includes lines loaded from previous runs.
+        """
+        ...
+
+    async def start_logging(
+        self,
+        session_id: int,
+        path: Optional[str] = None,
+        format: Optional[LogFormat] = None,
+    ) -> str:
+        """
+        Starts logging everything added to the output buffer of the given session ID to
+        a file: MUD output, input, connection events and command results. Each line is
+        prefixed with a UTC timestamp.
+
+        If `path` is `None` a new file named for the MUD and the current time is created in
+        the `logs` directory of the data directory. Relative paths are resolved against
+        the data directory. Existing files are appended to.
+
+        If `format` is `None` the MUD's `Mud.log_format` is used.
+
+        Any log already open for the session is closed first. Returns the path of the log file.
+        """
+        ...
+
+    async def stop_logging(self, session_id: int) -> Optional[str]:
+        """
+        Stops logging the output buffer of the given session ID. Output added before this
+        call is still written before the file is closed.
+
+        Returns the path of the log file that was closed, or `None` if the session wasn't
+        being logged.
+        """
+        ...
+
+    async def log_path(self, session_id: int) -> Optional[str]:
+        """
+        Returns the path of the file the given session ID's output is being logged to,
+        or `None` if it isn't being logged.
+        """
+        ...
+
+    async def prompt_history(
+        self, session_id: int, n: Optional[int] = None
+    ) -> list[tuple[float, MudLine]]:
+        """
+        Returns up to the `n` most recent prompts received by the given session ID, oldest
+        first. All remembered prompts are returned if `n` is `None`.
+
+        Each prompt is paired with the time it was received, as seconds since the Unix epoch.
+        This is handy for tracking trends like HP or mana over time.
+
+        The number of prompts remembered is set by `Mud.prompt_history_size`. History is
+        cleared each time the session connects.
+        """
+        ...
+
+    async def gag_prompt(self, session_id: int) -> bool:
+        """
+        Gags the most recent prompt received by the given session ID, hiding it from
+        the output buffer (including when it's held at the bottom of the buffer).
+
+        Returns `False` if no prompt has been received yet.
+        """
+        ...
+
+    async def send_bytes(self, session_id: int, data: bytes, newline: bool = True):
+        """
+        Sends raw `data` bytes to the given session ID without any re-encoding.
+
+        This is useful for MUDs that expect a specific byte encoding, or a binary protocol.
+        IAC (255) bytes in `data` are escaped automatically. The MUD's `line_ending` is
+        appended unless `newline` is `False`.
+
+        Unlike `MudpuppyCore.send_line()` the data isn't processed by aliases, or displayed in
+        the output buffer.
+
+        Raises an exception if the session isn't connected.
+        """
+        ...
+
+    async def send_raw(self, session_id: int, data: bytes):
+        """
+        Sends `data` bytes to the given session ID exactly as given, with no line ending.
+
+        This is the same as `MudpuppyCore.send_bytes()` with `newline=False`. It's handy for
+        answering prompts that expect a single key press, e.g. a bare `y` for a "[Y/n]" prompt:
+
+        ```python
+        await mudpuppy_core.send_raw(session_id, b"y")
+        ```
+
+        Aliases aren't applied and nothing is displayed in the output buffer. IAC (255) bytes
+        in `data` are escaped automatically.
+
+        Raises an exception if the session isn't connected.
+        """
+        ...
+
+    async def connect(self, session_id: int):
+        """
+        Connects the given session ID if it isn't already connected.
+
+        You can use `MudpuppyCore.status()` to determine a session's connection `Status`
+        before calling `connect()`.
+
+        A `EventType.Connection` event will be emitted with the new `Status`.
+
+        This returns once the session is connecting, without waiting for the connection to
+        be established. If `max_concurrent_connects` sessions are already connecting the
+        session stays connecting until a slot is free. A `EventType.Connection` event is
+        emitted again when the connection is established, or when it fails and the session
+        is disconnected.
+
+        Raises a `RuntimeError` if the session's MUD config is invalid.
+        """
+        ...
+
+    async def disconnect(self, session_id: int):
+        """
+        Disconnects the given session ID if it isn't already disconnected.
+
+        You can use `MudpuppyCore.status()` to determine a session's connection `Status`
+        before calling `connect()`.
+
+        A `EventType.Connection` event will be emitted with the new `Status`.
+        """
+        ...
+
+    async def connect_all(self) -> list[Tuple[int, Optional[str]]]:
+        """
+        Connects every session that isn't already connected, e.g. after a server reboot.
+
+        The sessions connect concurrently, subject to the `max_concurrent_connects` config.
+        Like `MudpuppyCore.connect()` this doesn't wait for the connections to be
+        established. Returns a list of `(session_id, error)` tuples ordered by session ID.
+        The `error` is `None` if the session started connecting, or a description of why it
+        couldn't:
+
+        ```python
+        for session_id, error in await mudpuppy_core.connect_all():
+            if error is not None:
+                logging.warning(f"session {session_id} failed to connect: {error}")
+        ```
+        """
+        ...
+
+    async def disconnect_all(self) -> list[Tuple[int, Optional[str]]]:
+        """
+        Disconnects every session that isn't already disconnected.
+
+        The sessions disconnect concurrently. Returns a list of `(session_id, error)` tuples
+        like `MudpuppyCore.connect_all()`.
+        """
+        ...
+
+    async def schedule_connect(
+        self, session_id: int, at: float, reconnect: bool = False
+    ):
+        """
+        Schedules the given session ID to connect at the future time `at`, expressed
+        as a UNIX timestamp in seconds (e.g. from `time.time()`).
+
+        If the session is already connected at the scheduled time nothing happens,
+        unless `reconnect` is `True`, in which case the session is disconnected and
+        connected again.
+
+        Only one connection can be scheduled per session. Scheduling a new connection
+        replaces any pending one. Timestamps in the past connect immediately.
+
+        Raises a `ValueError` if `at` is negative or not a finite number.
+        """
+        ...
+
+    async def cancel_scheduled_connect(self, session_id: int) -> bool:
+        """
+        Cancels a connection scheduled with `MudpuppyCore.schedule_connect()` for the
+        given session ID.
+
+        Returns `True` if a pending scheduled connection was cancelled.
+        """
+        ...
+
+    async def cancel_reconnect(self, session_id: int) -> bool:
+        """
+        Cancels a pending automatic reconnect for the given session ID. See
+        `Mud.auto_reconnect`.
+
+        Returns `True` if a pending reconnect was cancelled. Calling
+        `MudpuppyCore.disconnect()` also cancels any pending reconnect.
+        """
+        ...
+
+    async def request_enable_option(self, session_id: int, option: int):
+        """
+        Requests that the MUD server enable a telnet option for the given session ID.
+
+        If the option is enabled by the server a `EventType.OptionEnabled` event will be
+        emitted with the same session ID.
+        """
+        ...
+
+    async def request_disable_option(self, session_id: int, option: int):
+        """
+        Requests that the MUD server disable a telnet option for the given session ID.
+
+        If the option is disabled by the server a `EventType.OptionDisabled` event will be
+        emitted with the same session ID.
+        """
+        ...
+
+    async def send_subnegotiation(self, session_id: int, option: int, data: bytes):
+        """
+        Sends a telnet subnegotiation to the given session.
+
+        The data should be the raw bytes of the subnegotiation payload for the given option
+        code.
+        """
+        ...
+
+    async def prompt_mode(self, session_id: int) -> PromptMode:
+        """
+        Returns the `PromptMode` the given session ID is using to detect prompt lines.
+
+        This is useful for understanding why prompts are, or aren't, being detected.
+        """
+        ...
+
+    async def set_prompt_mode(self, session_id: int, mode: PromptMode):
+        """
+        Forces the given session ID to use the `PromptMode` provided for detecting
+        prompt lines.
+
+        Note that later telnet negotiation of the EOR option may change the mode again.
+        """
+        ...
+
+    async def confirm(
+        self,
+        session_id: int,
+        message: str,
+        callback: Callable[[int, Optional[bool]], Awaitable[None]],
+        timeout_ms: Optional[int] = None,
+    ):
+        """
+        Shows a dialog asking the user a yes or no question with the given `message`.
+
+        The async `callback` is called with the session ID and `True` or `False` once
+        the user answers, or `None` if they cancel the dialog or `timeout_ms` passes
+        without an answer. Dialogs are shown one at a time, in the order they were
+        opened. A session's dialogs are dropped without calling their callbacks when
+        the session is closed.
+
+        Raises a `TypeError` if `callback` isn't an async function.
+        """
+        ...
+
+    async def prompt(
+        self,
+        session_id: int,
+        message: str,
+        callback: Callable[[int, Optional[str]], Awaitable[None]],
+        timeout_ms: Optional[int] = None,
+    ):
+        """
+        Shows a dialog asking the user to enter a line of text in answer to `message`.
+
+        The async `callback` is called with the session ID and the entered text once
+        the user presses Enter, or `None` if they cancel the dialog or `timeout_ms`
+        passes without an answer.
+        """
+        ...
+
+    async def select(
+        self,
+        session_id: int,
+        message: str,
+        options: list[str],
+        callback: Callable[[int, Optional[int]], Awaitable[None]],
+        timeout_ms: Optional[int] = None,
+    ):
+        """
+        Shows a dialog asking the user to choose one of `options` with the arrow keys.
+
+        The async `callback` is called with the session ID and the index of the chosen
+        option once the user presses Enter, or `None` if they cancel the dialog or
+        `timeout_ms` passes without an answer.
+
+        Raises an exception if `options` is empty.
+        """
+        ...
+
+    async def capture_block(
+        self,
+        session_id: int,
+        until_pattern: str,
+        callback: Callable[[int, list[MudLine]], Awaitable[None]],
+        max_lines: int = 500,
+    ):
+        """
+        Gags the following lines of output for the given session ID, collecting them until
+        one matches the `until_pattern` regexp, or `max_lines` have been collected.
+
+        The block of lines, including the one that matched, is then passed to the async
+        `callback` along with the session ID. This is useful for parsing multi-line output
+        like a score screen. The pattern is matched against the text of each line without
+        ANSI colour. Captured lines aren't evaluated by triggers.
+
+        Raises an exception if the pattern is invalid, or a block is already being captured
+        for the session. A capture in progress is cancelled when the session reconnects.
+        """
+        ...
+
+    async def cancel_capture(self, session_id: int) -> bool:
+        """
+        Stops capturing a block of output for the given session ID without calling its
+        callback. Returns `True` if a block was being captured.
+
+        Lines that were already captured stay gagged.
+        """
+        ...
+
+    async def on_subnegotiation(
+        self,
+        session_id: int,
+        option: int,
+        callback: Callable[[int, int, bytes], Awaitable[None]],
+        module: str,
+    ):
+        """
+        Registers an async `callback` to be invoked with the session ID, option code and raw
+        subnegotiation payload bytes whenever subnegotiation data for `option` is received
+        by the given session.
+
+        Handlers are only invoked for options that have been negotiated. The generic
+        `EventType.Subnegotiation` event is emitted for all subnegotiations regardless.
+        When more than one handler is registered for an option they are called in the
+        order they were registered.
+
+        The `module` str is used to associate the handler with the Python module that
+        registered it. Handlers are removed when Python modules are reloaded, so modules
+        should register them again with `mudpuppy.on_new_session_or_reload()`.
+        """
+        ...
+
+    async def add_input_transform(
+        self,
+        session_id: int,
+        callback: Callable[[int, InputLine], Optional[InputLine]],
+        module: str,
+    ):
+        """
+        Registers a `callback` to transform input for the given session ID before it is sent.
+
+        The `callback` must be a regular function, not a coroutine. It's invoked with the
+        session ID and the `InputLine` about to be sent, and must return the (possibly
+        modified) `InputLine` to send, or `None` to cancel sending the input.
+
+        Transforms run unconditionally, before any aliases are evaluated. When more than one
+        transform is registered they're run in the order they were registered, each receiving
+        the output of the previous transform.
+
+        The `module` str is used to associate the transform with the Python module that
+        registered it. See `MudpuppyCore.remove_module_input_transforms()`. Transforms are
+        removed when Python modules are reloaded.
+        """
+        ...
+
+    async def remove_module_input_transforms(self, session_id: int, module: str):
+        """
+        Removes all input transforms registered by the given module for the given session ID.
+        """
+        ...
+
+    async def import_triggers(
+        self, session_id: int, path: str, module: str, format: str = "json"
+    ) -> ImportReport:
+        """
+        Imports triggers and aliases for the given session ID from the file at `path`.
+
+        `format` is `"json"` for mudpuppy's own JSON format, or `"tinyfugue"` for
+        TinyFugue `/def` commands with a trigger pattern. See the user guide for the
+        JSON format, and which TinyFugue features are supported.
+
+        Like `MudpuppyCore.new_trigger()` the `module` str associates the triggers and
+        aliases with the Python module that imported them.
+
+        Entries that can't be imported, e.g. because their pattern is invalid or one
+        with the same name already exists, are listed in the returned
+        `ImportReport.failures` rather than failing the whole import. Raises an
+        exception if the file can't be read or parsed at all.
+        """
+        ...
+
+    async def new_trigger(
+        self, session_id: int, config: TriggerConfig, module: str
+    ) -> int:
+        """
+        Creates a new trigger for the given session ID for the given `TriggerConfig`.
+
+        Returns an `int` trigger ID that can be used with `MudpuppyCore.get_trigger()`,
+        `MudpuppyCore.disable_trigger()` and `MudpuppyCore.remove_trigger()`.
+
+        The `module` str is used to associate the trigger with a specific Python module that created
+        it so that if the module is reloaded, the trigger will be deleted first to avoid duplicates
+        when the module re-creates it at load.
+        """
+        ...
+
+    async def get_trigger(self, session_id: int, trigger_id: int) -> Optional[Trigger]:
+        """
+        Returns the `Trigger` for the given trigger ID if it exists for the provided session ID.
+
+        See `MudpuppyCore.new_trigger()` for creating triggers.
+        """
+        ...
+
+    async def test_trigger(
+        self, session_id: int, trigger_id: int, sample: str
+    ) -> Optional[PatternMatch]:
+        """
+        Tests the pattern of the given trigger ID against `sample` without running the
+        trigger. See `TriggerConfig.test()`.
+
+        Raises an exception if the trigger ID doesn't exist for the provided session ID.
+        """
+        ...
+
+    async def disable_trigger(self, session_id: int, trigger_id: int):
+        """
+        Disables the trigger with the given trigger ID for the given session ID if it
+        is currently enabled.
+
+        The trigger will no longer be evaluated when new input is received, even
+        if it matches the trigger's pattern.
+
+        You can use `MudpuppyCore.get_trigger()` to get a `Trigger` to determine if
+        it is currently enabled or disabled. Use `MudpuppyCore.enable_trigger()` to
+        enable the trigger again.
+        """
+        ...
+
+    async def enable_trigger(self, session_id: int, trigger_id: int):
+        """
+        Enables the trigger with the given trigger ID for the given session ID if it
+        was previously disabled.
+
+        You can use `MudpuppyCore.get_trigger()` to get a `Trigger` to determine if
+        it is currently enabled or disabled. Use `MudpuppyCore.disable_trigger()` to
+        disable the trigger again.
+        """
+        ...
+
+    async def enable_group(self, session_id: int, group: str) -> int:
+        """
+        Enables every trigger and alias for the given session ID whose `group` is
+        `group`, returning how many there were.
+
+        Use `MudpuppyCore.disable_group()` to disable them again.
+        """
+        ...
+
+    async def disable_group(self, session_id: int, group: str) -> int:
+        """
+        Disables every trigger and alias for the given session ID whose `group` is
+        `group`, returning how many there were.
+
+        Use `MudpuppyCore.enable_group()` to enable them again.
+        """
+        ...
+
+    async def add_substitution(
+        self,
+        session_id: int,
+        pattern: str,
+        replacement: str,
+        *,
+        match_ansi: bool = False,
+    ) -> int:
+        """
+        Adds a substitution for the given session ID that replaces each match of the
+        `pattern` regex in lines of output with `replacement`. Returns the ID of the
+        new `Substitution`.
+
+        Like triggers, the pattern is matched against the visible text with ANSI
+        stripped unless `match_ansi` is `True`. Colours inside a replaced match are
+        kept after the replacement.
+
+        Substitutions are applied after triggers, in the order they were added.
+
+        Raises an exception if `pattern` is not a valid regex.
+        """
+        ...
+
+    async def remove_substitution(self, session_id: int, substitution_id: int):
+        """
+        Removes the substitution with the given ID for the given session ID if it
+        exists.
+        """
+        ...
+
+    async def substitutions(self, session_id: int) -> list[Substitution]:
+        """
+        Returns the substitutions for the given session ID, in the order they're
+        applied.
         """
         ...
 
@@ -1704,6 +3108,26 @@ class MudpuppyCore:
         """
         ...
 
+    async def set_triggers_enabled(self, session_id: int, enabled: bool):
+        """
+        Sets whether triggers are evaluated at all for the given session ID.
+
+        This is a master switch that's useful for temporarily bypassing all triggers, e.g.
+        while reviewing spammy output. It doesn't change the enabled state of individual triggers: when
+        triggers are enabled again, each trigger that was enabled before is evaluated as normal.
+
+        See `MudpuppyCore.triggers_enabled()`.
+        """
+        ...
+
+    async def triggers_enabled(self, session_id: int) -> bool:
+        """
+        Returns whether triggers are evaluated for the given session ID.
+
+        See `MudpuppyCore.set_triggers_enabled()`.
+        """
+        ...
+
     async def remove_module_triggers(self, session_id: int, module: str):
         """
         Removes all triggers created by the given module for the given session ID.
@@ -1740,6 +3164,17 @@ class MudpuppyCore:
         """
         ...
 
+    async def test_alias(
+        self, session_id: int, alias_id: int, sample: str
+    ) -> Optional[PatternMatch]:
+        """
+        Tests the pattern of the given alias ID against `sample` without running the
+        alias. See `AliasConfig.test()`.
+
+        Raises an exception if the alias ID doesn't exist for the provided session ID.
+        """
+        ...
+
     async def disable_alias(self, session_id: int, alias_id: int):
         """
         Disables the alias with the given alias ID for the given session if it
@@ -1779,6 +3214,26 @@ class MudpuppyCore:
         """
         ...
 
+    async def set_aliases_enabled(self, session_id: int, enabled: bool):
+        """
+        Sets whether aliases are evaluated at all for the given session ID.
+
+        This is a master switch that's useful for temporarily bypassing all aliases, e.g.
+        while pasting bulk input. It doesn't change the enabled state of individual aliases: when
+        aliases are enabled again, each alias that was enabled before is evaluated as normal.
+
+        See `MudpuppyCore.aliases_enabled()`.
+        """
+        ...
+
+    async def aliases_enabled(self, session_id: int) -> bool:
+        """
+        Returns whether aliases are evaluated for the given session ID.
+
+        See `MudpuppyCore.set_aliases_enabled()`.
+        """
+        ...
+
     async def remove_module_aliases(self, session_id: int, module: str):
         """
         Removes all aliases created by the given module for the given session ID.
@@ -1837,6 +3292,39 @@ class MudpuppyCore:
         """
         ...
 
+    async def pause_timer(self, timer_id: int):
+        """
+        Pauses the running timer with the given timer ID.
+
+        Unlike `MudpuppyCore.stop_timer()` the time remaining until the timer next fires
+        is kept. Use `MudpuppyCore.resume_timer()` to continue the countdown from where
+        it was paused.
+
+        Raises an exception if the timer isn't running.
+        """
+        ...
+
+    async def resume_timer(self, timer_id: int):
+        """
+        Resumes the paused timer with the given timer ID. The timer next fires once the time
+        that was remaining when it was paused has elapsed.
+
+        Raises an exception if the timer isn't running.
+        """
+        ...
+
+    async def reset_timer(self, timer_id: int):
+        """
+        Restarts the countdown of the running timer with the given timer ID, so it next
+        fires once the timer's full duration has elapsed. A paused timer is resumed.
+
+        This is useful for keeping a timer in step with a MUD's periodic "tick", by
+        resetting it from a trigger that matches the tick message.
+
+        Raises an exception if the timer isn't running.
+        """
+        ...
+
     async def remove_timer(self, timer_id: int):
         """
         Removes the timer with the given timer ID if it exists.
@@ -1908,6 +3396,18 @@ class MudpuppyCore:
         """
         ...
 
+    async def echo(self, session_id: int, text: str):
+        """
+        Adds each line of `text` to the main output buffer for the given session ID, as
+        if it came from the MUD.
+
+        Colours can be given with the same markup as `cformat.cformat()`, e.g.
+        `"<bold><red>Low HP!<reset>"`. The markup is converted natively, so this is
+        cheaper than formatting with `cformat()` and adding an `OutputItem.mud()`.
+        Markup applies until the end of the line it's on.
+        """
+        ...
+
     async def add_output(self, session_id: int, output: OutputItem):
         """
         Adds an `OutputItem` to the main output buffer for the given session ID.
@@ -1918,11 +3418,44 @@ class MudpuppyCore:
         """
         ...
 
-    async def add_outputs(self, session_id: int, outputs: list[OutputItem]):
+    async def replace_last_line(self, session_id: int, output: OutputItem):
+        """
+        Replaces the most recent `OutputItem` in the main output buffer for the given
+        session ID, or adds `output` if the buffer is empty.
+
+        This is useful for output that updates itself in place, like a progress indicator.
+        The scroll position is kept. See `Output.replace_last()`.
+        """
+        ...
+
+    async def insert_output(self, session_id: int, index: int, output: OutputItem):
+        """
+        Inserts an `OutputItem` into the main output buffer for the given session ID, before
+        the item at `index`.
+
+        Indexes past the end of the buffer add the item last, like `add_output()`. See
+        `Output.insert()`.
+        """
+        ...
+
+    async def add_outputs(self, session_id: int, outputs: list[OutputItem]):
+        """
+        Adds a list of `OutputItem` instances to the main output buffer for the given session ID.
+
+        USe `MudpuppyCore.add_output()` if you only have one `OutputItem` to add.
+        """
+        ...
+
+    async def search_output(
+        self, session_id: int, needle: str, max_results: Optional[int] = None
+    ) -> list[tuple[int, OutputItem]]:
         """
-        Adds a list of `OutputItem` instances to the main output buffer for the given session ID.
+        Searches the main output buffer for the given session ID for items with text
+        containing `needle`, ignoring case and ANSI colour.
 
-        USe `MudpuppyCore.add_output()` if you only have one `OutputItem` to add.
+        Returns a list of `(index, OutputItem)` tuples, with the most recent matches first,
+        up to `max_results` if provided. This is useful for scripts that need to find e.g.
+        the last time something happened. See `Output.search()`.
         """
         ...
 
@@ -1978,6 +3511,18 @@ class MudpuppyCore:
         """
         ...
 
+    async def clear_buffer(self, session_id: int, buffer_id: Optional[int] = None):
+        """
+        Removes all output from a buffer for the given session ID, and scrolls it back to
+        the bottom.
+
+        The MUD output buffer is cleared unless the `buffer_id` of an `ExtraBuffer` is
+        given. An `EventType.BufferCleared` event is emitted afterwards.
+
+        Raises an exception if there's no buffer with the given buffer ID.
+        """
+        ...
+
     async def remove_buffer(self, session_id: int, buffer_id: int):
         """
         Removes the buffer with the given buffer ID for the given session ID if it
@@ -1989,6 +3534,54 @@ class MudpuppyCore:
         """
         ...
 
+    async def move_buffer(self, session_id: int, buffer_id: int, direction: int):
+        """
+        Moves the layout section of the `ExtraBuffer` with the given buffer ID for the
+        given session ID within its parent `LayoutNode`.
+
+        A negative `direction` moves the section towards the start of the parent's sections,
+        a positive `direction` towards the end. The position is clamped to the bounds of
+        the parent. The change is visible on the next render.
+
+        Raises an exception if the buffer's `BufferConfig.layout_name` isn't in the layout.
+        """
+        ...
+
+    async def resize_buffer(
+        self, session_id: int, buffer_id: int, constraint: Constraint
+    ):
+        """
+        Replaces the `Constraint` of the layout section of the `ExtraBuffer` with the given
+        buffer ID for the given session ID.
+
+        The constraint is clamped to sane values (e.g. a length of at least 1, a percentage
+        between 1 and 100) so that the buffer can't be resized out of view.
+
+        Raises an exception if the buffer's `BufferConfig.layout_name` isn't in the layout.
+        """
+        ...
+
+    async def set_filter(self, session_id: int, pattern: Optional[str] = None):
+        """
+        Set a live filter regex `pattern` for the main output buffer of the given session ID.
+
+        While a filter is set, only output with text matching the pattern is displayed.
+        New output that matches will appear as it arrives. The underlying output is not
+        changed, and clearing the filter by passing `None` (or an empty pattern) restores
+        all lines.
+
+        Raises an exception if the pattern isn't a valid regex. The previous filter is
+        kept in this case.
+        """
+        ...
+
+    async def get_filter(self, session_id: int) -> Optional[str]:
+        """
+        Returns the live filter regex pattern for the main output buffer of the given
+        session ID, or `None` if no filter is set.
+        """
+        ...
+
     async def new_gauge(
         self,
         session_id: int,
@@ -1998,15 +3591,169 @@ class MudpuppyCore:
         value: Optional[float] = None,
         max: Optional[float] = None,
         rgb: Optional[Tuple[int, int, int]] = None,
+        thresholds: Optional[list[Tuple[float, Tuple[int, int, int]]]] = None,
     ) -> Gauge:
         """
         Creates a new `Gauge` based on the provided arguments, for the given `session_id`.
 
+        The optional `thresholds` are colours to use while the gauge is low. See
+        `Gauge.set_thresholds()`.
+
         Returns the created `Gauge` instance. You can read/write values of this instance
         to customize the gauge.
         """
         ...
 
+    async def visible_lines(
+        self, session_id: int, buffer_name: str, raw: bool = False
+    ) -> list[str]:
+        """
+        Returns the rows of text currently rendered in a buffer for the given session ID,
+        from top to bottom.
+
+        This is exactly what the user can see, accounting for line wrapping, the buffer's
+        size and its scroll position. Use `"output_area"` as the `buffer_name` for the main
+        output buffer, or the `BufferConfig.layout_name` of an `ExtraBuffer`. The scrollback
+        window that's shown while scrolling isn't included.
+
+        By default the plain text is returned. When `raw` is `True` the text styles are
+        included as ANSI escape sequences.
+
+        Raises an exception if there's no buffer with the given name.
+        """
+        ...
+
+    async def scroll(self, session_id: int, buffer_name: str, lines: int) -> int:
+        """
+        Scrolls a buffer for the given session ID by `lines` output items, returning the new
+        scroll position.
+
+        Positive `lines` scroll back through older output, and negative `lines` towards the
+        newest output. The position is clamped, so scrolling stops at the oldest and newest
+        output. Use `"output_area"` as the `buffer_name` for the main output buffer, or the
+        `BufferConfig.layout_name` of an `ExtraBuffer`.
+
+        Raises an exception if there's no buffer with the given name.
+        """
+        ...
+
+    async def scroll_to_item(self, session_id: int, buffer_name: str, index: int) -> int:
+        """
+        Scrolls a buffer for the given session ID so the output item at `index` is shown,
+        returning the new scroll position.
+
+        Indexes are the same as those returned by `search_output()` and `Output.search()`.
+        Use `"output_area"` as the `buffer_name` for the main output buffer, or the
+        `BufferConfig.layout_name` of an `ExtraBuffer`.
+
+        Raises an exception if there's no buffer with the given name.
+        """
+        ...
+
+    async def scroll_to_prompt(self, session_id: int, previous: bool = True) -> int:
+        """
+        Scrolls the main output buffer for the given session ID so the closest prompt before
+        the bottom-most visible output item is shown, returning the new scroll position. With
+        `previous=False` the closest prompt after it is shown instead.
+
+        Like the prompt navigation shortcuts, this stops at the oldest and newest prompts
+        rather than wrapping around. The scroll position is unchanged if there's no prompt to
+        move to.
+        """
+        ...
+
+    async def scroll_position(self, session_id: int, buffer_name: str) -> int:
+        """
+        Returns the scroll position of a buffer for the given session ID, counted in output
+        items back from the most recent item. `0` means the buffer isn't scrolled back.
+
+        Use `"output_area"` as the `buffer_name` for the main output buffer, or the
+        `BufferConfig.layout_name` of an `ExtraBuffer`.
+
+        Raises an exception if there's no buffer with the given name.
+        """
+        ...
+
+    async def get_wrap(self, session_id: int, buffer_name: str) -> bool:
+        """
+        Returns whether a buffer for the given session ID wraps long lines.
+
+        Use `"output_area"` as the `buffer_name` for the main output buffer, or the
+        `BufferConfig.layout_name` of an `ExtraBuffer`.
+
+        Raises an exception if there's no buffer with the given name.
+        """
+        ...
+
+    async def set_wrap(self, session_id: int, buffer_name: str, wrap: bool):
+        """
+        Enables or disables wrapping long lines in a buffer for the given session ID.
+
+        Existing content is re-wrapped the next time the buffer is drawn, and the scroll
+        position is kept. For the `"output_area"` buffer the change is transient: it overrides
+        the MUD's `no_line_wrap` setting without changing the config file, until the line wrap
+        shortcut is used or the session ends.
+
+        Raises an exception if there's no buffer with the given name.
+        """
+        ...
+
+    async def get_timestamps(self, session_id: int) -> bool:
+        """
+        Returns whether the output of the given session ID is shown with a gutter of the
+        times lines were received.
+        """
+        ...
+
+    async def set_timestamps(self, session_id: int, show: bool):
+        """
+        Shows or hides the timestamp gutter for the output of the given session ID.
+
+        The change is transient: it overrides the global `show_timestamps` setting without
+        changing the config file, until the session ends.
+        """
+        ...
+
+    async def set_gauge(
+        self, session_id: int, gauge_id: int, current: float, max: float
+    ):
+        """
+        Sets the `value` and `max` of the `Gauge` with the given `gauge_id`, for the given
+        `session_id`. The gauge is labelled with both, like `450/500`, instead of a percentage.
+
+        This is convenient for HP, mana or movement bars driven by GMCP vitals:
+
+        ```python
+        await mudpuppy_core.set_gauge(session_id, hp_gauge.id, vitals["hp"], vitals["maxhp"])
+        ```
+
+        Raises an exception if the gauge ID doesn't exist.
+        """
+        ...
+
+    async def update_gauges(self, session_id: int, updates: dict[int, dict[str, Any]]):
+        """
+        Updates several `Gauge`s for the given `session_id` at once.
+
+        The `updates` dict maps `Gauge.id` values to a dict of changes. Each may
+        contain `"value"`, `"max"` and `"title"` keys. Fields that aren't
+        included are left unchanged. For example:
+
+        ```python
+        await mudpuppy_core.update_gauges(session_id, {
+            hp_gauge.id: {"value": 80, "max": 120},
+            sp_gauge.id: {"value": 15, "title": "SP (low!)"},
+        })
+        ```
+
+        Gauge IDs that don't exist are skipped with a warning. The other updates are
+        still applied.
+
+        This is cheaper than updating each `Gauge` individually when handling frequent
+        GMCP updates.
+        """
+        ...
+
     async def gmcp_enabled(self, session_id: int) -> bool:
         """
         Returns `True` if negotiation has completed and GMCP is enabled for the given
@@ -2026,6 +3773,25 @@ class MudpuppyCore:
         before sending GMCP messages.
 
         Use `MudpuppyCore.gmcp_register()` to register the `module` if required.
+
+        See also `MudpuppyCore.gmcp_send_obj()` to send Python data without encoding it
+        yourself.
+        """
+        ...
+
+    async def gmcp_send_obj(self, session_id: int, module: str, data: Any):
+        """
+        Sends a GMCP package to the MUD for the given session ID.
+
+        The `module` is the GMCP module name and the `data` is a Python object that is
+        encoded to JSON for you. It may be made of `dict`s with `str` keys, `list`s,
+        `tuple`s, `str`s, `int`s, `float`s, `bool`s and `None`.
+
+        Raises an exception if the `data` can't be encoded, and a `ValueError` if it
+        contains a `dict` or `list` that contains itself.
+
+        Use `MudpuppyCore.gmcp_enabled()` to verify GMCP is enabled for a session
+        before sending GMCP messages.
         """
         ...
 
@@ -2057,6 +3823,27 @@ class MudpuppyCore:
         """
         ...
 
+    async def msdp_enabled(self, session_id: int) -> bool:
+        """
+        Returns `True` if negotiation has completed and MSDP is enabled for the given
+        session ID, `False` otherwise.
+        """
+        ...
+
+    async def msdp_report(self, session_id: int, variables: list[str]):
+        """
+        Asks the MUD to report updates to the given MSDP `variables` for the given session ID.
+
+        Each update is produced as an `EventType.MsdpMessage` event.
+
+        Use `MudpuppyCore.msdp_enabled()` to verify MSDP is enabled for a session
+        before sending MSDP requests.
+
+        For example, you may wish to `msdp_report(id, ["HEALTH", "HEALTH_MAX"])` once
+        MSDP is enabled.
+        """
+        ...
+
     async def emit_event(self, custom_type: str, data: Any, id: Optional[int]):
         """
         Emits a custom event with the given `custom_type` and `data` for the given session ID.
@@ -2113,6 +3900,19 @@ class EventType(StrEnum):
     An event emitted when a prompt is received.
     """
 
+    Lines = auto()
+    """
+    An event emitted with the batch of output lines received in a single read.
+
+    Only emitted for MUDs with `batch_line_events` enabled in their config.
+    """
+
+    OutputLine = auto()
+    """
+    An event emitted for each output line received from the MUD, after triggers have been
+    evaluated for it.
+    """
+
     ConfigReloaded = auto()
     """
     An event emitted when the `Config` has been reloaded.
@@ -2157,6 +3957,12 @@ class EventType(StrEnum):
     changes have occurred.
     """
 
+    BufferCleared = auto()
+    """
+    An event emitted when the MUD output buffer, or an extra buffer, is cleared with
+    `MudpuppyCore.clear_buffer()`.
+    """
+
     InputLine = auto()
     """
     An event emitted after a line of input was sent to the MUD.
@@ -2198,11 +4004,48 @@ class EventType(StrEnum):
     An event emitted when a GMCP message is received.
     """
 
+    MsdpMessage = auto()
+    """
+    An event emitted for each variable in a received MSDP message.
+
+    See also `MudpuppyCore.msdp_report()`.
+    """
+
     ResumeSession = auto()
     """
     An event emitted for each session ID after a `PythonReloaded` event.
     """
 
+    Reconnecting = auto()
+    """
+    An event emitted when an automatic reconnect attempt is scheduled for a session.
+
+    See also `Mud.auto_reconnect`.
+    """
+
+    TimerExpired = auto()
+    """
+    An event emitted when a timer has run its last tick and was removed.
+
+    See also `TimerConfig.once` and `TimerConfig.max_ticks`.
+    """
+
+    Focus = auto()
+    """
+    An event emitted when the terminal running Mudpuppy gains or loses focus, e.g. when
+    you switch to another window and back.
+
+    Not every terminal reports focus changes.
+    """
+
+    GlobalTick = auto()
+    """
+    An event emitted at a regular interval, once a second by default. It isn't associated
+    with a session.
+
+    The interval is configured with the `tick_interval_ms` config setting.
+    """
+
 class Event:
     """
     An event emitted by Mudpuppy when something interesting happens.
@@ -2279,6 +4122,45 @@ class Event:
         as part of a `Prompt` event.
         """
 
+    class Lines:
+        """
+        An `EventType.Lines` event. This is produced once for all of the output
+        lines received from the MUD in a single read, when the MUD has
+        `batch_line_events` enabled.
+        """
+
+        id: int
+        """
+        The session ID that received the lines.
+        """
+
+        lines: list[MudLine]
+        """
+        The `MudLine`s that were received, in order.
+
+        Triggers have already been evaluated for each line.
+        """
+
+    class OutputLine:
+        """
+        An `EventType.OutputLine` event. This is produced for each output line
+        received from the MUD, once triggers have been evaluated for it and it's
+        added to the output buffer.
+        """
+
+        id: int
+        """
+        The session ID that received the line.
+        """
+
+        line: MudLine
+        """
+        The `MudLine` as it's added to the output buffer.
+
+        Changes made by triggers are included. In particular `MudLine.gag` is `True`
+        if a trigger gagged the line, and it won't be displayed.
+        """
+
     class Iac:
         """
         An `EventType.Iac` event. This is produced when a Telnet IAC
@@ -2373,6 +4255,23 @@ class Event:
         The new width and height of the MUD output area.
         """
 
+    class BufferCleared:
+        """
+        An `EventType.BufferCleared` event. This is produced when a buffer is cleared
+        with `MudpuppyCore.clear_buffer()`.
+        """
+
+        id: int
+        """
+        The session ID that had a buffer cleared.
+        """
+
+        buffer_id: Optional[int]
+        """
+        The buffer ID of the `ExtraBuffer` that was cleared, or `None` if the MUD output
+        buffer was cleared.
+        """
+
     class InputLine:
         """
         An `EventType.InputLine` event. This is produced after a line of input
@@ -2466,6 +4365,39 @@ class Event:
         The JSON-encoded data for the GMCP message.
         """
 
+        data: Any
+        """
+        The data for the GMCP message, decoded from JSON into Python `dict`, `list`, `str`,
+        `int`, `float`, `bool` and `None` values.
+
+        If the message data isn't valid JSON this is `None`, and only `json` is available.
+        """
+
+    class MsdpMessage:
+        """
+        An `EventType.MsdpMessage` event. This is produced for each variable in an MSDP
+        message received from the MUD.
+
+        Typically this happens for variables that have been requested with
+        `MudpuppyCore.msdp_report()`.
+        """
+
+        id: int
+        """
+        The session ID that received the MSDP message.
+        """
+
+        variable: str
+        """
+        The MSDP variable name.
+        """
+
+        value: Any
+        """
+        The variable's value. MSDP values are a `str`, a `list` for arrays, or a `dict`
+        with `str` keys for tables. Arrays and tables may be nested.
+        """
+
     class Python:
         """
         An `EventType.Python` event. This is produced when a custom event is emitted
@@ -2517,6 +4449,61 @@ class Event:
         The session ID that is being resumed.
         """
 
+    class Reconnecting:
+        """
+        An `EventType.Reconnecting` event. This is produced when a session's connection was
+        lost unexpectedly and an automatic reconnect attempt has been scheduled.
+        """
+
+        id: int
+        """
+        The session ID that will reconnect.
+        """
+
+        attempt: int
+        """
+        The number of the scheduled attempt, starting from `1`. The count resets once a
+        connection succeeds.
+        """
+
+    class TimerExpired:
+        """
+        An `EventType.TimerExpired` event. This is produced when a timer that was created
+        with `TimerConfig.once`, or that reached `TimerConfig.max_ticks`, has been removed.
+        """
+
+        id: Optional[int]
+        """
+        The session ID the timer was associated with, if any.
+        """
+
+        timer_id: int
+        """
+        The ID of the timer that expired.
+        """
+
+    class Focus:
+        """
+        An `EventType.Focus` event. This is produced when the terminal gains or loses
+        focus. It isn't associated with a session.
+        """
+
+        focused: bool
+        """
+        `True` if the terminal gained focus, `False` if it lost focus.
+        """
+
+    class GlobalTick:
+        """
+        An `EventType.GlobalTick` event. This is produced at the configured
+        `tick_interval_ms` interval. It isn't associated with a session.
+        """
+
+        count: int
+        """
+        The number of ticks since Mudpuppy started, starting from `1`.
+        """
+
 class PromptSignal(StrEnum):
     """
     Describes a possible way of signalling the a partial line is a prompt
@@ -2621,6 +4608,23 @@ class Input:
         """
         ...
 
+    def undo(self) -> bool:
+        """
+        Undoes the most recent edit to the input area.
+
+        Returns `True` if there was an edit to undo. The undo history is cleared
+        when a line is popped from the input area.
+        """
+        ...
+
+    def redo(self) -> bool:
+        """
+        Redoes the most recently undone edit to the input area.
+
+        Returns `True` if there was an edit to redo.
+        """
+        ...
+
     def set_telnet_echo(self, state: EchoState):
         """
         Sets the telnet echo state of the input area.
@@ -2763,6 +4767,16 @@ class EventHandlers:
         """
         ...
 
+    def get_handlers_detail(self) -> dict[EventType, list[tuple[str, str]]]:
+        """
+        Returns a `(module, qualname)` tuple for each registered handler, keyed by
+        `EventType`. Handlers are listed in the order they're invoked.
+
+        This is read-only, and is useful for finding handlers that were registered
+        more than once, for example after reloading a module.
+        """
+        ...
+
 mudpuppy_core: MudpuppyCore
 """
 A `MudpuppyCore` instance for interacting with the client.